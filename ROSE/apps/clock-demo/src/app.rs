@@ -1,112 +1,230 @@
-// TODO: violates clean arch principles.  I'd like to find a better solution to these dependencies.
-use crate::{Cmd, HostAction};
-
-use bitblt::{blit_rect, BlitContext, BlitOp};
-use chrono::prelude::{DateTime, Local};
+use bitblt::BlitOp;
+use chrono::prelude::{DateTime, Local, Utc};
+use stencil::events::{
+    AppController, AppEventSink, ButtonEvent, KeyCode, KeyboardEventSink, Modifiers,
+    MouseEventSink, TimerEventSink,
+};
+use stencil::mediator::Mediator;
+use stencil::simple_bitmap_font::text_width;
 use stencil::simple_printer::SimplePrinter;
-use stencil::stencil::{Draw, Stencil};
-use stencil::sysfont_bsw_9::SYSTEM_BITMAP_FONT;
+use stencil::stencil::Draw;
+use stencil::timers::TimerId;
 use stencil::types::{Point, Rect, Unit};
-use stencil::utils::{draw_desktop, draw_dialog_box, WHITE_PATTERN};
+use stencil::view::View;
+use stencil::windows::{WindowEvent, WindowId, WindowManager};
 
-/// The only real state the clock has is whether or not it should display the colon.
-/// Still, state is state.
+/// How often the clock redraws itself, blinking the seconds colon as it goes.
+const PERIOD: u32 = 500; // milliseconds
+
+/// The clocks' windows, where they first open.
+const LOCAL_FRAME: Rect = ((40, 40), (180, 100));
+const UTC_FRAME: Rect = ((120, 90), (260, 150));
+
+/// A clock face, showing the time in the middle of its area.
+/// The seconds colon blinks, showing on one tick and hidden the next.
+pub struct ClockFace {
+    area: Rect,
+
+    /// Shows Coordinated Universal Time rather than local time.
+    utc: bool,
 
-pub struct ClockState {
     hide_colon: bool,
 }
 
-impl ClockState {
-    pub fn new() -> Self {
-        Self { hide_colon: false }
+impl ClockFace {
+    pub fn new(utc: bool) -> Self {
+        Self {
+            area: ((0, 0), (0, 0)),
+            utc,
+            hide_colon: false,
+        }
     }
 
+    /// Shows or hides the colon the next time the face is drawn.
+    fn tick(&mut self) {
+        self.hide_colon = !self.hide_colon;
+    }
+}
+
+impl View for ClockFace {
     /// Redraw the current time
-    fn redraw_time(&mut self, desktop: &mut Stencil) -> HostAction {
-        let dt: DateTime<Local> = Local::now();
-        let time_string_1 = format!("{}", dt.format("%H:%M"));
-        let time_string_2 = format!("{}", dt.format(":%S"));
-        let font = &SYSTEM_BITMAP_FONT;
-        let label_region: Rect = ((160, 100), (230, 120));
+    fn draw(&mut self, med: &mut dyn Mediator) {
+        let theme = med.theme();
+        let desktop = med.borrow_mut_desktop();
+        let (time_string_1, time_string_2) = if self.utc {
+            let dt: DateTime<Utc> = Utc::now();
+            (format!("{}", dt.format("%H:%M")), format!("{}", dt.format(":%S")))
+        } else {
+            let dt: DateTime<Local> = Local::now();
+            (format!("{}", dt.format("%H:%M")), format!("{}", dt.format(":%S")))
+        };
+        let font = theme.font;
 
         // Erase the old time pattern.
-        desktop.filled_rectangle(label_region.0, label_region.1, &WHITE_PATTERN);
+        let ((left, top), (right, bottom)) = self.area;
+        desktop.filled_rectangle((left, top), (right, bottom), &theme.paper);
+
+        let width = text_width(&time_string_1, font) + text_width(&time_string_2, font);
+        let label_left = left + ((right - left - width) >> 1);
+        let label_top = top + ((bottom - top - font.height) >> 1);
+        let label_region: Rect = ((label_left, label_top), (right, bottom));
 
         // Print the time string, but do so in a way that lets us capture where the seconds colon sits
         // on the screen.
         let xcolon: Unit;
-        let xmax: Unit;
         {
-            let mut printer = SimplePrinter::new(desktop, label_region, &font);
+            let mut printer = SimplePrinter::new(desktop, label_region, font);
             printer.print(&time_string_1);
             xcolon = printer.head.0;
 
             printer.print(&time_string_2);
-            xmax = printer.head.0;
         }
 
         // Now, if we need to hide the colon, use the XOR operator to remove it.
-        self.hide_colon = !self.hide_colon;
         if self.hide_colon {
-            let mut printer = SimplePrinter::new(desktop, label_region, &font);
+            let mut printer = SimplePrinter::new(desktop, label_region, font);
             printer.blit_op = BlitOp::Xor;
             printer.head.0 = xcolon;
             printer.print(":");
         }
+    }
 
-        // Tell event loop to commit changes to the desktop.
-        HostAction::Repaint((label_region.0, (xmax, 100 + font.height)))
+    fn set_area(&mut self, area: Rect) {
+        self.area = area;
     }
+}
+
+/// The clock face ignores the mouse.
+impl MouseEventSink<()> for ClockFace {
+    fn pointer_moved(&mut self, _med: &mut dyn Mediator, _to: Point) {}
+
+    fn button_up(&mut self, _med: &mut dyn Mediator, _event: ButtonEvent) {}
+
+    fn button_down(&mut self, _med: &mut dyn Mediator, _event: ButtonEvent) {}
+
+    fn wheel(&mut self, _med: &mut dyn Mediator, _delta: Point, _modifiers: Modifiers) {}
+
+    fn enter(&mut self, _med: &mut dyn Mediator, _at: Point) {}
+
+    fn leave(&mut self, _med: &mut dyn Mediator) {}
+}
+
+/// The clock application shows a clock face in each of its windows.
+pub struct ClockState {
+    windows: WindowManager<()>,
+
+    /// The windows still open.
+    clocks: Vec<WindowId>,
 
-    /// Process input events for the clock application.
-    pub fn demo_tick(&mut self, desktop: &mut Stencil, previous: Cmd) -> HostAction {
-        match previous {
-            Cmd::ButtonUp { at: point, .. } => {
-                if clicked_in_close_gadget(point) {
-                    HostAction::Quit
-                } else {
-                    HostAction::None
-                }
+    /// The timer which tells us when to redraw the time.
+    tick: TimerId,
+}
+
+impl ClockState {
+    pub fn new(windows: WindowManager<()>, clocks: Vec<WindowId>, tick: TimerId) -> Self {
+        Self {
+            windows,
+            clocks,
+            tick,
+        }
+    }
+
+    /// Acts on what the window manager reports.
+    /// Closing the last clock quits.
+    fn handle(&mut self, med: &mut dyn Mediator, event: WindowEvent<()>) {
+        if let WindowEvent::CloseRequested(id) = event {
+            self.windows.close(med, id);
+            self.clocks.retain(|&clock| clock != id);
+            if self.clocks.is_empty() {
+                med.quit();
             }
-            Cmd::TimerTick => self.redraw_time(desktop),
-            _ => HostAction::None,
         }
     }
 }
 
-/// A monochrome bitmap of the application close gadget.
-/// Although only 12x10 pixels, it's arranged as a 24x10 bitmap
-/// to accomodate a bitblit::blit_rect bug.
-/// See its docs and unit tests for more details.
-static CLOSE_BITMAP: [u8; 30] = [
-    0b11111111, 0b11110000, 0, 0b10000000, 0b00010000, 0, 0b10111111, 0b11010000, 0, 0b10101010,
-    0b11010000, 0, 0b10110101, 0b01010000, 0, 0b10101010, 0b11010000, 0, 0b10110101, 0b01010000, 0,
-    0b10111111, 0b11010000, 0, 0b10000000, 0b00010000, 0, 0b11111111, 0b11110000, 0,
-];
-
-/// Configure the initial state of the clock application.
-pub fn demo_init(desktop: &mut Stencil) -> ClockState {
-    let width = desktop.dimensions.0;
-
-    draw_desktop(desktop);
-    draw_dialog_box(desktop, ((80, 50), (240, 150)));
-    {
-        // to scope a mutable borrow
-        let mut bc = BlitContext::new(&CLOSE_BITMAP, 3, &mut desktop.bits, (width >> 3) as usize);
-        blit_rect(&mut bc, 0, 0, 12, 10, 81, 51, BlitOp::DandNotS);
+/// Tell the host environment that we are equipped to represent the whole application.
+impl AppController for ClockState {}
+
+/// Tell the host environment we can determine the application life-cycle.
+impl AppEventSink for ClockState {
+    fn request_quit(&mut self, _med: &mut dyn Mediator) -> bool {
+        true
+    }
+}
+
+/// The window manager looks after the mouse.
+impl MouseEventSink<()> for ClockState {
+    fn pointer_moved(&mut self, med: &mut dyn Mediator, to: Point) {
+        let event = self.windows.pointer_moved(med, to);
+        self.handle(med, event);
     }
-    desktop.horizontal_line((80, 62), 240, 0x00);
 
-    {
-        let font = &SYSTEM_BITMAP_FONT;
-        let mut printer = SimplePrinter::new(desktop, ((96, 52), (238, 52 + font.height)), font);
-        printer.print("<-- Click to close");
+    fn button_up(&mut self, med: &mut dyn Mediator, event: ButtonEvent) {
+        let event = self.windows.button_up(med, event);
+        self.handle(med, event);
+    }
+
+    fn button_down(&mut self, med: &mut dyn Mediator, event: ButtonEvent) {
+        let event = self.windows.button_down(med, event);
+        self.handle(med, event);
+    }
+
+    fn wheel(&mut self, med: &mut dyn Mediator, delta: Point, modifiers: Modifiers) {
+        let event = self.windows.wheel(med, delta, modifiers);
+        self.handle(med, event);
+    }
+
+    fn enter(&mut self, med: &mut dyn Mediator, at: Point) {
+        let event = self.windows.enter(med, at);
+        self.handle(med, event);
+    }
+
+    fn leave(&mut self, med: &mut dyn Mediator) {
+        let event = self.windows.leave(med);
+        self.handle(med, event);
+    }
+}
+
+/// RUN/STOP (Escape, on a PC) quits the clock too.
+impl KeyboardEventSink<()> for ClockState {
+    fn key_down(&mut self, med: &mut dyn Mediator, key: KeyCode, _modifiers: Modifiers) {
+        if key == KeyCode::Escape {
+            med.quit();
+        }
+    }
+
+    fn key_up(&mut self, _med: &mut dyn Mediator, _key: KeyCode, _modifiers: Modifiers) {}
+
+    fn text_input(&mut self, _med: &mut dyn Mediator, _text: &str) {}
+}
+
+/// Our timer ticks twice a second.
+/// Each clock is redrawn wherever it can be seen, even if other windows cover part of it.
+impl TimerEventSink<()> for ClockState {
+    fn timer_fired(&mut self, med: &mut dyn Mediator, id: TimerId) {
+        if id != self.tick {
+            return;
+        }
+        for &clock in self.clocks.iter() {
+            if let Some(face) = self.windows.get_content_mut::<ClockFace>(clock) {
+                face.tick();
+            }
+            self.windows.refresh(med, clock);
+        }
     }
 
-    ClockState::new()
+    fn idle(&mut self, _med: &mut dyn Mediator) {}
 }
 
-fn clicked_in_close_gadget(point: Point) -> bool {
-    let (x, y) = point;
-    (81 <= x) && (x < 94) && (50 <= y) && (y < 62)
+/// Configure the initial state of the clock application:
+/// one window showing local time, and another, partly covering it, showing UTC.
+pub fn init_root(med: &mut dyn Mediator) -> Box<dyn AppController> {
+    let mut windows = WindowManager::new();
+    windows.draw(med);
+    let clocks = vec![
+        windows.open(med, LOCAL_FRAME, "Local time", ClockFace::new(false), |()| ()),
+        windows.open(med, UTC_FRAME, "UTC", ClockFace::new(true), |()| ()),
+    ];
+
+    Box::new(ClockState::new(windows, clocks, med.start_timer(PERIOD, true)))
 }
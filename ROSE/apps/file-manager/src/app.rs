@@ -0,0 +1,433 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
+use reader::text_view::{read_text, TextView};
+use stencil::events::{
+    AppController, AppEventSink, ButtonEvent, KeyCode, KeyboardEventSink, Modifiers, MouseButton,
+    MouseEventSink, TimerEventSink,
+};
+use stencil::gadgets::list::{ListEvent, ListView, SelectionMode};
+use stencil::gadgets::menu::{Menu, MenuEvent, MenuId, MenuItem, PopupMenuView};
+use stencil::gadgets::requester::{list_drawer, PARENT};
+use stencil::icons::{IconEvent, IconImage, IconLayer, DOCUMENT_ICON, DRAWER_ICON, TEXT_ICON};
+use stencil::mediator::Mediator;
+use stencil::stencil::Draw;
+use stencil::simple_bitmap_font::SimpleBitmapFont;
+use stencil::timers::TimerId;
+use stencil::types::{Point, Rect};
+use stencil::windows::{content_area, WindowEvent, WindowId, WindowManager};
+
+/// Where the first window opens.
+/// Each one after it opens a little further down and to the right,
+/// until they start again from here.
+const FIRST_FRAME: Rect = ((40, 40), (340, 220));
+const STAGGER: Point = (16, 12);
+const STAGGER_STEPS: isize = 8;
+
+/// Where the desktop's drawer icon sits.
+const HOME_ICON_AT: Point = (8, 8);
+
+/// Commands in the pop-up menu.
+const MENU_OPEN_HOME: MenuId = 0;
+const MENU_AS_ICONS: MenuId = 1;
+const MENU_AS_LIST: MenuId = 2;
+const MENU_PARENT: MenuId = 3;
+const MENU_QUIT: MenuId = 4;
+
+/// Builds the menu which pops up when the right mouse button is pressed.
+fn file_manager_menu() -> Menu {
+    Menu::new("Files", vec![
+        MenuItem::command(MENU_OPEN_HOME, "Open home drawer"),
+        MenuItem::command(MENU_PARENT, "Open parent").with_shortcut('u'),
+        MenuItem::separator(),
+        MenuItem::command(MENU_AS_ICONS, "View as icons"),
+        MenuItem::command(MENU_AS_LIST, "View as list"),
+        MenuItem::separator(),
+        MenuItem::command(MENU_QUIT, "Quit").with_shortcut('q'),
+    ])
+}
+
+/// The events of every view on the desktop, tagged with the kind of view.
+enum Event {
+    Icons(IconEvent),
+    List(ListEvent),
+    Text,
+}
+
+/// How a drawer window shows what's inside the drawer.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Style {
+    Icons,
+    List,
+}
+
+/// A window showing what's inside a drawer.
+struct Drawer {
+    window: WindowId,
+    path: PathBuf,
+    style: Style,
+}
+
+/// Answers the name to show for a drawer or file.
+fn title(path: &Path) -> String {
+    match path.file_name() {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => path.display().to_string(),
+    }
+}
+
+/// Answers `true` if the file `name` looks like text.
+fn is_text(name: &str) -> bool {
+    Path::new(name).extension().is_some_and(|ext| ext.eq_ignore_ascii_case("txt"))
+}
+
+/// Answers the label of the icon for `entry`, one of the entries [[list_drawer]] answers.
+fn label(entry: &str) -> &str {
+    if entry == PARENT {
+        ".."
+    } else {
+        entry.trim_end_matches('/')
+    }
+}
+
+/// Answers the picture of the icon for `entry`, one of the entries [[list_drawer]] answers.
+fn image(entry: &str) -> &'static IconImage {
+    if entry.ends_with('/') {
+        &DRAWER_ICON
+    } else if is_text(entry) {
+        &TEXT_ICON
+    } else {
+        &DOCUMENT_ICON
+    }
+}
+
+/// Replaces the icons in `layer` with one for each entry of `drawer`.
+fn fill_icons(layer: &mut IconLayer, drawer: &Path) {
+    layer.clear();
+    for entry in list_drawer(drawer) {
+        layer.add(label(&entry), image(&entry));
+    }
+}
+
+/// The application state:
+/// the desktop, with the home drawer's icon on it,
+/// and windows showing drawers and text files.
+pub struct FileManager {
+    windows: WindowManager<Event>,
+
+    /// The windows showing drawers.
+    drawers: Vec<Drawer>,
+
+    /// The drawer the desktop's icon opens.
+    home: PathBuf,
+
+    popup: PopupMenuView<'static>,
+
+    /// How many windows have been opened, which decides where the next one opens.
+    opened: isize,
+}
+
+impl FileManager {
+    /// Creates a file manager whose desktop icon opens the drawer `home`,
+    /// with its menu printed in `font`.
+    pub fn new(home: PathBuf, font: &'static SimpleBitmapFont<'static>) -> Self {
+        Self {
+            windows: WindowManager::new(),
+            drawers: Vec::new(),
+            home,
+            popup: PopupMenuView::new(file_manager_menu(), font),
+            opened: 0,
+        }
+    }
+
+    /// Answers where the next window should open.
+    fn next_frame(&mut self) -> Rect {
+        let step = self.opened % STAGGER_STEPS;
+        self.opened += 1;
+        let ((left, top), (right, bottom)) = FIRST_FRAME;
+        let (dx, dy) = (STAGGER.0 * step, STAGGER.1 * step);
+        ((left + dx, top + dy), (right + dx, bottom + dy))
+    }
+
+    fn drawer(&self, window: WindowId) -> Option<&Drawer> {
+        self.drawers.iter().find(|d| d.window == window)
+    }
+
+    /// Opens a window occupying `frame`, showing what's in the drawer `path` in the given `style`.
+    fn open_view(&mut self, med: &mut dyn Mediator, frame: Rect, path: &Path, style: Style) -> WindowId {
+        let area = content_area(frame);
+        match style {
+            Style::Icons => {
+                let mut layer = IconLayer::new(area);
+                fill_icons(&mut layer, path);
+                self.windows.open(med, frame, &title(path), layer, Event::Icons)
+            }
+            Style::List => {
+                let list = ListView::new(area, list_drawer(path), SelectionMode::Single, med.theme().font);
+                self.windows.open(med, frame, &title(path), list, Event::List)
+            }
+        }
+    }
+
+    /// Opens a window showing what's in the drawer `path`,
+    /// or brings the window already showing it to the front.
+    fn open_drawer(&mut self, med: &mut dyn Mediator, path: PathBuf) {
+        if let Some(drawer) = self.drawers.iter().find(|d| d.path == path) {
+            let window = drawer.window;
+            self.windows.bring_to_front(med, window);
+            return;
+        }
+        let frame = self.next_frame();
+        let window = self.open_view(med, frame, &path, Style::Icons);
+        self.drawers.push(Drawer { window, path, style: Style::Icons });
+    }
+
+    /// Opens a window showing the text file `path` in the reader's view.
+    fn open_text(&mut self, med: &mut dyn Mediator, path: &Path) {
+        let frame = self.next_frame();
+        let view = TextView::new(read_text(path), content_area(frame), med.theme().font);
+        self.windows.open(med, frame, &title(path), view, |()| Event::Text);
+    }
+
+    /// Opens the entry `name` of the drawer shown in `window`:
+    /// a drawer is shown in its place, and a text file opens in a window of its own.
+    /// Other files can't be opened.
+    fn open_entry(&mut self, med: &mut dyn Mediator, window: WindowId, name: &str) {
+        let Some(drawer) = self.drawer(window) else {
+            return;
+        };
+        let path = if (name == PARENT) || (name == "..") {
+            match drawer.path.parent() {
+                Some(parent) => parent.to_path_buf(),
+                None => return,
+            }
+        } else {
+            drawer.path.join(name.trim_end_matches('/'))
+        };
+
+        if path.is_dir() {
+            self.show_drawer(med, window, path);
+        } else if is_text(name) {
+            self.open_text(med, &path);
+        }
+    }
+
+    /// Shows what's in the drawer `path` in place of whatever `window` showed.
+    fn show_drawer(&mut self, med: &mut dyn Mediator, window: WindowId, path: PathBuf) {
+        if let Some(layer) = self.windows.get_content_mut::<IconLayer>(window) {
+            fill_icons(layer, &path);
+        }
+        if let Some(list) = self.windows.get_content_mut::<ListView<'static>>(window) {
+            list.set_items(list_drawer(&path));
+        }
+        self.windows.set_title(med, window, &title(&path));
+        self.windows.refresh(med, window);
+        if let Some(drawer) = self.drawers.iter_mut().find(|d| d.window == window) {
+            drawer.path = path;
+        }
+    }
+
+    /// Shows the frontmost drawer window's drawer in the given `style`,
+    /// by opening a new window in its place.
+    fn restyle(&mut self, med: &mut dyn Mediator, style: Style) {
+        let Some(front) = self.windows.front() else {
+            return;
+        };
+        let Some(i) = self.drawers.iter().position(|d| d.window == front) else {
+            return;
+        };
+        let Some(frame) = self.windows.get_frame(front) else {
+            return;
+        };
+        if self.drawers[i].style == style {
+            return;
+        }
+        let path = self.drawers[i].path.clone();
+        self.windows.close(med, front);
+        self.drawers[i].window = self.open_view(med, frame, &path, style);
+        self.drawers[i].style = style;
+    }
+
+    /// Opens the pop-up menu at `at`, with its items brought up to date.
+    fn open_menu(&mut self, med: &mut dyn Mediator, at: Point) {
+        let style = self.windows.front().and_then(|w| self.drawer(w)).map(|d| d.style);
+        let menu = self.popup.menu_mut();
+        for (id, shows) in [(MENU_AS_ICONS, Style::Icons), (MENU_AS_LIST, Style::List)] {
+            menu.set_enabled(id, style.is_some());
+            menu.set_checked(id, style == Some(shows));
+        }
+        menu.set_enabled(MENU_PARENT, style.is_some());
+        self.popup.open(med, at);
+    }
+
+    /// Carries out whatever command the pop-up menu reports.
+    fn menu_chosen(&mut self, med: &mut dyn Mediator, event: MenuEvent) {
+        let MenuEvent::Selected(id) = event else {
+            return;
+        };
+        match id {
+            MENU_OPEN_HOME => self.open_drawer(med, self.home.clone()),
+            MENU_AS_ICONS => self.restyle(med, Style::Icons),
+            MENU_AS_LIST => self.restyle(med, Style::List),
+            MENU_PARENT => {
+                if let Some(front) = self.windows.front() {
+                    self.open_entry(med, front, PARENT);
+                }
+            }
+            MENU_QUIT => med.quit(),
+            _ => (),
+        }
+    }
+
+    /// Acts on what the window manager reports.
+    fn handle(&mut self, med: &mut dyn Mediator, event: WindowEvent<Event>) {
+        match event {
+            WindowEvent::Backdrop(Event::Icons(IconEvent::Opened(_))) => self.open_drawer(med, self.home.clone()),
+            WindowEvent::Content(window, Event::Icons(IconEvent::Opened(icon))) => {
+                let layer = self.windows.get_content::<IconLayer>(window);
+                if let Some(name) = layer.and_then(|l| l.label(icon)).map(String::from) {
+                    self.open_entry(med, window, &name);
+                }
+            }
+            WindowEvent::Content(window, Event::List(ListEvent::Activated(n))) => {
+                let list = self.windows.get_content::<ListView<'static>>(window);
+                if let Some(name) = list.and_then(|l| l.items().get(n)).cloned() {
+                    self.open_entry(med, window, &name);
+                }
+            }
+            WindowEvent::Resized(window) => {
+                // Icons are lined up afresh to suit the window's new width.
+                if let Some(layer) = self.windows.get_content_mut::<IconLayer>(window) {
+                    layer.arrange();
+                    self.windows.refresh(med, window);
+                }
+            }
+            WindowEvent::CloseRequested(window) => {
+                self.windows.close(med, window);
+                self.drawers.retain(|d| d.window != window);
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Tell the host environment that we are equipped to represent the whole application.
+impl AppController for FileManager {}
+
+/// Tell the host environment we can determine the application life-cycle.
+impl AppEventSink for FileManager {
+    fn request_quit(&mut self, _med: &mut dyn Mediator) -> bool {
+        true
+    }
+}
+
+/// The window manager looks after the mouse,
+/// except while the pop-up menu is open, or being opened with the right button.
+impl MouseEventSink<()> for FileManager {
+    fn pointer_moved(&mut self, med: &mut dyn Mediator, to: Point) {
+        if self.popup.is_open() {
+            let _ = self.popup.pointer_moved(med, to);
+            return;
+        }
+        let event = self.windows.pointer_moved(med, to);
+        self.handle(med, event);
+    }
+
+    fn button_up(&mut self, med: &mut dyn Mediator, event: ButtonEvent) {
+        if self.popup.is_open() {
+            let chosen = self.popup.button_up(med, event);
+            self.menu_chosen(med, chosen);
+            return;
+        }
+        let event = self.windows.button_up(med, event);
+        self.handle(med, event);
+    }
+
+    fn button_down(&mut self, med: &mut dyn Mediator, event: ButtonEvent) {
+        if self.popup.is_open() {
+            let _ = self.popup.button_down(med, event);
+            return;
+        }
+        if event.button == MouseButton::Right {
+            self.open_menu(med, event.at);
+            return;
+        }
+        let event = self.windows.button_down(med, event);
+        self.handle(med, event);
+    }
+
+    fn wheel(&mut self, med: &mut dyn Mediator, delta: Point, modifiers: Modifiers) {
+        let event = self.windows.wheel(med, delta, modifiers);
+        self.handle(med, event);
+    }
+
+    fn enter(&mut self, med: &mut dyn Mediator, at: Point) {
+        let event = self.windows.enter(med, at);
+        self.handle(med, event);
+    }
+
+    fn leave(&mut self, med: &mut dyn Mediator) {
+        let event = self.windows.leave(med);
+        self.handle(med, event);
+    }
+}
+
+/// The menu's shortcuts work anytime;
+/// otherwise, keys go to the frontmost window's list or text.
+impl KeyboardEventSink<()> for FileManager {
+    fn key_down(&mut self, med: &mut dyn Mediator, key: KeyCode, modifiers: Modifiers) {
+        let was_open = self.popup.is_open();
+        let chosen = self.popup.key_down(med, key, modifiers);
+        if was_open || (chosen != MenuEvent::None) {
+            self.menu_chosen(med, chosen);
+            return;
+        }
+
+        let Some(front) = self.windows.front() else {
+            return;
+        };
+        if let Some(text) = self.windows.get_content_mut::<TextView>(front) {
+            text.key_down(med, key, modifiers);
+        }
+        if let Some(list) = self.windows.get_content_mut::<ListView<'static>>(front) {
+            let event = list.key_down(med, key, modifiers);
+            self.handle(med, WindowEvent::Content(front, Event::List(event)));
+        }
+    }
+
+    fn key_up(&mut self, _med: &mut dyn Mediator, _key: KeyCode, _modifiers: Modifiers) {}
+
+    fn text_input(&mut self, _med: &mut dyn Mediator, _text: &str) {}
+}
+
+/// Only the frontmost window's scroll bars can be held down, so timers go to its view.
+impl TimerEventSink<()> for FileManager {
+    fn timer_fired(&mut self, med: &mut dyn Mediator, id: TimerId) {
+        let Some(front) = self.windows.front() else {
+            return;
+        };
+        if let Some(text) = self.windows.get_content_mut::<TextView>(front) {
+            text.timer_fired(med, id);
+        }
+        if let Some(list) = self.windows.get_content_mut::<ListView<'static>>(front) {
+            let event = list.timer_fired(med, id);
+            self.handle(med, WindowEvent::Content(front, Event::List(event)));
+        }
+    }
+
+    fn idle(&mut self, _med: &mut dyn Mediator) {}
+}
+
+/// Puts an icon for the drawer named on the command line (or the current drawer) on the desktop.
+pub fn init_root(med: &mut dyn Mediator) -> Box<dyn AppController> {
+    let home = env::args().nth(1).map(PathBuf::from).unwrap_or_else(|| env::current_dir().unwrap_or_default());
+    let home = home.canonicalize().unwrap_or(home);
+
+    let mut desktop = IconLayer::new(((0, 0), med.borrow_mut_desktop().get_dimensions()));
+    desktop.set_on_desktop(true);
+    desktop.add_at(HOME_ICON_AT, &title(&home), &DRAWER_ICON);
+
+    let mut manager = Box::new(FileManager::new(home, med.theme().font));
+    manager.windows.set_backdrop(med, desktop, Event::Icons);
+    manager
+}
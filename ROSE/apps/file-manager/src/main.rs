@@ -0,0 +1,19 @@
+mod app;
+
+extern crate sdlstate;
+extern crate stencil;
+
+use stencil::types::Dimension;
+
+use app::init_root;
+
+const W: Dimension = 480;
+const H: Dimension = 320;
+
+/// The file manager shows the drawer named on the command line
+/// (or the current drawer) as an icon on the desktop.
+/// Opening it shows what's inside, in a window;
+/// drawers inside open in turn, and text files open in the reader's view.
+fn main() {
+    sdlstate::run("File Manager", W, H, init_root);
+}
@@ -1,14 +1,33 @@
-use stencil::events::{AppController, AppEventSink, MouseEventSink};
+use std::env;
+use std::ptr;
+
+use stencil::container::{ComponentId, Container};
+use stencil::dialog::{DialogEvent, DialogResult, ModalDialog};
+use stencil::events::{
+    AppController, AppEventSink, ButtonEvent, KeyCode, KeyboardEventSink, Modifiers, MouseButton,
+    MouseEventSink, TimerEventSink,
+};
+use stencil::layout::{Align, Layout};
+use stencil::focus::{FocusEvent, FocusId, FocusRing};
 use stencil::mediator::Mediator;
+use stencil::simple_bitmap_font::SimpleBitmapFont;
 use stencil::stencil::Draw;
-use stencil::sysfont_bsw_9::SYSTEM_BITMAP_FONT;
-use stencil::types::{Point, Rect, Unit};
+use stencil::theme::{CLASSIC_THEME, HIGH_CONTRAST_THEME};
+use stencil::timers::TimerId;
+use stencil::types::{Dimensions, Point, Rect, Unit};
 use stencil::utils::{draw_desktop, draw_dialog_box};
-use stencil::utils::{LINE_BLACK, WHITE_PATTERN};
+use stencil::utils::LINE_BLACK;
 use stencil::view::{rect_contains, View};
 
 use stencil::gadgets::proportional::{PropGadgetEvent, PropGadgetView};
 use stencil::gadgets::boolean::{PushButtonView, PushButtonEvent};
+use stencil::gadgets::checkbox::{CheckBoxEvent, CheckBoxView};
+use stencil::gadgets::cycle::{CycleEvent, CycleView};
+use stencil::gadgets::radio::{RadioEvent, RadioGroupView};
+use stencil::gadgets::ruler::{RulerEvent, RulerView};
+use stencil::gadgets::text_field::{TextFieldEvent, TextFieldView};
+use stencil::gadgets::menu::{Menu, MenuEvent, MenuId, MenuItem, PopupMenuView};
+use stencil::gadgets::requester::Requester;
 
 /// This is the main entry point to all ROSE applications.
 ///
@@ -17,42 +36,81 @@ use stencil::gadgets::boolean::{PushButtonView, PushButtonEvent};
 ///
 /// It also renders the initial desktop image.
 pub fn init_root(med: &mut dyn Mediator) -> Box<dyn AppController> {
-    let mut toybox = Box::new(ToyBoxApp::new());
+    let size = med.borrow_mut_desktop().get_dimensions();
+    let mut toybox = Box::new(ToyBoxApp::new(size, med.theme().font));
     toybox.draw(med);
-    Box::new(ToyBoxApp::new())
+    toybox
 }
 
+/// The push buttons in the toybox, whose labels and font live forever.
+type Button = PushButtonView<'static, 'static>;
+
+/// The radio group choosing between selections A, B and C.
+type Choice = RadioGroupView<'static, 'static>;
+
+/// The ways the toybox can greet whoever enters their name,
+/// chosen with a cycle gadget.
+const GREETINGS: [&str; 3] = ["Hello", "Howdy", "Ahoy"];
+
 /// The application state,
 /// which directly or indirectly
 /// includes all models and views on those models.
-pub struct ToyBoxApp<'l, 'f> {
+pub struct ToyBoxApp {
     dbox_area: Rect,
     mouse_pt: Point,
     selected: Selectable,
-    hr_area: Rect,
     hr_cursor_left: Unit,
     hr_cursor_right: Unit,
     vr_area: Rect,
     vr_cursor_top: Unit,
     vr_cursor_bottom: Unit,
-    hprop: PropGadgetView,
-    vprop: PropGadgetView,
-    xyprop: PropGadgetView,
-    quit_btn: PushButtonView<'l, 'f>,
-    sel_a: PushButtonView<'l, 'f>,
-    sel_b: PushButtonView<'l, 'f>,
-    sel_c: PushButtonView<'l, 'f>,
-    sel: Selector,
-    sel_d: PushButtonView<'l, 'f>,
+    gadgets: Container<GadgetEvent>,
+    controls: Layout,
+    popup: PopupMenuView<'static>,
+    dialog: Option<(Asking, ModalDialog)>,
+    quit_confirmed: bool,
+    ruler: ComponentId,
+    hprop: ComponentId,
+    vprop: ComponentId,
+    xyprop: ComponentId,
+    quit_btn: ComponentId,
+    choice: ComponentId,
+    enable_choice: ComponentId,
+    push_btn: ComponentId,
+    greeting: ComponentId,
+    name_field: ComponentId,
+    focus_ring: FocusRing,
 }
 
-/// Our toggle-buttons implement a kind of "radio button" control.
-/// Only one of three selections are possible.
-/// Selector encodes which selection is currently set.
-enum Selector {
-    OptionA,
-    OptionB,
-    OptionC,
+/// The events of every standard gadget in the toybox,
+/// tagged with the gadget they came from.
+enum GadgetEvent {
+    Ruler(RulerEvent),
+    HProp(PropGadgetEvent),
+    VProp(PropGadgetEvent),
+    XyProp(PropGadgetEvent),
+    Quit(PushButtonEvent),
+    Choice(RadioEvent),
+    EnableChoice(CheckBoxEvent),
+    Push(PushButtonEvent),
+    Greeting(CycleEvent),
+    Name(TextFieldEvent),
+}
+
+/// Focus identifiers for the gadgets the operator can Tab between.
+const QUIT_FOCUS: usize = 0;
+const CHOICE_FOCUS: usize = 1;
+const ENABLE_CHOICE_FOCUS: usize = 2;
+const PUSH_FOCUS: usize = 3;
+const GREETING_FOCUS: usize = 4;
+const NAME_FOCUS: usize = 5;
+
+/// What the open dialog, if any, is asking the operator.
+enum Asking {
+    About,
+    Name,
+    File,
+    Quit,
 }
 
 /// This toybox application
@@ -64,148 +122,351 @@ enum Selector {
 /// This enumeration identifies those individual parts.
 enum Selectable {
     None,
-    LeftRulerKnob,
-    RightRulerKnob,
     TopRulerKnob,
     BottomRulerKnob,
 }
 
-impl<'l, 'f> ToyBoxApp<'l, 'f> {
-    /// Provides the application state with default values.
-    pub fn new() -> Self {
-        Self {
+/// Commands in the pop-up menu.
+const MENU_SEL_A: MenuId = 0;
+const MENU_SEL_B: MenuId = 1;
+const MENU_SEL_C: MenuId = 2;
+const MENU_CENTRE_KNOBS: MenuId = 3;
+const MENU_FILL_KNOBS: MenuId = 4;
+const MENU_CLEAR_NAME: MenuId = 5;
+const MENU_QUIT: MenuId = 6;
+const MENU_ENTER_NAME: MenuId = 7;
+const MENU_OPEN_FILE: MenuId = 8;
+const MENU_ABOUT: MenuId = 9;
+const MENU_HIGH_CONTRAST: MenuId = 10;
+
+/// Builds the menu which pops up when the right mouse button is pressed.
+fn toybox_menu() -> Menu {
+    Menu::new("Toybox", vec![
+        MenuItem::command(MENU_SEL_A, "Select A"),
+        MenuItem::command(MENU_SEL_B, "Select B"),
+        MenuItem::command(MENU_SEL_C, "Select C"),
+        MenuItem::separator(),
+        MenuItem::submenu("Knobs", Menu::new("Knobs", vec![
+            MenuItem::command(MENU_CENTRE_KNOBS, "Centre"),
+            MenuItem::command(MENU_FILL_KNOBS, "Fill track"),
+        ])),
+        MenuItem::command(MENU_ENTER_NAME, "Enter name..."),
+        MenuItem::command(MENU_CLEAR_NAME, "Clear name"),
+        MenuItem::command(MENU_OPEN_FILE, "Open file..."),
+        MenuItem::separator(),
+        MenuItem::command(MENU_HIGH_CONTRAST, "High contrast"),
+        MenuItem::command(MENU_ABOUT, "About..."),
+        MenuItem::command(MENU_QUIT, "Quit").with_shortcut('q'),
+    ])
+}
+
+/// Where gadgets sit until the first reflow places them.
+const UNPLACED: Rect = ((0, 0), (0, 0));
+
+/// Creates a push button labelled in `font`, which the operator can Tab to as `focus_id`.
+fn button(label: &'static str, font: &'static SimpleBitmapFont<'static>, focus_id: FocusId) -> Button {
+    let mut btn = PushButtonView::new(UNPLACED, label, font);
+    btn.set_focus_id(focus_id);
+    btn
+}
+
+impl ToyBoxApp {
+    /// Provides the application state with default values,
+    /// for a desktop `size` pixels across and down,
+    /// with the standard gadgets' text printed in `font`.
+    pub fn new(size: Dimensions, font: &'static SimpleBitmapFont<'static>) -> Self {
+        let mut gadgets = Container::new();
+
+        let ruler_area = ((16, 16), (202, 24));
+        let ruler = gadgets.add(ruler_area, RulerView::new(ruler_area), GadgetEvent::Ruler);
+        let hprop_area = ((16, 30), (202, 38));
+        let hprop = gadgets.add(hprop_area, PropGadgetView::new(hprop_area), GadgetEvent::HProp);
+        let vprop_area = ((210, 46), (218, 184));
+        let vprop = gadgets.add(vprop_area, PropGadgetView::new(vprop_area), GadgetEvent::VProp);
+        let xyprop_area = ((16, 46), (202, 184));
+        let xyprop = gadgets.add(xyprop_area, PropGadgetView::new(xyprop_area), GadgetEvent::XyProp);
+
+        // The controls are placed by a layout, down the right-hand side of the desktop.
+        let quit_btn = gadgets.add(UNPLACED, button("Quit", font, QUIT_FOCUS), GadgetEvent::Quit);
+        let mut radio = RadioGroupView::new(UNPLACED, vec!["Select A", "Select B", "Select C"], font);
+        radio.set_focus_id(CHOICE_FOCUS);
+        let choice = gadgets.add(UNPLACED, radio, GadgetEvent::Choice);
+
+        let mut check = CheckBoxView::new(UNPLACED, "Enable", font);
+        check.set_focus_id(ENABLE_CHOICE_FOCUS);
+        check.set_checked(true);
+        let enable_choice = gadgets.add(UNPLACED, check, GadgetEvent::EnableChoice);
+
+        let push_btn = gadgets.add(UNPLACED, button("Push me!", font, PUSH_FOCUS), GadgetEvent::Push);
+
+        let mut cycle = CycleView::new(UNPLACED, GREETINGS.to_vec(), font);
+        cycle.set_focus_id(GREETING_FOCUS);
+        let greeting = gadgets.add(UNPLACED, cycle, GadgetEvent::Greeting);
+
+        let mut name = TextFieldView::new(UNPLACED, font);
+        name.set_focus_id(NAME_FOCUS);
+        name.set_max_length(Some(32));
+        let name_field = gadgets.add(UNPLACED, name, GadgetEvent::Name);
+
+        let full_width = |id| Layout::aligned(id, Align::Stretch, Align::Start);
+        let controls = Layout::column(2, vec![
+            full_width(quit_btn),
+            Layout::Glue,
+            full_width(choice),
+            full_width(enable_choice),
+            Layout::Glue,
+            full_width(push_btn),
+            full_width(greeting),
+            full_width(name_field),
+        ]);
+
+        let mut toybox = Self {
             dbox_area: ((8, 8), (240, 192)),
             mouse_pt: (0, 0),
             selected: Selectable::None,
-            hr_area: ((16, 16), (202, 24)),
             hr_cursor_left: 16,
             hr_cursor_right: 201,
             vr_area: ((224, 46), (232, 184)),
             vr_cursor_top: 46,
             vr_cursor_bottom: 183,
-            hprop: PropGadgetView::new(((16, 30), (202, 38))),
-            vprop: PropGadgetView::new(((210, 46), (218, 184))),
-            xyprop: PropGadgetView::new(((16, 46), (202, 184))),
-            quit_btn: PushButtonView::new(
-                ((248, 8), (312, 28)),
-                "Quit",
-                &SYSTEM_BITMAP_FONT,
-            ),
-            sel_a: PushButtonView::new(
-                ((248, 48), (312, 68)),
-                "Select A",
-                &SYSTEM_BITMAP_FONT,
-            ),
-            sel_b: PushButtonView::new(
-                ((248, 70), (312, 90)),
-                "Select B",
-                &SYSTEM_BITMAP_FONT,
-            ),
-            sel_c: PushButtonView::new(
-                ((248, 92), (312, 112)),
-                "Select C",
-                &SYSTEM_BITMAP_FONT,
-            ),
-            sel: Selector::OptionA,
-            sel_d: PushButtonView::new(
-                ((248, 134), (312, 154)),
-                "Push me!",
-                &SYSTEM_BITMAP_FONT,
-            ),
-        }
-    }
-
-    fn invert_selection(&mut self, med: &mut dyn Mediator) {
-        match self.sel {
-            Selector::OptionA => self.sel_a.invert(med),
-            Selector::OptionB => self.sel_b.invert(med),
-            Selector::OptionC => self.sel_c.invert(med),
-        }
+            gadgets,
+            ruler,
+            hprop,
+            vprop,
+            xyprop,
+            quit_btn,
+            choice,
+            enable_choice,
+            push_btn,
+            greeting,
+            name_field,
+            focus_ring: FocusRing::new(vec![
+                QUIT_FOCUS,
+                CHOICE_FOCUS,
+                ENABLE_CHOICE_FOCUS,
+                PUSH_FOCUS,
+                GREETING_FOCUS,
+                NAME_FOCUS,
+            ]),
+            controls,
+            popup: PopupMenuView::new(toybox_menu(), font),
+            dialog: None,
+            quit_confirmed: false,
+        };
+        toybox.reflow(size);
+        toybox
     }
 
-    /// Draws the entire application state onto the screen.
-    fn draw(&mut self, med: &mut dyn Mediator) {
-        draw_desktop(med.borrow_mut_desktop());
+    /// Places the controls to suit a desktop `size` pixels across and down,
+    /// to the right of the dialog box.
+    fn reflow(&mut self, size: Dimensions) {
+        let (width, height) = size;
+        let left = self.dbox_area.1.0 + 8;
+        self.gadgets.reflow(&self.controls, ((left, 8), (width - 8, height - 8)));
+    }
+
+    fn button(&mut self, id: ComponentId) -> &mut Button {
+        self.gadgets.get_mut(id).expect("toybox buttons are push buttons")
+    }
 
-        // Draw the quit button
-        self.quit_btn.draw(med);
+    fn prop(&mut self, id: ComponentId) -> &mut PropGadgetView {
+        self.gadgets.get_mut(id).expect("toybox props are prop gadgets")
+    }
 
-        // Draw the mutually-exclusive set of toggle buttons.
-        self.sel_a.draw(med);
-        self.sel_b.draw(med);
-        self.sel_c.draw(med);
-        self.invert_selection(med);
+    fn ruler(&mut self) -> &mut RulerView {
+        self.gadgets.get_mut(self.ruler).expect("toybox ruler is a ruler")
+    }
 
-        // Draw dummy push-button.
-        self.sel_d.draw(med);
+    fn name_field(&mut self) -> &mut TextFieldView<'static> {
+        self.gadgets.get_mut(self.name_field).expect("toybox name field is a text field")
+    }
 
-        // Draw the window in which our prop gadgets will sit.
-        draw_dialog_box(med.borrow_mut_desktop(), self.dbox_area);
+    fn choice(&mut self) -> &mut Choice {
+        self.gadgets.get_mut(self.choice).expect("toybox choice is a radio group")
+    }
 
-        // Draw the custom and standard gadgets.
-        self.draw_rulers(med);
-        self.draw_prop_gadgets(med);
+    fn enable_choice(&mut self) -> &mut CheckBoxView<'static, 'static> {
+        self.gadgets.get_mut(self.enable_choice).expect("toybox enable choice is a check box")
+    }
+
+    fn greeting(&mut self) -> &mut CycleView<'static, 'static> {
+        self.gadgets.get_mut(self.greeting).expect("toybox greeting is a cycle gadget")
+    }
 
+    /// Makes selection `n` the current one, as though the operator had clicked its radio button.
+    fn select(&mut self, med: &mut dyn Mediator, n: usize) {
+        self.choice().set_selected(n);
+        self.choice().draw(med);
         med.repaint_all();
     }
 
-    fn draw_prop_gadgets(&mut self, med: &mut dyn Mediator) {
-        self.hprop
-            .set_knob(((self.hr_cursor_left, 30), (self.hr_cursor_right + 1, 38)));
-        self.hprop.draw(med);
+    /// Opens the pop-up menu at `at`, with its items brought up to date.
+    fn open_menu(&mut self, med: &mut dyn Mediator, at: Point) {
+        let sel = self.choice().get_selected();
+        let can_select = self.choice().is_enabled();
+        let has_name = !self.name_field().get_text().is_empty();
+        let menu = self.popup.menu_mut();
+        for (n, id) in [MENU_SEL_A, MENU_SEL_B, MENU_SEL_C].into_iter().enumerate() {
+            menu.set_checked(id, sel == n);
+            menu.set_enabled(id, can_select);
+        }
+        menu.set_enabled(MENU_CLEAR_NAME, has_name);
+        menu.set_checked(MENU_HIGH_CONTRAST, ptr::eq(med.theme(), &HIGH_CONTRAST_THEME));
+        self.popup.open(med, at);
+    }
 
-        self.vprop
-            .set_knob(((210, self.vr_cursor_top), (218, self.vr_cursor_bottom + 1)));
-        self.vprop.draw(med);
+    /// Carries out whatever command the pop-up menu reports.
+    fn menu_chosen(&mut self, med: &mut dyn Mediator, event: MenuEvent) {
+        let MenuEvent::Selected(id) = event else {
+            return;
+        };
+        match id {
+            MENU_SEL_A => self.select(med, 0),
+            MENU_SEL_B => self.select(med, 1),
+            MENU_SEL_C => self.select(med, 2),
+            MENU_CENTRE_KNOBS | MENU_FILL_KNOBS => {
+                let ((track_left, track_top), (track_right, track_bottom)) = self.xyprop_track();
+                let (dx, dy) = if id == MENU_CENTRE_KNOBS {
+                    ((track_right - track_left) >> 2, (track_bottom - track_top) >> 2)
+                } else {
+                    (0, 0)
+                };
+                self.hr_cursor_left = track_left + dx;
+                self.hr_cursor_right = track_right - 1 - dx;
+                self.vr_cursor_top = track_top + dy;
+                self.vr_cursor_bottom = track_bottom - 1 - dy;
+                self.draw_cursor_gadgets(med);
+            }
+            MENU_CLEAR_NAME => {
+                self.name_field().set_text("");
+                self.name_field().draw(med);
+                med.repaint_all();
+            }
+            MENU_ENTER_NAME => {
+                let name = self.name_field().get_text();
+                let requester = Requester::prompt(med.theme(), "What's your name?", &name);
+                self.ask(med, Asking::Name, requester);
+            }
+            MENU_OPEN_FILE => {
+                let drawer = env::current_dir().unwrap_or_default();
+                let requester = Requester::file(med.theme(), "Pick any file; it won't be opened.", drawer);
+                self.ask(med, Asking::File, requester);
+            }
+            MENU_ABOUT => {
+                let about = "The gadget toybox shows off the standard gadgets of the ROSE stencil library.";
+                let requester = Requester::alert(med.theme(), about);
+                self.ask(med, Asking::About, requester);
+            }
+            MENU_HIGH_CONTRAST => {
+                // Every gadget takes up the new theme as it's redrawn.
+                let high_contrast = ptr::eq(med.theme(), &HIGH_CONTRAST_THEME);
+                med.set_theme(if high_contrast { &CLASSIC_THEME } else { &HIGH_CONTRAST_THEME });
+                self.draw(med);
+            }
+            MENU_QUIT => med.quit(),
+            _ => (),
+        }
+    }
 
-        self.xyprop.set_knob((
-            (self.hr_cursor_left, self.vr_cursor_top),
-            (self.hr_cursor_right + 1, self.vr_cursor_bottom + 1),
-        ));
-        self.xyprop.draw(med);
+    /// Opens `requester`, which has the desktop to itself until the operator answers it.
+    fn ask(&mut self, med: &mut dyn Mediator, asking: Asking, requester: Requester) {
+        self.dialog = Some((asking, ModalDialog::open(med, requester)));
     }
 
-    fn draw_rulers(&mut self, med: &mut dyn Mediator) {
-        self.draw_h_ruler(med);
+    /// Acts on the operator's answer, once the open dialog reports one.
+    fn dialog_event(&mut self, med: &mut dyn Mediator, event: DialogEvent) {
+        let DialogEvent::Dismissed(result) = event else {
+            return;
+        };
+        let Some((asking, _)) = self.dialog.take() else {
+            return;
+        };
+        match (asking, result) {
+            (Asking::Name, DialogResult::Text(name)) => {
+                self.name_field().set_text(&name);
+                self.name_field().draw(med);
+                med.repaint_all();
+                let greeting = GREETINGS[self.greeting().get_current()];
+                println!("{}, {}!", greeting, name);
+            }
+            (Asking::File, DialogResult::File(path)) => println!("You picked {}.", path.display()),
+            (Asking::Quit, DialogResult::Ok) => {
+                self.quit_confirmed = true;
+                med.quit();
+            }
+            _ => (),
+        }
+    }
+
+    fn xyprop_track(&mut self) -> Rect {
+        self.prop(self.xyprop).get_track()
+    }
+
+    /// Draws the entire application state onto the screen.
+    fn draw(&mut self, med: &mut dyn Mediator) {
+        let theme = med.theme();
+        draw_desktop(med.borrow_mut_desktop(), theme);
+
+        // Draw the window in which our prop gadgets will sit.
+        draw_dialog_box(med.borrow_mut_desktop(), self.dbox_area, theme);
+
+        // Draw the standard gadgets, then the custom ones.
+        self.follow_cursors();
+        self.gadgets.draw(med);
         self.draw_v_ruler(med);
+
+        med.repaint_all();
     }
 
-    fn draw_h_ruler(&mut self, med: &mut dyn Mediator) {
-        let d = med.borrow_mut_desktop();
-        let ((hr_left, hr_top), (hr_right, hr_bottom)) = self.hr_area;
-        let hr_rule_y = (hr_top + hr_bottom) >> 1;
-        let hr_cursor_left = self.hr_cursor_left;
-        let hr_cursor_right = self.hr_cursor_right;
-        let hr_knob_bottom = self.hr_area.1 .1 - 1; // Why did I need this again?
-
-        d.filled_rectangle(self.hr_area.0, self.hr_area.1, &WHITE_PATTERN);
-        d.horizontal_line((hr_left, hr_rule_y), hr_right, LINE_BLACK);
-
-        d.vertical_line((hr_cursor_left, hr_top), hr_bottom, LINE_BLACK);
-        d.horizontal_line((hr_cursor_left, hr_top), hr_cursor_left + 8, LINE_BLACK);
-        d.horizontal_line(
-            (hr_cursor_left, hr_knob_bottom),
-            hr_cursor_left + 8,
-            LINE_BLACK,
-        );
+    /// Draws the gadgets the operator can Tab between,
+    /// which must be redrawn whenever the focus moves.
+    fn draw_focusable_gadgets(&mut self, med: &mut dyn Mediator) {
+        for id in [self.quit_btn, self.push_btn] {
+            self.button(id).draw(med);
+        }
+        self.choice().draw(med);
+        self.enable_choice().draw(med);
+        self.greeting().draw(med);
+        self.name_field().draw(med);
+    }
 
-        d.vertical_line((hr_cursor_right, hr_top), hr_bottom, LINE_BLACK);
-        d.horizontal_line((hr_cursor_right - 8, hr_top), hr_cursor_right, LINE_BLACK);
-        d.horizontal_line(
-            (hr_cursor_right - 8, hr_knob_bottom),
-            hr_cursor_right,
-            LINE_BLACK,
-        );
+    /// Brings the prop gadget knobs and the ruler indents into line with the ruler cursors.
+    fn follow_cursors(&mut self) {
+        let (left, right) = (self.hr_cursor_left, self.hr_cursor_right);
+        let (top, bottom) = (self.vr_cursor_top, self.vr_cursor_bottom);
+        self.prop(self.hprop).set_knob(((left, 30), (right + 1, 38)));
+        self.prop(self.vprop).set_knob(((210, top), (218, bottom + 1)));
+        self.prop(self.xyprop).set_knob(((left, top), (right + 1, bottom + 1)));
+
+        // The ruler's indents follow the horizontal extent of the knobs.
+        let ruler = self.ruler();
+        let ((hr_left, _), (hr_right, _)) = ruler.get_area();
+        let mut format = ruler.get_format().clone();
+        format.left_indent = left - hr_left;
+        format.right_indent = hr_right - 1 - right;
+        ruler.set_format(format);
+    }
+
+    /// Redraws the gadgets which follow the ruler cursors.
+    fn draw_cursor_gadgets(&mut self, med: &mut dyn Mediator) {
+        self.follow_cursors();
+        self.ruler().draw(med);
+        self.draw_v_ruler(med);
+        for id in [self.hprop, self.vprop, self.xyprop] {
+            self.prop(id).draw(med);
+        }
+        med.repaint_all();
     }
 
     fn draw_v_ruler(&mut self, med: &mut dyn Mediator) {
+        let paper = &med.theme().paper;
         let d = med.borrow_mut_desktop();
         let ((vr_left, vr_top), (vr_right, vr_bottom)) = self.vr_area;
         let vr_rule_x = (vr_left + vr_right) >> 1;
         let vr_cursor_top = self.vr_cursor_top;
         let vr_cursor_bottom = self.vr_cursor_bottom;
 
-        d.filled_rectangle(self.vr_area.0, self.vr_area.1, &WHITE_PATTERN);
+        d.filled_rectangle(self.vr_area.0, self.vr_area.1, paper);
         d.vertical_line((vr_rule_x, vr_top), vr_bottom, LINE_BLACK);
 
         d.horizontal_line((vr_left, vr_cursor_top), vr_right, LINE_BLACK);
@@ -227,79 +488,46 @@ impl<'l, 'f> ToyBoxApp<'l, 'f> {
 }
 
 /// Tell the host environment that we are equipped to represent the whole application.
-impl<'l, 'f> AppController for ToyBoxApp<'l, 'f> {}
+impl AppController for ToyBoxApp {}
 
 /// Tell the host environment we can determine the application life-cycle.
-impl<'l, 'f> AppEventSink for ToyBoxApp<'l, 'f> {
-    fn request_quit(&self) -> bool {
-        // We have no reason to deny quitting, so yes.
-        true
+impl AppEventSink for ToyBoxApp {
+    /// Quits right away, unless the operator has typed in their name;
+    /// then, asks whether they're sure first.
+    fn request_quit(&mut self, med: &mut dyn Mediator) -> bool {
+        if self.quit_confirmed || self.name_field().get_text().is_empty() {
+            return true;
+        }
+        if self.dialog.is_none() {
+            let requester = Requester::confirm(med.theme(), "Quit the toybox, and forget your name?");
+            self.ask(med, Asking::Quit, requester);
+        }
+        false
     }
 }
 
 /// Sink for host environment mouse events.
-impl<'l, 'f> MouseEventSink<()> for ToyBoxApp<'l, 'f> {
+///
+/// The container routes events to the standard gadgets;
+/// we only look after the custom vertical ruler ourselves.
+/// While a dialog is open, it has the mouse to itself.
+impl MouseEventSink<()> for ToyBoxApp {
     fn pointer_moved(&mut self, med: &mut dyn Mediator, pt: Point) {
         self.mouse_pt = pt;
-
-        // Let the gadgets handle their own pointer motion events.
-
-        match self.xyprop.pointer_moved(med, pt) {
-            PropGadgetEvent::KnobMoved(((left, top), (right, bottom))) => {
-                self.hr_cursor_left = left;
-                self.vr_cursor_top = top;
-                self.hr_cursor_right = right - 1;
-                self.vr_cursor_bottom = bottom - 1;
-            }
-
-            _ => (),
+        if let Some((_, dialog)) = &mut self.dialog {
+            let event = dialog.pointer_moved(med, pt);
+            self.dialog_event(med, event);
+            return;
         }
-
-        match self.vprop.pointer_moved(med, pt) {
-            PropGadgetEvent::KnobMoved(((_, top), (_, bottom))) => {
-                self.vr_cursor_top = top;
-                self.vr_cursor_bottom = bottom - 1;
-            }
-
-            _ => (),
-        }
-
-        match self.hprop.pointer_moved(med, pt) {
-            PropGadgetEvent::KnobMoved(((left, _), (right, _))) => {
-                self.hr_cursor_left = left;
-                self.hr_cursor_right = right - 1;
-            }
-
-            _ => (),
+        if self.popup.is_open() {
+            let _ = self.popup.pointer_moved(med, pt);
+            return;
         }
 
-        let _ = self.quit_btn.pointer_moved(med, pt);
-
-        let _ = self.sel_a.pointer_moved(med, pt);
-        let _ = self.sel_b.pointer_moved(med, pt);
-        let _ = self.sel_c.pointer_moved(med, pt);
-        let _ = self.sel_d.pointer_moved(med, pt);
-
-        // Now let's consider pointer motion events for what the user
-        // thinks are custom gadgets.
+        let events = self.gadgets.pointer_moved(med, pt);
+        self.handle(med, events);
 
         match self.selected {
-            Selectable::LeftRulerKnob => {
-                let new_x = pt.0;
-
-                if (self.hr_area.0 .0 <= new_x) && (new_x < self.hr_cursor_right - 16) {
-                    self.hr_cursor_left = new_x;
-                }
-            }
-
-            Selectable::RightRulerKnob => {
-                let new_x = pt.0;
-
-                if (self.hr_cursor_left + 16 <= new_x) && (new_x < self.hr_area.1 .0) {
-                    self.hr_cursor_right = new_x;
-                }
-            }
-
             Selectable::TopRulerKnob => {
                 let new_y = pt.1;
 
@@ -319,136 +547,253 @@ impl<'l, 'f> MouseEventSink<()> for ToyBoxApp<'l, 'f> {
             _ => (),
         }
 
-        self.draw_rulers(med);
-        self.draw_prop_gadgets(med);
-        med.repaint_all();
+        self.draw_cursor_gadgets(med);
     }
 
-    fn button_down(&mut self, med: &mut dyn Mediator) {
-        // Handle button events for the regular gadgets.
-
-        let _ = self.xyprop.button_down(med);
-        let _ = self.vprop.button_down(med);
-        let _ = self.hprop.button_down(med);
-        let _ = self.quit_btn.button_down(med);
+    fn button_down(&mut self, med: &mut dyn Mediator, event: ButtonEvent) {
+        self.mouse_pt = event.at;
+        if let Some((_, dialog)) = &mut self.dialog {
+            let event = dialog.button_down(med, event);
+            self.dialog_event(med, event);
+            return;
+        }
 
-        let _ = self.sel_a.button_down(med);
-        let _ = self.sel_b.button_down(med);
-        let _ = self.sel_c.button_down(med);
-        let _ = self.sel_d.button_down(med);
+        // While the menu is open, it has the mouse to itself.
+        if self.popup.is_open() {
+            let _ = self.popup.button_down(med, event);
+            return;
+        }
+        if event.button == MouseButton::Right {
+            self.open_menu(med, event.at);
+            return;
+        }
 
-        // Handle button events for the custom gadgets.
+        // Clicking in the text field may take the focus from another gadget.
+        let focus = med.focus();
+        let events = self.gadgets.button_down(med, event);
+        self.handle(med, events);
+        if med.focus() != focus {
+            self.draw_focusable_gadgets(med);
+            med.repaint_all();
+        }
 
-        if self.mouse_in_hr_left_cursor() {
-            self.selected = Selectable::LeftRulerKnob;
-        } else if self.mouse_in_hr_right_cursor() {
-            self.selected = Selectable::RightRulerKnob;
-        } else if self.mouse_in_vr_top_cursor() {
+        if event.button != MouseButton::Left {
+            return;
+        }
+        if self.mouse_in_vr_top_cursor() {
             self.selected = Selectable::TopRulerKnob;
         } else if self.mouse_in_vr_bottom_cursor() {
             self.selected = Selectable::BottomRulerKnob;
         }
     }
 
-    fn button_up(&mut self, med: &mut dyn Mediator) {
-        // Handle button events for the regular gadgets.
+    fn button_up(&mut self, med: &mut dyn Mediator, event: ButtonEvent) {
+        if let Some((_, dialog)) = &mut self.dialog {
+            let event = dialog.button_up(med, event);
+            self.dialog_event(med, event);
+            return;
+        }
+        if self.popup.is_open() {
+            let chosen = self.popup.button_up(med, event);
+            self.menu_chosen(med, chosen);
+            return;
+        }
 
-        let _ = self.xyprop.button_up(med);
-        let _ = self.vprop.button_up(med);
-        let _ = self.hprop.button_up(med);
+        let events = self.gadgets.button_up(med, event);
+        self.handle(med, events);
+        self.selected = Selectable::None;
 
-        match self.quit_btn.button_up(med) {
-            PushButtonEvent::Clicked => {
-                med.quit();
+        // Text dragged out of the name field can be dropped back into it.
+        // Dropping it there gives the field the focus, too.
+        if let Some(session) = med.end_drag() {
+            let focus = med.focus();
+            let event = self.name_field().accept_drop(med, session.data(), event.at);
+            self.handle(med, vec![GadgetEvent::Name(event)]);
+            if med.focus() != focus {
+                self.draw_focusable_gadgets(med);
             }
-            _ => (),
+            med.repaint_all();
         }
+    }
 
-        match self.sel_a.button_up(med) {
-            PushButtonEvent::Clicked => {
-                // New setting already highlit; "deselect" old setting.
-                self.invert_selection(med);
-                self.sel = Selector::OptionA;
-                med.repaint_all();
-            }
-            _ => (),
+    fn enter(&mut self, med: &mut dyn Mediator, at: Point) {
+        if let Some((_, dialog)) = &mut self.dialog {
+            let event = dialog.enter(med, at);
+            self.dialog_event(med, event);
+            return;
         }
+        let events = self.gadgets.enter(med, at);
+        self.handle(med, events);
+    }
 
-        match self.sel_b.button_up(med) {
-            PushButtonEvent::Clicked => {
-                self.invert_selection(med);
-                self.sel = Selector::OptionB;
-                med.repaint_all();
-            }
-            _ => (),
+    fn wheel(&mut self, med: &mut dyn Mediator, delta: Point, modifiers: Modifiers) {
+        if let Some((_, dialog)) = &mut self.dialog {
+            let event = dialog.wheel(med, delta, modifiers);
+            self.dialog_event(med, event);
+            return;
         }
+        let events = self.gadgets.wheel(med, delta, modifiers);
+        self.handle(med, events);
+        self.draw_cursor_gadgets(med);
+    }
 
-        match self.sel_c.button_up(med) {
-            PushButtonEvent::Clicked => {
-                self.invert_selection(med);
-                self.sel = Selector::OptionC;
-                med.repaint_all();
-            }
-            _ => (),
+    fn leave(&mut self, med: &mut dyn Mediator) {
+        if let Some((_, dialog)) = &mut self.dialog {
+            let event = dialog.leave(med);
+            self.dialog_event(med, event);
+            return;
         }
+        let events = self.gadgets.leave(med);
+        self.handle(med, events);
+    }
+}
 
-        match self.sel_d.button_up(med) {
-            PushButtonEvent::Clicked => {
-                // Not selected anymore, so undo inversion.
-                self.sel_d.invert(med);
-                med.repaint_all();
-                println!("You clicked me!");
-            }
-            _ => (),
+/// Sink for host environment keyboard events.
+///
+/// While a dialog is open, it has the keyboard to itself, too.
+impl KeyboardEventSink<()> for ToyBoxApp {
+    fn key_down(&mut self, med: &mut dyn Mediator, key: KeyCode, modifiers: Modifiers) {
+        if let Some((_, dialog)) = &mut self.dialog {
+            let event = dialog.key_down(med, key, modifiers);
+            self.dialog_event(med, event);
+            return;
         }
 
-        // Handle button events for the custom gadgets.
+        // The menu has the keyboard to itself while it's open, and its shortcuts work anytime.
+        let was_open = self.popup.is_open();
+        let chosen = self.popup.key_down(med, key, modifiers);
+        if was_open || (chosen != MenuEvent::None) {
+            self.menu_chosen(med, chosen);
+            return;
+        }
 
-        self.selected = Selectable::None;
+        if let FocusEvent::Moved { .. } = self.focus_ring.key_down(med, key, modifiers) {
+            self.draw_focusable_gadgets(med);
+            med.repaint_all();
+            return;
+        }
+
+        if let PushButtonEvent::Clicked = self.button(self.quit_btn).key_down(med, key, modifiers) {
+            med.quit();
+        }
+        if let PushButtonEvent::Clicked = self.button(self.push_btn).key_down(med, key, modifiers) {
+            println!("You clicked me!");
+        }
+
+        let events = vec![
+            GadgetEvent::Choice(self.choice().key_down(med, key, modifiers)),
+            GadgetEvent::EnableChoice(self.enable_choice().key_down(med, key, modifiers)),
+            GadgetEvent::Greeting(self.greeting().key_down(med, key, modifiers)),
+            GadgetEvent::Name(self.name_field().key_down(med, key, modifiers)),
+        ];
+        self.handle(med, events);
     }
 
-    fn enter(&mut self, med: &mut dyn Mediator, at: Point) {
-        let _ = self.xyprop.enter(med, at);
-        let _ = self.vprop.enter(med, at);
-        let _ = self.hprop.enter(med, at);
-        let _ = self.quit_btn.enter(med, at);
-        let _ = self.sel_a.enter(med, at);
-        let _ = self.sel_b.enter(med, at);
-        let _ = self.sel_c.enter(med, at);
-        let _ = self.sel_d.enter(med, at);
+    fn key_up(&mut self, med: &mut dyn Mediator, key: KeyCode, modifiers: Modifiers) {
+        if let Some((_, dialog)) = &mut self.dialog {
+            let event = dialog.key_up(med, key, modifiers);
+            self.dialog_event(med, event);
+            return;
+        }
+        let _ = self.name_field().key_up(med, key, modifiers);
     }
 
-    fn leave(&mut self, med: &mut dyn Mediator) {
-        let _ = self.xyprop.leave(med);
-        let _ = self.vprop.leave(med);
-        let _ = self.hprop.leave(med);
-        let _ = self.quit_btn.leave(med);
-        let _ = self.sel_a.leave(med);
-        let _ = self.sel_b.leave(med);
-        let _ = self.sel_c.leave(med);
-        let _ = self.sel_d.leave(med);
+    fn text_input(&mut self, med: &mut dyn Mediator, text: &str) {
+        if let Some((_, dialog)) = &mut self.dialog {
+            let event = dialog.text_input(med, text);
+            self.dialog_event(med, event);
+            return;
+        }
+        if self.popup.is_open() {
+            return;
+        }
+        let _ = self.name_field().text_input(med, text);
     }
 }
 
-impl<'l, 'f> ToyBoxApp<'l, 'f> {
-    fn mouse_in_hr_left_cursor(&self) -> bool {
-        let cursor_left = self.hr_cursor_left;
-        let cursor_top = self.hr_area.0 .1;
-        let cursor_right = cursor_left + 8;
-        let cursor_bottom = self.hr_area.1 .1;
+/// Sink for host environment timer events.
+///
+/// The toybox starts no timers of its own, and wants no idle time;
+/// but an open dialog's gadgets might.
+impl TimerEventSink<()> for ToyBoxApp {
+    fn timer_fired(&mut self, med: &mut dyn Mediator, id: TimerId) {
+        if let Some((_, dialog)) = &mut self.dialog {
+            let event = dialog.timer_fired(med, id);
+            self.dialog_event(med, event);
+        }
+    }
 
-        let cursor_area = ((cursor_left, cursor_top), (cursor_right, cursor_bottom));
-        rect_contains(cursor_area, self.mouse_pt)
+    fn idle(&mut self, med: &mut dyn Mediator) {
+        if let Some((_, dialog)) = &mut self.dialog {
+            let event = dialog.idle(med);
+            self.dialog_event(med, event);
+        }
     }
+}
 
-    fn mouse_in_hr_right_cursor(&self) -> bool {
-        let cursor_right = self.hr_cursor_right;
-        let cursor_bottom = self.hr_area.1 .1;
-        let cursor_left = cursor_right - 8;
-        let cursor_top = self.hr_area.0 .1;
+impl ToyBoxApp {
+    /// Responds to what the standard gadgets report.
+    fn handle(&mut self, med: &mut dyn Mediator, events: Vec<GadgetEvent>) {
+        for event in events {
+            match event {
+                GadgetEvent::XyProp(PropGadgetEvent::KnobMoved(((left, top), (right, bottom)))) => {
+                    self.hr_cursor_left = left;
+                    self.vr_cursor_top = top;
+                    self.hr_cursor_right = right - 1;
+                    self.vr_cursor_bottom = bottom - 1;
+                }
 
-        let cursor_area = ((cursor_left, cursor_top), (cursor_right, cursor_bottom));
-        rect_contains(cursor_area, self.mouse_pt)
+                GadgetEvent::VProp(PropGadgetEvent::KnobMoved(((_, top), (_, bottom)))) => {
+                    self.vr_cursor_top = top;
+                    self.vr_cursor_bottom = bottom - 1;
+                }
+
+                GadgetEvent::HProp(PropGadgetEvent::KnobMoved(((left, _), (right, _)))) => {
+                    self.hr_cursor_left = left;
+                    self.hr_cursor_right = right - 1;
+                }
+
+                GadgetEvent::Ruler(RulerEvent::FormatChanged(format)) => {
+                    let ruler = self.ruler();
+                    let ((hr_left, _), (hr_right, _)) = ruler.get_area();
+                    self.hr_cursor_left = hr_left + format.indent(false);
+                    self.hr_cursor_right = hr_right - 1 - format.right_indent;
+                    self.ruler().set_format(format);
+                    self.draw_cursor_gadgets(med);
+                }
+
+                GadgetEvent::Quit(PushButtonEvent::Clicked) => med.quit(),
+
+                GadgetEvent::Choice(RadioEvent::Selected(n)) => {
+                    println!("Selected {}.", ["A", "B", "C"][n]);
+                }
+
+                // The radio group can be switched off, to show what disabled gadgets look like.
+                GadgetEvent::EnableChoice(CheckBoxEvent::Toggled(enabled)) => {
+                    self.choice().set_enabled(enabled);
+                    self.choice().draw(med);
+                    med.repaint_all();
+                }
+
+                GadgetEvent::Push(PushButtonEvent::Clicked) => {
+                    // Not selected anymore, so undo inversion.
+                    self.button(self.push_btn).invert(med);
+                    med.repaint_all();
+                    println!("You clicked me!");
+                }
+
+                GadgetEvent::Greeting(CycleEvent::Chose(n)) => {
+                    println!("Greeting with \"{}\" from now on.", GREETINGS[n]);
+                }
+
+                GadgetEvent::Name(TextFieldEvent::Committed) => {
+                    let greeting = GREETINGS[self.greeting().get_current()];
+                    println!("{}, {}!", greeting, self.name_field().get_text());
+                }
+
+                _ => (),
+            }
+        }
     }
 
     fn mouse_in_vr_top_cursor(&self) -> bool {
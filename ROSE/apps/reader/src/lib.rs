@@ -0,0 +1,4 @@
+//! The reader's text view,
+//! which other applications may show text files in, too.
+
+pub mod text_view;
@@ -0,0 +1,217 @@
+//! A scrolling view of a text file.
+
+use std::fs;
+use std::path::Path;
+
+use stencil::chase::Forme;
+use stencil::events::{ButtonEvent, KeyCode, KeyboardEventSink, Modifiers, MouseEventSink, TimerEventSink};
+use stencil::gadgets::proportional::PropAxis;
+use stencil::gadgets::scroll_bar::{Orientation, ScrollBarEvent, ScrollBarView, THICKNESS, WHEEL_LINES};
+use stencil::mediator::Mediator;
+use stencil::paragraph::PLAIN_PARAGRAPH;
+use stencil::simple_bitmap_font::SimpleBitmapFont;
+use stencil::stencil::Draw;
+use stencil::timers::TimerId;
+use stencil::types::{Point, Rect};
+use stencil::view::{rect_contains, View};
+use stencil::wrapping_printer::WrappingPrinter;
+
+/// Answers the contents of the file at `path`, as text;
+/// or, if it couldn't be read, why not.
+///
+/// Files need not be ASCII, or even valid UTF-8.
+/// Anything we can't decode is shown as the font's unknown glyph.
+pub fn read_text(path: &Path) -> String {
+    match fs::read(path) {
+        Ok(contents) => String::from_utf8_lossy(&contents).into_owned(),
+        Err(e) => format!("Could not open {} because...\n{}", path.display(), e),
+    }
+}
+
+/// Shows text, a screenful at a time, scrolled with a scroll bar or the mouse wheel.
+pub struct TextView {
+    /// The text being read.
+    text: String,
+
+    /// Where each wrapped line of `text` starts, in bytes.
+    line_starts: Vec<usize>,
+
+    /// The first line shown.
+    top: usize,
+
+    /// The rectangle the view occupies, scroll bar and all.
+    area: Rect,
+
+    /// Where the text is printed.
+    text_area: Rect,
+
+    /// Scrolls the text up and down.
+    scroll_bar: ScrollBarView,
+
+    /// The font the text is printed in.
+    font: &'static SimpleBitmapFont<'static>,
+
+    /// Tracks the current mouse position.
+    mouse_pt: Point,
+}
+
+impl TextView {
+    /// Creates a view showing `text` in `area`, printed in `font`,
+    /// with a scroll bar down its right-hand side.
+    pub fn new(text: String, area: Rect, font: &'static SimpleBitmapFont<'static>) -> Self {
+        let mut view = Self {
+            text,
+            line_starts: Vec::new(),
+            top: 0,
+            area,
+            text_area: area,
+            scroll_bar: ScrollBarView::new(area, Orientation::Vertical),
+            font,
+            mouse_pt: (0, 0),
+        };
+        view.set_area(area);
+        view
+    }
+
+    /// Wraps the text to fit the text area, and notes where each line starts.
+    fn wrap(&mut self) {
+        let ((left, _), (right, _)) = self.text_area;
+        let mut forme = Forme::new((right - left).max(0) as usize, self.font);
+        forme.layout_from_str(&self.text);
+
+        let byte_offsets: Vec<usize> = self.text.char_indices().map(|(i, _)| i).collect();
+        self.line_starts = forme.borrow_lines().iter()
+            .map(|&(start, _)| byte_offsets.get(start).copied().unwrap_or(self.text.len()))
+            .collect();
+    }
+
+    /// Answers how many lines fit in the text area.
+    fn lines_visible(&self) -> usize {
+        let ((_, top), (_, bottom)) = self.text_area;
+        ((bottom - top) / PLAIN_PARAGRAPH.line_spacing.line_height(self.font.height)).max(1) as usize
+    }
+
+    /// Answers which of the text's lines are showing, for the scroll bar.
+    fn values(&self) -> PropAxis {
+        PropAxis::new(self.line_starts.len(), self.lines_visible(), self.top)
+    }
+
+    /// Prints the text from the top line down, as far as it fits.
+    fn print_text(&self, med: &mut dyn Mediator) {
+        let paper = &med.theme().paper;
+        let desktop = med.borrow_mut_desktop();
+        let (upper_left, lower_right) = self.text_area;
+        desktop.filled_rectangle(upper_left, lower_right, paper);
+
+        let start = self.line_starts.get(self.top).copied().unwrap_or(self.text.len());
+        let mut printer = WrappingPrinter::new(desktop, self.text_area, self.font);
+        printer.print(&self.text[start..]);
+        med.repaint_all();
+    }
+
+    /// Scrolls so that the text starts at line `top`, as near as it can.
+    fn scroll_to(&mut self, med: &mut dyn Mediator, top: isize) {
+        let values = PropAxis::new(self.line_starts.len(), self.lines_visible(), top.max(0) as usize);
+        if values.top != self.top {
+            self.top = values.top;
+            self.print_text(med);
+        }
+        self.scroll_bar.set_values(values);
+        self.scroll_bar.draw(med);
+    }
+
+    /// Acts on what the scroll bar asked for.
+    fn handle(&mut self, med: &mut dyn Mediator, evt: ScrollBarEvent) {
+        let top = self.top as isize;
+        match evt {
+            ScrollBarEvent::ScrollLines(n) => self.scroll_to(med, top + n),
+            ScrollBarEvent::ScrollPages(n) => self.scroll_to(med, top + n * self.lines_visible() as isize),
+            ScrollBarEvent::ScrollTo(line) => self.scroll_to(med, line as isize),
+            ScrollBarEvent::None => (),
+        }
+    }
+}
+
+impl View for TextView {
+    fn draw(&mut self, med: &mut dyn Mediator) {
+        let ((left, top), (_, bottom)) = self.area;
+        let ((bar_left, _), _) = self.scroll_bar.get_area();
+        let paper = &med.theme().paper;
+        med.borrow_mut_desktop().filled_rectangle((left, top), (bar_left, bottom), paper);
+        self.print_text(med);
+        self.scroll_bar.draw(med);
+    }
+
+    /// Moves the view to `area`, wrapping the text afresh to suit its width.
+    fn set_area(&mut self, area: Rect) {
+        let ((left, top), (right, bottom)) = area;
+        let bar_left = right - THICKNESS;
+        self.area = area;
+        self.text_area = ((left + 2, top + 2), (bar_left - 2, bottom - 2));
+        self.scroll_bar.set_area(((bar_left, top), (right, bottom)));
+        self.wrap();
+        self.top = self.values().top;
+        self.scroll_bar.set_values(self.values());
+    }
+}
+
+/// The scroll bar gets first refusal of the mouse.
+/// The wheel scrolls the text from anywhere over it, too.
+impl MouseEventSink<()> for TextView {
+    fn pointer_moved(&mut self, med: &mut dyn Mediator, to: Point) {
+        self.mouse_pt = to;
+        let evt = self.scroll_bar.pointer_moved(med, to);
+        self.handle(med, evt);
+    }
+
+    fn button_up(&mut self, med: &mut dyn Mediator, event: ButtonEvent) {
+        let evt = self.scroll_bar.button_up(med, event);
+        self.handle(med, evt);
+    }
+
+    fn button_down(&mut self, med: &mut dyn Mediator, event: ButtonEvent) {
+        let evt = self.scroll_bar.button_down(med, event);
+        self.handle(med, evt);
+    }
+
+    fn wheel(&mut self, med: &mut dyn Mediator, delta: Point, modifiers: Modifiers) {
+        let evt = if rect_contains(self.text_area, self.mouse_pt) {
+            ScrollBarEvent::ScrollLines(-delta.1 * WHEEL_LINES)
+        } else {
+            self.scroll_bar.wheel(med, delta, modifiers)
+        };
+        self.handle(med, evt);
+    }
+
+    fn enter(&mut self, _med: &mut dyn Mediator, _at: Point) {}
+
+    fn leave(&mut self, _med: &mut dyn Mediator) {}
+}
+
+/// The cursor keys scroll too.
+impl KeyboardEventSink<()> for TextView {
+    fn key_down(&mut self, med: &mut dyn Mediator, key: KeyCode, _modifiers: Modifiers) {
+        let evt = match key {
+            KeyCode::Up => ScrollBarEvent::ScrollLines(-1),
+            KeyCode::Down => ScrollBarEvent::ScrollLines(1),
+            KeyCode::Home => ScrollBarEvent::ScrollTo(0),
+            KeyCode::End => ScrollBarEvent::ScrollTo(self.line_starts.len()),
+            _ => ScrollBarEvent::None,
+        };
+        self.handle(med, evt);
+    }
+
+    fn key_up(&mut self, _med: &mut dyn Mediator, _key: KeyCode, _modifiers: Modifiers) {}
+
+    fn text_input(&mut self, _med: &mut dyn Mediator, _text: &str) {}
+}
+
+/// Holding a scroll bar arrow down repeats it.
+impl TimerEventSink<()> for TextView {
+    fn timer_fired(&mut self, med: &mut dyn Mediator, id: TimerId) {
+        let evt = self.scroll_bar.timer_fired(med, id);
+        self.handle(med, evt);
+    }
+
+    fn idle(&mut self, _med: &mut dyn Mediator) {}
+}
@@ -0,0 +1,335 @@
+//! A ROSE host environment built on SDL.
+//!
+//! [[run]] opens a window, hands a desktop stencil to the application,
+//! and then feeds it events until it quits:
+//! mouse and keyboard events translated from SDL,
+//! plus the timer and idle events the application asks for through its [[Mediator]].
+//!
+//! The host draws the mouse pointer itself, in place of the system's,
+//! as the desktop is copied to the window;
+//! and the outline of anything being dragged along with it.
+//! Text put on the clipboard goes on the system clipboard too.
+
+use sdl2::event::{Event, WindowEvent};
+use sdl2::clipboard::ClipboardUtil;
+use sdl2::mouse::MouseWheelDirection;
+use sdl2::TimerSubsystem;
+use stencil::events::{AppController, ButtonEvent, ClickCounter};
+use stencil::focus::FocusId;
+use stencil::mediator::Mediator;
+use stencil::pointer::{PointerShape, ARROW};
+use stencil::stencil::{Draw, Stencil};
+use stencil::theme::{Theme, CLASSIC_THEME};
+use stencil::timers::{TimerId, TimerQueue};
+use stencil::transfer::{DragSession, TransferData};
+use stencil::types::{Dimension, Point, Unit};
+use stencil::utils::invert_outline;
+
+use crate::{button_for, key_for, modifiers_for, SdlState};
+
+/// Runs a ROSE application in a `width` by `height` window titled `title`.
+///
+/// The application is created by `init`,
+/// which is expected to draw the initial desktop as well.
+/// Returns once the application agrees to quit.
+pub fn run<F>(title: &str, width: Dimension, height: Dimension, init: F)
+where
+    F: FnOnce(&mut dyn Mediator) -> Box<dyn AppController>,
+{
+    let mut sdl = SdlState::new(title, width as u32, height as u32);
+    let mut event_pump = sdl.context.event_pump().expect("event pump issue");
+    let timer = sdl.context.timer().expect("timer subsystem issue");
+    let clipboard = sdl.context.video().expect("video subsystem issue").clipboard();
+    sdl.context.mouse().show_cursor(false);
+
+    let mut med = SdlMediator::new(Stencil::new_with_dimensions(width, height), timer, clipboard);
+    let mut app = init(&mut med);
+    let mut clicks = ClickCounter::default();
+    med.try_redrawing(&mut sdl);
+
+    loop {
+        // Sleep until the next event, or until the next timer is due,
+        // or not at all if the application wants idle time.
+        let now = med.now();
+        let event = if med.idle_requested {
+            event_pump.poll_event()
+        } else {
+            match med.timers.next_deadline() {
+                Some(deadline) => event_pump.wait_event_timeout(deadline.saturating_sub(now) as u32),
+                None => Some(event_pump.wait_event()),
+            }
+        };
+
+        match event {
+            Some(Event::Quit { .. }) => {
+                if app.request_quit(&mut med) {
+                    break;
+                }
+            }
+            Some(Event::MouseMotion { x, y, .. }) => {
+                let at = (x as Unit, y as Unit);
+                med.move_pointer(Some(at));
+                app.pointer_moved(&mut med, at);
+            }
+            Some(Event::MouseButtonDown { mouse_btn: b, x, y, timestamp, .. }) => {
+                if let Some(button) = button_for(b) {
+                    let at = (x as Unit, y as Unit);
+                    let event = ButtonEvent {
+                        button,
+                        at,
+                        clicks: clicks.press(button, at, timestamp),
+                        modifiers: modifiers_for(sdl.context.keyboard().mod_state()),
+                    };
+                    app.button_down(&mut med, event);
+                }
+            }
+            Some(Event::MouseButtonUp { mouse_btn: b, x, y, .. }) => {
+                if let Some(button) = button_for(b) {
+                    let event = ButtonEvent {
+                        button,
+                        at: (x as Unit, y as Unit),
+                        clicks: clicks.count(),
+                        modifiers: modifiers_for(sdl.context.keyboard().mod_state()),
+                    };
+                    app.button_up(&mut med, event);
+                }
+            }
+            Some(Event::MouseWheel { x, y, direction, .. }) => {
+                let delta = match direction {
+                    MouseWheelDirection::Flipped => (-x as Unit, -y as Unit),
+                    _ => (x as Unit, y as Unit),
+                };
+                let modifiers = modifiers_for(sdl.context.keyboard().mod_state());
+                app.wheel(&mut med, delta, modifiers);
+            }
+            Some(Event::KeyDown { keycode: Some(k), keymod: m, .. }) => {
+                if let Some(key) = key_for(k) {
+                    app.key_down(&mut med, key, modifiers_for(m));
+                }
+            }
+            Some(Event::KeyUp { keycode: Some(k), keymod: m, .. }) => {
+                if let Some(key) = key_for(k) {
+                    app.key_up(&mut med, key, modifiers_for(m));
+                }
+            }
+            Some(Event::TextInput { text, .. }) => {
+                app.text_input(&mut med, &text);
+            }
+            Some(Event::Window { win_event: WindowEvent::Exposed, .. }) => {
+                med.repaint_all();
+            }
+            Some(Event::Window { win_event: WindowEvent::Leave, .. }) => {
+                med.move_pointer(None);
+            }
+            Some(_) => (),
+            None => {
+                if med.idle_requested {
+                    med.idle_requested = false;
+                    app.idle(&mut med);
+                }
+            }
+        }
+
+        let now = med.now();
+        for id in med.timers.expire(now) {
+            app.timer_fired(&mut med, id);
+        }
+
+        med.try_redrawing(&mut sdl);
+
+        if med.quit_requested {
+            if app.request_quit(&mut med) {
+                break;
+            }
+            med.quit_requested = false;
+        }
+    }
+}
+
+/// The mediator through which applications reach the SDL host.
+struct SdlMediator {
+    desktop: Stencil,
+    timer: TimerSubsystem,
+    timers: TimerQueue,
+    focus: Option<FocusId>,
+    quit_requested: bool,
+    needs_repaint: bool,
+    idle_requested: bool,
+    pointer: &'static PointerShape,
+
+    /// Where the pointer is, or `None` while it's outside the window.
+    pointer_at: Option<Point>,
+
+    /// The system clipboard, which holds any text put on ours.
+    clipboard: ClipboardUtil,
+
+    /// A bitmap put on the clipboard, which the system clipboard can't hold.
+    bitmap: Option<Stencil>,
+
+    /// Whatever is being dragged, if anything.
+    drag: Option<DragSession>,
+
+    /// The theme gadgets draw themselves in.
+    theme: &'static Theme,
+}
+
+impl SdlMediator {
+    fn new(desktop: Stencil, timer: TimerSubsystem, clipboard: ClipboardUtil) -> Self {
+        Self {
+            desktop,
+            timer,
+            timers: TimerQueue::new(),
+            focus: None,
+            quit_requested: false,
+            needs_repaint: false,
+            idle_requested: false,
+            pointer: &ARROW,
+            pointer_at: None,
+            clipboard,
+            bitmap: None,
+            drag: None,
+            theme: &CLASSIC_THEME,
+        }
+    }
+
+    /// Answers the number of milliseconds since SDL started.
+    fn now(&self) -> u64 {
+        u64::from(self.timer.ticks())
+    }
+
+    /// Moves the pointer to `at`, or hides it if `None`,
+    /// taking anything being dragged along with it.
+    fn move_pointer(&mut self, at: Option<Point>) {
+        if at != self.pointer_at {
+            self.pointer_at = at;
+            if let (Some(drag), Some(at)) = (&mut self.drag, at) {
+                drag.move_to(at);
+            }
+            self.needs_repaint = true;
+        }
+    }
+
+    /// Repaints the window if anything has changed,
+    /// drawing the drag outline and the pointer onto the desktop
+    /// only for as long as that takes.
+    fn try_redrawing(&mut self, sdl: &mut SdlState) {
+        if self.needs_repaint {
+            let outline = self.drag.as_ref().map(DragSession::outline);
+            if let Some(outline) = outline {
+                invert_outline(&mut self.desktop, outline);
+            }
+            let saved = self.pointer_at.map(|at| self.pointer.draw(&mut self.desktop, at));
+            repaint(&self.desktop, sdl);
+            if let Some(saved) = saved {
+                self.desktop.restore_area(&saved);
+            }
+            if let Some(outline) = outline {
+                invert_outline(&mut self.desktop, outline);
+            }
+            self.needs_repaint = false;
+        }
+    }
+}
+
+impl Mediator for SdlMediator {
+    fn repaint_all(&mut self) {
+        self.needs_repaint = true;
+    }
+
+    fn quit(&mut self) {
+        self.quit_requested = true;
+    }
+
+    fn borrow_mut_desktop(&mut self) -> &mut Stencil {
+        &mut self.desktop
+    }
+
+    fn focus(&self) -> Option<FocusId> {
+        self.focus
+    }
+
+    fn set_focus(&mut self, focus: Option<FocusId>) {
+        self.focus = focus;
+    }
+
+    fn start_timer(&mut self, delay: u32, periodic: bool) -> TimerId {
+        let now = self.now();
+        self.timers.start(now, delay, periodic)
+    }
+
+    fn cancel_timer(&mut self, id: TimerId) {
+        self.timers.cancel(id);
+    }
+
+    fn request_idle(&mut self) {
+        self.idle_requested = true;
+    }
+
+    fn set_pointer(&mut self, shape: &'static PointerShape) {
+        if !std::ptr::eq(self.pointer, shape) {
+            self.pointer = shape;
+            self.needs_repaint = true;
+        }
+    }
+
+    /// Answers any text on the system clipboard,
+    /// which might have been put there by another program since a bitmap was put on ours.
+    fn clipboard(&self) -> Option<TransferData> {
+        match self.clipboard.clipboard_text() {
+            Ok(text) if !text.is_empty() => Some(TransferData::Text(text)),
+            _ => self.bitmap.clone().map(TransferData::Bitmap),
+        }
+    }
+
+    /// Puts text on the system clipboard.
+    /// A bitmap is kept to ourselves, and empties the system clipboard instead.
+    fn set_clipboard(&mut self, data: TransferData) {
+        let text = match data {
+            TransferData::Text(text) => {
+                self.bitmap = None;
+                text
+            }
+            TransferData::Bitmap(bitmap) => {
+                self.bitmap = Some(bitmap);
+                String::new()
+            }
+        };
+        let _ = self.clipboard.set_clipboard_text(&text);
+    }
+
+    fn start_drag(&mut self, mut session: DragSession) {
+        if let Some(at) = self.pointer_at {
+            session.move_to(at);
+        }
+        self.drag = Some(session);
+        self.needs_repaint = true;
+    }
+
+    fn drag(&self) -> Option<&DragSession> {
+        self.drag.as_ref()
+    }
+
+    fn end_drag(&mut self) -> Option<DragSession> {
+        self.needs_repaint = true;
+        self.drag.take()
+    }
+
+    fn theme(&self) -> &'static Theme {
+        self.theme
+    }
+
+    fn set_theme(&mut self, theme: &'static Theme) {
+        self.theme = theme;
+    }
+}
+
+/// Repaints the whole window from the `desktop` stencil.
+fn repaint(desktop: &Stencil, sdl: &mut SdlState) {
+    let origin = (0, 0);
+    let (w, h) = desktop.get_dimensions();
+    let dimensions = (w as usize, h as usize);
+
+    sdl.paint_with(|ctx| {
+        ctx.paste_stamp_be(origin, dimensions, desktop.get_span(), origin, desktop.borrow_bits());
+    });
+}
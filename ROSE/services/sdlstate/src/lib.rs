@@ -1,5 +1,12 @@
 //! All things having to do with SDL input and output.
 
+use sdl2::keyboard::{Keycode, Mod};
+use stencil::events::{KeyCode, Modifiers, MouseButton};
+
+mod host;
+
+pub use host::run;
+
 /// A convenient anchor for SDL-related data structures.
 pub struct SdlState {
     pub context: sdl2::Sdl,
@@ -125,3 +132,67 @@ impl<'a> TextureContext<'a> {
     }
 }
 
+/// Translates an SDL key into a platform-neutral key code.
+///
+/// Answers `None` for keys ROSE doesn't distinguish.
+pub fn key_for(k: Keycode) -> Option<KeyCode> {
+    let key = match k {
+        Keycode::Return | Keycode::KpEnter => KeyCode::Return,
+        Keycode::Escape => KeyCode::Escape,
+        Keycode::Tab => KeyCode::Tab,
+        Keycode::Backspace => KeyCode::Backspace,
+        Keycode::Delete => KeyCode::Delete,
+        Keycode::Insert => KeyCode::Insert,
+        Keycode::Left => KeyCode::Left,
+        Keycode::Right => KeyCode::Right,
+        Keycode::Up => KeyCode::Up,
+        Keycode::Down => KeyCode::Down,
+        Keycode::Home => KeyCode::Home,
+        Keycode::End => KeyCode::End,
+        Keycode::F1 => KeyCode::Function(1),
+        Keycode::F2 => KeyCode::Function(2),
+        Keycode::F3 => KeyCode::Function(3),
+        Keycode::F4 => KeyCode::Function(4),
+        Keycode::F5 => KeyCode::Function(5),
+        Keycode::F6 => KeyCode::Function(6),
+        Keycode::F7 => KeyCode::Function(7),
+        Keycode::F8 => KeyCode::Function(8),
+        _ => {
+            // SDL codes printable ASCII keys by the character they type.
+            let code = k as i32;
+            if (0x20..0x7F).contains(&code) {
+                KeyCode::Char(char::from(code as u8))
+            } else {
+                return None;
+            }
+        }
+    };
+    Some(key)
+}
+
+/// Translates SDL's modifier key state into ROSE's.
+pub fn modifiers_for(m: Mod) -> Modifiers {
+    let mut modifiers = Modifiers::NONE;
+    if m.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) {
+        modifiers = modifiers | Modifiers::SHIFT;
+    }
+    if m.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) {
+        modifiers = modifiers | Modifiers::CONTROL;
+    }
+    if m.intersects(Mod::LALTMOD | Mod::RALTMOD) {
+        modifiers = modifiers | Modifiers::COMMODORE;
+    }
+    modifiers
+}
+
+/// Translates an SDL mouse button into ROSE's.
+///
+/// Answers `None` for buttons ROSE doesn't distinguish.
+pub fn button_for(b: sdl2::mouse::MouseButton) -> Option<MouseButton> {
+    match b {
+        sdl2::mouse::MouseButton::Left => Some(MouseButton::Left),
+        sdl2::mouse::MouseButton::Middle => Some(MouseButton::Middle),
+        sdl2::mouse::MouseButton::Right => Some(MouseButton::Right),
+        _ => None,
+    }
+}
@@ -14,12 +14,26 @@
 //! > *noun* the dialect of English spoken at Oxford University and regarded by many as affected and pretentious
 //! &mdash; *[WordNik](https://www.wordnik.com/words/oxford%20english), accessed 2022-Oct-26.*
 
+use std::ops::Range;
+
+use crate::hyphenation::Hyphenator;
+use crate::paragraph::{DECIMAL_POINT, PLAIN_PARAGRAPH, ParagraphFormat, TabStop};
+use crate::types::Dimension;
+
 /// Trait implemented by fonts.
 // should this be moved into a centralized font module?
 pub trait FontProvider {
     /// Returns the width of the rendered glyph
     /// for the requested character `char`.
     fn get_glyph_width(&self, ch: char) -> usize;
+
+    /// Returns the adjustment to the spacing between
+    /// the characters `left` and `right` when they appear next to each other.
+    ///
+    /// By default, fonts are not kerned.
+    fn get_kerning(&self, _left: char, _right: char) -> isize {
+        0
+    }
 }
 
 /// A line is described as a span of text in a (possibly, gap) buffer
@@ -27,11 +41,84 @@ pub trait FontProvider {
 /// Like other measurements in ROSE, `start` is inclusive while `end` is exclusive.
 pub type Line = (usize, usize);
 
+/// Describes a single edit to the source text of a [[Forme]],
+/// in terms of character indices into the text *before* the edit.
+///
+/// `deleted` characters starting at `at` were replaced by `inserted` new ones.
+/// Pure insertions delete nothing; pure deletions insert nothing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Edit {
+    /// Where the edit took place.
+    pub at: usize,
+
+    /// How many characters were removed.
+    pub deleted: usize,
+
+    /// How many characters were put in their place.
+    pub inserted: usize,
+}
+
+impl Edit {
+    /// Describes inserting `count` characters at `at`.
+    pub fn insert(at: usize, count: usize) -> Self {
+        Self { at, deleted: 0, inserted: count }
+    }
+
+    /// Describes deleting `count` characters starting at `at`.
+    pub fn delete(at: usize, count: usize) -> Self {
+        Self { at, deleted: count, inserted: 0 }
+    }
+
+    /// Answers where an index at or past the end of the edited range ends up afterwards.
+    fn shifted(&self, index: usize) -> usize {
+        index + self.inserted - self.deleted
+    }
+}
+
+/// Describes how a [[Line]] comes to an end.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineBreak {
+    /// The line ends in a paragraph break.
+    Paragraph,
+
+    /// The line was word-wrapped, and ends in breaking whitespace.
+    Space,
+
+    /// The line was wrapped in the middle of a word,
+    /// which a printer should mark by printing a hyphen.
+    Hyphen,
+
+    /// The line was wrapped in the middle of a word with no place to hyphenate it.
+    Wrap,
+
+    /// The line ends with the text itself.
+    End,
+}
+
+/// A tab whose advance depends on the width of the text following it.
+#[derive(Clone, Copy)]
+struct PendingTab {
+    /// The stop the text following the tab is aligned against.
+    stop: TabStop,
+
+    /// Where the virtual print head stood before the tab.
+    from: usize,
+
+    /// The width of the text following the tab.
+    width: usize,
+
+    /// The width of the text following the tab up to its decimal point, once there is one.
+    to_point: Option<usize>,
+}
+
 /// This structure implements the state for the word-wrapping algorithm.
 pub struct Forme<'a> {
     /// A collection of [[Line]]s mapping back to the source document.
     lines: Vec<Line>,
 
+    /// How each of the [[Line]]s in `lines` comes to an end.
+    breaks: Vec<LineBreak>,
+
     /// The width of the virtual carriage.
     margin: usize,
 
@@ -47,14 +134,46 @@ pub struct Forme<'a> {
     /// Tracks the location of the most recently known breaking whitespace.
     brk: Option<usize>,
 
+    /// Tracks the latest place on the current line where a word may be hyphenated,
+    /// with room to spare for the hyphen.
+    hyphen: Option<usize>,
+
+    /// Holds the text following the most recent breaking whitespace
+    /// (or since the start of the line, if there is none),
+    /// along with whether each character may be hyphenated before.
+    /// This is the text which moves to the next line if we wrap.
+    word: Vec<(char, bool)>,
+
+    /// Tracks the width of the most recently processed character.
+    last_width: usize,
+
+    /// Tracks the most recently processed character on the current line, for kerning purposes.
+    previous: Option<char>,
+
     /// True if we've reached a terminal state.
     done: bool,
 
     /// True if we just processed a newline request.
     newline: bool,
 
+    /// True if the most recently processed character was a carriage return.
+    /// A line feed immediately following it belongs to the same paragraph break.
+    after_cr: bool,
+
     /// The font provider which measures glyph widths for us.
     font_provider: &'a dyn FontProvider,
+
+    /// The hyphenator which finds places to break words at, if any.
+    hyphenator: Option<&'a Hyphenator>,
+
+    /// Holds the word being read ahead of layout, so it can be hyphenated as a whole.
+    lookahead: Vec<char>,
+
+    /// The indents and tab stops lines are laid out with.
+    format: &'a ParagraphFormat,
+
+    /// Tracks the most recent tab on the current line, if it lands on a tab stop.
+    tab: Option<PendingTab>,
 }
 
 impl<'a> Forme<'a> {
@@ -67,24 +186,58 @@ impl<'a> Forme<'a> {
             font_provider,
 
             lines: Vec::new(),
+            breaks: Vec::new(),
             head: 0,
             start: 0,
             end: 0,
             brk: None,
+            hyphen: None,
+            word: Vec::new(),
+            last_width: 0,
+            previous: None,
             done: true, // not a typo
             newline: false,
+            after_cr: false,
+            hyphenator: None,
+            lookahead: Vec::new(),
+            format: &PLAIN_PARAGRAPH,
+            tab: None,
         }
     }
 
+    /// Selects the paragraph `format` lines are laid out with.
+    ///
+    /// Lines are indented within the virtual carriage,
+    /// and tabs advance to its tab stops.
+    pub fn set_format(&mut self, format: &'a ParagraphFormat) {
+        self.format = format;
+    }
+
+    /// Selects the `hyphenator` used to break words too long to fit at the end of a line.
+    ///
+    /// Soft hyphens (U+00AD) in the text are honoured with or without one.
+    /// Words containing soft hyphens are only ever hyphenated where they're marked.
+    pub fn set_hyphenator(&mut self, hyphenator: Option<&'a Hyphenator>) {
+        self.hyphenator = hyphenator;
+    }
+
     /// Resets the forme back to its default state.
     pub fn clear(&mut self) {
         self.lines.clear();
-        self.head = 0;
+        self.breaks.clear();
+        self.head = self.indent(true);
         self.start = 0;
         self.end = 0;
         self.brk = None;
+        self.hyphen = None;
+        self.word.clear();
+        self.last_width = 0;
+        self.previous = None;
         self.done = false;
         self.newline = false;
+        self.after_cr = false;
+        self.lookahead.clear();
+        self.tab = None;
     }
 
     /// Processes a string of text to determine
@@ -92,19 +245,221 @@ impl<'a> Forme<'a> {
     /// if printed with the selected font
     /// on a surface with the configured width.
     pub fn layout_from_str(&mut self, s: &str) {
+        self.layout_from_chars(s.chars());
+    }
+
+    /// Like [[Forme::layout_from_str]],
+    /// but takes its text from any source of characters,
+    /// such as the two halves of a gap buffer.
+    pub fn layout_from_chars<I: IntoIterator<Item = char>>(&mut self, chars: I) {
         self.clear();
-        for ch in s.chars() {
-            match ch {
-                _ if is_line_break(ch) => self.handle_space(ch),
-                _ if is_paragraph_break(ch) => self.paragraph_break(),
-                _ => self.handle_char(ch),
+        for ch in chars {
+            self.push(ch);
+        }
+        self.terminate_input();
+    }
+
+    /// Updates the layout after `edit` has been applied to the source text,
+    /// which `s` now holds in its entirety.
+    ///
+    /// Only lines that could have been affected by the edit are laid out again;
+    /// the rest are reused, shifted as needed.
+    /// Answers the range of (new) line indices whose contents changed.
+    /// If the number of lines changed as well,
+    /// every line after that range has moved, too.
+    pub fn relayout_from_str(&mut self, s: &str, edit: Edit) -> Range<usize> {
+        self.relayout_from_chars(s.chars(), edit)
+    }
+
+    /// Like [[Forme::relayout_from_str]],
+    /// but takes the edited text from any source of characters.
+    pub fn relayout_from_chars<I: IntoIterator<Item = char>>(&mut self, chars: I, edit: Edit) -> Range<usize> {
+        let old = std::mem::take(&mut self.lines);
+        let old_breaks = std::mem::take(&mut self.breaks);
+        self.clear();
+
+        // A line's extent depends on its own text,
+        // and, if it was wrapped, on the text which pushed it past the margin.
+        // That text lies no further away than the end of the following line,
+        // so the line before the first one reaching the edit
+        // must be laid out again too, unless it ends in a paragraph break.
+        let k = old.partition_point(|&(_, end)| end < edit.at);
+        let mut restart = match k {
+            0 => 0,
+            _ if old_breaks[k - 1] == LineBreak::Paragraph => k,
+            _ => k - 1,
+        };
+
+        // A hyphenator looks at whole words, though,
+        // so laying out must pick up at the start of one.
+        // A line is also hyphenated according to the word following it,
+        // so that word must lie wholly before the edit,
+        // ending no later than the first line to end between words.
+        if self.hyphenator.is_some() {
+            while (restart > 0) && (old_breaks[restart - 1] != LineBreak::Paragraph) {
+                if old_breaks[restart - 1] == LineBreak::Space {
+                    let word_end = (restart..old.len())
+                        .find(|&q| matches!(old_breaks[q], LineBreak::Space | LineBreak::Paragraph))
+                        .map(|q| old[q].1);
+                    if word_end.is_some_and(|end| end <= edit.at) {
+                        break;
+                    }
+                }
+                restart -= 1;
             }
+        }
+
+        // Every line starts out unkerned, with nothing carried over from the line before,
+        // so laying out can pick up at the start of any line.
+        self.lines.extend_from_slice(&old[..restart]);
+        self.breaks.extend_from_slice(&old_breaks[..restart]);
+        self.start = if restart > 0 { old[restart - 1].1 } else { 0 };
+        self.end = self.start;
+        self.head = self.indent((restart == 0) || (old_breaks[restart - 1] == LineBreak::Paragraph));
 
-            if self.head > self.margin {
-                self.breach_margin();
+        // Once a new line starts where a shifted old line (wholly past the edit) did,
+        // every line from there on comes out exactly as it did before.
+        let edited_end = edit.at + edit.inserted;
+        let mut j = k;
+        for ch in chars.into_iter().skip(self.start) {
+            let count = self.lines.len();
+            self.push(ch);
+            for n in count..self.lines.len() {
+                let start = self.lines[n].1;
+                let word_boundary = self.hyphenator.is_none() || matches!(self.breaks[n], LineBreak::Space | LineBreak::Paragraph);
+                if (start <= edited_end) || !word_boundary {
+                    continue;
+                }
+                while (j < old.len()) && ((old[j].0 <= edit.at + edit.deleted) || (edit.shifted(old[j].0) < start)) {
+                    j += 1;
+                }
+                if (j < old.len()) && (edit.shifted(old[j].0) == start) {
+                    self.lines.truncate(n + 1);
+                    self.breaks.truncate(n + 1);
+                    let changed = self.unchanged_prefix(&old, &old_breaks, restart, edit)..self.lines.len();
+                    self.lines.extend(old[j..].iter().map(|&(s, e)| (edit.shifted(s), edit.shifted(e))));
+                    self.breaks.extend_from_slice(&old_breaks[j..]);
+                    self.done = true;
+                    return changed;
+                }
             }
         }
         self.terminate_input();
+        self.unchanged_prefix(&old, &old_breaks, restart, edit)..self.lines.len()
+    }
+
+    /// Answers the index of the first line, from `restart` onwards,
+    /// which differs from its counterpart in the `old` layout.
+    ///
+    /// Relayout restarts conservatively early,
+    /// so the first few lines it produces often come out just as they were.
+    fn unchanged_prefix(&self, old: &[Line], old_breaks: &[LineBreak], restart: usize, edit: Edit) -> usize {
+        let mut n = restart;
+        while (n < self.lines.len()) && (n < old.len())
+            && (self.lines[n] == old[n]) && (self.breaks[n] == old_breaks[n]) && (old[n].1 <= edit.at) {
+            n += 1;
+        }
+        n
+    }
+
+    /// Takes the next character of the source text.
+    ///
+    /// With a hyphenator, the characters of a word are held back until it's complete,
+    /// since the places it may be hyphenated depend on the word as a whole.
+    fn push(&mut self, ch: char) {
+        if self.hyphenator.is_some() && !is_line_break(ch) && !is_paragraph_break(ch) {
+            self.lookahead.push(ch);
+        } else {
+            self.flush_word();
+            self.feed(ch, false);
+        }
+    }
+
+    /// Lays out the word held back by [[Forme::push]],
+    /// marking each alphabetic run in it where the hyphenator permits.
+    fn flush_word(&mut self) {
+        let Some(hyphenator) = self.hyphenator else {
+            return;
+        };
+
+        let word = std::mem::take(&mut self.lookahead);
+        let mut points = vec![false; word.len()];
+        if !word.contains(&SOFT_HYPHEN) {
+            let mut i = 0;
+            while i < word.len() {
+                let run = word[i..].iter().take_while(|ch| ch.is_alphabetic()).count();
+                if run > 0 {
+                    let letters: String = word[i..i + run].iter().collect();
+                    for n in hyphenator.hyphenate(&letters) {
+                        points[i + n] = true;
+                    }
+                }
+                i += run.max(1);
+            }
+        }
+
+        for (&ch, &point) in word.iter().zip(&points) {
+            self.feed(ch, point);
+        }
+        self.lookahead = word;
+        self.lookahead.clear();
+    }
+
+    /// Runs a single character through the word-wrapping state machine.
+    ///
+    /// The `point` flag permits hyphenating the word just before `ch`.
+    fn feed(&mut self, ch: char, point: bool) {
+        match ch {
+            _ if is_line_break(ch) => self.handle_space(ch),
+            _ if is_paragraph_break(ch) => self.paragraph_break(ch),
+            SOFT_HYPHEN => self.handle_soft_hyphen(),
+            _ => self.handle_char(ch, point),
+        }
+
+        if self.head > self.limit() {
+            self.breach_margin();
+        }
+    }
+
+    /// Answers where the virtual print head starts out on a line,
+    /// depending on whether or not it's the `first_line` of its paragraph.
+    fn indent(&self, first_line: bool) -> usize {
+        self.format.indent(first_line) as usize
+    }
+
+    /// Answers how far the virtual print head may advance before breaching the margin.
+    fn limit(&self) -> usize {
+        self.margin.saturating_sub(self.format.right_indent.max(0) as usize)
+    }
+
+    /// Advances the virtual print head past `ch`, which is `width` wide,
+    /// keeping the text following a tab aligned against its stop.
+    fn advance_head(&mut self, ch: char, width: usize) {
+        self.last_width = width;
+        match self.tab.as_mut() {
+            None => self.head += width,
+            Some(tab) => {
+                if (ch == DECIMAL_POINT) && tab.to_point.is_none() {
+                    tab.to_point = Some(tab.width);
+                }
+                tab.width += width;
+                let to_point = tab.to_point.unwrap_or(tab.width);
+                let advance = tab.stop.advance(tab.from as Dimension, tab.width as Dimension, to_point as Dimension);
+                self.head = tab.from + advance as usize + tab.width;
+            }
+        }
+    }
+
+    /// Answers how far the virtual print head advances when printing `ch`,
+    /// taking kerning against the previous character into account.
+    fn advance_for(&mut self, ch: char) -> usize {
+        let width = self.font_provider.get_glyph_width(ch) as isize;
+        let kern = match self.previous {
+            Some(p) => self.font_provider.get_kerning(p, ch),
+            None => 0,
+        };
+        self.previous = Some(ch);
+        (width + kern).max(0) as usize
     }
 
     /// Handles a non-breaking whitespace or graphic character.
@@ -112,63 +467,154 @@ impl<'a> Forme<'a> {
     /// # Safety
     ///
     /// This function does not perform wrapping.
-    fn handle_char(&mut self, ch: char) {
+    fn handle_char(&mut self, ch: char, point: bool) {
         if !self.done {
-            self.head += self.font_provider.get_glyph_width(ch);
+            if point {
+                self.offer_hyphen(self.end);
+            }
+            let width = self.advance_for(ch);
+            self.advance_head(ch, width);
+            self.word.push((ch, point));
             self.end += 1;
             self.newline = false;
+            self.after_cr = false;
+        }
+    }
+
+    /// Handles a soft hyphen,
+    /// which takes up no room unless the line is hyphenated just after it.
+    ///
+    /// # Safety
+    ///
+    /// This function does not perform wrapping.
+    fn handle_soft_hyphen(&mut self) {
+        if !self.done {
+            self.word.push((SOFT_HYPHEN, false));
+            self.end += 1;
+            self.offer_hyphen(self.end);
+            self.newline = false;
+            self.after_cr = false;
+        }
+    }
+
+    /// Notes that the current line could end in a hyphen at `at`,
+    /// if there's still room for the hyphen itself.
+    fn offer_hyphen(&mut self, at: usize) {
+        let kern = match self.previous {
+            Some(p) => self.font_provider.get_kerning(p, HYPHEN),
+            None => 0,
+        };
+        let width = (self.font_provider.get_glyph_width(HYPHEN) as isize + kern).max(0) as usize;
+        if (at > self.start) && (self.head + width <= self.limit()) {
+            self.hyphen = Some(at);
         }
     }
 
     /// Handles a breaking whitespace character.
     ///
+    /// A tab advances to the next tab stop, if there is one.
+    /// Nothing kerns against it.
+    ///
     /// # Safety
     ///
     /// This function does not perform wrapping.
     fn handle_space(&mut self, ch: char) {
         if !self.done {
             let end = self.end;
+            let stop = if ch == '\t' { self.format.next_tab_stop(self.head as Dimension) } else { None };
+            match stop {
+                Some(stop) => {
+                    self.previous = None;
+                    self.tab = Some(PendingTab { stop, from: self.head, width: 0, to_point: None });
+                    self.advance_head(ch, 0);
+                },
+                None => {
+                    let width = self.advance_for(ch);
+                    self.advance_head(ch, width);
+                },
+            }
             self.brk = Some(end);
-            self.head += self.font_provider.get_glyph_width(ch);
+            self.hyphen = None;
+            self.word.clear();
             self.end = end + 1;
+            self.newline = false;
+            self.after_cr = false;
         }
     }
 
     /// Handles a new line/paragraph break.
-    fn paragraph_break(&mut self) {
+    ///
+    /// A carriage return followed by a line feed counts as a single break.
+    fn paragraph_break(&mut self, ch: char) {
         if !self.done {
+            if self.after_cr && (ch == '\n') {
+                if let Some(line) = self.lines.last_mut() {
+                    line.1 += 1;
+                }
+                self.start += 1;
+                self.end += 1;
+                self.after_cr = false;
+                return;
+            }
+
             let end = self.end + 1;
 
             self.lines.push((self.start, end));
+            self.breaks.push(LineBreak::Paragraph);
             self.start = end;
+            self.end = end;
             self.newline = true;
             self.brk = None;
-            self.head = 0;
+            self.hyphen = None;
+            self.word.clear();
+            self.tab = None;
+            self.head = self.indent(true);
+            self.previous = None;
+            self.after_cr = ch == '\r';
         }
     }
 
-    /// Process a margin breach by either line- or word-wrapping as appropriate.
+    /// Process a margin breach by hyphenating, word-, or line-wrapping as appropriate.
+    ///
+    /// A glyph too wide for the carriage overhangs the margin
+    /// rather than leaving an empty line behind.
     fn breach_margin(&mut self) {
         if !self.done {
-            match self.brk {
-                None => {
-                    let end = self.end;
-                    self.lines.push((self.start, end - 1));
-                    self.start = end - 1;
-                    self.head = 0;
-                },
-
-                Some(brk) => {
-                    self.lines.push((self.start, brk + 1));
-                    self.start = brk + 1;
-                    self.head = 0;
-                }
+            match (self.hyphen, self.brk) {
+                (Some(at), _) => self.wrap_at(at, LineBreak::Hyphen),
+                (None, Some(brk)) => self.wrap_at(brk + 1, LineBreak::Space),
+                (None, None) if self.end - 1 > self.start => self.wrap_at(self.end - 1, LineBreak::Wrap),
+                (None, None) => {},
             }
         }
     }
 
+    /// Ends the current line at `at`, and starts the next one from there.
+    ///
+    /// Whatever text moves down to the next line
+    /// is laid out all over again, as though the line had started there to begin with.
+    /// It's no longer kerned against the text left behind,
+    /// and may yet have to wrap (or hyphenate) again.
+    fn wrap_at(&mut self, at: usize, brk: LineBreak) {
+        let carried = self.word.split_off(self.word.len() - (self.end - at));
+        self.lines.push((self.start, at));
+        self.breaks.push(brk);
+        self.start = at;
+        self.end = at;
+        self.head = self.indent(false);
+        self.brk = None;
+        self.hyphen = None;
+        self.word.clear();
+        self.tab = None;
+        self.previous = None;
+        for (ch, point) in carried {
+            self.feed(ch, point);
+        }
+    }
+
     /// Process the end of input condition.
     fn terminate_input(&mut self) {
+        self.flush_word();
         if !self.done {
             if self.newline {
                 // Nothing to be done; the last line has already been pushed
@@ -176,20 +622,34 @@ impl<'a> Forme<'a> {
             } else {
                 if self.end > self.start {
                     self.lines.push((self.start, self.end));
+                    self.breaks.push(LineBreak::End);
                 }
             }
             self.done = true;
         }
     }
-    
+
     /// Borrow the lines vector.
     pub fn borrow_lines(&self) -> &Vec<Line> {
         &self.lines
     }
+
+    /// Borrow the vector describing how each line ends,
+    /// which runs parallel to the lines vector.
+    pub fn borrow_breaks(&self) -> &Vec<LineBreak> {
+        &self.breaks
+    }
 }
 
+/// The soft hyphen, which marks where a word may be hyphenated,
+/// but is otherwise invisible.
+pub(crate) const SOFT_HYPHEN: char = '\u{00AD}';
+
+/// The hyphen printed at the end of a hyphenated line.
+pub(crate) const HYPHEN: char = '-';
+
 /// Answers true if the character is a good point to break a line.
-fn is_line_break(ch: char) -> bool {
+pub(crate) fn is_line_break(ch: char) -> bool {
     // Taken from https://en.wikipedia.org/wiki/Whitespace_character
     // Accessed: 2022-Oct-27
     //
@@ -225,7 +685,7 @@ fn is_line_break(ch: char) -> bool {
 }
 
 /// Answers true if the character is a paragraph break (an explicit line break).
-fn is_paragraph_break(ch: char) -> bool {
+pub(crate) fn is_paragraph_break(ch: char) -> bool {
     // Taken from https://en.wikipedia.org/wiki/Whitespace_character
     // Accessed: 2022-Oct-27
     //
@@ -247,8 +707,9 @@ fn is_paragraph_break(ch: char) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::FontProvider;
-    use super::Forme;
+    use super::{Edit, FontProvider, Forme, LineBreak};
+    use crate::hyphenation::Hyphenator;
+    use crate::paragraph::{PLAIN_PARAGRAPH, ParagraphFormat, TabAlignment, TabStop};
 
     struct MyFont {}
 
@@ -258,6 +719,26 @@ mod tests {
         }
     }
 
+    /// Every glyph is two pixels wide, except for a six pixel wide `W`.
+    /// A space kerns tightly against a following `b`.
+    struct WideFont {}
+
+    impl FontProvider for WideFont {
+        fn get_glyph_width(&self, ch: char) -> usize {
+            match ch {
+                'W' => 6,
+                _ => 2,
+            }
+        }
+
+        fn get_kerning(&self, left: char, right: char) -> isize {
+            match (left, right) {
+                (' ', 'b') => -1,
+                _ => 0,
+            }
+        }
+    }
+
     #[test]
     fn empty_str() {
         let fp = MyFont{};
@@ -287,6 +768,279 @@ mod tests {
         assert_eq!(*f.borrow_lines().get(1).unwrap(), (6, 15));
     }
 
+    #[test]
+    fn lines_after_paragraph_breaks() {
+        let fp = MyFont{};
+        let mut f = Forme::new(10, &fp);
+        f.layout_from_str("ab\ncd\nef");
+        assert_eq!(*f.borrow_lines(), vec![(0, 3), (3, 6), (6, 8)]);
+    }
+
+    #[test]
+    fn trailing_whitespace_after_paragraph_break() {
+        let fp = MyFont{};
+        let mut f = Forme::new(10, &fp);
+        f.layout_from_str("ab\n ");
+        assert_eq!(*f.borrow_lines(), vec![(0, 3), (3, 4)]);
+    }
+
+    #[test]
+    fn wrapped_word_occupies_next_line() {
+        let fp = MyFont{};
+        let mut f = Forme::new(10, &fp);
+        // "carbonara" carries its width over to the second line,
+        // so "al" can't join it there.
+        f.layout_from_str("pasta carbonara al");
+        assert_eq!(*f.borrow_lines(), vec![(0, 6), (6, 16), (16, 18)]);
+    }
+
+    #[test]
+    fn long_words_line_wrap_repeatedly() {
+        let fp = MyFont{};
+        let mut f = Forme::new(4, &fp);
+        f.layout_from_str("abcdefghij");
+        assert_eq!(*f.borrow_lines(), vec![(0, 4), (4, 8), (8, 10)]);
+    }
+
+    #[test]
+    fn word_wrap_only_once_per_break() {
+        let fp = MyFont{};
+        let mut f = Forme::new(4, &fp);
+        // Once wrapped at the space, a long word must line-wrap rather than
+        // wrapping at the (now previous line's) space again.
+        f.layout_from_str("ab cdefghij");
+        assert_eq!(*f.borrow_lines(), vec![(0, 3), (3, 7), (7, 11)]);
+    }
+
+    /// A font with a little variety, so relayout can't get lucky.
+    struct KernedFont {}
+
+    impl FontProvider for KernedFont {
+        fn get_glyph_width(&self, ch: char) -> usize {
+            match ch {
+                'm' => 3,
+                ' ' | 'i' => 1,
+                _ => 2,
+            }
+        }
+
+        fn get_kerning(&self, left: char, right: char) -> isize {
+            match (left, right) {
+                ('a', 'm') | (' ', 'i') => -1,
+                _ => 0,
+            }
+        }
+    }
+
+    #[test]
+    fn relayout_reports_changed_lines() {
+        let fp = MyFont{};
+        let mut f = Forme::new(10, &fp);
+        f.layout_from_str("ab\ncd\nef");
+        let changed = f.relayout_from_str("ab\ncxd\nef", Edit::insert(4, 1));
+        assert_eq!(changed, 1..2);
+        assert_eq!(*f.borrow_lines(), vec![(0, 3), (3, 7), (7, 9)]);
+
+        let changed = f.relayout_from_str("ab\ncxd\n", Edit::delete(7, 2));
+        assert_eq!(changed, 2..2);
+        assert_eq!(*f.borrow_lines(), vec![(0, 3), (3, 7)]);
+    }
+
+    /// Makes random edits to random text, checking relayout against laying out from scratch.
+    fn check_relayout(alphabet: &[char], hyphenator: Option<&Hyphenator>, format: &ParagraphFormat) {
+        let fp = KernedFont{};
+        let mut seed: u32 = 12345;
+        let mut random = move |n: usize| {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            ((seed >> 16) as usize) % n
+        };
+
+        let mut text: Vec<char> = Vec::new();
+        let mut incremental = Forme::new(12, &fp);
+        incremental.set_hyphenator(hyphenator);
+        incremental.set_format(format);
+        incremental.layout_from_chars(text.iter().copied());
+
+        for _ in 0..2000 {
+            let at = random(text.len() + 1);
+            let deleted = if random(3) == 0 { random(text.len() - at + 1).min(6) } else { 0 };
+            let inserted = if (deleted == 0) || (random(2) == 0) { random(8) } else { 0 };
+            let new: Vec<char> = (0..inserted).map(|_| alphabet[random(alphabet.len())]).collect();
+            text.splice(at..at + deleted, new);
+
+            let old = incremental.borrow_lines().clone();
+            let edit = Edit { at, deleted, inserted };
+            let changed = incremental.relayout_from_chars(text.iter().copied(), edit);
+
+            let mut full = Forme::new(12, &fp);
+            full.set_hyphenator(hyphenator);
+            full.set_format(format);
+            full.layout_from_chars(text.iter().copied());
+            let lines = incremental.borrow_lines();
+            assert_eq!(lines, full.borrow_lines(), "after {:?}", edit);
+            assert_eq!(incremental.borrow_breaks(), full.borrow_breaks(), "after {:?}", edit);
+
+            // Lines outside the reported range are the old ones, shifted past the edit.
+            assert_eq!(lines[..changed.start], old[..changed.start]);
+            let unchanged = lines.len() - changed.end;
+            for (new, old) in lines[changed.end..].iter().zip(&old[old.len() - unchanged..]) {
+                assert_eq!(*new, (old.0 + inserted - deleted, old.1 + inserted - deleted));
+            }
+        }
+    }
+
+    #[test]
+    fn relayout_matches_full_layout() {
+        check_relayout(&['a', 'm', 'i', ' ', ' ', '\n', '\r'], None, &PLAIN_PARAGRAPH);
+    }
+
+    #[test]
+    fn relayout_matches_full_layout_when_hyphenating() {
+        let mut h = Hyphenator::new("a1m m1i i1a");
+        h.left_min = 1;
+        h.right_min = 1;
+        check_relayout(&['a', 'm', 'i', 'a', 'm', 'i', ' ', '\n', '\u{AD}'], Some(&h), &PLAIN_PARAGRAPH);
+    }
+
+    #[test]
+    fn relayout_matches_full_layout_when_formatted() {
+        let mut format = ParagraphFormat { left_indent: 2, right_indent: 1, first_line_indent: 3, ..Default::default() };
+        format.set_tab_stop(TabStop::new(6, TabAlignment::Right));
+        format.set_tab_stop(TabStop::new(9, TabAlignment::Decimal));
+        format.default_tab_interval = 4;
+        check_relayout(&['a', 'm', 'i', '.', ' ', '\t', '\t', '\n'], None, &format);
+    }
+
+    #[test]
+    fn lines_are_indented() {
+        let fp = MyFont{};
+        let format = ParagraphFormat { left_indent: 2, right_indent: 1, first_line_indent: 2, ..Default::default() };
+        let mut f = Forme::new(10, &fp);
+        f.set_format(&format);
+        f.layout_from_str("aaa bbb ccc ddd");
+        assert_eq!(*f.borrow_lines(), vec![(0, 4), (4, 12), (12, 15)]);
+    }
+
+    #[test]
+    fn tabs_advance_to_tab_stops() {
+        let fp = MyFont{};
+        let mut format = ParagraphFormat::default();
+        let mut f = Forme::new(12, &fp);
+
+        // Without tab stops, a tab is just whitespace.
+        f.layout_from_str("a\tbbb");
+        assert_eq!(*f.borrow_lines(), vec![(0, 5)]);
+
+        format.set_tab_stop(TabStop::new(10, TabAlignment::Left));
+        f.set_format(&format);
+        f.layout_from_str("a\tbbb");
+        assert_eq!(*f.borrow_lines(), vec![(0, 2), (2, 5)]);
+
+        // Right-aligned text grows leftwards from the stop, until it meets the tab.
+        let mut format = ParagraphFormat::default();
+        format.set_tab_stop(TabStop::new(10, TabAlignment::Right));
+        f.set_format(&format);
+        f.layout_from_str("a\tbbbbbbbbb");
+        assert_eq!(*f.borrow_lines(), vec![(0, 11)]);
+        f.layout_from_str("a\tbbbbbbbbbbbb");
+        assert_eq!(*f.borrow_lines(), vec![(0, 2), (2, 14)]);
+    }
+
+    #[test]
+    fn decimal_tabs_align_decimal_points() {
+        let fp = MyFont{};
+        let mut format = ParagraphFormat::default();
+        format.set_tab_stop(TabStop::new(8, TabAlignment::Decimal));
+
+        // The "." sits at 8, so the "5" following it ends at 10.
+        let mut f = Forme::new(10, &fp);
+        f.set_format(&format);
+        f.layout_from_str("\t12.5");
+        assert_eq!(*f.borrow_lines(), vec![(0, 5)]);
+
+        let mut f = Forme::new(9, &fp);
+        f.set_format(&format);
+        f.layout_from_str("\t12.5");
+        assert_eq!(*f.borrow_lines(), vec![(0, 1), (1, 5)]);
+    }
+
+    #[test]
+    fn overlong_wrapped_word_line_wraps_at_once() {
+        let fp = MyFont{};
+        let mut f = Forme::new(4, &fp);
+        // "cdefg" overflows even after moving down,
+        // so it line-wraps where the margin was breached.
+        f.layout_from_str("ab cdefg");
+        assert_eq!(*f.borrow_lines(), vec![(0, 3), (3, 7), (7, 8)]);
+    }
+
+    #[test]
+    fn overwide_glyph_leaves_no_empty_line() {
+        let fp = WideFont{};
+        let mut f = Forme::new(4, &fp);
+        f.layout_from_str("Wab");
+        assert_eq!(*f.borrow_lines(), vec![(0, 1), (1, 3)]);
+    }
+
+    #[test]
+    fn wrapped_text_is_not_kerned_across_lines() {
+        let fp = WideFont{};
+        let mut f = Forme::new(5, &fp);
+        // Had "bbb" kept its kerning against the space, it would seem to fit in 5 pixels.
+        f.layout_from_str("aa bbb");
+        assert_eq!(*f.borrow_lines(), vec![(0, 3), (3, 5), (5, 6)]);
+
+        // "bbb" moves down without its kerning, making it 6 pixels wide,
+        // which leaves no room for "c".
+        let mut f = Forme::new(9, &fp);
+        f.layout_from_str("aaa bbb c");
+        assert_eq!(*f.borrow_lines(), vec![(0, 4), (4, 8), (8, 9)]);
+    }
+
+    #[test]
+    fn crlf_is_a_single_paragraph_break() {
+        let fp = MyFont{};
+        let mut f = Forme::new(10, &fp);
+        f.layout_from_str("one\r\ntwo\r\n");
+        assert_eq!(*f.borrow_lines(), vec![(0, 5), (5, 10)]);
+
+        // Lone carriage returns and line feeds still break paragraphs on their own.
+        f.layout_from_str("a\rb\n\rc");
+        assert_eq!(*f.borrow_lines(), vec![(0, 2), (2, 4), (4, 5), (5, 6)]);
+    }
+
+    #[test]
+    fn soft_hyphen_breaks_words() {
+        let fp = MyFont{};
+        let mut f = Forme::new(5, &fp);
+        // The soft hyphen takes no room, but leaves room for a hyphen if the word breaks there.
+        f.layout_from_str("abc\u{AD}defgh");
+        assert_eq!(*f.borrow_lines(), vec![(0, 4), (4, 9)]);
+        assert_eq!(*f.borrow_breaks(), vec![LineBreak::Hyphen, LineBreak::End]);
+
+        f.layout_from_str("ab\u{AD}c");
+        assert_eq!(*f.borrow_lines(), vec![(0, 4)]);
+    }
+
+    #[test]
+    fn hyphenator_breaks_words() {
+        let fp = MyFont{};
+        let h = Hyphenator::new("hy3ph he2n hena4 hen5at 1na n2at 1tio 2io o2n");
+        let mut f = Forme::new(8, &fp);
+        f.layout_from_str("the hyphenation");
+        assert_eq!(*f.borrow_lines(), vec![(0, 4), (4, 12), (12, 15)]);
+
+        f.set_hyphenator(Some(&h));
+        f.layout_from_str("the hyphenation");
+        assert_eq!(*f.borrow_lines(), vec![(0, 6), (6, 10), (10, 15)]);
+        assert_eq!(*f.borrow_breaks(), vec![LineBreak::Hyphen, LineBreak::Hyphen, LineBreak::End]);
+
+        // Soft hyphens override the hyphenator.
+        f.layout_from_str("the hyphen\u{AD}ation");
+        assert_eq!(*f.borrow_lines(), vec![(0, 4), (4, 11), (11, 16)]);
+        assert_eq!(*f.borrow_breaks(), vec![LineBreak::Space, LineBreak::Hyphen, LineBreak::End]);
+    }
+
     #[test]
     fn pasta_is_delicious() {
         let fp = MyFont{};
@@ -0,0 +1,468 @@
+//! Containers
+//!
+//! A [[Container]] holds a collection of views,
+//! each occupying a rectangle,
+//! and passes mouse events on to whichever of them is under the pointer.
+//! It works out for itself when the pointer enters or leaves a view,
+//! and keeps sending events to the view a button was pressed in
+//! until the button is let go again (*pointer capture*),
+//! so that dragging a knob off the edge of its track still drags the knob.
+//!
+//! Every gadget reports its own kind of event.
+//! So that they can share a container,
+//! each is added along with a function turning its events
+//! into the single event type `E` the application deals in.
+//! The container hands back whatever events its children produced,
+//! already converted.
+
+use std::any::Any;
+
+use crate::events::{ButtonEvent, Modifiers, MouseButton, MouseEventSink};
+use crate::mediator::Mediator;
+use crate::layout::Layout;
+use crate::types::{Dimensions, Point, Rect};
+use crate::view::{View, rect_contains};
+
+/// Identifies a view within its container.
+pub type ComponentId = usize;
+
+/// A view held by a container (or a window),
+/// with its events converted to the container's event type `E`.
+pub(crate) trait Component<E>: View + MouseEventSink<E> {
+    /// Answers the underlying view, so that it can be downcast.
+    fn view(&self) -> &dyn Any;
+
+    /// Answers the underlying view, so that it can be downcast.
+    fn view_mut(&mut self) -> &mut dyn Any;
+}
+
+/// A view whose events of type `G` are converted to type `E` by `map`.
+struct Mapped<V, G, E> {
+    view: V,
+    map: fn(G) -> E,
+}
+
+impl<V: View, G, E> View for Mapped<V, G, E> {
+    fn draw(&mut self, med: &mut dyn Mediator) {
+        self.view.draw(med);
+    }
+
+    fn preferred_size(&self) -> Option<Dimensions> {
+        self.view.preferred_size()
+    }
+
+    fn set_area(&mut self, area: Rect) {
+        self.view.set_area(area);
+    }
+}
+
+impl<V: MouseEventSink<G>, G, E> MouseEventSink<E> for Mapped<V, G, E> {
+    fn pointer_moved(&mut self, med: &mut dyn Mediator, to: Point) -> E {
+        (self.map)(self.view.pointer_moved(med, to))
+    }
+
+    fn button_up(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> E {
+        (self.map)(self.view.button_up(med, event))
+    }
+
+    fn button_down(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> E {
+        (self.map)(self.view.button_down(med, event))
+    }
+
+    fn wheel(&mut self, med: &mut dyn Mediator, delta: Point, modifiers: Modifiers) -> E {
+        (self.map)(self.view.wheel(med, delta, modifiers))
+    }
+
+    fn enter(&mut self, med: &mut dyn Mediator, at: Point) -> E {
+        (self.map)(self.view.enter(med, at))
+    }
+
+    fn leave(&mut self, med: &mut dyn Mediator) -> E {
+        (self.map)(self.view.leave(med))
+    }
+}
+
+impl<V, G, E> Component<E> for Mapped<V, G, E>
+where
+    V: View + MouseEventSink<G> + 'static,
+{
+    fn view(&self) -> &dyn Any {
+        &self.view
+    }
+
+    fn view_mut(&mut self) -> &mut dyn Any {
+        &mut self.view
+    }
+}
+
+/// Answers `view` as a component, its events converted by `map`.
+pub(crate) fn component<V, G, E>(view: V, map: fn(G) -> E) -> Box<dyn Component<E>>
+where
+    V: View + MouseEventSink<G> + 'static,
+    G: 'static,
+    E: 'static,
+{
+    Box::new(Mapped { view, map })
+}
+
+/// Answers the width and height of `r`.
+fn size_of(r: Rect) -> Dimensions {
+    let ((left, top), (right, bottom)) = r;
+    (right - left, bottom - top)
+}
+
+/// A view held by a container, and where it sits.
+struct Child<E> {
+    id: ComponentId,
+    area: Rect,
+    component: Box<dyn Component<E>>,
+}
+
+/// Holds views, routing mouse events to them and drawing them.
+///
+/// Views are stacked in the order they were added;
+/// where they overlap, the most recently added view is on top,
+/// and receives events first.
+pub struct Container<E> {
+    /// The views held, from bottom to top.
+    children: Vec<Child<E>>,
+
+    /// The identifier the next view added will receive.
+    next_id: ComponentId,
+
+    /// The view under the pointer, if any.
+    hot: Option<ComponentId>,
+
+    /// The view which captured the pointer, and the button which it captured it with.
+    captured: Option<(ComponentId, MouseButton)>,
+
+    /// Tracks the current mouse pointer position on the screen.
+    mouse_pt: Point,
+}
+
+impl<E> Default for Container<E> {
+    fn default() -> Self {
+        Self {
+            children: Vec::new(),
+            next_id: 0,
+            hot: None,
+            captured: None,
+            mouse_pt: (0, 0),
+        }
+    }
+}
+
+impl<E: 'static> Container<E> {
+    /// Creates an empty container.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `view`, occupying `area`, on top of every other view.
+    /// Its events are converted with `map`;
+    /// often, this is simply a variant of `E`.
+    ///
+    /// Answers the identifier of the view within the container.
+    pub fn add<V, G>(&mut self, area: Rect, view: V, map: fn(G) -> E) -> ComponentId
+    where
+        V: View + MouseEventSink<G> + 'static,
+        G: 'static,
+    {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.children.push(Child { id, area, component: component(view, map) });
+        id
+    }
+
+    /// Removes the view `id`, if it's there.
+    pub fn remove(&mut self, id: ComponentId) {
+        self.children.retain(|c| c.id != id);
+        if self.hot == Some(id) {
+            self.hot = None;
+        }
+        if self.captured.is_some_and(|(c, _)| c == id) {
+            self.captured = None;
+        }
+    }
+
+    /// Answers the view `id`, if it's there and of type `V`.
+    pub fn get<V: 'static>(&self, id: ComponentId) -> Option<&V> {
+        let child = self.children.iter().find(|c| c.id == id)?;
+        child.component.view().downcast_ref()
+    }
+
+    /// Answers the view `id`, if it's there and of type `V`.
+    pub fn get_mut<V: 'static>(&mut self, id: ComponentId) -> Option<&mut V> {
+        let child = self.children.iter_mut().find(|c| c.id == id)?;
+        child.component.view_mut().downcast_mut()
+    }
+
+    /// Answers the rectangle occupied by the view `id`, if it's there.
+    pub fn get_area(&self, id: ComponentId) -> Option<Rect> {
+        self.children.iter().find(|c| c.id == id).map(|c| c.area)
+    }
+
+    /// Moves the view `id` to `area`, for the purposes of routing events to it.
+    /// The view itself must be told separately.
+    pub fn set_area(&mut self, id: ComponentId, area: Rect) {
+        if let Some(c) = self.children.iter_mut().find(|c| c.id == id) {
+            c.area = area;
+        }
+    }
+
+    /// Moves the views named in `layout` to where it puts them within `area`.
+    ///
+    /// Views with no preferred size are taken to prefer the size they are now.
+    /// Nothing is redrawn.
+    pub fn reflow(&mut self, layout: &Layout, area: Rect) {
+        let preferred = |id| {
+            let child = self.children.iter().find(|c| c.id == id);
+            child.map_or((0, 0), |c| c.component.preferred_size().unwrap_or(size_of(c.area)))
+        };
+        for (id, area) in layout.arrange(area, &preferred) {
+            if let Some(c) = self.children.iter_mut().find(|c| c.id == id) {
+                c.area = area;
+                c.component.set_area(area);
+            }
+        }
+    }
+
+    /// Answers the topmost view under the point `pt`, if any.
+    pub fn component_at(&self, pt: Point) -> Option<ComponentId> {
+        self.children.iter().rev().find(|c| rect_contains(c.area, pt)).map(|c| c.id)
+    }
+
+    /// Answers the view which has captured the pointer, if any.
+    pub fn captured(&self) -> Option<ComponentId> {
+        self.captured.map(|(id, _)| id)
+    }
+
+    /// Answers the view `id`.
+    fn child(&mut self, id: ComponentId) -> &mut dyn Component<E> {
+        let child = self.children.iter_mut().find(|c| c.id == id).expect("component ids stay valid");
+        child.component.as_mut()
+    }
+
+    /// Sends leave and enter events as needed
+    /// to make the view under the pointer the hot one.
+    fn track_hot(&mut self, med: &mut dyn Mediator, events: &mut Vec<E>) {
+        let hot = self.component_at(self.mouse_pt);
+        if hot != self.hot {
+            if let Some(old) = self.hot {
+                events.push(self.child(old).leave(med));
+            }
+            self.hot = hot;
+            if let Some(new) = hot {
+                let at = self.mouse_pt;
+                events.push(self.child(new).enter(med, at));
+            }
+        }
+    }
+
+    /// Answers the view mouse events go to: the one which captured the pointer, if any,
+    /// otherwise the one under the pointer.
+    fn target(&self) -> Option<ComponentId> {
+        self.captured().or(self.hot)
+    }
+}
+
+impl<E: 'static> View for Container<E> {
+    /// Draws every view, from the bottom up.
+    fn draw(&mut self, med: &mut dyn Mediator) {
+        for c in self.children.iter_mut() {
+            c.component.draw(med);
+        }
+    }
+}
+
+impl<E: 'static> MouseEventSink<Vec<E>> for Container<E> {
+    /// Handles mouse motion events.
+    ///
+    /// While a view has captured the pointer, only it hears of motion.
+    /// Otherwise, views the pointer leaves and enters are told so,
+    /// and the view under the pointer hears of the motion.
+    ///
+    /// Answers the events of the views involved, in the order they happened.
+    fn pointer_moved(&mut self, med: &mut dyn Mediator, to: Point) -> Vec<E> {
+        self.mouse_pt = to;
+        let mut events = Vec::new();
+        if self.captured.is_none() {
+            self.track_hot(med, &mut events);
+        }
+        if let Some(id) = self.target() {
+            events.push(self.child(id).pointer_moved(med, to));
+        }
+        events
+    }
+
+    /// Handles mouse button-up events,
+    /// sending them to the view under the pointer,
+    /// or to the view which captured the pointer.
+    ///
+    /// Releasing the button which captured the pointer releases it,
+    /// after which the views the pointer has since left or entered are told so.
+    fn button_up(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> Vec<E> {
+        self.mouse_pt = event.at;
+        let mut events = Vec::new();
+        if let Some(id) = self.target() {
+            events.push(self.child(id).button_up(med, event));
+        }
+        if self.captured.is_some_and(|(_, button)| button == event.button) {
+            self.captured = None;
+            self.track_hot(med, &mut events);
+        }
+        events
+    }
+
+    /// Handles mouse button-down events,
+    /// sending them to the view under the pointer,
+    /// which captures the pointer until the button is released.
+    fn button_down(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> Vec<E> {
+        self.mouse_pt = event.at;
+        let mut events = Vec::new();
+        if self.captured.is_none() {
+            self.track_hot(med, &mut events);
+            self.captured = self.hot.map(|id| (id, event.button));
+        }
+        if let Some(id) = self.target() {
+            events.push(self.child(id).button_down(med, event));
+        }
+        events
+    }
+
+    /// Handles mouse wheel events,
+    /// sending them to the view under the pointer,
+    /// or to the view which captured the pointer.
+    fn wheel(&mut self, med: &mut dyn Mediator, delta: Point, modifiers: Modifiers) -> Vec<E> {
+        match self.target() {
+            Some(id) => vec![self.child(id).wheel(med, delta, modifiers)],
+            None => Vec::new(),
+        }
+    }
+
+    /// Handles mouse entry events,
+    /// telling the view under the pointer that the pointer entered it.
+    fn enter(&mut self, med: &mut dyn Mediator, at: Point) -> Vec<E> {
+        self.mouse_pt = at;
+        let mut events = Vec::new();
+        if self.captured.is_none() {
+            self.track_hot(med, &mut events);
+        }
+        events
+    }
+
+    /// Handles mouse exit events,
+    /// telling the view under the pointer that the pointer left it,
+    /// unless it has captured the pointer.
+    fn leave(&mut self, med: &mut dyn Mediator) -> Vec<E> {
+        let mut events = Vec::new();
+        if self.captured.is_none() {
+            if let Some(old) = self.hot.take() {
+                events.push(self.child(old).leave(med));
+            }
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Container;
+    use crate::events::{ButtonEvent, Modifiers, MouseEventSink};
+    use crate::layout::Layout;
+    use crate::mediator::Mediator;
+    use crate::types::Point;
+    use crate::utils::testing::TestMediator;
+    use crate::view::View;
+
+    /// A view which reports every event it receives.
+    struct Probe;
+
+    impl View for Probe {
+        fn draw(&mut self, _med: &mut dyn Mediator) {}
+    }
+
+    impl MouseEventSink<&'static str> for Probe {
+        fn pointer_moved(&mut self, _med: &mut dyn Mediator, _to: Point) -> &'static str {
+            "move"
+        }
+
+        fn button_up(&mut self, _med: &mut dyn Mediator, _event: ButtonEvent) -> &'static str {
+            "up"
+        }
+
+        fn button_down(&mut self, _med: &mut dyn Mediator, _event: ButtonEvent) -> &'static str {
+            "down"
+        }
+
+        fn wheel(&mut self, _med: &mut dyn Mediator, _delta: Point, _modifiers: Modifiers) -> &'static str {
+            "wheel"
+        }
+
+        fn enter(&mut self, _med: &mut dyn Mediator, _at: Point) -> &'static str {
+            "enter"
+        }
+
+        fn leave(&mut self, _med: &mut dyn Mediator) -> &'static str {
+            "leave"
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Ev {
+        A(&'static str),
+        B(&'static str),
+    }
+
+    fn container() -> Container<Ev> {
+        let mut c = Container::new();
+        c.add(((0, 0), (10, 10)), Probe, Ev::A);
+        c.add(((5, 0), (20, 10)), Probe, Ev::B);
+        c
+    }
+
+    #[test]
+    fn pointer_events_go_to_the_topmost_view() {
+        let mut med = TestMediator::new(32, 32);
+        let mut c = container();
+        assert_eq!(c.pointer_moved(&mut med, (2, 2)), vec![Ev::A("enter"), Ev::A("move")]);
+        assert_eq!(c.pointer_moved(&mut med, (3, 2)), vec![Ev::A("move")]);
+
+        // Where the views overlap, the later one is on top.
+        assert_eq!(c.pointer_moved(&mut med, (7, 2)), vec![Ev::A("leave"), Ev::B("enter"), Ev::B("move")]);
+        assert_eq!(c.pointer_moved(&mut med, (30, 2)), vec![Ev::B("leave")]);
+        assert_eq!(c.wheel(&mut med, (0, 1), Modifiers::NONE), vec![]);
+        assert!(c.get::<Probe>(1).is_some());
+        assert_eq!(c.component_at((7, 2)), Some(1));
+    }
+
+    #[test]
+    fn pressing_a_button_captures_the_pointer() {
+        let mut med = TestMediator::new(32, 32);
+        let mut c = container();
+        c.pointer_moved(&mut med, (2, 2));
+        assert_eq!(c.button_down(&mut med, ButtonEvent::left((2, 2))), vec![Ev::A("down")]);
+        assert_eq!(c.captured(), Some(0));
+
+        // While captured, the pointer stays with the view it was pressed in.
+        assert_eq!(c.pointer_moved(&mut med, (15, 2)), vec![Ev::A("move")]);
+        assert_eq!(c.pointer_moved(&mut med, (30, 2)), vec![Ev::A("move")]);
+        assert_eq!(c.leave(&mut med), vec![]);
+
+        // Letting go catches up on where the pointer went.
+        assert_eq!(c.button_up(&mut med, ButtonEvent::left((15, 2))), vec![Ev::A("up"), Ev::A("leave"), Ev::B("enter")]);
+        assert_eq!(c.captured(), None);
+    }
+
+    #[test]
+    fn reflowing_moves_views_where_events_find_them() {
+        let mut c = container();
+        let row = Layout::row(4, vec![Layout::item(0), Layout::stretched(1)]);
+        c.reflow(&row, ((0, 20), (40, 30)));
+        assert_eq!(c.get_area(0), Some(((0, 20), (10, 30))));
+        assert_eq!(c.get_area(1), Some(((14, 20), (40, 30))));
+        assert_eq!(c.component_at((7, 2)), None);
+        assert_eq!(c.component_at((12, 25)), None);
+        assert_eq!(c.component_at((20, 25)), Some(1));
+    }
+}
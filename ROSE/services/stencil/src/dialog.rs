@@ -0,0 +1,285 @@
+//! Modal dialogs
+//!
+//! A [[Dialog]] asks the operator something,
+//! and keeps asking until they answer.
+//! While one is open,
+//! the application hands it every mouse, keyboard and timer event,
+//! so nothing else on the desktop responds until it's dismissed.
+//!
+//! A [[ModalDialog]] takes care of the rest:
+//! it centres the dialog on the desktop,
+//! saves the pixels the dialog (and its shadow) will cover,
+//! gives the dialog the keyboard focus,
+//! and draws it.
+//! When the dialog answers [[DialogEvent::Dismissed]],
+//! it puts the pixels back and returns the focus to whoever had it,
+//! so the application need not redraw anything behind it.
+//!
+//! The stock requesters in [[crate::gadgets::requester]]
+//! cover the usual questions.
+
+use std::path::PathBuf;
+
+use crate::events::{ButtonEvent, KeyCode, KeyboardEventSink, Modifiers, MouseEventSink, TimerEventSink};
+use crate::focus::FocusId;
+use crate::mediator::Mediator;
+use crate::stencil::{Draw, SavedArea};
+use crate::timers::TimerId;
+use crate::types::{Point, Rect};
+use crate::utils::draw_dialog_box;
+use crate::view::View;
+
+/// Something which can be shown modally by a [[ModalDialog]].
+///
+/// Its [[View::draw]] draws only its contents;
+/// the box they sit in is drawn for it.
+pub trait Dialog: View + MouseEventSink<DialogEvent> + KeyboardEventSink<DialogEvent> + TimerEventSink<DialogEvent> {
+    /// Answers the rectangle the dialog's paper occupies.
+    fn get_area(&self) -> Rect;
+
+    /// Answers the gadget which should have the keyboard focus when the dialog opens, if any.
+    fn initial_focus(&self) -> Option<FocusId>;
+}
+
+/// How the operator answered a dialog.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DialogResult {
+    /// The operator accepted the dialog.
+    Ok,
+
+    /// The operator backed out.
+    Cancel,
+
+    /// The operator accepted the dialog, having typed this text.
+    Text(String),
+
+    /// The operator accepted the dialog, having picked this file.
+    File(PathBuf),
+}
+
+/// Events a dialog may answer with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DialogEvent {
+    /// Nothing of interest happened.
+    None,
+
+    /// The operator answered the dialog, which should now be closed.
+    Dismissed(DialogResult),
+}
+
+/// A dialog shown on top of the desktop, with everything it covers saved.
+///
+/// The application keeps hold of it for as long as it's open,
+/// sending it every event it receives.
+/// Once an event answers [[DialogEvent::Dismissed]],
+/// the screen is already back as it was,
+/// and the modal dialog should be dropped.
+pub struct ModalDialog {
+    /// The dialog being shown.
+    dialog: Box<dyn Dialog>,
+
+    /// The pixels under the dialog and its shadow.
+    saved: SavedArea,
+
+    /// Whoever had the keyboard focus before the dialog opened.
+    old_focus: Option<FocusId>,
+
+    /// Set once the dialog has been dismissed.
+    closed: bool,
+}
+
+impl ModalDialog {
+    /// Opens `dialog` in the middle of the desktop,
+    /// at its preferred size if it has one.
+    pub fn open(med: &mut dyn Mediator, dialog: impl Dialog + 'static) -> Self {
+        let mut dialog: Box<dyn Dialog> = Box::new(dialog);
+        let (width, height) = med.borrow_mut_desktop().get_dimensions();
+        let ((left, top), (right, bottom)) = dialog.get_area();
+        let (w, h) = dialog.preferred_size().unwrap_or((right - left, bottom - top));
+        let (left, top) = ((width - w) / 2, (height - h) / 2);
+        let area = ((left, top), (left + w, top + h));
+        dialog.set_area(area);
+
+        // The box's border lies just outside the paper, and its shadow beyond that.
+        let theme = med.theme();
+        let (border, beyond) = (theme.border, theme.border + theme.shadow);
+        let desktop = med.borrow_mut_desktop();
+        let saved = desktop.save_area((left - border, top - border), (left + w + beyond, top + h + beyond));
+        draw_dialog_box(desktop, area, theme);
+
+        let old_focus = med.focus();
+        med.set_focus(dialog.initial_focus());
+        dialog.draw(med);
+        med.repaint_all();
+
+        Self { dialog, saved, old_focus, closed: false }
+    }
+
+    /// Answers `true` until the dialog has been dismissed.
+    pub fn is_open(&self) -> bool {
+        !self.closed
+    }
+
+    /// Closes the dialog if `evt` dismissed it, and passes `evt` on.
+    fn follow(&mut self, med: &mut dyn Mediator, evt: DialogEvent) -> DialogEvent {
+        if let DialogEvent::Dismissed(_) = evt {
+            self.closed = true;
+            med.borrow_mut_desktop().restore_area(&self.saved);
+            med.set_focus(self.old_focus);
+            med.repaint_all();
+        }
+        evt
+    }
+}
+
+/// Every mouse event goes to the dialog, wherever the pointer is.
+/// Once dismissed, the dialog hears nothing more, and answers [[DialogEvent::None]].
+impl MouseEventSink<DialogEvent> for ModalDialog {
+    fn pointer_moved(&mut self, med: &mut dyn Mediator, to: Point) -> DialogEvent {
+        if self.closed {
+            return DialogEvent::None;
+        }
+        let evt = self.dialog.pointer_moved(med, to);
+        self.follow(med, evt)
+    }
+
+    fn button_up(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> DialogEvent {
+        if self.closed {
+            return DialogEvent::None;
+        }
+        let evt = self.dialog.button_up(med, event);
+        self.follow(med, evt)
+    }
+
+    fn button_down(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> DialogEvent {
+        if self.closed {
+            return DialogEvent::None;
+        }
+        let evt = self.dialog.button_down(med, event);
+        self.follow(med, evt)
+    }
+
+    fn wheel(&mut self, med: &mut dyn Mediator, delta: Point, modifiers: Modifiers) -> DialogEvent {
+        if self.closed {
+            return DialogEvent::None;
+        }
+        let evt = self.dialog.wheel(med, delta, modifiers);
+        self.follow(med, evt)
+    }
+
+    fn enter(&mut self, med: &mut dyn Mediator, at: Point) -> DialogEvent {
+        if self.closed {
+            return DialogEvent::None;
+        }
+        let evt = self.dialog.enter(med, at);
+        self.follow(med, evt)
+    }
+
+    fn leave(&mut self, med: &mut dyn Mediator) -> DialogEvent {
+        if self.closed {
+            return DialogEvent::None;
+        }
+        let evt = self.dialog.leave(med);
+        self.follow(med, evt)
+    }
+}
+
+/// Every key goes to the dialog, whatever has the focus.
+impl KeyboardEventSink<DialogEvent> for ModalDialog {
+    fn key_down(&mut self, med: &mut dyn Mediator, key: KeyCode, modifiers: Modifiers) -> DialogEvent {
+        if self.closed {
+            return DialogEvent::None;
+        }
+        let evt = self.dialog.key_down(med, key, modifiers);
+        self.follow(med, evt)
+    }
+
+    fn key_up(&mut self, med: &mut dyn Mediator, key: KeyCode, modifiers: Modifiers) -> DialogEvent {
+        if self.closed {
+            return DialogEvent::None;
+        }
+        let evt = self.dialog.key_up(med, key, modifiers);
+        self.follow(med, evt)
+    }
+
+    fn text_input(&mut self, med: &mut dyn Mediator, text: &str) -> DialogEvent {
+        if self.closed {
+            return DialogEvent::None;
+        }
+        let evt = self.dialog.text_input(med, text);
+        self.follow(med, evt)
+    }
+}
+
+/// Timers go to the dialog too, for the sake of gadgets which repeat or blink.
+/// The application's own timers keep firing underneath,
+/// so it must still pass those on to whatever started them.
+impl TimerEventSink<DialogEvent> for ModalDialog {
+    fn timer_fired(&mut self, med: &mut dyn Mediator, id: TimerId) -> DialogEvent {
+        if self.closed {
+            return DialogEvent::None;
+        }
+        let evt = self.dialog.timer_fired(med, id);
+        self.follow(med, evt)
+    }
+
+    fn idle(&mut self, med: &mut dyn Mediator) -> DialogEvent {
+        if self.closed {
+            return DialogEvent::None;
+        }
+        let evt = self.dialog.idle(med);
+        self.follow(med, evt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DialogEvent, DialogResult, ModalDialog};
+    use crate::events::{ButtonEvent, KeyCode, KeyboardEventSink, Modifiers, MouseEventSink};
+    use crate::gadgets::requester::Requester;
+    use crate::mediator::Mediator;
+    use crate::theme::HIGH_CONTRAST_THEME;
+    use crate::utils::{draw_desktop, testing::TestMediator};
+
+    #[test]
+    fn dismissing_restores_the_screen_and_focus() {
+        let mut med = TestMediator::new(320, 200);
+        draw_desktop(&mut med.desktop, med.theme);
+        med.set_focus(Some(7));
+        let before = med.desktop.borrow_bits().to_vec();
+
+        let requester = Requester::alert(med.theme(), "Hello!");
+        let mut dialog = ModalDialog::open(&mut med, requester);
+        assert_ne!(med.desktop.borrow_bits(), &before[..]);
+        assert_ne!(med.focus(), Some(7));
+
+        // Everything goes to the dialog, even clicks far away from it.
+        assert_eq!(dialog.button_down(&mut med, ButtonEvent::left((1, 1))), DialogEvent::None);
+        assert_eq!(
+            dialog.key_down(&mut med, KeyCode::Return, Modifiers::NONE),
+            DialogEvent::Dismissed(DialogResult::Ok),
+        );
+        assert!(!dialog.is_open());
+        assert_eq!(med.desktop.borrow_bits(), &before[..]);
+        assert_eq!(med.focus(), Some(7));
+
+        assert_eq!(dialog.key_down(&mut med, KeyCode::Return, Modifiers::NONE), DialogEvent::None);
+    }
+
+    #[test]
+    fn thicker_borders_are_restored_too() {
+        // The grey desktop shows up any of the black border or shadow left behind.
+        let mut med = TestMediator::new(320, 200);
+        draw_desktop(&mut med.desktop, med.theme);
+        med.set_theme(&HIGH_CONTRAST_THEME);
+        let before = med.desktop.borrow_bits().to_vec();
+
+        let requester = Requester::confirm(med.theme(), "Really?");
+        let mut dialog = ModalDialog::open(&mut med, requester);
+        assert_eq!(
+            dialog.key_down(&mut med, KeyCode::Escape, Modifiers::NONE),
+            DialogEvent::Dismissed(DialogResult::Cancel),
+        );
+        assert_eq!(med.desktop.borrow_bits(), &before[..]);
+    }
+}
@@ -15,8 +15,11 @@
 //! Thankfully, a gadget library exists to make this easier
 //! for the most commonly used types of gadgets.
 
-use crate::types::Point;
+use std::ops::BitOr;
+
+use crate::types::{Dimension, Point};
 use crate::mediator::Mediator;
+use crate::timers::TimerId;
 
 /// Application controller.
 ///
@@ -25,7 +28,7 @@ use crate::mediator::Mediator;
 /// In general,
 /// the top-level application state structure
 /// will also be the global application controller.
-pub trait AppController: MouseEventSink<()> + AppEventSink {}
+pub trait AppController: MouseEventSink<()> + KeyboardEventSink<()> + TimerEventSink<()> + AppEventSink {}
 
 /// A sink for application events.
 pub trait AppEventSink {
@@ -39,12 +42,17 @@ pub trait AppEventSink {
     /// to save any unsaved state to storage before
     /// actually quitting.
     ///
+    /// An application with unsaved changes might instead open a [[ModalDialog]]
+    /// asking whether to save them, and answer `false`;
+    /// once the operator has answered,
+    /// it calls [[Mediator::quit]] again, and this time answers `true`.
+    ///
     /// **NOTE:** Don't abuse this function by always returning `false`.
     /// The host operating system will almost certainly
     /// have a way to force the termination of your program regardless.
     ///
     /// See also [[Mediator::quit]].
-    fn request_quit(&self) -> bool;
+    fn request_quit(&mut self, med: &mut dyn Mediator) -> bool;
 }
 
 /// A sink for raw mouse events.
@@ -65,13 +73,233 @@ pub trait AppEventSink {
 pub trait MouseEventSink<T> {
     /// Called when the mouse pointer moves.
     fn pointer_moved(&mut self, med: &mut dyn Mediator, to: Point) -> T;
-    /// Called when the mouse button is released.
-    fn button_up(&mut self, med: &mut dyn Mediator) -> T;
-    /// Called when the mouse button is pressed.
-    fn button_down(&mut self, med: &mut dyn Mediator) -> T;
+    /// Called when a mouse button is released.
+    fn button_up(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> T;
+    /// Called when a mouse button is pressed.
+    fn button_down(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> T;
+    /// Called when the mouse wheel turns by `delta` notches.
+    /// Positive deltas turn the wheel right, or away from the operator.
+    fn wheel(&mut self, med: &mut dyn Mediator, delta: Point, modifiers: Modifiers) -> T;
     /// Called when the mouse enters into a view's boundary.
     fn enter(&mut self, med: &mut dyn Mediator, at: Point) -> T;
     /// Called when the mouse leaves a view's boundary.
     fn leave(&mut self, med: &mut dyn Mediator) -> T;
 }
 
+
+/// A sink for timer and idle events.
+///
+/// Both are only delivered on request;
+/// see [[Mediator::start_timer]] and [[Mediator::request_idle]].
+pub trait TimerEventSink<T> {
+    /// Called when the timer `id` fires.
+    fn timer_fired(&mut self, med: &mut dyn Mediator, id: TimerId) -> T;
+    /// Called once no other events are waiting, after idle time was requested.
+    fn idle(&mut self, med: &mut dyn Mediator) -> T;
+}
+
+/// Identifies a mouse button.
+///
+/// A one-button mouse, like the C64's 1351, only ever reports [[MouseButton::Left]].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+}
+
+/// Describes a mouse button being pressed or released.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ButtonEvent {
+    /// Which button was pressed or released.
+    pub button: MouseButton,
+
+    /// Where the mouse pointer was at the time.
+    pub at: Point,
+
+    /// How many times in quick succession the button has been pressed,
+    /// counting this press: 1 for a single click, 2 for a double click, and so on.
+    /// A release reports the same count as the press before it.
+    pub clicks: usize,
+
+    /// The modifier keys held down at the time.
+    pub modifiers: Modifiers,
+}
+
+impl ButtonEvent {
+    /// Describes a single click of the left mouse button at `at`, without modifiers.
+    pub fn left(at: Point) -> Self {
+        Self { button: MouseButton::Left, at, clicks: 1, modifiers: Modifiers::NONE }
+    }
+}
+
+/// How long, in milliseconds, a click may follow the one before it
+/// and still count towards a double (or triple) click, unless configured otherwise.
+pub const DEFAULT_CLICK_INTERVAL: u32 = 500;
+
+/// How far, in pixels, the pointer may stray between clicks
+/// and still count towards a double (or triple) click, unless configured otherwise.
+pub const DEFAULT_CLICK_SLOP: Dimension = 4;
+
+/// Counts successive presses of a mouse button,
+/// so that hosts can tell double and triple clicks from single ones.
+///
+/// Presses count together when they're of the same button,
+/// close enough together in time,
+/// and close enough together on the screen.
+#[derive(Clone, Debug)]
+pub struct ClickCounter {
+    /// The longest time, in milliseconds, allowed between presses counted together.
+    pub interval: u32,
+
+    /// The furthest distance, in pixels, allowed between presses counted together.
+    pub slop: Dimension,
+
+    /// The button, place and time of the latest press, if any.
+    last: Option<(MouseButton, Point, u32)>,
+
+    /// How many presses have been counted together so far.
+    count: usize,
+}
+
+impl Default for ClickCounter {
+    fn default() -> Self {
+        Self::new(DEFAULT_CLICK_INTERVAL, DEFAULT_CLICK_SLOP)
+    }
+}
+
+impl ClickCounter {
+    /// Creates a click counter which counts presses together
+    /// if they're no more than `interval` milliseconds
+    /// and `slop` pixels apart.
+    pub fn new(interval: u32, slop: Dimension) -> Self {
+        Self { interval, slop, last: None, count: 0 }
+    }
+
+    /// Records a press of `button` at `at`, `time` milliseconds after some fixed moment.
+    /// Answers how many presses in a row this makes.
+    pub fn press(&mut self, button: MouseButton, at: Point, time: u32) -> usize {
+        let follows = self.last.is_some_and(|(b, (x, y), t)| {
+            (b == button)
+                && (time.wrapping_sub(t) <= self.interval)
+                && ((at.0 - x).abs() <= self.slop)
+                && ((at.1 - y).abs() <= self.slop)
+        });
+        self.count = if follows { self.count + 1 } else { 1 };
+        self.last = Some((button, at, time));
+        self.count
+    }
+
+    /// Answers how many presses in a row the latest press made,
+    /// for reporting alongside its release.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+/// Identifies a key on the keyboard, independently of the host platform.
+///
+/// Only keys a Commodore 64 also has (or has an obvious stand-in for)
+/// are distinguished, so that applications written against them
+/// can run on a C64's 64-key keyboard.
+/// For instance, [[KeyCode::Home]] is CLR/HOME,
+/// [[KeyCode::Backspace]] is INST/DEL,
+/// and [[KeyCode::Escape]] is RUN/STOP.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyCode {
+    /// A key which types a character,
+    /// identified by the character it types without any modifiers.
+    /// Letters are always lower-case.
+    Char(char),
+
+    Return,
+    Escape,
+    Tab,
+    Backspace,
+    Delete,
+    Insert,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+
+    /// A function key, numbered from 1.
+    Function(u8),
+}
+
+/// A set of modifier keys held down while a key or button is pressed.
+///
+/// Modifiers combine freely with `|`;
+/// for example, `Modifiers::SHIFT | Modifiers::CONTROL`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Modifiers(u8);
+
+impl Modifiers {
+    /// No modifier keys are held down.
+    pub const NONE: Modifiers = Modifiers(0);
+
+    /// Either shift key.
+    pub const SHIFT: Modifiers = Modifiers(0x01);
+
+    /// The control key.
+    pub const CONTROL: Modifiers = Modifiers(0x02);
+
+    /// The Commodore key; the Alt key on a PC keyboard.
+    pub const COMMODORE: Modifiers = Modifiers(0x04);
+
+    /// Answers `true` if every modifier in `other` is also held down in `self`.
+    pub fn contains(self, other: Modifiers) -> bool {
+        (self.0 & other.0) == other.0
+    }
+}
+
+impl BitOr for Modifiers {
+    type Output = Modifiers;
+
+    fn bitor(self, rhs: Modifiers) -> Modifiers {
+        Modifiers(self.0 | rhs.0)
+    }
+}
+
+/// A sink for raw keyboard events.
+///
+/// Like [[MouseEventSink]],
+/// all trait methods return a value of type `T`,
+/// which gadgets use to report higher-level events.
+///
+/// Keys report what was pressed;
+/// text input reports what was typed.
+/// Anything wanting text, like a text field,
+/// should take it from [[KeyboardEventSink::text_input]]
+/// rather than interpreting [[KeyCode::Char]] keys itself,
+/// since the host knows best how keys (and modifiers) turn into characters.
+pub trait KeyboardEventSink<T> {
+    /// Called when a key is pressed, or repeats while held down.
+    fn key_down(&mut self, med: &mut dyn Mediator, key: KeyCode, modifiers: Modifiers) -> T;
+    /// Called when a key is released.
+    fn key_up(&mut self, med: &mut dyn Mediator, key: KeyCode, modifiers: Modifiers) -> T;
+    /// Called when the operator types `text`.
+    fn text_input(&mut self, med: &mut dyn Mediator, text: &str) -> T;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClickCounter, MouseButton};
+
+    #[test]
+    fn click_counter_counts_quick_clicks_in_place() {
+        let mut clicks = ClickCounter::new(300, 2);
+        assert_eq!(clicks.press(MouseButton::Left, (10, 10), 1000), 1);
+        assert_eq!(clicks.press(MouseButton::Left, (11, 9), 1200), 2);
+        assert_eq!(clicks.press(MouseButton::Left, (12, 10), 1500), 3);
+        assert_eq!(clicks.count(), 3);
+
+        // Too slow, too far, or the wrong button, and counting starts over.
+        assert_eq!(clicks.press(MouseButton::Left, (12, 10), 1801), 1);
+        assert_eq!(clicks.press(MouseButton::Left, (15, 10), 1900), 1);
+        assert_eq!(clicks.press(MouseButton::Right, (15, 10), 2000), 1);
+        assert_eq!(clicks.press(MouseButton::Right, (15, 10), 2100), 2);
+    }
+}
@@ -0,0 +1,123 @@
+//! Keyboard focus.
+//!
+//! Only one gadget at a time receives keyboard input:
+//! the *focus owner*.
+//! The [[Mediator]] remembers which one it is,
+//! so that any gadget can ask whether it has the focus.
+//!
+//! Gadgets are identified by a [[FocusId]] the application gives them.
+//! A [[FocusRing]] lists them in the order the operator visits them
+//! when pressing Tab (or Shift-Tab, to go the other way).
+
+use crate::events::{KeyCode, KeyboardEventSink, Modifiers};
+use crate::mediator::Mediator;
+
+/// Identifies a gadget which can own the keyboard focus.
+///
+/// Identifiers are chosen by the application,
+/// and need only be unique among its gadgets.
+pub type FocusId = usize;
+
+/// The gadgets the operator can move the focus between, in order.
+pub struct FocusRing {
+    /// The members of the ring, in the order Tab visits them.
+    members: Vec<FocusId>,
+}
+
+impl FocusRing {
+    /// Creates a focus ring visiting `members` in order.
+    pub fn new(members: Vec<FocusId>) -> Self {
+        Self { members }
+    }
+
+    /// Moves the focus to the next member of the ring,
+    /// or to the previous member if `backwards` is set,
+    /// wrapping around at either end.
+    ///
+    /// If no member has the focus,
+    /// the first (or last) member receives it.
+    /// Answers what happened, unless the ring is empty.
+    pub fn advance(&self, med: &mut dyn Mediator, backwards: bool) -> FocusEvent {
+        let n = self.members.len();
+        if n == 0 {
+            return FocusEvent::None;
+        }
+
+        let from = med.focus();
+        let current = from.and_then(|id| self.members.iter().position(|&m| m == id));
+        let next = match (current, backwards) {
+            (Some(i), false) => (i + 1) % n,
+            (Some(i), true) => (i + n - 1) % n,
+            (None, false) => 0,
+            (None, true) => n - 1,
+        };
+        let to = self.members[next];
+        med.set_focus(Some(to));
+        FocusEvent::Moved { from, to }
+    }
+}
+
+/// Events unique to the focus ring.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FocusEvent {
+    /// No event recognized.
+    None,
+
+    /// The focus moved from one gadget to another.
+    /// Both should be redrawn, to show which has the focus now.
+    Moved { from: Option<FocusId>, to: FocusId },
+}
+
+impl KeyboardEventSink<FocusEvent> for FocusRing {
+    /// Handles key-down events.
+    ///
+    /// Tab advances the focus, and Shift-Tab moves it back.
+    /// Otherwise, answers with [[FocusEvent::None]].
+    fn key_down(&mut self, med: &mut dyn Mediator, key: KeyCode, modifiers: Modifiers) -> FocusEvent {
+        match key {
+            KeyCode::Tab => self.advance(med, modifiers.contains(Modifiers::SHIFT)),
+            _ => FocusEvent::None,
+        }
+    }
+
+    /// Handles key-up events.
+    ///
+    /// Currently, always answers with [[FocusEvent::None]].
+    fn key_up(&mut self, _med: &mut dyn Mediator, _key: KeyCode, _modifiers: Modifiers) -> FocusEvent {
+        FocusEvent::None
+    }
+
+    /// Handles text input events.
+    ///
+    /// Currently, always answers with [[FocusEvent::None]].
+    fn text_input(&mut self, _med: &mut dyn Mediator, _text: &str) -> FocusEvent {
+        FocusEvent::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FocusEvent, FocusRing};
+    use crate::events::{KeyCode, KeyboardEventSink, Modifiers};
+    use crate::mediator::Mediator;
+    use crate::utils::testing::TestMediator;
+
+    #[test]
+    fn tab_cycles_through_the_ring() {
+        let mut med = TestMediator::new(8, 8);
+        let mut ring = FocusRing::new(vec![3, 1, 2]);
+        let mut tab = |med: &mut TestMediator, modifiers| ring.key_down(med, KeyCode::Tab, modifiers);
+
+        assert_eq!(tab(&mut med, Modifiers::NONE), FocusEvent::Moved { from: None, to: 3 });
+        assert_eq!(tab(&mut med, Modifiers::NONE), FocusEvent::Moved { from: Some(3), to: 1 });
+        tab(&mut med, Modifiers::NONE);
+        assert_eq!(tab(&mut med, Modifiers::NONE), FocusEvent::Moved { from: Some(2), to: 3 });
+        assert_eq!(tab(&mut med, Modifiers::SHIFT), FocusEvent::Moved { from: Some(3), to: 2 });
+
+        // Focus held by something outside the ring starts over at either end.
+        med.set_focus(Some(9));
+        assert_eq!(tab(&mut med, Modifiers::SHIFT), FocusEvent::Moved { from: Some(9), to: 2 });
+        assert_eq!(ring.key_down(&mut med, KeyCode::Return, Modifiers::NONE), FocusEvent::None);
+        assert_eq!(med.focus(), Some(2));
+    }
+}
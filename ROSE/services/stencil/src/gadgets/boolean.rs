@@ -1,15 +1,18 @@
 //! Support for boolean gadgets.
 
 use crate::mediator::Mediator;
-use crate::types::{Point, Rect};
+use crate::types::{Dimension, Dimensions, Point, Rect};
 use crate::view::{View, rect_contains};
-use crate::events::MouseEventSink;
+use crate::events::{ButtonEvent, KeyCode, KeyboardEventSink, Modifiers, MouseButton, MouseEventSink};
+use crate::focus::FocusId;
 use crate::simple_printer::SimplePrinter;
-use crate::utils::{LINE_BLACK, WHITE_PATTERN};
+use crate::utils::LINE_BLACK;
 use crate::simple_bitmap_font::{SimpleBitmapFont, text_width};
-use crate::sysfont_bsw_9::SYSTEM_BITMAP_FONT;
 use crate::stencil::Draw;
 
+/// How much space a push button leaves either side of its label.
+const LABEL_MARGIN: Dimension = 8;
+
 /// Maintains the appearance of a boolean gadget.
 ///
 /// The lifetime `'l` corresponds to the lifetime of the label.
@@ -26,6 +29,9 @@ pub struct PushButtonView<'l, 'f> {
     /// Tracks the current mouse position.
     mouse_pt: Point,
 
+    /// Identifies the button to the mediator's focus owner, if it takes part in focus at all.
+    focus_id: Option<FocusId>,
+
     // TODO:
     // The following fields really belongs in a separate sub-view
     // structure of some kind.  Refactor accordingly.
@@ -52,9 +58,24 @@ impl<'l, 'f> PushButtonView<'l, 'f> {
 
             selected: false,
             mouse_pt: (0, 0),
+            focus_id: None,
         }
     }
 
+    /// Identifies the button as `id` to the mediator's focus owner.
+    ///
+    /// While it's the focus owner,
+    /// the button is drawn with a second frame inside the first,
+    /// and Return or the space bar click it.
+    pub fn set_focus_id(&mut self, id: FocusId) {
+        self.focus_id = Some(id);
+    }
+
+    /// Answers `true` if the button has the keyboard focus.
+    fn has_focus(&self, med: &dyn Mediator) -> bool {
+        self.focus_id.is_some() && (med.focus() == self.focus_id)
+    }
+
     /// Inverts the gadget's appearance.
     pub fn invert(&mut self, med: &mut dyn Mediator) {
         med.borrow_mut_desktop().invert_rectangle(self.area.0, self.area.1);
@@ -67,50 +88,47 @@ impl<'l, 'f> PushButtonView<'l, 'f> {
 
 impl<'l, 'f> View for PushButtonView<'l, 'f> {
     fn draw(&mut self, med: &mut dyn Mediator) {
+        let focused = self.has_focus(med);
+        let theme = med.theme();
         let d = med.borrow_mut_desktop();
-        let font = &SYSTEM_BITMAP_FONT;
 
+        // The button's face and frame leave room at the right and bottom for its shadow.
         let ((btn_left, btn_top), (btn_right, btn_bottom)) = self.area;
-        let border_left = btn_left;
-        let border_top = btn_top;
-        let border_right = btn_right - 1;
-        let border_bottom = btn_bottom - 1;
-
-        let r_shadow_left = btn_right - 1;
-        let r_shadow_top = btn_top + 1;
-        let r_shadow_bottom = btn_bottom;
-
-        let b_shadow_left = btn_left + 1;
-        let b_shadow_top = btn_bottom - 1;
-        let b_shadow_right = btn_right;
-
-        let subview_left = btn_left;
-        let subview_top = btn_top;
-        let subview_right = btn_right - 1;
-        let subview_bottom = btn_bottom - 1;
+        let face_right = btn_right - theme.shadow;
+        let face_bottom = btn_bottom - theme.shadow;
 
         let label_width = text_width(self.label, self.font);
-        let btn_width = subview_right - subview_left;
+        let btn_width = face_right - btn_left;
         let label_left = ((btn_width - label_width) >> 1) + btn_left;
-        let label_top = subview_top + font.baseline;
-        let label_region = ((label_left, label_top), (subview_right, subview_bottom));
-
-        d.filled_rectangle(
-            (subview_left, subview_top),
-            (subview_right, subview_bottom),
-            &WHITE_PATTERN,
-        );
-        d.framed_rectangle(
-            (border_left, border_top),
-            (border_right, border_bottom),
-            LINE_BLACK,
-        );
-        d.horizontal_line((b_shadow_left, b_shadow_top), b_shadow_right, LINE_BLACK);
-        d.vertical_line((r_shadow_left, r_shadow_top), r_shadow_bottom, LINE_BLACK);
+        let label_top = btn_top + self.font.baseline;
+        let label_region = ((label_left, label_top), (face_right, face_bottom));
+
+        d.filled_rectangle((btn_left, btn_top), (face_right, face_bottom), &theme.paper);
+        theme.frame(d, (btn_left, btn_top), (face_right, face_bottom));
+        if focused {
+            let inset = theme.border + 1;
+            d.framed_rectangle(
+                (btn_left + inset, btn_top + inset),
+                (face_right - inset, face_bottom - inset),
+                LINE_BLACK,
+            );
+        }
+        theme.drop_shadow(d, (btn_left, btn_top), (face_right, face_bottom));
 
         let mut p = SimplePrinter::new(d, label_region, self.font);
         p.print(self.label);
     }
+
+    /// Answers a size with room for the label, its margins, and the button's frame and shadow.
+    fn preferred_size(&self) -> Option<Dimensions> {
+        let width = text_width(self.label, self.font) + 2 * LABEL_MARGIN;
+        let height = self.font.baseline + self.font.height + 4;
+        Some((width, height))
+    }
+
+    fn set_area(&mut self, area: Rect) {
+        self.area = area;
+    }
 }
 
 pub enum PushButtonEvent {
@@ -129,7 +147,11 @@ impl<'l, 'f> MouseEventSink<PushButtonEvent> for PushButtonView<'l, 'f> {
         PushButtonEvent::None
     }
 
-    fn button_up(&mut self, _: &mut dyn Mediator) -> PushButtonEvent {
+    fn button_up(&mut self, _: &mut dyn Mediator, event: ButtonEvent) -> PushButtonEvent {
+        if event.button != MouseButton::Left {
+            return PushButtonEvent::None;
+        }
+        self.mouse_pt = event.at;
         let evt = if self.selected && self.is_hot() {
             PushButtonEvent::Clicked
         } else {
@@ -139,8 +161,9 @@ impl<'l, 'f> MouseEventSink<PushButtonEvent> for PushButtonView<'l, 'f> {
         evt
     }
 
-    fn button_down(&mut self, med: &mut dyn Mediator) -> PushButtonEvent {
-        if rect_contains(self.area, self.mouse_pt) {
+    fn button_down(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> PushButtonEvent {
+        self.mouse_pt = event.at;
+        if (event.button == MouseButton::Left) && rect_contains(self.area, self.mouse_pt) {
             self.selected = true;
             self.invert(med);
             med.repaint_all();
@@ -155,5 +178,25 @@ impl<'l, 'f> MouseEventSink<PushButtonEvent> for PushButtonView<'l, 'f> {
     fn leave(&mut self, _: &mut dyn Mediator) -> PushButtonEvent {
         PushButtonEvent::None
     }
+
+    fn wheel(&mut self, _: &mut dyn Mediator, _: Point, _: Modifiers) -> PushButtonEvent {
+        PushButtonEvent::None
+    }
 }
 
+impl<'l, 'f> KeyboardEventSink<PushButtonEvent> for PushButtonView<'l, 'f> {
+    fn key_down(&mut self, med: &mut dyn Mediator, key: KeyCode, _: Modifiers) -> PushButtonEvent {
+        match key {
+            KeyCode::Return | KeyCode::Char(' ') if self.has_focus(med) => PushButtonEvent::Clicked,
+            _ => PushButtonEvent::None,
+        }
+    }
+
+    fn key_up(&mut self, _: &mut dyn Mediator, _: KeyCode, _: Modifiers) -> PushButtonEvent {
+        PushButtonEvent::None
+    }
+
+    fn text_input(&mut self, _: &mut dyn Mediator, _: &str) -> PushButtonEvent {
+        PushButtonEvent::None
+    }
+}
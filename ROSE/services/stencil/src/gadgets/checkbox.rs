@@ -0,0 +1,334 @@
+//! Support for check boxes.
+//!
+//! A check box is a boolean gadget
+//! which stays set or clear between clicks.
+//! It's drawn as a small box beside its label,
+//! ticked while it's set.
+//!
+//! Unlike the proportional gadget,
+//! a check box keeps its own state:
+//! it ticks or clears itself when clicked,
+//! and then reports the fact.
+
+use crate::events::{ButtonEvent, KeyCode, KeyboardEventSink, Modifiers, MouseButton, MouseEventSink};
+use crate::focus::FocusId;
+use crate::mediator::Mediator;
+use crate::simple_bitmap_font::{SimpleBitmapFont, text_width};
+use crate::simple_printer::SimplePrinter;
+use crate::stencil::{Draw, Stencil};
+use crate::types::{Dimension, Dimensions, Point, Rect};
+use crate::utils::{Glyph, LINE_BLACK, draw_glyph};
+use crate::view::{View, rect_contains};
+
+/// How big the box is, frame included, in pixels.
+pub const BOX_SIZE: Dimension = 10;
+
+/// How far the label sits from the box, in pixels.
+pub const LABEL_GAP: Dimension = 4;
+
+/// The tick drawn inside a set box.
+static TICK: Glyph = [0x01, 0x03, 0x06, 0x8C, 0xD8, 0x70, 0x20, 0x00];
+
+/// Answers where a box goes at the left-hand end of a row occupying `row`,
+/// centred from top to bottom.
+///
+/// Shared with the radio buttons, which line up the same way.
+pub(crate) fn box_in_row(row: Rect) -> Rect {
+    let ((left, top), (_, bottom)) = row;
+    let box_top = top + ((bottom - top - BOX_SIZE) >> 1);
+    ((left, box_top), (left + BOX_SIZE, box_top + BOX_SIZE))
+}
+
+/// Prints `label` to the right of the box in a row occupying `row`,
+/// framing it if the gadget has the focus.
+///
+/// Shared with the radio buttons, which label each button the same way.
+pub(crate) fn draw_label(d: &mut Stencil, row: Rect, label: &str, font: &SimpleBitmapFont, focused: bool) {
+    let ((left, top), (right, bottom)) = row;
+    let label_left = left + BOX_SIZE + LABEL_GAP;
+    let label_top = top + ((bottom - top - font.height) >> 1);
+    if focused {
+        let label_right = (label_left + text_width(label, font) + 2).min(right);
+        d.framed_rectangle((label_left - 2, label_top - 1), (label_right, label_top + font.height + 1), LINE_BLACK);
+    }
+    let mut p = SimplePrinter::new(d, ((label_left, label_top), (right, bottom)), font);
+    p.print(label);
+}
+
+/// Maintains the appearance of a check box.
+///
+/// The lifetime `'l` corresponds to the lifetime of the label.
+/// The lifetime `'f` corresponds to the lifetime of the font.
+pub struct CheckBoxView<'l, 'f> {
+    /// The rectangle occupied by the box and its label.
+    area: Rect,
+
+    /// True while the box is ticked.
+    checked: bool,
+
+    /// False if the check box ignores the operator, and is drawn lightened.
+    enabled: bool,
+
+    /// True while the operator holds the mouse button down,
+    /// having pressed it inside the check box.
+    pressed: bool,
+
+    /// True while the box is drawn inverted,
+    /// which it is only while pressed with the pointer over it.
+    inverted: bool,
+
+    /// Tracks the current mouse position.
+    mouse_pt: Point,
+
+    /// Identifies the check box to the mediator's focus owner, if it takes part in focus at all.
+    focus_id: Option<FocusId>,
+
+    /// Label to print beside the box.
+    label: &'l str,
+
+    /// Font to print the label in.
+    font: &'f SimpleBitmapFont<'f>,
+}
+
+impl<'l, 'f> CheckBoxView<'l, 'f> {
+    /// Creates a new, clear, enabled check box.
+    pub fn new(area: Rect, label: &'l str, font: &'f SimpleBitmapFont) -> Self {
+        Self {
+            area,
+            checked: false,
+            enabled: true,
+            pressed: false,
+            inverted: false,
+            mouse_pt: (0, 0),
+            focus_id: None,
+            label,
+            font,
+        }
+    }
+
+    /// Identifies the check box as `id` to the mediator's focus owner.
+    ///
+    /// While it's the focus owner,
+    /// the label is framed,
+    /// and Return or the space bar toggle it.
+    pub fn set_focus_id(&mut self, id: FocusId) {
+        self.focus_id = Some(id);
+    }
+
+    /// Answers `true` if the box is ticked.
+    pub fn is_checked(&self) -> bool {
+        self.checked
+    }
+
+    /// Ticks or clears the box.
+    /// The check box must be redrawn to show the change.
+    pub fn set_checked(&mut self, checked: bool) {
+        self.checked = checked;
+    }
+
+    /// Answers `true` if the check box responds to the operator.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enables or disables the check box.
+    /// The check box must be redrawn to show the change.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Answers `true` if the check box has the keyboard focus.
+    fn has_focus(&self, med: &dyn Mediator) -> bool {
+        self.focus_id.is_some() && (med.focus() == self.focus_id)
+    }
+
+    /// Answers `true` if the pointer is over the check box.
+    fn is_hot(&self) -> bool {
+        rect_contains(self.area, self.mouse_pt)
+    }
+
+    /// Inverts the inside of the box, if need be, so that it looks `pressed` or not.
+    fn show_pressed(&mut self, med: &mut dyn Mediator, pressed: bool) {
+        if pressed != self.inverted {
+            let ((left, top), (right, bottom)) = box_in_row(self.area);
+            med.borrow_mut_desktop().invert_rectangle((left + 1, top + 1), (right - 1, bottom - 1));
+            med.repaint_all();
+            self.inverted = pressed;
+        }
+    }
+
+    /// Ticks the box if it was clear, or clears it if it was ticked,
+    /// redrawing it to match.
+    fn toggle(&mut self, med: &mut dyn Mediator) -> CheckBoxEvent {
+        self.checked = !self.checked;
+        self.draw(med);
+        med.repaint_all();
+        CheckBoxEvent::Toggled(self.checked)
+    }
+}
+
+impl<'l, 'f> View for CheckBoxView<'l, 'f> {
+    fn draw(&mut self, med: &mut dyn Mediator) {
+        let focused = self.has_focus(med);
+        let theme = med.theme();
+        let d = med.borrow_mut_desktop();
+        let (upper_left, lower_right) = self.area;
+        d.filled_rectangle(upper_left, lower_right, &theme.paper);
+
+        let ((box_left, box_top), box_lower_right) = box_in_row(self.area);
+        theme.frame(d, (box_left, box_top), box_lower_right);
+        if self.checked {
+            draw_glyph(d, (box_left + 1, box_top + 1), &TICK);
+        }
+
+        draw_label(d, self.area, self.label, self.font, focused);
+
+        if !self.enabled {
+            d.lighten_rectangle(upper_left, lower_right);
+        }
+        if self.inverted {
+            self.inverted = false;
+            self.show_pressed(med, true);
+        }
+    }
+
+    /// Answers a size with room for the box, the label, and the frame shown around the label
+    /// while the check box has the focus.
+    fn preferred_size(&self) -> Option<Dimensions> {
+        let width = BOX_SIZE + LABEL_GAP + text_width(self.label, self.font) + 2;
+        let height = BOX_SIZE.max(self.font.height + 2);
+        Some((width, height))
+    }
+
+    fn set_area(&mut self, area: Rect) {
+        self.area = area;
+    }
+}
+
+/// Events a check box may answer with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckBoxEvent {
+    /// Nothing happened.
+    None,
+
+    /// The operator ticked the box (`true`) or cleared it (`false`).
+    Toggled(bool),
+}
+
+impl<'l, 'f> MouseEventSink<CheckBoxEvent> for CheckBoxView<'l, 'f> {
+    /// Handles pointer motion events, showing the box pressed only while the pointer is over it.
+    ///
+    /// Currently, always answers with [[CheckBoxEvent::None]].
+    fn pointer_moved(&mut self, med: &mut dyn Mediator, to: Point) -> CheckBoxEvent {
+        self.mouse_pt = to;
+        if self.pressed {
+            self.show_pressed(med, self.is_hot());
+        }
+        CheckBoxEvent::None
+    }
+
+    /// Handles mouse button-up events.
+    ///
+    /// Releasing the button over the check box it was pressed on toggles it,
+    /// answering with [[CheckBoxEvent::Toggled]].
+    /// Otherwise, answers with [[CheckBoxEvent::None]].
+    fn button_up(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> CheckBoxEvent {
+        if (event.button != MouseButton::Left) || !self.pressed {
+            return CheckBoxEvent::None;
+        }
+        self.mouse_pt = event.at;
+        self.show_pressed(med, false);
+        self.pressed = false;
+        if self.is_hot() {
+            self.toggle(med)
+        } else {
+            CheckBoxEvent::None
+        }
+    }
+
+    /// Handles mouse button-down events, pressing the box if it's enabled.
+    ///
+    /// Currently, always answers with [[CheckBoxEvent::None]].
+    fn button_down(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> CheckBoxEvent {
+        self.mouse_pt = event.at;
+        if (event.button == MouseButton::Left) && self.enabled && self.is_hot() {
+            self.pressed = true;
+            self.show_pressed(med, true);
+        }
+        CheckBoxEvent::None
+    }
+
+    fn wheel(&mut self, _: &mut dyn Mediator, _: Point, _: Modifiers) -> CheckBoxEvent {
+        CheckBoxEvent::None
+    }
+
+    fn enter(&mut self, med: &mut dyn Mediator, at: Point) -> CheckBoxEvent {
+        self.pointer_moved(med, at)
+    }
+
+    fn leave(&mut self, _: &mut dyn Mediator) -> CheckBoxEvent {
+        CheckBoxEvent::None
+    }
+}
+
+impl<'l, 'f> KeyboardEventSink<CheckBoxEvent> for CheckBoxView<'l, 'f> {
+    /// Handles key-down events.
+    ///
+    /// Return or the space bar toggle the check box while it has the focus,
+    /// answering with [[CheckBoxEvent::Toggled]].
+    /// Otherwise, answers with [[CheckBoxEvent::None]].
+    fn key_down(&mut self, med: &mut dyn Mediator, key: KeyCode, _: Modifiers) -> CheckBoxEvent {
+        match key {
+            KeyCode::Return | KeyCode::Char(' ') if self.enabled && self.has_focus(med) => self.toggle(med),
+            _ => CheckBoxEvent::None,
+        }
+    }
+
+    fn key_up(&mut self, _: &mut dyn Mediator, _: KeyCode, _: Modifiers) -> CheckBoxEvent {
+        CheckBoxEvent::None
+    }
+
+    fn text_input(&mut self, _: &mut dyn Mediator, _: &str) -> CheckBoxEvent {
+        CheckBoxEvent::None
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{CheckBoxEvent, CheckBoxView};
+    use crate::events::{ButtonEvent, KeyCode, KeyboardEventSink, Modifiers, MouseEventSink};
+    use crate::mediator::Mediator;
+    use crate::sysfont_bsw_9::SYSTEM_BITMAP_FONT;
+    use crate::utils::testing::TestMediator;
+
+    #[test]
+    fn clicking_toggles_unless_disabled() {
+        let mut med = TestMediator::new(64, 32);
+        let mut cb = CheckBoxView::new(((0, 0), (60, 12)), "Tick", &SYSTEM_BITMAP_FONT);
+
+        let _ = cb.button_down(&mut med, ButtonEvent::left((4, 4)));
+        assert_eq!(cb.button_up(&mut med, ButtonEvent::left((30, 6))), CheckBoxEvent::Toggled(true));
+        assert!(cb.is_checked());
+
+        // Releasing elsewhere changes nothing.
+        let _ = cb.button_down(&mut med, ButtonEvent::left((4, 4)));
+        assert_eq!(cb.button_up(&mut med, ButtonEvent::left((4, 20))), CheckBoxEvent::None);
+        assert!(cb.is_checked());
+
+        cb.set_enabled(false);
+        let _ = cb.button_down(&mut med, ButtonEvent::left((4, 4)));
+        assert_eq!(cb.button_up(&mut med, ButtonEvent::left((4, 4))), CheckBoxEvent::None);
+        assert!(cb.is_checked());
+    }
+
+    #[test]
+    fn space_toggles_with_focus() {
+        let mut med = TestMediator::new(64, 32);
+        let mut cb = CheckBoxView::new(((0, 0), (60, 12)), "Tick", &SYSTEM_BITMAP_FONT);
+        cb.set_focus_id(3);
+        assert_eq!(cb.key_down(&mut med, KeyCode::Char(' '), Modifiers::NONE), CheckBoxEvent::None);
+        med.set_focus(Some(3));
+        assert_eq!(cb.key_down(&mut med, KeyCode::Char(' '), Modifiers::NONE), CheckBoxEvent::Toggled(true));
+    }
+}
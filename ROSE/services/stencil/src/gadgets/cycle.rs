@@ -0,0 +1,330 @@
+//! Support for cycle gadgets.
+//!
+//! A cycle gadget looks like a push button showing one of several choices.
+//! Each click moves on to the next choice,
+//! going back to the first after the last;
+//! a shifted click goes the other way.
+//! It takes less room than a radio group,
+//! at the cost of showing only the current choice.
+//!
+//! Like the check box,
+//! a cycle gadget keeps its own state,
+//! and reports each change once it's made.
+
+use crate::events::{ButtonEvent, KeyCode, KeyboardEventSink, Modifiers, MouseButton, MouseEventSink};
+use crate::focus::FocusId;
+use crate::mediator::Mediator;
+use crate::simple_bitmap_font::{SimpleBitmapFont, text_width};
+use crate::simple_printer::SimplePrinter;
+use crate::stencil::Draw;
+use crate::types::{Dimension, Dimensions, Point, Rect};
+use crate::utils::{Glyph, LINE_BLACK, draw_glyph};
+use crate::view::{View, rect_contains};
+
+/// How wide the part of the gadget showing the cycle glyph is, divider included.
+pub const GLYPH_WIDTH: Dimension = 16;
+
+/// How much space the gadget leaves either side of the current choice.
+const LABEL_MARGIN: Dimension = 4;
+
+/// A circular arrow, telling the operator the choices go round.
+static CYCLE: Glyph = [0x3C, 0x42, 0x01, 0x01, 0xE1, 0x41, 0x42, 0x3C];
+
+/// Maintains the appearance of a cycle gadget.
+///
+/// The lifetime `'l` corresponds to the lifetime of the choices.
+/// The lifetime `'f` corresponds to the lifetime of the font.
+pub struct CycleView<'l, 'f> {
+    /// The rectangle occupied by the gadget, including its shadow.
+    area: Rect,
+
+    /// The choices, in the order the gadget cycles through them.
+    choices: Vec<&'l str>,
+
+    /// Which choice is showing.
+    current: usize,
+
+    /// False if the gadget ignores the operator, and is drawn lightened.
+    enabled: bool,
+
+    /// True while the operator holds the mouse button down,
+    /// having pressed it inside the gadget;
+    /// and whether Shift was held, to go backwards.
+    pressed: Option<bool>,
+
+    /// True while the gadget is drawn inverted,
+    /// which it is only while pressed with the pointer over it.
+    inverted: bool,
+
+    /// Tracks the current mouse position.
+    mouse_pt: Point,
+
+    /// Identifies the gadget to the mediator's focus owner, if it takes part in focus at all.
+    focus_id: Option<FocusId>,
+
+    /// Font to print the choices in.
+    font: &'f SimpleBitmapFont<'f>,
+}
+
+impl<'l, 'f> CycleView<'l, 'f> {
+    /// Creates a new, enabled cycle gadget offering `choices`,
+    /// showing the first.
+    pub fn new(area: Rect, choices: Vec<&'l str>, font: &'f SimpleBitmapFont) -> Self {
+        Self {
+            area,
+            choices,
+            current: 0,
+            enabled: true,
+            pressed: None,
+            inverted: false,
+            mouse_pt: (0, 0),
+            focus_id: None,
+            font,
+        }
+    }
+
+    /// Identifies the gadget as `id` to the mediator's focus owner.
+    ///
+    /// While it's the focus owner,
+    /// the gadget is drawn with a second frame inside the first,
+    /// Return, the space bar, or Down move on to the next choice,
+    /// and Up moves back to the previous one.
+    pub fn set_focus_id(&mut self, id: FocusId) {
+        self.focus_id = Some(id);
+    }
+
+    /// Answers which choice is showing, counting from zero.
+    pub fn get_current(&self) -> usize {
+        self.current
+    }
+
+    /// Shows choice `n`, counting from zero.
+    /// The gadget must be redrawn to show the change.
+    pub fn set_current(&mut self, n: usize) {
+        if n < self.choices.len() {
+            self.current = n;
+        }
+    }
+
+    /// Answers `true` if the gadget responds to the operator.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enables or disables the gadget.
+    /// The gadget must be redrawn to show the change.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Answers `true` if the gadget has the keyboard focus.
+    fn has_focus(&self, med: &dyn Mediator) -> bool {
+        self.focus_id.is_some() && (med.focus() == self.focus_id)
+    }
+
+    /// Answers `true` if the pointer is over the gadget.
+    fn is_hot(&self) -> bool {
+        rect_contains(self.area, self.mouse_pt)
+    }
+
+    /// Inverts the inside of the gadget, if need be, so that it looks `pressed` or not.
+    fn show_pressed(&mut self, med: &mut dyn Mediator, pressed: bool) {
+        if pressed != self.inverted {
+            let ((left, top), (right, bottom)) = self.area;
+            med.borrow_mut_desktop().invert_rectangle((left + 1, top + 1), (right - 2, bottom - 2));
+            med.repaint_all();
+            self.inverted = pressed;
+        }
+    }
+
+    /// Moves on to the next choice, or back to the previous one,
+    /// redrawing the gadget to match.
+    fn cycle(&mut self, med: &mut dyn Mediator, backwards: bool) -> CycleEvent {
+        let n = self.choices.len();
+        if n < 2 {
+            return CycleEvent::None;
+        }
+        self.current = if backwards { (self.current + n - 1) % n } else { (self.current + 1) % n };
+        self.draw(med);
+        med.repaint_all();
+        CycleEvent::Chose(self.current)
+    }
+}
+
+impl<'l, 'f> View for CycleView<'l, 'f> {
+    fn draw(&mut self, med: &mut dyn Mediator) {
+        let focused = self.has_focus(med);
+        let theme = med.theme();
+        let d = med.borrow_mut_desktop();
+        let ((left, top), (right, bottom)) = self.area;
+
+        // Like a push button, the frame leaves room for a shadow to the right and below.
+        let (face_right, face_bottom) = (right - theme.shadow, bottom - theme.shadow);
+        d.filled_rectangle((left, top), (face_right, face_bottom), &theme.paper);
+        theme.frame(d, (left, top), (face_right, face_bottom));
+        if focused {
+            let inset = theme.border + 1;
+            d.framed_rectangle((left + inset, top + inset), (face_right - inset, face_bottom - inset), LINE_BLACK);
+        }
+        theme.drop_shadow(d, (left, top), (face_right, face_bottom));
+
+        let divider = left + GLYPH_WIDTH;
+        draw_glyph(d, (left + ((GLYPH_WIDTH - 8) >> 1), (top + bottom - 1 - 8) >> 1), &CYCLE);
+        d.vertical_line((divider, top + 3), bottom - 4, LINE_BLACK);
+
+        if let Some(choice) = self.choices.get(self.current) {
+            let space = right - 1 - divider;
+            let label_left = divider + ((space - text_width(choice, self.font)) >> 1).max(LABEL_MARGIN);
+            let label_top = top + ((bottom - 1 - top - self.font.height) >> 1);
+            let mut p = SimplePrinter::new(d, ((label_left, label_top), (right - 2, bottom - 2)), self.font);
+            p.print(choice);
+        }
+
+        if !self.enabled {
+            d.lighten_rectangle((left, top), (right, bottom));
+        }
+        if self.inverted {
+            self.inverted = false;
+            self.show_pressed(med, true);
+        }
+    }
+
+    /// Answers a size with room for the glyph and the longest choice,
+    /// as tall as a push button in the same font.
+    fn preferred_size(&self) -> Option<Dimensions> {
+        let widest = self.choices.iter().map(|c| text_width(c, self.font)).max().unwrap_or(0);
+        let width = GLYPH_WIDTH + widest + 2 * LABEL_MARGIN + 1;
+        let height = self.font.baseline + self.font.height + 4;
+        Some((width, height))
+    }
+
+    fn set_area(&mut self, area: Rect) {
+        self.area = area;
+    }
+}
+
+/// Events a cycle gadget may answer with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CycleEvent {
+    /// Nothing happened.
+    None,
+
+    /// The operator moved on to the given choice, counting from zero.
+    Chose(usize),
+}
+
+impl<'l, 'f> MouseEventSink<CycleEvent> for CycleView<'l, 'f> {
+    /// Handles pointer motion events, showing the gadget pressed only while the pointer is over it.
+    ///
+    /// Currently, always answers with [[CycleEvent::None]].
+    fn pointer_moved(&mut self, med: &mut dyn Mediator, to: Point) -> CycleEvent {
+        self.mouse_pt = to;
+        if self.pressed.is_some() {
+            self.show_pressed(med, self.is_hot());
+        }
+        CycleEvent::None
+    }
+
+    /// Handles mouse button-up events.
+    ///
+    /// Releasing the mouse button over the gadget moves on to the next choice,
+    /// or back to the previous one if Shift was held when it was pressed,
+    /// answering with [[CycleEvent::Chose]].
+    /// Otherwise, answers with [[CycleEvent::None]].
+    fn button_up(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> CycleEvent {
+        if event.button != MouseButton::Left {
+            return CycleEvent::None;
+        }
+        let Some(backwards) = self.pressed.take() else {
+            return CycleEvent::None;
+        };
+        self.mouse_pt = event.at;
+        self.show_pressed(med, false);
+        if self.is_hot() {
+            self.cycle(med, backwards)
+        } else {
+            CycleEvent::None
+        }
+    }
+
+    /// Handles mouse button-down events, pressing the gadget if it's enabled.
+    ///
+    /// Currently, always answers with [[CycleEvent::None]].
+    fn button_down(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> CycleEvent {
+        self.mouse_pt = event.at;
+        if (event.button == MouseButton::Left) && self.enabled && self.is_hot() {
+            self.pressed = Some(event.modifiers.contains(Modifiers::SHIFT));
+            self.show_pressed(med, true);
+        }
+        CycleEvent::None
+    }
+
+    fn wheel(&mut self, _: &mut dyn Mediator, _: Point, _: Modifiers) -> CycleEvent {
+        CycleEvent::None
+    }
+
+    fn enter(&mut self, med: &mut dyn Mediator, at: Point) -> CycleEvent {
+        self.pointer_moved(med, at)
+    }
+
+    fn leave(&mut self, _: &mut dyn Mediator) -> CycleEvent {
+        CycleEvent::None
+    }
+}
+
+impl<'l, 'f> KeyboardEventSink<CycleEvent> for CycleView<'l, 'f> {
+    /// Handles key-down events.
+    ///
+    /// While the gadget has the focus,
+    /// Return, the space bar, or Down move on to the next choice,
+    /// and Up moves back to the previous one,
+    /// answering with [[CycleEvent::Chose]].
+    /// Otherwise, answers with [[CycleEvent::None]].
+    fn key_down(&mut self, med: &mut dyn Mediator, key: KeyCode, _: Modifiers) -> CycleEvent {
+        if !self.enabled || !self.has_focus(med) {
+            return CycleEvent::None;
+        }
+        match key {
+            KeyCode::Return | KeyCode::Char(' ') | KeyCode::Down => self.cycle(med, false),
+            KeyCode::Up => self.cycle(med, true),
+            _ => CycleEvent::None,
+        }
+    }
+
+    fn key_up(&mut self, _: &mut dyn Mediator, _: KeyCode, _: Modifiers) -> CycleEvent {
+        CycleEvent::None
+    }
+
+    fn text_input(&mut self, _: &mut dyn Mediator, _: &str) -> CycleEvent {
+        CycleEvent::None
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{CycleEvent, CycleView};
+    use crate::events::{ButtonEvent, Modifiers, MouseEventSink};
+    use crate::sysfont_bsw_9::SYSTEM_BITMAP_FONT;
+    use crate::utils::testing::TestMediator;
+
+    #[test]
+    fn clicks_go_round_and_shift_goes_back() {
+        let mut med = TestMediator::new(64, 32);
+        let mut cycle = CycleView::new(((0, 0), (60, 20)), vec!["One", "Two", "Three"], &SYSTEM_BITMAP_FONT);
+
+        let click = |cycle: &mut CycleView, med: &mut TestMediator, modifiers| {
+            let press = ButtonEvent { modifiers, ..ButtonEvent::left((30, 10)) };
+            let _ = cycle.button_down(med, press);
+            cycle.button_up(med, press)
+        };
+        assert_eq!(click(&mut cycle, &mut med, Modifiers::NONE), CycleEvent::Chose(1));
+        assert_eq!(click(&mut cycle, &mut med, Modifiers::NONE), CycleEvent::Chose(2));
+        assert_eq!(click(&mut cycle, &mut med, Modifiers::NONE), CycleEvent::Chose(0));
+        assert_eq!(click(&mut cycle, &mut med, Modifiers::SHIFT), CycleEvent::Chose(2));
+
+        cycle.set_enabled(false);
+        assert_eq!(click(&mut cycle, &mut med, Modifiers::NONE), CycleEvent::None);
+        assert_eq!(cycle.get_current(), 2);
+    }
+}
@@ -0,0 +1,560 @@
+//! Support for list views.
+//!
+//! A list view shows a column of text items,
+//! such as files or fonts,
+//! one per row,
+//! with a scroll bar down its right-hand side.
+//! The operator selects items by clicking or with the cursor keys,
+//! and activates one, perhaps to open it, by double-clicking it or pressing Return.
+//!
+//! Only the rows in view are ever drawn,
+//! so a list may be as long as it likes.
+//! The list view scrolls itself,
+//! and keeps its own selection;
+//! it only reports changes to the selection, and activations.
+
+use std::collections::BTreeSet;
+
+use crate::events::{ButtonEvent, KeyCode, KeyboardEventSink, Modifiers, MouseButton, MouseEventSink, TimerEventSink};
+use crate::focus::FocusId;
+use crate::gadgets::proportional::PropAxis;
+use crate::gadgets::scroll_bar::{Orientation, ScrollBarEvent, ScrollBarView, THICKNESS, WHEEL_LINES};
+use crate::mediator::Mediator;
+use crate::simple_bitmap_font::SimpleBitmapFont;
+use crate::simple_printer::SimplePrinter;
+use crate::stencil::Draw;
+use crate::timers::TimerId;
+use crate::types::{Dimension, Point, Rect, Unit};
+use crate::utils::{LINE_BLACK, LINE_WHITE};
+use crate::view::{View, rect_contains};
+
+/// How far the text of each row sits from the left-hand edge of the list.
+const TEXT_MARGIN: Dimension = 3;
+
+/// How many items the operator may select at once.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectionMode {
+    /// At most one item is selected at a time.
+    Single,
+
+    /// Any number of items may be selected.
+    /// Shift-clicking selects a range of items;
+    /// Control- or Commodore-clicking selects or deselects one item, leaving the rest alone.
+    Multiple,
+}
+
+/// Maintains the appearance of a list view.
+///
+/// The lifetime `'f` corresponds to the lifetime of the font.
+pub struct ListView<'f> {
+    /// The rectangle occupied by the list and its scroll bar.
+    area: Rect,
+
+    /// The items, one per row.
+    items: Vec<String>,
+
+    /// How many items may be selected at once.
+    mode: SelectionMode,
+
+    /// Which items are selected.
+    selected: BTreeSet<usize>,
+
+    /// The item the keyboard acts on, drawn framed while the list has the focus.
+    cursor: usize,
+
+    /// Where ranges of items selected with Shift start from:
+    /// the last item picked without Shift.
+    anchor: usize,
+
+    /// The item showing in the top row.
+    top: usize,
+
+    /// Scrolls the list up and down.
+    scroll_bar: ScrollBarView,
+
+    /// True while the operator drags the selection,
+    /// having pressed the mouse button over the rows.
+    dragging: bool,
+
+    /// Tracks the current mouse position.
+    mouse_pt: Point,
+
+    /// Identifies the list to the mediator's focus owner, if it takes part in focus at all.
+    focus_id: Option<FocusId>,
+
+    /// Font to print the items in.
+    font: &'f SimpleBitmapFont<'f>,
+}
+
+impl<'f> ListView<'f> {
+    /// Creates a new list view showing `items`, none of them selected,
+    /// scrolled to the top.
+    pub fn new(area: Rect, items: Vec<String>, mode: SelectionMode, font: &'f SimpleBitmapFont) -> Self {
+        let mut list = Self {
+            area,
+            items,
+            mode,
+            selected: BTreeSet::new(),
+            cursor: 0,
+            anchor: 0,
+            top: 0,
+            scroll_bar: ScrollBarView::new(area, Orientation::Vertical),
+            dragging: false,
+            mouse_pt: (0, 0),
+            focus_id: None,
+            font,
+        };
+        list.set_area(area);
+        list
+    }
+
+    /// Identifies the list as `id` to the mediator's focus owner.
+    ///
+    /// While it's the focus owner,
+    /// the cursor keys move the selection,
+    /// and Return activates the item under the cursor.
+    pub fn set_focus_id(&mut self, id: FocusId) {
+        self.focus_id = Some(id);
+    }
+
+    /// Retrieves the items.
+    pub fn items(&self) -> &[String] {
+        &self.items
+    }
+
+    /// Replaces the items, deselecting everything and scrolling back to the top.
+    /// The list must be redrawn to show the change.
+    pub fn set_items(&mut self, items: Vec<String>) {
+        self.items = items;
+        self.selected.clear();
+        self.cursor = 0;
+        self.anchor = 0;
+        self.top = 0;
+        self.scroll_bar.set_values(self.scroll_values());
+    }
+
+    /// Answers the selected items, in order.
+    pub fn selection(&self) -> Vec<usize> {
+        self.selected.iter().copied().collect()
+    }
+
+    /// Answers `true` if item `n` is selected.
+    pub fn is_selected(&self, n: usize) -> bool {
+        self.selected.contains(&n)
+    }
+
+    /// Selects or deselects item `n`.
+    /// In [[SelectionMode::Single]], selecting an item deselects any other.
+    /// The list must be redrawn to show the change.
+    pub fn set_selected(&mut self, n: usize, selected: bool) {
+        if n >= self.items.len() {
+            return;
+        }
+        if !selected {
+            self.selected.remove(&n);
+            return;
+        }
+        if self.mode == SelectionMode::Single {
+            self.selected.clear();
+        }
+        self.selected.insert(n);
+        self.cursor = n;
+        self.anchor = n;
+    }
+
+    /// Answers which item shows in the top row.
+    pub fn get_top(&self) -> usize {
+        self.top
+    }
+
+    /// Answers `true` if the list has the keyboard focus.
+    fn has_focus(&self, med: &dyn Mediator) -> bool {
+        self.focus_id.is_some() && (med.focus() == self.focus_id)
+    }
+
+    /// Answers the rectangle framing the rows; its right-hand edge is shared with the scroll bar.
+    fn frame(&self) -> Rect {
+        let ((left, top), (right, bottom)) = self.area;
+        ((left, top), (right - THICKNESS + 1, bottom))
+    }
+
+    /// Answers how tall each row is.
+    fn row_height(&self) -> Dimension {
+        self.font.height + 2
+    }
+
+    /// Answers how many rows fit in the list, whole.
+    fn rows_visible(&self) -> usize {
+        let ((_, top), (_, bottom)) = self.frame();
+        ((bottom - top - 2) / self.row_height()).max(1) as usize
+    }
+
+    /// Answers the rectangle occupied by the `i`th row from the top.
+    fn row(&self, i: usize) -> Rect {
+        let ((left, top), (right, _)) = self.frame();
+        let row_top = top + 1 + i as Dimension * self.row_height();
+        ((left + 1, row_top), (right - 1, row_top + self.row_height()))
+    }
+
+    /// Answers the item under `pt`, if any.
+    fn item_at(&self, pt: Point) -> Option<usize> {
+        let ((left, top), (right, _)) = self.row(0);
+        let ((_, _), (_, bottom)) = self.row(self.rows_visible() - 1);
+        if !rect_contains(((left, top), (right, bottom)), pt) {
+            return None;
+        }
+        let n = self.top + ((pt.1 - top) / self.row_height()) as usize;
+        (n < self.items.len()).then_some(n)
+    }
+
+    /// Answers which of the items are showing, for the scroll bar.
+    fn scroll_values(&self) -> PropAxis {
+        PropAxis::new(self.items.len(), self.rows_visible(), self.top)
+    }
+
+    /// Scrolls so that item `top` shows in the top row, as near as it can,
+    /// redrawing the list if it moved.
+    fn scroll_to(&mut self, med: &mut dyn Mediator, top: Unit) {
+        let values = PropAxis::new(self.items.len(), self.rows_visible(), top.max(0) as usize);
+        let moved = values.top != self.top;
+        self.top = values.top;
+        self.scroll_bar.set_values(values);
+        if moved {
+            self.draw(med);
+            med.repaint_all();
+        }
+    }
+
+    /// Scrolls just far enough to bring item `n` into view.
+    fn reveal(&mut self, med: &mut dyn Mediator, n: usize) {
+        let rows = self.rows_visible();
+        if n < self.top {
+            self.scroll_to(med, n as Unit);
+        } else if n >= self.top + rows {
+            self.scroll_to(med, (n + 1 - rows) as Unit);
+        }
+    }
+
+    /// Acts on what the scroll bar asked for.
+    fn scrolled(&mut self, med: &mut dyn Mediator, evt: ScrollBarEvent) {
+        let top = self.top as Unit;
+        match evt {
+            ScrollBarEvent::ScrollLines(n) => self.scroll_to(med, top + n),
+            ScrollBarEvent::ScrollPages(n) => self.scroll_to(med, top + n * self.rows_visible() as Unit),
+            ScrollBarEvent::ScrollTo(t) => self.scroll_to(med, t as Unit),
+            ScrollBarEvent::None => (),
+        }
+    }
+
+    /// Picks item `n`, as described by [[ListView::picked]],
+    /// moving the cursor onto it and bringing it into view.
+    ///
+    /// Answers with [[ListEvent::SelectionChanged]] if the selection changed.
+    fn pick(&mut self, med: &mut dyn Mediator, n: usize, modifiers: Modifiers) -> ListEvent {
+        let selected = self.picked(n, modifiers);
+        if !modifiers.contains(Modifiers::SHIFT) {
+            self.anchor = n;
+        }
+        let changed = selected != self.selected;
+        if !changed && (n == self.cursor) {
+            return ListEvent::None;
+        }
+        self.selected = selected;
+        self.cursor = n;
+        self.reveal(med, n);
+        self.draw(med);
+        med.repaint_all();
+        if changed { ListEvent::SelectionChanged } else { ListEvent::None }
+    }
+
+    /// Answers the selection after the operator picks item `n`,
+    /// by clicking it or moving the cursor onto it,
+    /// with the given modifier keys held down.
+    fn picked(&self, n: usize, modifiers: Modifiers) -> BTreeSet<usize> {
+        let mut selected = BTreeSet::new();
+        if self.mode == SelectionMode::Single {
+            selected.insert(n);
+        } else if modifiers.contains(Modifiers::SHIFT) {
+            selected.extend(self.anchor.min(n)..=self.anchor.max(n));
+        } else if modifiers.contains(Modifiers::CONTROL) || modifiers.contains(Modifiers::COMMODORE) {
+            selected = self.selected.clone();
+            if !selected.remove(&n) {
+                selected.insert(n);
+            }
+        } else {
+            selected.insert(n);
+        }
+        selected
+    }
+
+    /// Prints `text` into the row occupying `row`, cutting it short rather than wrapping.
+    fn print_row(&self, med: &mut dyn Mediator, row: Rect, text: &str) {
+        let ((left, top), (right, bottom)) = row;
+        let d = med.borrow_mut_desktop();
+        let mut p = SimplePrinter::new(d, ((left + TEXT_MARGIN, top + 1), (right, bottom)), self.font);
+        for ch in text.chars() {
+            if p.head.0 + self.font.glyph_advance(ch) > right {
+                break;
+            }
+            p.print_char(ch);
+        }
+    }
+}
+
+impl<'f> View for ListView<'f> {
+    /// Draws the frame, the rows in view, and the scroll bar.
+    /// Items scrolled out of view aren't drawn at all.
+    fn draw(&mut self, med: &mut dyn Mediator) {
+        let focused = self.has_focus(med);
+        let theme = med.theme();
+        let (upper_left, lower_right) = self.frame();
+        med.borrow_mut_desktop().filled_rectangle(upper_left, lower_right, &theme.paper);
+
+        let last = self.items.len().min(self.top + self.rows_visible());
+        for n in self.top..last {
+            let row = self.row(n - self.top);
+            self.print_row(med, row, &self.items[n]);
+
+            let d = med.borrow_mut_desktop();
+            let selected = self.selected.contains(&n);
+            if selected {
+                d.invert_rectangle(row.0, row.1);
+            }
+            if focused && (n == self.cursor) {
+                d.framed_rectangle(row.0, row.1, if selected { LINE_WHITE } else { LINE_BLACK });
+            }
+        }
+
+        // The frame goes on last, so that a thick one isn't spoiled by the rows it overlaps.
+        theme.frame(med.borrow_mut_desktop(), upper_left, lower_right);
+
+        self.scroll_bar.draw(med);
+    }
+
+    fn set_area(&mut self, area: Rect) {
+        self.area = area;
+        let ((_, top), (right, bottom)) = area;
+        self.scroll_bar.set_area(((right - THICKNESS, top), (right, bottom)));
+
+        // A taller list may have room to show more of the end of the list.
+        let values = self.scroll_values();
+        self.top = values.top;
+        self.scroll_bar.set_values(values);
+    }
+}
+
+/// Events a list view may answer with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ListEvent {
+    /// Nothing happened.
+    None,
+
+    /// The operator changed which items are selected;
+    /// see [[ListView::selection]].
+    SelectionChanged,
+
+    /// The operator activated the given item,
+    /// by double-clicking it or pressing Return.
+    Activated(usize),
+}
+
+impl<'f> MouseEventSink<ListEvent> for ListView<'f> {
+    /// Handles pointer motion events.
+    ///
+    /// Dragging over the rows moves the selection with the pointer,
+    /// or, in [[SelectionMode::Multiple]], stretches it from where the drag began,
+    /// answering with [[ListEvent::SelectionChanged]].
+    /// Otherwise, answers with [[ListEvent::None]].
+    fn pointer_moved(&mut self, med: &mut dyn Mediator, to: Point) -> ListEvent {
+        self.mouse_pt = to;
+        let evt = self.scroll_bar.pointer_moved(med, to);
+        self.scrolled(med, evt);
+
+        match (self.dragging, self.item_at(to)) {
+            (true, Some(n)) => self.pick(med, n, Modifiers::SHIFT),
+            _ => ListEvent::None,
+        }
+    }
+
+    /// Handles mouse button-up events, ending any drag.
+    ///
+    /// Currently, always answers with [[ListEvent::None]].
+    fn button_up(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> ListEvent {
+        self.mouse_pt = event.at;
+        self.dragging = false;
+        let evt = self.scroll_bar.button_up(med, event);
+        self.scrolled(med, evt);
+        ListEvent::None
+    }
+
+    /// Handles mouse button-down events.
+    ///
+    /// Clicking an item selects it, as described by the [[SelectionMode]],
+    /// answering with [[ListEvent::SelectionChanged]] if that changed anything.
+    /// Double-clicking an item activates it,
+    /// answering with [[ListEvent::Activated]].
+    /// Otherwise, answers with [[ListEvent::None]].
+    fn button_down(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> ListEvent {
+        self.mouse_pt = event.at;
+        if rect_contains(self.scroll_bar.get_area(), event.at) {
+            let evt = self.scroll_bar.button_down(med, event);
+            self.scrolled(med, evt);
+            return ListEvent::None;
+        }
+        if event.button != MouseButton::Left {
+            return ListEvent::None;
+        }
+        let Some(n) = self.item_at(event.at) else {
+            return ListEvent::None;
+        };
+        if (event.clicks >= 2) && self.selected.contains(&n) {
+            return ListEvent::Activated(n);
+        }
+        self.dragging = true;
+        self.pick(med, n, event.modifiers)
+    }
+
+    /// Handles the mouse wheel, scrolling the list while the pointer is over it.
+    ///
+    /// Currently, always answers with [[ListEvent::None]].
+    fn wheel(&mut self, med: &mut dyn Mediator, delta: Point, modifiers: Modifiers) -> ListEvent {
+        let evt = if rect_contains(self.frame(), self.mouse_pt) {
+            ScrollBarEvent::ScrollLines(-delta.1 * WHEEL_LINES)
+        } else {
+            self.scroll_bar.wheel(med, delta, modifiers)
+        };
+        self.scrolled(med, evt);
+        ListEvent::None
+    }
+
+    fn enter(&mut self, med: &mut dyn Mediator, at: Point) -> ListEvent {
+        self.pointer_moved(med, at)
+    }
+
+    fn leave(&mut self, med: &mut dyn Mediator) -> ListEvent {
+        let _ = self.scroll_bar.leave(med);
+        ListEvent::None
+    }
+}
+
+impl<'f> KeyboardEventSink<ListEvent> for ListView<'f> {
+    /// Handles key-down events, while the list has the focus.
+    ///
+    /// Up and Down move the cursor a row at a time, and Home and End to either end of the list,
+    /// selecting the item they land on,
+    /// or, with Shift held in [[SelectionMode::Multiple]], the range of items they cross;
+    /// answering with [[ListEvent::SelectionChanged]].
+    /// Return activates the item under the cursor, answering with [[ListEvent::Activated]].
+    /// Otherwise, answers with [[ListEvent::None]].
+    fn key_down(&mut self, med: &mut dyn Mediator, key: KeyCode, modifiers: Modifiers) -> ListEvent {
+        let n = self.items.len();
+        if (n == 0) || !self.has_focus(med) {
+            return ListEvent::None;
+        }
+        let to = match key {
+            KeyCode::Up => self.cursor.saturating_sub(1),
+            KeyCode::Down => (self.cursor + 1).min(n - 1),
+            KeyCode::Home => 0,
+            KeyCode::End => n - 1,
+            KeyCode::Return => return ListEvent::Activated(self.cursor),
+            _ => return ListEvent::None,
+        };
+
+        let modifiers = if modifiers.contains(Modifiers::SHIFT) { Modifiers::SHIFT } else { Modifiers::NONE };
+        self.pick(med, to, modifiers)
+    }
+
+    fn key_up(&mut self, _: &mut dyn Mediator, _: KeyCode, _: Modifiers) -> ListEvent {
+        ListEvent::None
+    }
+
+    fn text_input(&mut self, _: &mut dyn Mediator, _: &str) -> ListEvent {
+        ListEvent::None
+    }
+}
+
+impl<'f> TimerEventSink<ListEvent> for ListView<'f> {
+    /// Repeats the scroll bar's arrows while they're held down.
+    ///
+    /// Currently, always answers with [[ListEvent::None]].
+    fn timer_fired(&mut self, med: &mut dyn Mediator, id: TimerId) -> ListEvent {
+        let evt = self.scroll_bar.timer_fired(med, id);
+        self.scrolled(med, evt);
+        ListEvent::None
+    }
+
+    fn idle(&mut self, _: &mut dyn Mediator) -> ListEvent {
+        ListEvent::None
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{ListEvent, ListView, SelectionMode};
+    use crate::events::{ButtonEvent, KeyCode, KeyboardEventSink, Modifiers, MouseEventSink};
+    use crate::mediator::Mediator;
+    use crate::sysfont_bsw_9::SYSTEM_BITMAP_FONT;
+    use crate::utils::testing::TestMediator;
+
+    /// A list of a hundred items, eleven pixels per row, showing five rows at a time.
+    fn list(mode: SelectionMode) -> ListView<'static> {
+        let items = (0..100).map(|n| format!("Item {}", n)).collect();
+        ListView::new(((0, 0), (100, 57)), items, mode, &SYSTEM_BITMAP_FONT)
+    }
+
+    /// Answers a left-button press on the `i`th row from the top, with `modifiers` held.
+    fn press(i: isize, clicks: usize, modifiers: Modifiers) -> ButtonEvent {
+        ButtonEvent { clicks, modifiers, ..ButtonEvent::left((20, 1 + 11 * i + 5)) }
+    }
+
+    #[test]
+    fn clicks_select_and_double_clicks_activate() {
+        let mut med = TestMediator::new(128, 64);
+        let mut list = list(SelectionMode::Single);
+
+        assert_eq!(list.button_down(&mut med, press(1, 1, Modifiers::NONE)), ListEvent::SelectionChanged);
+        let _ = list.button_up(&mut med, press(1, 1, Modifiers::NONE));
+        assert_eq!(list.button_down(&mut med, press(3, 1, Modifiers::CONTROL)), ListEvent::SelectionChanged);
+        assert_eq!(list.selection(), vec![3]);
+        assert_eq!(list.button_down(&mut med, press(3, 2, Modifiers::NONE)), ListEvent::Activated(3));
+    }
+
+    #[test]
+    fn multiple_selection_by_shift_and_control() {
+        let mut med = TestMediator::new(128, 64);
+        let mut list = list(SelectionMode::Multiple);
+
+        let _ = list.button_down(&mut med, press(1, 1, Modifiers::NONE));
+        let _ = list.button_up(&mut med, press(1, 1, Modifiers::NONE));
+        let _ = list.button_down(&mut med, press(3, 1, Modifiers::SHIFT));
+        let _ = list.button_up(&mut med, press(3, 1, Modifiers::SHIFT));
+        assert_eq!(list.selection(), vec![1, 2, 3]);
+
+        let _ = list.button_down(&mut med, press(2, 1, Modifiers::COMMODORE));
+        let _ = list.button_up(&mut med, press(2, 1, Modifiers::COMMODORE));
+        assert_eq!(list.selection(), vec![1, 3]);
+    }
+
+    #[test]
+    fn cursor_keys_move_the_selection_into_view() {
+        let mut med = TestMediator::new(128, 64);
+        let mut list = list(SelectionMode::Multiple);
+        list.set_focus_id(7);
+        med.set_focus(Some(7));
+
+        for _ in 0..6 {
+            let _ = list.key_down(&mut med, KeyCode::Down, Modifiers::NONE);
+        }
+        assert_eq!(list.selection(), vec![6]);
+        assert_eq!(list.get_top(), 2);
+
+        let _ = list.key_down(&mut med, KeyCode::Up, Modifiers::SHIFT);
+        let _ = list.key_down(&mut med, KeyCode::Up, Modifiers::SHIFT);
+        assert_eq!(list.selection(), vec![4, 5, 6]);
+
+        assert_eq!(list.key_down(&mut med, KeyCode::End, Modifiers::NONE), ListEvent::SelectionChanged);
+        assert_eq!(list.get_top(), 95);
+        assert_eq!(list.key_down(&mut med, KeyCode::Return, Modifiers::NONE), ListEvent::Activated(99));
+    }
+}
@@ -0,0 +1,933 @@
+//! Support for menus.
+//!
+//! A [[Menu]] is a list of [[MenuItem]]s:
+//! commands, which report their [[MenuId]] when chosen,
+//! submenus, which open beside the item when the pointer rests on it,
+//! and separators between groups of items.
+//! Commands may be disabled, which stipples them and stops them being chosen;
+//! they may carry a checkmark;
+//! and they may have a keyboard shortcut,
+//! typed with the Commodore key held down.
+//!
+//! A [[PopupMenuView]] shows a menu wherever the application opens it,
+//! usually where the operator pressed a mouse button.
+//! A [[MenuBarView]] shows the titles of several menus along a strip,
+//! and pulls each down beneath its title when pressed.
+//! Either way, the operator chooses an item by releasing the button over it,
+//! or, if they just clicked to open the menu, by clicking on it.
+//!
+//! Menus save the pixels they cover when they open, and put them back when they close,
+//! so the application need not redraw anything behind them.
+
+use crate::events::{ButtonEvent, KeyCode, KeyboardEventSink, Modifiers, MouseEventSink};
+use crate::mediator::Mediator;
+use crate::simple_bitmap_font::{SimpleBitmapFont, text_width};
+use crate::simple_printer::SimplePrinter;
+use crate::stencil::{Draw, SavedArea};
+use crate::types::{Dimension, Dimensions, Point, Rect};
+use crate::utils::LINE_BLACK;
+use crate::view::{View, rect_contains};
+
+/// Identifies a command in a menu.
+pub type MenuId = usize;
+
+/// The space to the left of each item's label, where its checkmark goes.
+const GUTTER: Dimension = 12;
+
+/// The space to the right of each item.
+const MARGIN: Dimension = 6;
+
+/// The least space between an item's label and its shortcut or submenu arrow.
+const SHORTCUT_GAP: Dimension = 12;
+
+/// The width of the arrow marking a submenu.
+const ARROW_WIDTH: Dimension = 4;
+
+/// The height of a separator.
+const SEPARATOR_HEIGHT: Dimension = 5;
+
+/// The space either side of each title in a menu bar.
+const TITLE_PADDING: Dimension = 6;
+
+/// What a menu item does.
+enum ItemKind {
+    Command { id: MenuId, shortcut: Option<char>, checked: bool },
+    Submenu(Menu),
+    Separator,
+}
+
+/// One entry in a menu.
+pub struct MenuItem {
+    label: String,
+    kind: ItemKind,
+    enabled: bool,
+}
+
+impl MenuItem {
+    /// Creates a command labelled `label`, which reports `id` when chosen.
+    pub fn command(id: MenuId, label: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            kind: ItemKind::Command { id, shortcut: None, checked: false },
+            enabled: true,
+        }
+    }
+
+    /// Creates an item labelled `label`, which opens `menu` beside it.
+    pub fn submenu(label: &str, menu: Menu) -> Self {
+        Self { label: label.to_string(), kind: ItemKind::Submenu(menu), enabled: true }
+    }
+
+    /// Creates a line separating groups of items.
+    pub fn separator() -> Self {
+        Self { label: String::new(), kind: ItemKind::Separator, enabled: false }
+    }
+
+    /// Lets Commodore-`key` choose the command, and shows as much beside it.
+    /// Only commands have shortcuts; other items are left alone.
+    pub fn with_shortcut(mut self, key: char) -> Self {
+        if let ItemKind::Command { ref mut shortcut, .. } = self.kind {
+            *shortcut = Some(key.to_ascii_uppercase());
+        }
+        self
+    }
+
+    /// Shows a checkmark beside the command if `checked` is set.
+    /// Only commands have checkmarks; other items are left alone.
+    pub fn with_check(mut self, checked: bool) -> Self {
+        if let ItemKind::Command { checked: ref mut c, .. } = self.kind {
+            *c = checked;
+        }
+        self
+    }
+
+    /// Disables the item, so that it can't be chosen.
+    pub fn disabled(mut self) -> Self {
+        self.enabled = false;
+        self
+    }
+
+    /// Answers `true` if the operator can choose the item, or open its submenu.
+    fn is_selectable(&self) -> bool {
+        self.enabled && !matches!(self.kind, ItemKind::Separator)
+    }
+
+    /// Answers the command's identifier, if the item is a command.
+    fn command_id(&self) -> Option<MenuId> {
+        match self.kind {
+            ItemKind::Command { id, .. } => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Answers the item's submenu, if it has one.
+    fn submenu_ref(&self) -> Option<&Menu> {
+        match &self.kind {
+            ItemKind::Submenu(menu) => Some(menu),
+            _ => None,
+        }
+    }
+
+    /// Answers the text shown for the item's shortcut, if it has one.
+    fn shortcut_text(&self) -> Option<String> {
+        match self.kind {
+            ItemKind::Command { shortcut: Some(key), .. } => Some(format!("C={}", key)),
+            _ => None,
+        }
+    }
+}
+
+/// A titled list of menu items.
+pub struct Menu {
+    title: String,
+    items: Vec<MenuItem>,
+}
+
+impl Menu {
+    /// Creates a menu titled `title`, holding `items` from top to bottom.
+    pub fn new(title: &str, items: Vec<MenuItem>) -> Self {
+        Self { title: title.to_string(), items }
+    }
+
+    /// Retrieves the menu's title.
+    pub fn get_title(&self) -> &str {
+        &self.title
+    }
+
+    /// Enables or disables the command `id`, wherever it is among the menu's submenus.
+    pub fn set_enabled(&mut self, id: MenuId, enabled: bool) {
+        if let Some(item) = self.find_mut(id) {
+            item.enabled = enabled;
+        }
+    }
+
+    /// Answers `true` if the command `id` is there and enabled.
+    pub fn is_enabled(&self, id: MenuId) -> bool {
+        self.find(id).is_some_and(|item| item.enabled)
+    }
+
+    /// Shows or hides the checkmark beside the command `id`,
+    /// wherever it is among the menu's submenus.
+    pub fn set_checked(&mut self, id: MenuId, checked: bool) {
+        if let Some(MenuItem { kind: ItemKind::Command { checked: c, .. }, .. }) = self.find_mut(id) {
+            *c = checked;
+        }
+    }
+
+    /// Answers `true` if the command `id` is there and checked.
+    pub fn is_checked(&self, id: MenuId) -> bool {
+        matches!(self.find(id), Some(MenuItem { kind: ItemKind::Command { checked: true, .. }, .. }))
+    }
+
+    /// Answers the enabled command whose shortcut is `key`, if any,
+    /// ignoring the case of letters.
+    /// Commands in disabled submenus don't count.
+    pub fn shortcut(&self, key: char) -> Option<MenuId> {
+        let key = key.to_ascii_uppercase();
+        self.items.iter().filter(|item| item.enabled).find_map(|item| match &item.kind {
+            ItemKind::Command { id, shortcut: Some(k), .. } if *k == key => Some(*id),
+            ItemKind::Submenu(menu) => menu.shortcut(key),
+            _ => None,
+        })
+    }
+
+    fn find(&self, id: MenuId) -> Option<&MenuItem> {
+        self.items.iter().find_map(|item| match &item.kind {
+            ItemKind::Submenu(menu) => menu.find(id),
+            _ if item.command_id() == Some(id) => Some(item),
+            _ => None,
+        })
+    }
+
+    fn find_mut(&mut self, id: MenuId) -> Option<&mut MenuItem> {
+        self.items.iter_mut().find_map(|item| match item.kind {
+            ItemKind::Submenu(ref mut menu) => menu.find_mut(id),
+            ItemKind::Command { id: i, .. } if i == id => Some(item),
+            _ => None,
+        })
+    }
+}
+
+/// Events unique to menus.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MenuEvent {
+    None,
+
+    /// The operator chose the command with this identifier.
+    Selected(MenuId),
+}
+
+/// One menu in an open cascade.
+struct Level {
+    /// The rectangle occupied by the menu, not counting its shadow.
+    area: Rect,
+
+    /// The pixels the menu covers.
+    saved: SavedArea,
+
+    /// The item the pointer is on, if any.
+    hot: Option<usize>,
+}
+
+/// Maintains the appearance of a pop-up menu, and of any submenus opened from it.
+pub struct PopupMenuView<'f> {
+    /// The menu shown.
+    menu: Menu,
+
+    /// The font items are printed in.
+    font: &'f SimpleBitmapFont<'f>,
+
+    /// The open menus, outermost first.
+    levels: Vec<Level>,
+
+    /// The index of the item each open menu was opened from, outermost first.
+    path: Vec<usize>,
+
+    /// True once the pointer has been over the menu since it opened.
+    entered: bool,
+}
+
+impl<'f> PopupMenuView<'f> {
+    /// Creates a closed pop-up menu showing `menu`, with its items printed in `font`.
+    pub fn new(menu: Menu, font: &'f SimpleBitmapFont<'f>) -> Self {
+        Self { menu, font, levels: Vec::new(), path: Vec::new(), entered: false }
+    }
+
+    /// Retrieves the menu shown.
+    pub fn menu(&self) -> &Menu {
+        &self.menu
+    }
+
+    /// Retrieves the menu shown, so items can be enabled, disabled, checked or unchecked.
+    /// Changes show the next time the menu opens.
+    pub fn menu_mut(&mut self) -> &mut Menu {
+        &mut self.menu
+    }
+
+    /// Answers `true` if the menu is open.
+    pub fn is_open(&self) -> bool {
+        !self.levels.is_empty()
+    }
+
+    /// Opens the menu with its top left corner at `at`,
+    /// or as near as it can get while staying on the desktop.
+    pub fn open(&mut self, med: &mut dyn Mediator, at: Point) {
+        self.close(med);
+        let (w, h) = self.menu_size(&self.menu);
+        let (dw, dh) = med.borrow_mut_desktop().get_dimensions();
+        let left = at.0.min(dw - w - 1).max(0);
+        let top = at.1.min(dh - h - 1).max(0);
+        self.entered = false;
+        self.push_level(med, ((left, top), (left + w, top + h)));
+    }
+
+    /// Closes the menu and any submenus, putting back what they covered.
+    pub fn close(&mut self, med: &mut dyn Mediator) {
+        if self.is_open() {
+            self.close_to(med, 0);
+            let level = self.levels.pop().expect("menu is open");
+            med.borrow_mut_desktop().restore_area(&level.saved);
+            med.repaint_all();
+        }
+    }
+
+    /// Closes any submenus deeper than `depth`.
+    fn close_to(&mut self, med: &mut dyn Mediator, depth: usize) {
+        while self.levels.len() > depth + 1 {
+            let level = self.levels.pop().expect("submenu is open");
+            med.borrow_mut_desktop().restore_area(&level.saved);
+            med.repaint_all();
+        }
+        self.path.truncate(depth);
+    }
+
+    /// Answers the menu shown at `depth` in the cascade.
+    fn menu_at(&self, depth: usize) -> &Menu {
+        self.path[..depth].iter().fold(&self.menu, |menu, &i| {
+            menu.items[i].submenu_ref().expect("submenus open from submenu items")
+        })
+    }
+
+    /// Answers the height of an item.
+    fn item_height(&self, item: &MenuItem) -> Dimension {
+        match item.kind {
+            ItemKind::Separator => SEPARATOR_HEIGHT,
+            _ => self.font.height + 4,
+        }
+    }
+
+    /// Answers the size of `menu` when open, not counting its shadow.
+    fn menu_size(&self, menu: &Menu) -> Dimensions {
+        let widest = menu.items.iter().map(|item| {
+            let label = text_width(&item.label, self.font);
+            match (&item.kind, item.shortcut_text()) {
+                (_, Some(shortcut)) => label + SHORTCUT_GAP + text_width(&shortcut, self.font),
+                (ItemKind::Submenu(_), _) => label + SHORTCUT_GAP + ARROW_WIDTH,
+                _ => label,
+            }
+        }).max().unwrap_or(0);
+        let height: Dimension = menu.items.iter().map(|item| self.item_height(item)).sum();
+        (GUTTER + widest + MARGIN + 2, height + 2)
+    }
+
+    /// Answers the rectangle each item occupies in the menu open at `depth`.
+    fn rows(&self, depth: usize) -> Vec<Rect> {
+        let ((left, top), (right, _)) = self.levels[depth].area;
+        let mut y = top + 1;
+        self.menu_at(depth).items.iter().map(|item| {
+            let height = self.item_height(item);
+            y += height;
+            ((left + 1, y - height), (right - 1, y))
+        }).collect()
+    }
+
+    /// Answers the selectable item under `pt` in the menu open at `depth`, if any.
+    fn item_at(&self, depth: usize, pt: Point) -> Option<usize> {
+        let menu = self.menu_at(depth);
+        self.rows(depth).iter().position(|&row| rect_contains(row, pt))
+            .filter(|&i| menu.items[i].is_selectable())
+    }
+
+    /// Opens a menu occupying `area` at the end of the cascade.
+    fn push_level(&mut self, med: &mut dyn Mediator, area: Rect) {
+        let ((left, top), (right, bottom)) = area;
+        let shadow = med.theme().shadow;
+        let saved = med.borrow_mut_desktop().save_area((left, top), (right + shadow, bottom + shadow));
+        self.levels.push(Level { area, saved, hot: None });
+        self.draw_level(med, self.levels.len() - 1);
+        med.repaint_all();
+    }
+
+    /// Opens the submenu of item `i` in the menu open at `depth`,
+    /// beside the item, on whichever side it fits.
+    fn open_submenu(&mut self, med: &mut dyn Mediator, depth: usize, i: usize) {
+        let submenu = self.menu_at(depth).items[i].submenu_ref().expect("item has a submenu");
+        let (w, h) = self.menu_size(submenu);
+        let (dw, dh) = med.borrow_mut_desktop().get_dimensions();
+        let ((parent_left, _), (parent_right, _)) = self.levels[depth].area;
+        let row_top = self.rows(depth)[i].0.1;
+
+        let left = if parent_right - 2 + w < dw { parent_right - 2 } else { (parent_left - w + 2).max(0) };
+        let top = (row_top - 1).min(dh - h - 1).max(0);
+        self.path.push(i);
+        self.push_level(med, ((left, top), (left + w, top + h)));
+    }
+
+    /// Highlights item `hot` in the menu open at `depth`, instead of whichever item was.
+    fn set_hot(&mut self, med: &mut dyn Mediator, depth: usize, hot: Option<usize>) {
+        let old = self.levels[depth].hot;
+        if old == hot {
+            return;
+        }
+        let rows = self.rows(depth);
+        let d = med.borrow_mut_desktop();
+        for i in [old, hot].into_iter().flatten() {
+            d.invert_rectangle(rows[i].0, rows[i].1);
+        }
+        self.levels[depth].hot = hot;
+        med.repaint_all();
+    }
+
+    /// Makes `hot` the highlighted item of the menu open at `depth`,
+    /// closing submenus opened from other items,
+    /// and opening its own submenu if it has one.
+    fn track(&mut self, med: &mut dyn Mediator, depth: usize, hot: Option<usize>) {
+        if self.path.get(depth).copied() != hot {
+            self.close_to(med, depth);
+        }
+        self.set_hot(med, depth, hot);
+        if let Some(i) = hot {
+            let has_submenu = self.menu_at(depth).items[i].submenu_ref().is_some();
+            if has_submenu && (self.levels.len() == depth + 1) {
+                self.open_submenu(med, depth, i);
+            }
+        }
+    }
+
+    /// Answers the deepest open menu under `pt`, if any.
+    fn level_at(&self, pt: Point) -> Option<usize> {
+        (0..self.levels.len()).rev().find(|&d| rect_contains(self.levels[d].area, pt))
+    }
+
+    /// Chooses item `i` of the menu open at `depth`:
+    /// a command closes the menu and answers its identifier;
+    /// a submenu opens, with its first item highlighted.
+    fn choose(&mut self, med: &mut dyn Mediator, depth: usize, i: usize) -> MenuEvent {
+        let item = &self.menu_at(depth).items[i];
+        if let Some(id) = item.command_id() {
+            self.close(med);
+            return MenuEvent::Selected(id);
+        }
+        self.track(med, depth, Some(i));
+        if self.levels.len() > depth + 1 {
+            let first = self.menu_at(depth + 1).items.iter().position(MenuItem::is_selectable);
+            self.set_hot(med, depth + 1, first);
+        }
+        MenuEvent::None
+    }
+
+    /// Highlights the next selectable item after the hot one in the innermost menu,
+    /// or the one before it if `backwards` is set,
+    /// wrapping around at either end.
+    fn step(&mut self, med: &mut dyn Mediator, backwards: bool) {
+        let depth = self.levels.len() - 1;
+        let items = &self.menu_at(depth).items;
+        let n = items.len();
+        let start = self.levels[depth].hot;
+        let next = (1..=n).map(|k| match (start, backwards) {
+            (Some(h), false) => (h + k) % n,
+            (Some(h), true) => (h + n - k) % n,
+            (None, false) => k - 1,
+            (None, true) => n - k,
+        }).find(|&i| items[i].is_selectable());
+        self.set_hot(med, depth, next);
+    }
+
+    /// Draws the menu open at `depth`.
+    fn draw_level(&self, med: &mut dyn Mediator, depth: usize) {
+        let menu = self.menu_at(depth);
+        let rows = self.rows(depth);
+        let ((left, top), (right, bottom)) = self.levels[depth].area;
+        let theme = med.theme();
+        let d = med.borrow_mut_desktop();
+
+        // The frame stays one pixel thick whatever the theme,
+        // since the rows inside it are inverted right up to it.
+        d.filled_rectangle((left, top), (right, bottom), &theme.paper);
+        d.framed_rectangle((left, top), (right, bottom), LINE_BLACK);
+        theme.drop_shadow(d, (left, top), (right, bottom));
+
+        for (item, &row) in menu.items.iter().zip(rows.iter()) {
+            let ((row_left, row_top), (row_right, row_bottom)) = row;
+            if let ItemKind::Separator = item.kind {
+                let y = (row_top + row_bottom) >> 1;
+                d.horizontal_line((row_left, y), row_right, 0xAA);
+                continue;
+            }
+
+            let text_top = row_top + 2;
+            let mut p = SimplePrinter::new(d, ((row_left + GUTTER - 1, text_top), (row_right, row_bottom)), self.font);
+            p.print(&item.label);
+
+            if let Some(shortcut) = item.shortcut_text() {
+                let x = row_right - MARGIN - text_width(&shortcut, self.font);
+                let mut p = SimplePrinter::new(d, ((x, text_top), (row_right, row_bottom)), self.font);
+                p.print(&shortcut);
+            }
+
+            if let ItemKind::Command { checked: true, .. } = item.kind {
+                // A tick, two pixels thick.
+                let y = row_top + ((row_bottom - row_top - 7) >> 1);
+                for (dx, dy) in [(0, 3), (1, 4), (2, 5), (3, 4), (4, 3), (5, 2), (6, 1), (7, 0)] {
+                    d.vertical_line((row_left + 2 + dx, y + dy), y + dy + 2, LINE_BLACK);
+                }
+            }
+
+            if let ItemKind::Submenu(_) = item.kind {
+                let x = row_right - MARGIN - ARROW_WIDTH;
+                let mid = (row_top + row_bottom) >> 1;
+                for dx in 0..ARROW_WIDTH {
+                    d.vertical_line((x + dx, mid - 3 + dx), mid + 4 - dx, LINE_BLACK);
+                }
+            }
+
+            if !item.enabled {
+                d.lighten_rectangle((row_left, row_top), (row_right, row_bottom));
+            }
+        }
+    }
+}
+
+impl<'f> MouseEventSink<MenuEvent> for PopupMenuView<'f> {
+    /// Handles mouse motion events,
+    /// highlighting the item under the pointer,
+    /// and opening its submenu if it has one.
+    ///
+    /// Currently, always answers with [[MenuEvent::None]].
+    fn pointer_moved(&mut self, med: &mut dyn Mediator, to: Point) -> MenuEvent {
+        if !self.is_open() {
+            return MenuEvent::None;
+        }
+        match self.level_at(to) {
+            Some(depth) => {
+                self.entered = true;
+                let hot = self.item_at(depth, to);
+                self.track(med, depth, hot);
+            }
+            None => {
+                let depth = self.levels.len() - 1;
+                self.set_hot(med, depth, None);
+            }
+        }
+        MenuEvent::None
+    }
+
+    /// Handles mouse button-up events.
+    ///
+    /// Releasing the button over a command chooses it,
+    /// closing the menu and answering [[MenuEvent::Selected]].
+    /// Releasing it anywhere off the menu closes the menu,
+    /// unless the pointer never reached the menu,
+    /// in which case the menu stays open to be clicked on.
+    fn button_up(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> MenuEvent {
+        if !self.is_open() {
+            return MenuEvent::None;
+        }
+        match self.level_at(event.at) {
+            Some(depth) => match self.item_at(depth, event.at) {
+                Some(i) if self.menu_at(depth).items[i].command_id().is_some() => self.choose(med, depth, i),
+                _ => MenuEvent::None,
+            },
+            None => {
+                if self.entered {
+                    self.close(med);
+                }
+                MenuEvent::None
+            }
+        }
+    }
+
+    /// Handles mouse button-down events.
+    /// Pressing a button anywhere off the menu closes it.
+    ///
+    /// Currently, always answers with [[MenuEvent::None]].
+    fn button_down(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> MenuEvent {
+        if self.is_open() && self.level_at(event.at).is_none() {
+            self.close(med);
+        }
+        MenuEvent::None
+    }
+
+    fn wheel(&mut self, _: &mut dyn Mediator, _: Point, _: Modifiers) -> MenuEvent {
+        MenuEvent::None
+    }
+
+    fn enter(&mut self, med: &mut dyn Mediator, at: Point) -> MenuEvent {
+        self.pointer_moved(med, at)
+    }
+
+    fn leave(&mut self, _: &mut dyn Mediator) -> MenuEvent {
+        MenuEvent::None
+    }
+}
+
+/// While the menu is open,
+/// Up and Down move the highlight,
+/// Right or Return opens a submenu,
+/// Left closes it again,
+/// Return chooses a command,
+/// and Escape closes the menu.
+///
+/// Whether the menu is open or not,
+/// a command's shortcut chooses it.
+impl<'f> KeyboardEventSink<MenuEvent> for PopupMenuView<'f> {
+    fn key_down(&mut self, med: &mut dyn Mediator, key: KeyCode, modifiers: Modifiers) -> MenuEvent {
+        if let (KeyCode::Char(c), true) = (key, modifiers.contains(Modifiers::COMMODORE)) {
+            if let Some(id) = self.menu.shortcut(c) {
+                self.close(med);
+                return MenuEvent::Selected(id);
+            }
+        }
+        if !self.is_open() {
+            return MenuEvent::None;
+        }
+
+        let depth = self.levels.len() - 1;
+        let hot = self.levels[depth].hot;
+        match key {
+            KeyCode::Escape => self.close(med),
+            KeyCode::Up => self.step(med, true),
+            KeyCode::Down => self.step(med, false),
+            KeyCode::Left if depth > 0 => self.close_to(med, depth - 1),
+            KeyCode::Right | KeyCode::Return => {
+                if let Some(i) = hot {
+                    let is_command = self.menu_at(depth).items[i].command_id().is_some();
+                    if (key == KeyCode::Return) || !is_command {
+                        return self.choose(med, depth, i);
+                    }
+                }
+            }
+            _ => (),
+        }
+        MenuEvent::None
+    }
+
+    fn key_up(&mut self, _: &mut dyn Mediator, _: KeyCode, _: Modifiers) -> MenuEvent {
+        MenuEvent::None
+    }
+
+    fn text_input(&mut self, _: &mut dyn Mediator, _: &str) -> MenuEvent {
+        MenuEvent::None
+    }
+}
+
+/// Maintains the appearance of a menu bar,
+/// and of the menus pulled down from it.
+pub struct MenuBarView<'f> {
+    /// The rectangle occupied by the bar.
+    area: Rect,
+
+    /// The font titles are printed in.
+    font: &'f SimpleBitmapFont<'f>,
+
+    /// The menus, from left to right.
+    menus: Vec<PopupMenuView<'f>>,
+
+    /// The index of the menu pulled down, if any.
+    open: Option<usize>,
+}
+
+impl<'f> MenuBarView<'f> {
+    /// Creates a menu bar occupying `area`,
+    /// offering `menus` from left to right,
+    /// with titles and items printed in `font`.
+    pub fn new(area: Rect, menus: Vec<Menu>, font: &'f SimpleBitmapFont<'f>) -> Self {
+        Self {
+            area,
+            font,
+            menus: menus.into_iter().map(|m| PopupMenuView::new(m, font)).collect(),
+            open: None,
+        }
+    }
+
+    /// Retrieves the menu at `index`, counting from the left.
+    pub fn menu(&self, index: usize) -> &Menu {
+        self.menus[index].menu()
+    }
+
+    /// Retrieves the menu at `index`, counting from the left,
+    /// so items can be enabled, disabled, checked or unchecked.
+    pub fn menu_mut(&mut self, index: usize) -> &mut Menu {
+        self.menus[index].menu_mut()
+    }
+
+    /// Answers `true` if a menu is pulled down.
+    pub fn is_open(&self) -> bool {
+        self.open.is_some()
+    }
+
+    /// Answers the rectangle each title occupies in the bar.
+    fn title_areas(&self) -> Vec<Rect> {
+        let ((left, top), (_, bottom)) = self.area;
+        let mut x = left + TITLE_PADDING;
+        self.menus.iter().map(|m| {
+            let width = text_width(m.menu().get_title(), self.font) + 2 * TITLE_PADDING;
+            x += width;
+            ((x - width, top), (x, bottom - 1))
+        }).collect()
+    }
+
+    /// Answers the title under `pt`, if any.
+    fn title_at(&self, pt: Point) -> Option<usize> {
+        self.title_areas().iter().position(|&t| rect_contains(t, pt))
+    }
+
+    /// Pulls down the menu at `index`, putting away any other.
+    fn pull_down(&mut self, med: &mut dyn Mediator, index: usize) {
+        self.put_away(med);
+        let title = self.title_areas()[index];
+        med.borrow_mut_desktop().invert_rectangle(title.0, title.1);
+        self.menus[index].open(med, (title.0.0, self.area.1.1));
+        self.open = Some(index);
+    }
+
+    /// Puts away the menu pulled down, if any.
+    fn put_away(&mut self, med: &mut dyn Mediator) {
+        if let Some(index) = self.open.take() {
+            self.menus[index].close(med);
+            self.unhighlight(med, index);
+        }
+    }
+
+    /// Takes the highlight off the title at `index`.
+    fn unhighlight(&mut self, med: &mut dyn Mediator, index: usize) {
+        let title = self.title_areas()[index];
+        med.borrow_mut_desktop().invert_rectangle(title.0, title.1);
+        med.repaint_all();
+    }
+
+    /// Notices when the menu pulled down has closed itself.
+    fn follow_menu(&mut self, med: &mut dyn Mediator) {
+        if let Some(index) = self.open {
+            if !self.menus[index].is_open() {
+                self.open = None;
+                self.unhighlight(med, index);
+            }
+        }
+    }
+}
+
+impl<'f> View for MenuBarView<'f> {
+    /// Draws the menu bar on the desktop stencil associated with the mediator `med`.
+    fn draw(&mut self, med: &mut dyn Mediator) {
+        let titles = self.title_areas();
+        let theme = med.theme();
+        let d = med.borrow_mut_desktop();
+        let ((left, top), (right, bottom)) = self.area;
+
+        d.filled_rectangle((left, top), (right, bottom), &theme.paper);
+        d.horizontal_line((left, bottom - 1), right, LINE_BLACK);
+        for (m, &((title_left, title_top), (title_right, title_bottom))) in self.menus.iter().zip(titles.iter()) {
+            let region = ((title_left + TITLE_PADDING, title_top + 1), (title_right, title_bottom));
+            let mut p = SimplePrinter::new(d, region, self.font);
+            p.print(m.menu().get_title());
+        }
+        if let Some(index) = self.open {
+            d.invert_rectangle(titles[index].0, titles[index].1);
+        }
+    }
+
+    /// Answers room for every title, and a line of text above the rule beneath them.
+    fn preferred_size(&self) -> Option<Dimensions> {
+        let right = self.title_areas().last().map_or(self.area.0.0, |t| t.1.0);
+        Some((right - self.area.0.0 + TITLE_PADDING, self.font.height + 3))
+    }
+
+    fn set_area(&mut self, area: Rect) {
+        self.area = area;
+    }
+}
+
+impl<'f> MouseEventSink<MenuEvent> for MenuBarView<'f> {
+    /// Handles mouse motion events.
+    /// While a menu is pulled down,
+    /// moving onto another title pulls down that title's menu instead.
+    ///
+    /// Currently, always answers with [[MenuEvent::None]].
+    fn pointer_moved(&mut self, med: &mut dyn Mediator, to: Point) -> MenuEvent {
+        if let Some(index) = self.open {
+            match self.title_at(to) {
+                Some(t) if t != index => self.pull_down(med, t),
+                _ => {
+                    let _ = self.menus[index].pointer_moved(med, to);
+                }
+            }
+        }
+        MenuEvent::None
+    }
+
+    /// Handles mouse button-up events.
+    ///
+    /// Releasing the button over a command chooses it,
+    /// answering [[MenuEvent::Selected]].
+    /// Releasing it over the title leaves its menu pulled down, to be clicked on.
+    fn button_up(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> MenuEvent {
+        let Some(index) = self.open else {
+            return MenuEvent::None;
+        };
+        if self.title_at(event.at) == Some(index) {
+            return MenuEvent::None;
+        }
+        let evt = self.menus[index].button_up(med, event);
+        self.follow_menu(med);
+        evt
+    }
+
+    /// Handles mouse button-down events.
+    /// Pressing a title pulls down its menu, or puts it away if it's already down.
+    ///
+    /// Currently, always answers with [[MenuEvent::None]].
+    fn button_down(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> MenuEvent {
+        match (self.title_at(event.at), self.open) {
+            (Some(t), Some(index)) if t == index => self.put_away(med),
+            (Some(t), _) => self.pull_down(med, t),
+            (None, Some(index)) => {
+                let _ = self.menus[index].button_down(med, event);
+                self.follow_menu(med);
+            }
+            (None, None) => (),
+        }
+        MenuEvent::None
+    }
+
+    fn wheel(&mut self, _: &mut dyn Mediator, _: Point, _: Modifiers) -> MenuEvent {
+        MenuEvent::None
+    }
+
+    fn enter(&mut self, med: &mut dyn Mediator, at: Point) -> MenuEvent {
+        self.pointer_moved(med, at)
+    }
+
+    fn leave(&mut self, _: &mut dyn Mediator) -> MenuEvent {
+        MenuEvent::None
+    }
+}
+
+/// Shortcuts choose commands from any of the bar's menus.
+/// While a menu is pulled down, other keys work it as they would a pop-up menu.
+impl<'f> KeyboardEventSink<MenuEvent> for MenuBarView<'f> {
+    fn key_down(&mut self, med: &mut dyn Mediator, key: KeyCode, modifiers: Modifiers) -> MenuEvent {
+        if let (KeyCode::Char(c), true) = (key, modifiers.contains(Modifiers::COMMODORE)) {
+            if let Some(id) = self.menus.iter().find_map(|m| m.menu().shortcut(c)) {
+                self.put_away(med);
+                return MenuEvent::Selected(id);
+            }
+        }
+        let Some(index) = self.open else {
+            return MenuEvent::None;
+        };
+        let evt = self.menus[index].key_down(med, key, modifiers);
+        self.follow_menu(med);
+        evt
+    }
+
+    fn key_up(&mut self, _: &mut dyn Mediator, _: KeyCode, _: Modifiers) -> MenuEvent {
+        MenuEvent::None
+    }
+
+    fn text_input(&mut self, _: &mut dyn Mediator, _: &str) -> MenuEvent {
+        MenuEvent::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Menu, MenuBarView, MenuEvent, MenuItem, PopupMenuView};
+    use crate::events::{ButtonEvent, KeyCode, KeyboardEventSink, Modifiers, MouseEventSink};
+    use crate::mediator::Mediator;
+    use crate::stencil::Draw;
+    use crate::sysfont_bsw_9::SYSTEM_BITMAP_FONT;
+    use crate::utils::testing::TestMediator;
+    use crate::view::View;
+
+    const OPEN: usize = 1;
+    const SAVE: usize = 2;
+    const WRAP: usize = 3;
+    const RULER: usize = 4;
+
+    fn file_menu() -> Menu {
+        Menu::new("File", vec![
+            MenuItem::command(OPEN, "Open").with_shortcut('o'),
+            MenuItem::command(SAVE, "Save").disabled(),
+            MenuItem::separator(),
+            MenuItem::submenu("Options", Menu::new("Options", vec![
+                MenuItem::command(WRAP, "Wrap").with_check(true),
+                MenuItem::command(RULER, "Ruler").with_shortcut('r'),
+            ])),
+        ])
+    }
+
+    #[test]
+    fn menus_find_commands_by_id_and_shortcut() {
+        let mut menu = file_menu();
+        assert!(menu.is_checked(WRAP));
+        menu.set_checked(WRAP, false);
+        assert!(!menu.is_checked(WRAP));
+        assert!(!menu.is_enabled(SAVE));
+        assert_eq!(menu.shortcut('R'), Some(RULER));
+        menu.set_enabled(RULER, false);
+        assert_eq!(menu.shortcut('r'), None);
+    }
+
+    #[test]
+    fn popups_choose_commands_and_restore_the_desktop() {
+        let mut med = TestMediator::new(160, 100);
+        med.borrow_mut_desktop().invert_rectangle((0, 0), (160, 100));
+        let before = med.desktop.bits.clone();
+
+        let mut popup = PopupMenuView::new(file_menu(), &SYSTEM_BITMAP_FONT);
+        popup.open(&mut med, (10, 10));
+        assert!(popup.is_open());
+        assert_ne!(med.desktop.bits, before);
+
+        // Rows are 13 pixels tall: Open, Save, a separator, then Options.
+        let save = (20, 11 + 13 + 6);
+        let options = (20, 11 + 13 + 13 + 5 + 6);
+        let _ = popup.pointer_moved(&mut med, save);
+        assert_eq!(popup.button_up(&mut med, ButtonEvent::left(save)), MenuEvent::None);
+        assert!(popup.is_open());
+
+        // Resting on Options opens its submenu, whose first item is Wrap.
+        let _ = popup.pointer_moved(&mut med, options);
+        assert_eq!(popup.levels.len(), 2);
+        let wrap = (popup.levels[1].area.0.0 + 10, popup.levels[1].area.0.1 + 6);
+        let _ = popup.pointer_moved(&mut med, wrap);
+        assert_eq!(popup.button_up(&mut med, ButtonEvent::left(wrap)), MenuEvent::Selected(WRAP));
+        assert!(!popup.is_open());
+        assert_eq!(med.desktop.bits, before);
+    }
+
+    #[test]
+    fn menu_bars_pull_down_menus_and_take_shortcuts() {
+        let mut med = TestMediator::new(160, 100);
+        let edit = Menu::new("Edit", vec![MenuItem::command(10, "Undo").with_shortcut('z')]);
+        let mut bar = MenuBarView::new(((0, 0), (160, 12)), vec![file_menu(), edit], &SYSTEM_BITMAP_FONT);
+        bar.draw(&mut med);
+        let before = med.desktop.bits.clone();
+
+        // Clicking a title leaves its menu down; clicking a command chooses it.
+        let _ = bar.button_down(&mut med, ButtonEvent::left((10, 5)));
+        let _ = bar.button_up(&mut med, ButtonEvent::left((10, 5)));
+        assert!(bar.is_open());
+        let open = (20, 14);
+        let _ = bar.pointer_moved(&mut med, open);
+        let _ = bar.button_down(&mut med, ButtonEvent::left(open));
+        assert_eq!(bar.button_up(&mut med, ButtonEvent::left(open)), MenuEvent::Selected(OPEN));
+        assert!(!bar.is_open());
+        assert_eq!(med.desktop.bits, before);
+
+        let c = Modifiers::COMMODORE;
+        assert_eq!(bar.key_down(&mut med, KeyCode::Char('z'), c), MenuEvent::Selected(10));
+        assert_eq!(bar.key_down(&mut med, KeyCode::Char('z'), Modifiers::NONE), MenuEvent::None);
+    }
+}
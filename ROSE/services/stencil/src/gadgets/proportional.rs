@@ -17,25 +17,118 @@
 //! supported in this module
 //! are largely inspired
 //! by those found in AmigaOS' Intuition library.
+//!
+//! An application can place the knob in pixels, with [[PropGadgetView::set_knob]],
+//! or describe what the gadget controls with a [[PropAxis]] for each direction,
+//! with [[PropGadgetView::set_values]],
+//! and let the gadget work out the knob's size and position for itself.
+//! A gadget given values reports changes to them,
+//! in the same units,
+//! rather than where the knob went.
 
-use crate::types::{Rect, Point};
-use crate::utils::{LINE_BLACK, WHITE_PATTERN};
-use crate::stencil::{Draw, Pattern};
-use crate::events::MouseEventSink;
+use crate::types::{Dimension, Rect, Point, Unit};
+use crate::utils::LINE_BLACK;
+use crate::stencil::Draw;
+use crate::events::{ButtonEvent, Modifiers, MouseButton, MouseEventSink};
 use crate::mediator::Mediator;
 use crate::view::{View, rect_contains};
 
-/// This is the 8x8 pixel pattern used to draw a proportional gadget's track.
-pub static PROP_TRACK_PATTERN: Pattern = [
-    0b11101110,
-    0b11011101,
-    0b10111011,
-    0b01110111,
-    0b11101110,
-    0b11011101,
-    0b10111011,
-    0b01110111,
-];
+/// How far, in pixels, one notch of the mouse wheel moves the knob.
+pub const WHEEL_STEP: Dimension = 8;
+
+/// The smallest a knob gets, in pixels, however little of the document is visible.
+pub const MIN_KNOB: Dimension = 6;
+
+/// The largest pot and body values.
+pub const MAX_POT: u16 = 0xFFFF;
+
+/// Describes what a proportional gadget controls along one axis,
+/// in whatever units suit the application:
+/// lines of a document, pixels of a picture, or levels of a colour.
+///
+/// Of `total` units, `visible` can be seen at once,
+/// starting from `top`.
+/// The knob is as long, relative to the track, as `visible` is to `total`,
+/// and as far along it as `top` is along the units which can be scrolled to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PropAxis {
+    pub total: usize,
+    pub visible: usize,
+    pub top: usize,
+}
+
+impl PropAxis {
+    /// Creates an axis `total` units long, of which `visible` can be seen, starting from `top`.
+    /// `visible` is limited to `total`, and `top` to [[PropAxis::max_top]].
+    pub fn new(total: usize, visible: usize, top: usize) -> Self {
+        let visible = visible.min(total);
+        Self { total, visible, top: top.min(total - visible) }
+    }
+
+    /// Creates an axis from Intuition-style pot and body values,
+    /// in [[MAX_POT]]ths of the way along, and of the whole, respectively.
+    pub fn from_pot_body(pot: u16, body: u16) -> Self {
+        let (pot, body, max) = (u64::from(pot), u64::from(body), u64::from(MAX_POT));
+        Self::new(max as usize, body as usize, ((pot * (max - body) + (max >> 1)) / max) as usize)
+    }
+
+    /// Answers the furthest `top` can go while keeping `visible` units in view.
+    pub fn max_top(&self) -> usize {
+        self.total - self.visible
+    }
+
+    /// Answers how far along `top` is, in [[MAX_POT]]ths.
+    pub fn pot(&self) -> u16 {
+        match self.max_top() {
+            0 => 0,
+            max_top => scale(self.top, u64::from(MAX_POT) as usize, max_top) as u16,
+        }
+    }
+
+    /// Answers how much of the whole is visible, in [[MAX_POT]]ths.
+    pub fn body(&self) -> u16 {
+        match self.total {
+            0 => MAX_POT,
+            total => scale(self.visible, u64::from(MAX_POT) as usize, total) as u16,
+        }
+    }
+
+    /// Answers the axis moved `pages` pages of `visible` units, forwards or backwards.
+    fn paged(&self, pages: isize) -> Self {
+        let step = self.visible.max(1);
+        let top = if pages < 0 { self.top.saturating_sub(step) } else { self.top + step };
+        Self::new(self.total, self.visible, top)
+    }
+
+    /// Answers where the knob goes along a track running from `start` to `end`.
+    fn knob_span(&self, start: Unit, end: Unit) -> (Unit, Unit) {
+        let length = (end - start).max(0);
+        let size = match self.total {
+            0 => length,
+            total => (scale(self.visible, length as usize, total) as Dimension).max(MIN_KNOB).min(length),
+        };
+        let offset = match self.max_top() {
+            0 => 0,
+            max_top => scale(self.top, (length - size) as usize, max_top) as Dimension,
+        };
+        (start + offset, start + offset + size)
+    }
+
+    /// Answers the axis with `top` moved to match a knob
+    /// starting at `knob_start` and `knob_size` long,
+    /// along a track running from `start` to `end`.
+    fn following(&self, start: Unit, end: Unit, knob_start: Unit, knob_size: Dimension) -> Self {
+        let travel = end - start - knob_size;
+        let top = if travel > 0 { scale((knob_start - start).max(0) as usize, self.max_top(), travel as usize) } else { 0 };
+        Self::new(self.total, self.visible, top)
+    }
+}
+
+/// Answers `n` times `numerator` over `denominator`, rounded to the nearest whole number.
+fn scale(n: usize, numerator: usize, denominator: usize) -> usize {
+    let (n, numerator, denominator) = (n as u64, numerator as u64, denominator as u64);
+    ((n * numerator + (denominator >> 1)) / denominator) as usize
+}
 
 /// Maintains the appearance of a proportional gadget.
 pub struct PropGadgetView {
@@ -52,6 +145,13 @@ pub struct PropGadgetView {
     /// True if the user has grabbed onto the knob; false otherwise.
     grabbed: bool,
 
+    /// Where, relative to the knob's top left corner, the user grabbed it.
+    grab_offset: Point,
+
+    /// What the gadget controls, horizontally and vertically,
+    /// if the application described it with [[PropGadgetView::set_values]].
+    values: Option<(PropAxis, PropAxis)>,
+
     /// Tracks the current mouse pointer position on the screen.
     mouse_pt: Point,
 }
@@ -70,6 +170,8 @@ impl PropGadgetView {
 
             knob: track,
             grabbed: false,
+            grab_offset: (0, 0),
+            values: None,
             mouse_pt: (0, 0),
         }
     }
@@ -90,6 +192,74 @@ impl PropGadgetView {
         );
     }
 
+    /// Describes what the gadget controls, horizontally and vertically,
+    /// placing the knob to match.
+    ///
+    /// From then on, the gadget reports [[PropGadgetEvent::ValuesChanged]]
+    /// rather than [[PropGadgetEvent::KnobMoved]].
+    /// Call this again to acknowledge the change,
+    /// or whenever the document changes.
+    pub fn set_values(&mut self, horizontal: PropAxis, vertical: PropAxis) {
+        self.values = Some((horizontal, vertical));
+        self.knob = self.knob_for(horizontal, vertical);
+    }
+
+    /// Retrieves what the gadget controls, horizontally and vertically,
+    /// if it was ever described with [[PropGadgetView::set_values]].
+    pub fn get_values(&self) -> Option<(PropAxis, PropAxis)> {
+        self.values
+    }
+
+    /// Answers where the knob goes to show `horizontal` and `vertical`.
+    fn knob_for(&self, horizontal: PropAxis, vertical: PropAxis) -> Rect {
+        let ((left, top), (right, bottom)) = self.track;
+        let (knob_left, knob_right) = horizontal.knob_span(left, right);
+        let (knob_top, knob_bottom) = vertical.knob_span(top, bottom);
+        ((knob_left, knob_top), (knob_right, knob_bottom))
+    }
+
+    /// Answers the event reporting that the operator moved the knob to `knob`:
+    /// the values it now shows, if the gadget has values,
+    /// otherwise the knob itself.
+    fn report(&self, knob: Rect) -> PropGadgetEvent {
+        let Some((h, v)) = self.values else {
+            return PropGadgetEvent::KnobMoved(knob);
+        };
+        let ((track_left, track_top), (track_right, track_bottom)) = self.track;
+        let ((left, top), (right, bottom)) = knob;
+        let h = h.following(track_left, track_right, left, right - left);
+        let v = v.following(track_top, track_bottom, top, bottom - top);
+        self.report_values(h, v)
+    }
+
+    /// Answers [[PropGadgetEvent::ValuesChanged]] if `h` or `v` differ from the gadget's values,
+    /// otherwise [[PropGadgetEvent::None]].
+    fn report_values(&self, h: PropAxis, v: PropAxis) -> PropGadgetEvent {
+        if self.values == Some((h, v)) {
+            PropGadgetEvent::None
+        } else {
+            PropGadgetEvent::ValuesChanged(h, v)
+        }
+    }
+
+    /// Answers the event for clicking the track at `pt`, outside the knob:
+    /// the knob jumps a page towards the pointer, along each axis it can move.
+    fn page_towards(&self, pt: Point) -> PropGadgetEvent {
+        let ((left, top), (right, bottom)) = self.knob;
+        let direction = |at: Unit, start: Unit, end: Unit| {
+            if at < start { -1 } else if at >= end { 1 } else { 0 }
+        };
+        let dx = direction(pt.0, left, right);
+        let dy = direction(pt.1, top, bottom);
+        match self.values {
+            Some((h, v)) => self.report_values(h.paged(dx), v.paged(dy)),
+            None => {
+                let knob = self.moved_knob(dx * (right - left), dy * (bottom - top));
+                if knob == self.knob { PropGadgetEvent::None } else { PropGadgetEvent::KnobMoved(knob) }
+            }
+        }
+    }
+
     /// Retrieves the current knob rectangle.
     pub fn get_knob(&self) -> Rect {
         self.knob
@@ -99,6 +269,42 @@ impl PropGadgetView {
     pub fn get_track(&self) -> Rect {
         self.track
     }
+
+    /// Answers where the knob would be if moved by `dx` and `dy`,
+    /// kept within the track.
+    fn moved_knob(&self, dx: Dimension, dy: Dimension) -> Rect {
+        let track_left = self.track.0.0;
+        let track_top = self.track.0.1;
+        let track_right = self.track.1.0;
+        let track_bottom = self.track.1.1;
+
+        let new_left = self.knob.0.0 + dx;
+        let new_top = self.knob.0.1 + dy;
+        let new_right = self.knob.1.0 + dx;
+        let new_bottom = self.knob.1.1 + dy;
+
+        // constraint_left goes positive if there's a correction to be made.
+        let constraint_left = (track_left - new_left).max(0);
+        let new_left = new_left + constraint_left;
+        let new_right = new_right + constraint_left;
+
+        // constraint_right goes negative if there's a correction to be made.
+        let constraint_right = (track_right - new_right).min(0);
+        let new_left = new_left + constraint_right;
+        let new_right = new_right + constraint_right;
+
+        // constraint_top goes positive if there's a correction to be made.
+        let constraint_top = (track_top - new_top).max(0);
+        let new_top = new_top + constraint_top;
+        let new_bottom = new_bottom + constraint_top;
+
+        // constraint_bottom goes negative if there's a correction to be made.
+        let constraint_bottom = (track_bottom - new_bottom).min(0);
+        let new_top = new_top + constraint_bottom;
+        let new_bottom = new_bottom + constraint_bottom;
+
+        ((new_left, new_top), (new_right, new_bottom))
+    }
 }
 
 /// Events unique to the proportional gadget.
@@ -110,6 +316,11 @@ pub enum PropGadgetEvent {
     /// to a new location.  Use [[PropGadgetView::set_knob]] to acknowledge
     /// the movement.
     KnobMoved(Rect),
+
+    /// The user has performed an action which requests the gadget's values change,
+    /// horizontally and vertically.
+    /// Use [[PropGadgetView::set_values]] to acknowledge the change.
+    ValuesChanged(PropAxis, PropAxis),
 }
 
 impl MouseEventSink<PropGadgetEvent> for PropGadgetView {
@@ -123,45 +334,22 @@ impl MouseEventSink<PropGadgetEvent> for PropGadgetView {
     /// a chance to filter and/or react to the event before
     /// updating the gadget's knob.
     ///
+    /// A gadget with values answers [[PropGadgetEvent::ValuesChanged]] instead,
+    /// when the values change.
+    ///
     /// Otherwise, [[PropGadgetEvent::None]] is returned.
     fn pointer_moved(&mut self, _med: &mut dyn Mediator, to: Point) -> PropGadgetEvent {
         let mut evt = PropGadgetEvent::None;
-        if self.grabbed {
+        if self.grabbed && self.values.is_some() {
+            // Follow the pointer from where the knob was grabbed,
+            // so that rounding values can't leave the knob behind.
+            let dx = to.0 - self.grab_offset.0 - self.knob.0.0;
+            let dy = to.1 - self.grab_offset.1 - self.knob.0.1;
+            evt = self.report(self.moved_knob(dx, dy));
+        } else if self.grabbed {
             let dx = to.0 - self.mouse_pt.0;
             let dy = to.1 - self.mouse_pt.1;
-
-            let track_left = self.track.0.0;
-            let track_top = self.track.0.1;
-            let track_right = self.track.1.0;
-            let track_bottom = self.track.1.1;
-
-            let new_left = self.knob.0.0 + dx;
-            let new_top = self.knob.0.1 + dy;
-            let new_right = self.knob.1.0 + dx;
-            let new_bottom = self.knob.1.1 + dy;
-
-            // constraint_left goes positive if there's a correction to be made.
-            let constraint_left = (track_left - new_left).max(0);
-            let new_left = new_left + constraint_left;
-            let new_right = new_right + constraint_left;
-
-            // constraint_right goes negative if there's a correction to be made.
-            let constraint_right = (track_right - new_right).min(0);
-            let new_left = new_left + constraint_right;
-            let new_right = new_right + constraint_right;
-
-            // constraint_top goes positive if there's a correction to be made.
-            let constraint_top = (track_top - new_top).max(0);
-            let new_top = new_top + constraint_top;
-            let new_bottom = new_bottom + constraint_top;
-
-            // constraint_bottom goes negative if there's a correction to be made.
-            let constraint_bottom = (track_bottom - new_bottom).min(0);
-            let new_top = new_top + constraint_bottom;
-            let new_bottom = new_bottom + constraint_bottom;
-
-            let new_knob = ((new_left, new_top), (new_right, new_bottom));
-            evt = PropGadgetEvent::KnobMoved(new_knob);
+            evt = self.report(self.moved_knob(dx, dy));
         }
         self.mouse_pt = to;
         evt
@@ -170,19 +358,32 @@ impl MouseEventSink<PropGadgetEvent> for PropGadgetView {
     /// Handles mouse button-up events.
     ///
     /// Currently, always answers with [[PropGadgetEvent::None]].
-    fn button_up(&mut self, _med: &mut dyn Mediator) -> PropGadgetEvent {
-        self.grabbed = false;
+    fn button_up(&mut self, _med: &mut dyn Mediator, event: ButtonEvent) -> PropGadgetEvent {
+        if event.button == MouseButton::Left {
+            self.grabbed = false;
+        }
         PropGadgetEvent::None
     }
 
     /// Handles mouse button-down events.
     ///
-    /// Currently, always answers with [[PropGadgetEvent::None]].
-    fn button_down(&mut self, _med: &mut dyn Mediator) -> PropGadgetEvent {
+    /// Pressing the knob grabs it, answering [[PropGadgetEvent::None]].
+    /// Pressing the track elsewhere jumps the knob a page towards the pointer,
+    /// answering with the move as dragging would.
+    fn button_down(&mut self, _med: &mut dyn Mediator, event: ButtonEvent) -> PropGadgetEvent {
+        self.mouse_pt = event.at;
+        if event.button != MouseButton::Left {
+            return PropGadgetEvent::None;
+        }
         if self.point_in_knob() {
             self.grabbed = true;
+            self.grab_offset = (event.at.0 - self.knob.0.0, event.at.1 - self.knob.0.1);
+            PropGadgetEvent::None
+        } else if rect_contains(self.track, event.at) {
+            self.page_towards(event.at)
+        } else {
+            PropGadgetEvent::None
         }
-        PropGadgetEvent::None
     }
 
     /// Handles mouse entry events.
@@ -198,19 +399,130 @@ impl MouseEventSink<PropGadgetEvent> for PropGadgetView {
     fn leave(&mut self, _med: &mut dyn Mediator) -> PropGadgetEvent {
         PropGadgetEvent::None
     }
+
+    /// Handles mouse wheel events.
+    ///
+    /// Turning the wheel while the pointer is over the track
+    /// moves the knob [[WHEEL_STEP]] pixels per notch:
+    /// turning it away from the operator moves the knob up,
+    /// and tilting it right moves the knob right.
+    /// With Shift held down, turning the wheel moves the knob sideways instead.
+    ///
+    /// Answers with [[PropGadgetEvent::KnobMoved]] if the knob would move;
+    /// as with dragging, use [[PropGadgetView::set_knob]] to acknowledge it.
+    /// A gadget with values answers [[PropGadgetEvent::ValuesChanged]] instead.
+    /// Otherwise, answers with [[PropGadgetEvent::None]].
+    fn wheel(&mut self, _med: &mut dyn Mediator, delta: Point, modifiers: Modifiers) -> PropGadgetEvent {
+        if !rect_contains(self.track, self.mouse_pt) {
+            return PropGadgetEvent::None;
+        }
+        let (dx, dy) = if modifiers.contains(Modifiers::SHIFT) { (delta.0 - delta.1, 0) } else { delta };
+        let knob = self.moved_knob(dx * WHEEL_STEP, -dy * WHEEL_STEP);
+        if knob != self.knob {
+            self.report(knob)
+        } else {
+            PropGadgetEvent::None
+        }
+    }
 }
 
 impl View for PropGadgetView {
     // ISSUE: Should we just pass in a &mut Stencil here?  Or &mut dyn Draw?
-    /// Draws the proportional gadget on the desktop stencil associated with the mediator `med`.
+    /// Draws the proportional gadget on the desktop stencil associated with the mediator `med`,
+    /// with its track filled in the theme's track pattern.
     fn draw(&mut self, med: &mut dyn Mediator) {
+        let theme = med.theme();
         let d = med.borrow_mut_desktop();
         let border = ((self.track.0.0 - 2, self.track.0.1 - 2),
                       (self.track.1.0 + 2, self.track.1.1 + 2));
-        d.filled_rectangle(border.0, border.1, &PROP_TRACK_PATTERN);
+        d.filled_rectangle(border.0, border.1, &theme.track);
         d.framed_rectangle(border.0, border.1, LINE_BLACK);
-        d.filled_rectangle(self.knob.0, self.knob.1, &WHITE_PATTERN);
-        d.framed_rectangle(self.knob.0, self.knob.1, LINE_BLACK);
+        d.filled_rectangle(self.knob.0, self.knob.1, &theme.paper);
+        theme.frame(d, self.knob.0, self.knob.1);
+    }
+
+    /// Moves the track to `area`.
+    /// The knob of a gadget with values is placed afresh to show them;
+    /// otherwise, the knob moves with the track, and is clipped to its new size.
+    fn set_area(&mut self, area: Rect) {
+        let (dx, dy) = (area.0.0 - self.track.0.0, area.0.1 - self.track.0.1);
+        let ((left, top), (right, bottom)) = self.knob;
+        self.track = area;
+        match self.values {
+            Some((h, v)) => self.knob = self.knob_for(h, v),
+            None => self.set_knob(((left + dx, top + dy), (right + dx, bottom + dy))),
+        }
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::{PropAxis, PropGadgetEvent, PropGadgetView, MAX_POT, WHEEL_STEP};
+    use crate::events::{ButtonEvent, Modifiers, MouseEventSink};
+    use crate::utils::testing::TestMediator;
+
+    #[test]
+    fn wheel_moves_knob_within_track() {
+        let mut med = TestMediator::new(64, 64);
+        let mut prop = PropGadgetView::new(((0, 0), (40, 40)));
+        prop.set_knob(((10, 10), (20, 20)));
+
+        // Nothing happens unless the pointer is over the track.
+        prop.pointer_moved(&mut med, (50, 50));
+        assert!(matches!(prop.wheel(&mut med, (0, -1), Modifiers::NONE), PropGadgetEvent::None));
+
+        prop.pointer_moved(&mut med, (15, 15));
+        match prop.wheel(&mut med, (0, -1), Modifiers::NONE) {
+            PropGadgetEvent::KnobMoved(knob) => assert_eq!(knob, ((10, 10 + WHEEL_STEP), (20, 20 + WHEEL_STEP))),
+            _ => panic!("knob should move down"),
+        }
+        match prop.wheel(&mut med, (0, 5), Modifiers::SHIFT) {
+            PropGadgetEvent::KnobMoved(knob) => assert_eq!(knob, ((0, 10), (10, 20))),
+            _ => panic!("knob should move left"),
+        }
+    }
+
+    #[test]
+    fn values_place_the_knob() {
+        let mut prop = PropGadgetView::new(((0, 0), (10, 100)));
+        prop.set_values(PropAxis::default(), PropAxis::new(1000, 250, 375));
+        assert_eq!(prop.get_knob(), ((0, 38), (10, 63)));
+
+        // Tiny views still get a knob big enough to grab.
+        prop.set_values(PropAxis::default(), PropAxis::new(1000, 1, 999));
+        assert_eq!(prop.get_knob(), ((0, 94), (10, 100)));
+
+        let axis = PropAxis::new(1000, 250, 375);
+        assert_eq!((axis.pot(), axis.body()), (0x8000, 0x4000));
+        assert_eq!(PropAxis::from_pot_body(MAX_POT, 0x4000).pot(), MAX_POT);
+    }
+
+    #[test]
+    fn dragging_and_paging_change_values() {
+        let mut med = TestMediator::new(64, 128);
+        let mut prop = PropGadgetView::new(((0, 0), (10, 100)));
+        let v = PropAxis::new(1000, 250, 0);
+        prop.set_values(PropAxis::default(), v);
+
+        // Dragging the knob a third of its travel scrolls a third of the way.
+        let _ = prop.button_down(&mut med, ButtonEvent::left((5, 5)));
+        match prop.pointer_moved(&mut med, (5, 30)) {
+            PropGadgetEvent::ValuesChanged(_, v) => assert_eq!(v.top, 250),
+            _ => panic!("values should change"),
+        }
+        let _ = prop.button_up(&mut med, ButtonEvent::left((5, 30)));
+
+        // Clicking below the knob pages down.
+        match prop.button_down(&mut med, ButtonEvent::left((5, 90))) {
+            PropGadgetEvent::ValuesChanged(_, v) => assert_eq!(v.top, 250),
+            _ => panic!("values should page"),
+        }
+        prop.set_values(PropAxis::default(), PropAxis::new(1000, 250, 700));
+        match prop.button_down(&mut med, ButtonEvent::left((5, 98))) {
+            PropGadgetEvent::ValuesChanged(_, v) => assert_eq!(v.top, 750),
+            _ => panic!("values should page to the end"),
+        }
+        assert!(matches!(prop.button_down(&mut med, ButtonEvent::left((5, 2))), PropGadgetEvent::ValuesChanged(_, PropAxis { top: 450, .. })));
+    }
+}
@@ -0,0 +1,346 @@
+//! Support for radio button groups.
+//!
+//! A radio group offers a handful of mutually exclusive choices,
+//! one above the other,
+//! each a round button beside its label.
+//! Exactly one of them is selected at any time;
+//! selecting another deselects the first,
+//! like the station buttons on an old car radio.
+//!
+//! Like the check box,
+//! a radio group keeps its own state,
+//! and reports each change once it's made.
+
+use crate::events::{ButtonEvent, KeyCode, KeyboardEventSink, Modifiers, MouseButton, MouseEventSink};
+use crate::focus::FocusId;
+use crate::gadgets::checkbox::{BOX_SIZE, LABEL_GAP, box_in_row, draw_label};
+use crate::mediator::Mediator;
+use crate::simple_bitmap_font::{SimpleBitmapFont, text_width};
+use crate::stencil::Draw;
+use crate::types::{Dimension, Dimensions, Point, Rect};
+use crate::utils::{Glyph, draw_glyph};
+use crate::view::{View, rect_contains};
+
+/// A button which isn't selected.
+static BUTTON_OFF: Glyph = [0x3C, 0x42, 0x81, 0x81, 0x81, 0x81, 0x42, 0x3C];
+
+/// The selected button.
+static BUTTON_ON: Glyph = [0x3C, 0x42, 0x99, 0xBD, 0xBD, 0x99, 0x42, 0x3C];
+
+/// Maintains the appearance of a group of radio buttons.
+///
+/// The lifetime `'l` corresponds to the lifetime of the labels.
+/// The lifetime `'f` corresponds to the lifetime of the font.
+pub struct RadioGroupView<'l, 'f> {
+    /// The rectangle occupied by all the buttons and their labels.
+    area: Rect,
+
+    /// The label of each button, from top to bottom.
+    labels: Vec<&'l str>,
+
+    /// Which button is selected.
+    selected: usize,
+
+    /// False if the group ignores the operator, and is drawn lightened.
+    enabled: bool,
+
+    /// The button the operator pressed the mouse button on, while it's held down.
+    pressed: Option<usize>,
+
+    /// True while the pressed button is drawn inverted,
+    /// which it is only while the pointer is over it.
+    inverted: bool,
+
+    /// Tracks the current mouse position.
+    mouse_pt: Point,
+
+    /// Identifies the group to the mediator's focus owner, if it takes part in focus at all.
+    focus_id: Option<FocusId>,
+
+    /// Font to print the labels in.
+    font: &'f SimpleBitmapFont<'f>,
+}
+
+impl<'l, 'f> RadioGroupView<'l, 'f> {
+    /// Creates a new, enabled radio group with a button for each of the `labels`,
+    /// the first of them selected.
+    pub fn new(area: Rect, labels: Vec<&'l str>, font: &'f SimpleBitmapFont) -> Self {
+        Self {
+            area,
+            labels,
+            selected: 0,
+            enabled: true,
+            pressed: None,
+            inverted: false,
+            mouse_pt: (0, 0),
+            focus_id: None,
+            font,
+        }
+    }
+
+    /// Identifies the group as `id` to the mediator's focus owner.
+    ///
+    /// While it's the focus owner,
+    /// the selected button's label is framed,
+    /// and the cursor keys select the buttons above and below it.
+    pub fn set_focus_id(&mut self, id: FocusId) {
+        self.focus_id = Some(id);
+    }
+
+    /// Answers which button is selected, counting from zero at the top.
+    pub fn get_selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Selects button `n`, counting from zero at the top.
+    /// The group must be redrawn to show the change.
+    pub fn set_selected(&mut self, n: usize) {
+        if n < self.labels.len() {
+            self.selected = n;
+        }
+    }
+
+    /// Answers `true` if the group responds to the operator.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enables or disables the whole group.
+    /// The group must be redrawn to show the change.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Answers `true` if the group has the keyboard focus.
+    fn has_focus(&self, med: &dyn Mediator) -> bool {
+        self.focus_id.is_some() && (med.focus() == self.focus_id)
+    }
+
+    /// Answers how tall each button's row is.
+    fn row_height(&self) -> Dimension {
+        BOX_SIZE.max(self.font.height + 2)
+    }
+
+    /// Answers the rectangle occupied by button `n` and its label.
+    fn row(&self, n: usize) -> Rect {
+        let ((left, top), (right, _)) = self.area;
+        let row_top = top + n as Dimension * self.row_height();
+        ((left, row_top), (right, row_top + self.row_height()))
+    }
+
+    /// Answers the button under `pt`, if any.
+    fn button_at(&self, pt: Point) -> Option<usize> {
+        (0..self.labels.len()).find(|&n| rect_contains(self.row(n), pt))
+    }
+
+    /// Inverts the pressed button, if need be, so that it looks `pressed` or not.
+    fn show_pressed(&mut self, med: &mut dyn Mediator, pressed: bool) {
+        if let (Some(n), true) = (self.pressed, pressed != self.inverted) {
+            let ((left, top), (right, bottom)) = box_in_row(self.row(n));
+            med.borrow_mut_desktop().invert_rectangle((left + 1, top + 1), (right - 1, bottom - 1));
+            med.repaint_all();
+            self.inverted = pressed;
+        }
+    }
+
+    /// Selects button `n` in place of the old one, redrawing the group to match.
+    ///
+    /// Answers with [[RadioEvent::Selected]] if that changed anything.
+    fn select(&mut self, med: &mut dyn Mediator, n: usize) -> RadioEvent {
+        if n == self.selected {
+            return RadioEvent::None;
+        }
+        self.selected = n;
+        self.draw(med);
+        med.repaint_all();
+        RadioEvent::Selected(n)
+    }
+}
+
+impl<'l, 'f> View for RadioGroupView<'l, 'f> {
+    fn draw(&mut self, med: &mut dyn Mediator) {
+        let focused = self.has_focus(med);
+        let theme = med.theme();
+        let d = med.borrow_mut_desktop();
+        let (upper_left, lower_right) = self.area;
+        d.filled_rectangle(upper_left, lower_right, &theme.paper);
+
+        for (n, label) in self.labels.iter().enumerate() {
+            let row = self.row(n);
+            let (box_upper_left, _) = box_in_row(row);
+            let glyph = if n == self.selected { &BUTTON_ON } else { &BUTTON_OFF };
+            draw_glyph(d, (box_upper_left.0 + 1, box_upper_left.1 + 1), glyph);
+            draw_label(d, row, label, self.font, focused && (n == self.selected));
+        }
+
+        if !self.enabled {
+            d.lighten_rectangle(upper_left, lower_right);
+        }
+        if self.inverted {
+            self.inverted = false;
+            self.show_pressed(med, true);
+        }
+    }
+
+    /// Answers a size with a row for each button,
+    /// wide enough for the longest label and the frame shown around it while focused.
+    fn preferred_size(&self) -> Option<Dimensions> {
+        let widest = self.labels.iter().map(|l| text_width(l, self.font)).max().unwrap_or(0);
+        let width = BOX_SIZE + LABEL_GAP + widest + 2;
+        let height = self.labels.len() as Dimension * self.row_height();
+        Some((width, height))
+    }
+
+    fn set_area(&mut self, area: Rect) {
+        self.area = area;
+    }
+}
+
+/// Events a radio group may answer with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RadioEvent {
+    /// Nothing happened.
+    None,
+
+    /// The operator selected the given button, counting from zero at the top.
+    Selected(usize),
+}
+
+impl<'l, 'f> MouseEventSink<RadioEvent> for RadioGroupView<'l, 'f> {
+    /// Handles pointer motion events,
+    /// showing a button pressed only while the pointer is over it.
+    ///
+    /// Currently, always answers with [[RadioEvent::None]].
+    fn pointer_moved(&mut self, med: &mut dyn Mediator, to: Point) -> RadioEvent {
+        self.mouse_pt = to;
+        if self.pressed.is_some() {
+            let hot = self.button_at(to) == self.pressed;
+            self.show_pressed(med, hot);
+        }
+        RadioEvent::None
+    }
+
+    /// Handles mouse button-up events.
+    ///
+    /// Releasing the mouse button over the button it was pressed on selects it,
+    /// answering with [[RadioEvent::Selected]] if it wasn't already.
+    /// Otherwise, answers with [[RadioEvent::None]].
+    fn button_up(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> RadioEvent {
+        if event.button != MouseButton::Left {
+            return RadioEvent::None;
+        }
+        let Some(n) = self.pressed else {
+            return RadioEvent::None;
+        };
+        self.mouse_pt = event.at;
+        self.show_pressed(med, false);
+        self.pressed = None;
+        if self.button_at(event.at) == Some(n) {
+            self.select(med, n)
+        } else {
+            RadioEvent::None
+        }
+    }
+
+    /// Handles mouse button-down events, pressing the button under the pointer if the group is enabled.
+    ///
+    /// Currently, always answers with [[RadioEvent::None]].
+    fn button_down(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> RadioEvent {
+        self.mouse_pt = event.at;
+        if (event.button == MouseButton::Left) && self.enabled {
+            self.pressed = self.button_at(event.at);
+            self.show_pressed(med, true);
+        }
+        RadioEvent::None
+    }
+
+    fn wheel(&mut self, _: &mut dyn Mediator, _: Point, _: Modifiers) -> RadioEvent {
+        RadioEvent::None
+    }
+
+    fn enter(&mut self, med: &mut dyn Mediator, at: Point) -> RadioEvent {
+        self.pointer_moved(med, at)
+    }
+
+    fn leave(&mut self, _: &mut dyn Mediator) -> RadioEvent {
+        RadioEvent::None
+    }
+}
+
+impl<'l, 'f> KeyboardEventSink<RadioEvent> for RadioGroupView<'l, 'f> {
+    /// Handles key-down events.
+    ///
+    /// While the group has the focus,
+    /// Up or Left select the button above the selected one,
+    /// and Down or Right the button below it,
+    /// wrapping around at either end;
+    /// answering with [[RadioEvent::Selected]].
+    /// Otherwise, answers with [[RadioEvent::None]].
+    fn key_down(&mut self, med: &mut dyn Mediator, key: KeyCode, _: Modifiers) -> RadioEvent {
+        let n = self.labels.len();
+        if !self.enabled || (n == 0) || !self.has_focus(med) {
+            return RadioEvent::None;
+        }
+        match key {
+            KeyCode::Up | KeyCode::Left => self.select(med, (self.selected + n - 1) % n),
+            KeyCode::Down | KeyCode::Right => self.select(med, (self.selected + 1) % n),
+            _ => RadioEvent::None,
+        }
+    }
+
+    fn key_up(&mut self, _: &mut dyn Mediator, _: KeyCode, _: Modifiers) -> RadioEvent {
+        RadioEvent::None
+    }
+
+    fn text_input(&mut self, _: &mut dyn Mediator, _: &str) -> RadioEvent {
+        RadioEvent::None
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{RadioEvent, RadioGroupView};
+    use crate::events::{ButtonEvent, KeyCode, KeyboardEventSink, Modifiers, MouseEventSink};
+    use crate::mediator::Mediator;
+    use crate::sysfont_bsw_9::SYSTEM_BITMAP_FONT;
+    use crate::utils::testing::TestMediator;
+
+    fn radio() -> RadioGroupView<'static, 'static> {
+        RadioGroupView::new(((0, 0), (60, 33)), vec!["A", "B", "C"], &SYSTEM_BITMAP_FONT)
+    }
+
+    #[test]
+    fn clicking_selects_exactly_one() {
+        let mut med = TestMediator::new(64, 64);
+        let mut group = radio();
+
+        // Rows are eleven pixels tall; the third row starts at 22.
+        let _ = group.button_down(&mut med, ButtonEvent::left((4, 25)));
+        assert_eq!(group.button_up(&mut med, ButtonEvent::left((30, 25))), RadioEvent::Selected(2));
+        assert_eq!(group.get_selected(), 2);
+
+        // Selecting the selected button again changes nothing.
+        let _ = group.button_down(&mut med, ButtonEvent::left((4, 25)));
+        assert_eq!(group.button_up(&mut med, ButtonEvent::left((4, 25))), RadioEvent::None);
+
+        // Nor does releasing over a different button.
+        let _ = group.button_down(&mut med, ButtonEvent::left((4, 3)));
+        assert_eq!(group.button_up(&mut med, ButtonEvent::left((4, 14))), RadioEvent::None);
+        assert_eq!(group.get_selected(), 2);
+    }
+
+    #[test]
+    fn arrows_move_the_selection_with_focus() {
+        let mut med = TestMediator::new(64, 64);
+        let mut group = radio();
+        group.set_focus_id(1);
+        med.set_focus(Some(1));
+        assert_eq!(group.key_down(&mut med, KeyCode::Down, Modifiers::NONE), RadioEvent::Selected(1));
+        assert_eq!(group.key_down(&mut med, KeyCode::Up, Modifiers::NONE), RadioEvent::Selected(0));
+        assert_eq!(group.key_down(&mut med, KeyCode::Up, Modifiers::NONE), RadioEvent::Selected(2));
+
+        group.set_enabled(false);
+        assert_eq!(group.key_down(&mut med, KeyCode::Down, Modifiers::NONE), RadioEvent::None);
+    }
+}
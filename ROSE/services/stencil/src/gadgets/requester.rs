@@ -0,0 +1,653 @@
+//! Stock requesters.
+//!
+//! A [[Requester]] is a ready-made [[Dialog]] for the questions applications ask most often:
+//!
+//! - an *alert* tells the operator something, and waits for them to click OK;
+//! - a *confirmation* asks them to click OK to go ahead, or Cancel to back out;
+//! - a *prompt* asks them to type in some text;
+//! - a *file requester* lets them pick a file, or type in the name of a new one,
+//!   browsing from drawer to drawer (that is, from directory to directory) as they go.
+//!
+//! Show one with [[ModalDialog::open]].
+//!
+//! Whatever has the focus,
+//! Escape cancels the requester (or, for an alert, acknowledges it),
+//! and Return accepts it, unless the Cancel button or the file list has the focus;
+//! Tab and Shift-Tab move the focus between the requester's gadgets.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::chase::Forme;
+use crate::container::{ComponentId, Container};
+use crate::dialog::{Dialog, DialogEvent, DialogResult};
+use crate::events::{ButtonEvent, KeyCode, KeyboardEventSink, Modifiers, MouseEventSink, TimerEventSink};
+use crate::focus::{FocusId, FocusRing};
+use crate::gadgets::boolean::{PushButtonEvent, PushButtonView};
+use crate::gadgets::list::{ListEvent, ListView, SelectionMode};
+use crate::gadgets::text_field::{TextFieldEvent, TextFieldView};
+use crate::layout::Layout;
+use crate::mediator::Mediator;
+use crate::paragraph::PLAIN_PARAGRAPH;
+use crate::simple_bitmap_font::SimpleBitmapFont;
+use crate::simple_printer::SimplePrinter;
+use crate::stencil::Draw;
+use crate::theme::Theme;
+use crate::timers::TimerId;
+use crate::types::{Dimension, Dimensions, Point, Rect, Unit};
+use crate::view::View;
+use crate::wrapping_printer::WrappingPrinter;
+
+/// The width of a requester's paper.
+pub const WIDTH: Dimension = 240;
+
+/// The width of the OK and Cancel buttons.
+const BUTTON_WIDTH: Dimension = 64;
+
+/// How many files a file requester shows at once.
+const LIST_ROWS: Dimension = 8;
+
+/// The entry at the top of a file list which leads to the parent drawer.
+pub const PARENT: &str = "../";
+
+/// Focus identifiers for a requester's gadgets.
+/// They're well clear of any an application is likely to give its own gadgets,
+/// which keep their identifiers while the requester is open.
+const OK_FOCUS: FocusId = usize::MAX - 3;
+const CANCEL_FOCUS: FocusId = usize::MAX - 2;
+const TEXT_FOCUS: FocusId = usize::MAX - 1;
+const FILES_FOCUS: FocusId = usize::MAX;
+
+/// What a requester's gadgets reported.
+enum Part {
+    None,
+    Ok,
+    Cancel,
+    Text(TextFieldEvent),
+    Files(ListEvent),
+}
+
+fn ok_part(evt: PushButtonEvent) -> Part {
+    match evt {
+        PushButtonEvent::Clicked => Part::Ok,
+        PushButtonEvent::None => Part::None,
+    }
+}
+
+fn cancel_part(evt: PushButtonEvent) -> Part {
+    match evt {
+        PushButtonEvent::Clicked => Part::Cancel,
+        PushButtonEvent::None => Part::None,
+    }
+}
+
+/// Answers the entries of `drawer`, drawers first (marked with a trailing slash), then files,
+/// each in alphabetical order;
+/// led by an entry for the parent drawer, if there is one.
+/// A drawer which can't be read is shown empty.
+pub fn list_drawer(drawer: &Path) -> Vec<String> {
+    let mut drawers = Vec::new();
+    let mut files = Vec::new();
+    if let Ok(entries) = fs::read_dir(drawer) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if entry.path().is_dir() {
+                drawers.push(format!("{}/", name));
+            } else {
+                files.push(name);
+            }
+        }
+    }
+    drawers.sort();
+    files.sort();
+
+    let mut items = Vec::new();
+    if drawer.parent().is_some() {
+        items.push(String::from(PARENT));
+    }
+    items.extend(drawers);
+    items.extend(files);
+    items
+}
+
+/// A ready-made dialog: an alert, confirmation, prompt, or file requester.
+pub struct Requester {
+    /// The rectangle occupied by the requester's paper.
+    area: Rect,
+
+    /// What the requester says to the operator.
+    message: String,
+
+    /// How many lines the message wraps onto.
+    message_lines: Dimension,
+
+    /// The requester's gadgets.
+    gadgets: Container<Part>,
+
+    /// The OK button.
+    ok: ComponentId,
+
+    /// The Cancel button, unless this is an alert.
+    cancel: Option<ComponentId>,
+
+    /// The text field, for prompts and file requesters.
+    text: Option<ComponentId>,
+
+    /// The list of files in the drawer, for file requesters.
+    files: Option<ComponentId>,
+
+    /// The drawer a file requester is showing.
+    drawer: PathBuf,
+
+    /// The gadgets Tab moves the focus between.
+    ring: FocusRing,
+
+    /// The font everything is printed in.
+    font: &'static SimpleBitmapFont<'static>,
+
+    /// The space between the edge of the paper and everything on it.
+    margin: Dimension,
+
+    /// The space between one part of a requester and the next.
+    gap: Dimension,
+}
+
+impl Requester {
+    /// Creates an alert showing `message`, with an OK button.
+    ///
+    /// Answers [[DialogResult::Ok]] when dismissed.
+    pub fn alert(theme: &'static Theme, message: &str) -> Self {
+        Self::new(theme, message, false, None, None)
+    }
+
+    /// Creates a confirmation showing `message`, with OK and Cancel buttons.
+    ///
+    /// Answers [[DialogResult::Ok]] or [[DialogResult::Cancel]] when dismissed.
+    pub fn confirm(theme: &'static Theme, message: &str) -> Self {
+        Self::new(theme, message, true, None, None)
+    }
+
+    /// Creates a prompt showing `message`, asking for text, which starts out as `text`.
+    ///
+    /// Answers [[DialogResult::Text]] with whatever was typed, or [[DialogResult::Cancel]].
+    pub fn prompt(theme: &'static Theme, message: &str, text: &str) -> Self {
+        Self::new(theme, message, true, Some(text), None)
+    }
+
+    /// Creates a file requester showing `message`, browsing the files in `drawer`.
+    ///
+    /// Answers [[DialogResult::File]] with the path of the file picked or named, or [[DialogResult::Cancel]].
+    pub fn file(theme: &'static Theme, message: &str, drawer: impl Into<PathBuf>) -> Self {
+        Self::new(theme, message, true, Some(""), Some(drawer.into()))
+    }
+
+    /// Creates a requester laid out to suit `theme`:
+    /// everything is printed in its font, and spaced as it says.
+    fn new(theme: &'static Theme, message: &str, cancellable: bool, text: Option<&str>, drawer: Option<PathBuf>) -> Self {
+        let font = theme.font;
+        let (margin, gap) = (theme.spacing, theme.spacing * 3 / 4);
+        let mut forme = Forme::new((WIDTH - 2 * margin) as usize, font);
+        forme.layout_from_str(message);
+        let message_lines = forme.borrow_lines().len().max(1) as Dimension;
+
+        let nowhere = ((0, 0), (0, 0));
+        let mut gadgets = Container::new();
+        let mut members = Vec::new();
+
+        let files = drawer.as_ref().map(|drawer| {
+            let mut list = ListView::new(nowhere, list_drawer(drawer), SelectionMode::Single, font);
+            list.set_focus_id(FILES_FOCUS);
+            members.push(FILES_FOCUS);
+            gadgets.add(nowhere, list, Part::Files)
+        });
+        let text = text.map(|text| {
+            let mut field = TextFieldView::new(nowhere, font);
+            field.set_text(text);
+            field.set_focus_id(TEXT_FOCUS);
+            members.push(TEXT_FOCUS);
+            gadgets.add(nowhere, field, Part::Text)
+        });
+
+        let mut ok = PushButtonView::new(nowhere, "OK", font);
+        ok.set_focus_id(OK_FOCUS);
+        members.push(OK_FOCUS);
+        let ok = gadgets.add(nowhere, ok, ok_part);
+
+        let cancel = cancellable.then(|| {
+            let mut cancel = PushButtonView::new(nowhere, "Cancel", font);
+            cancel.set_focus_id(CANCEL_FOCUS);
+            members.push(CANCEL_FOCUS);
+            gadgets.add(nowhere, cancel, cancel_part)
+        });
+
+        let mut requester = Self {
+            area: nowhere,
+            message: String::from(message),
+            message_lines,
+            gadgets,
+            ok,
+            cancel,
+            text,
+            files,
+            drawer: drawer.unwrap_or_default(),
+            ring: FocusRing::new(members),
+            font,
+            margin,
+            gap,
+        };
+        let (width, height) = requester.size();
+        requester.set_area(((0, 0), (width, height)));
+        requester
+    }
+
+    /// Answers the drawer a file requester is showing.
+    pub fn drawer(&self) -> &Path {
+        &self.drawer
+    }
+
+    /// Answers the height of a line of text.
+    fn line_height(&self) -> Dimension {
+        PLAIN_PARAGRAPH.line_spacing.line_height(self.font.height)
+    }
+
+    /// Answers the height of the file list, showing [[LIST_ROWS]] rows and its frame.
+    fn list_height(&self) -> Dimension {
+        LIST_ROWS * (self.font.height + 2) + 2
+    }
+
+    /// Answers the size of the OK and Cancel buttons.
+    fn button_size(&self) -> Dimensions {
+        (BUTTON_WIDTH, self.font.baseline + self.font.height + 4)
+    }
+
+    /// Answers where the message goes.
+    fn message_area(&self) -> Rect {
+        let ((left, top), (right, _)) = self.area;
+        let top = top + self.margin;
+        ((left + self.margin, top), (right - self.margin, top + self.message_lines * self.line_height()))
+    }
+
+    /// Answers where a file requester shows the name of its drawer.
+    fn drawer_area(&self) -> Rect {
+        let ((left, _), (right, bottom)) = self.message_area();
+        ((left, bottom + self.gap), (right, bottom + self.gap + self.line_height()))
+    }
+
+    /// Answers where each gadget goes, and the bottom of the paper.
+    fn arrange(&self) -> (Vec<(ComponentId, Rect)>, Unit) {
+        let ((left, _), (right, _)) = self.area;
+        let (left, right) = (left + self.margin, right - self.margin);
+        let mut placed = Vec::new();
+
+        let (_, (_, mut y)) = self.message_area();
+        if let Some(files) = self.files {
+            let (_, (_, bottom)) = self.drawer_area();
+            y = bottom + self.gap;
+            placed.push((files, ((left, y), (right, y + self.list_height()))));
+            y += self.list_height();
+        }
+        if let Some(text) = self.text {
+            y += self.gap;
+            placed.push((text, ((left, y), (right, y + self.font.height + 5))));
+            y += self.font.height + 5;
+        }
+
+        let (width, height) = self.button_size();
+        y += 2 * self.gap;
+        placed.push((self.ok, ((left, y), (left + width, y + height))));
+        if let Some(cancel) = self.cancel {
+            placed.push((cancel, ((right - width, y), (right, y + height))));
+        }
+        (placed, y + height + self.margin)
+    }
+
+    /// Answers the size of the paper.
+    fn size(&self) -> Dimensions {
+        let ((_, top), _) = self.area;
+        let (_, bottom) = self.arrange();
+        (WIDTH, bottom - top)
+    }
+
+    /// Prints the name of the drawer, as much of it as fits.
+    fn draw_drawer(&self, med: &mut dyn Mediator) {
+        let ((left, top), (right, bottom)) = self.drawer_area();
+        let paper = &med.theme().paper;
+        let d = med.borrow_mut_desktop();
+        d.filled_rectangle((left, top), (right, bottom), paper);
+        let mut p = SimplePrinter::new(d, ((left, top), (right, bottom)), self.font);
+        for ch in self.drawer.to_string_lossy().chars() {
+            if p.head.0 + self.font.glyph_advance(ch) > right {
+                break;
+            }
+            p.print_char(ch);
+        }
+    }
+
+    fn list(&mut self) -> Option<&mut ListView<'static>> {
+        self.files.and_then(|id| self.gadgets.get_mut(id))
+    }
+
+    fn field(&mut self) -> Option<&mut TextFieldView<'static>> {
+        self.text.and_then(|id| self.gadgets.get_mut(id))
+    }
+
+    /// Shows the contents of `drawer` in the file list.
+    fn open_drawer(&mut self, med: &mut dyn Mediator, drawer: PathBuf) {
+        let items = list_drawer(&drawer);
+        self.drawer = drawer;
+        if let Some(list) = self.list() {
+            list.set_items(items);
+            list.draw(med);
+        }
+        self.draw_drawer(med);
+        med.repaint_all();
+    }
+
+    /// Accepts the requester, answering with what the operator asked for.
+    ///
+    /// A file requester naming a drawer opens it instead,
+    /// and naming nothing at all does nothing.
+    fn accept(&mut self, med: &mut dyn Mediator) -> DialogEvent {
+        let text = self.field().map(|field| field.get_text());
+        match (self.files, text) {
+            (Some(_), Some(name)) if name.is_empty() => DialogEvent::None,
+            (Some(_), Some(name)) => {
+                let path = self.drawer.join(name);
+                if path.is_dir() {
+                    if let Some(field) = self.field() {
+                        field.set_text("");
+                        field.draw(med);
+                    }
+                    self.open_drawer(med, path);
+                    DialogEvent::None
+                } else {
+                    DialogEvent::Dismissed(DialogResult::File(path))
+                }
+            }
+            (None, Some(text)) => DialogEvent::Dismissed(DialogResult::Text(text)),
+            _ => DialogEvent::Dismissed(DialogResult::Ok),
+        }
+    }
+
+    /// Acts on what the gadgets reported, answering with the first dismissal, if any.
+    fn handle(&mut self, med: &mut dyn Mediator, parts: Vec<Part>) -> DialogEvent {
+        for part in parts {
+            let evt = match part {
+                Part::Ok | Part::Text(TextFieldEvent::Committed) => self.accept(med),
+                Part::Cancel => DialogEvent::Dismissed(DialogResult::Cancel),
+                Part::Files(ListEvent::SelectionChanged) => {
+                    let picked = self.list()
+                        .and_then(|list| list.selection().first().map(|&n| list.items()[n].clone()))
+                        .filter(|name| !name.ends_with('/'));
+                    if let (Some(name), Some(field)) = (picked, self.field()) {
+                        field.set_text(&name);
+                        field.draw(med);
+                        med.repaint_all();
+                    }
+                    DialogEvent::None
+                }
+                Part::Files(ListEvent::Activated(n)) => {
+                    let name = self.list().map(|list| list.items()[n].clone()).unwrap_or_default();
+                    if name == PARENT {
+                        let parent = self.drawer.parent().map(Path::to_path_buf).unwrap_or_default();
+                        self.open_drawer(med, parent);
+                        DialogEvent::None
+                    } else if let Some(drawer) = name.strip_suffix('/') {
+                        let drawer = self.drawer.join(drawer);
+                        self.open_drawer(med, drawer);
+                        DialogEvent::None
+                    } else {
+                        DialogEvent::Dismissed(DialogResult::File(self.drawer.join(name)))
+                    }
+                }
+                _ => DialogEvent::None,
+            };
+            if evt != DialogEvent::None {
+                return evt;
+            }
+        }
+        DialogEvent::None
+    }
+
+    /// Redraws the gadgets if the focus has moved away from `was`.
+    fn follow_focus(&mut self, med: &mut dyn Mediator, was: Option<FocusId>) {
+        if med.focus() != was {
+            self.gadgets.draw(med);
+            med.repaint_all();
+        }
+    }
+}
+
+impl View for Requester {
+    /// Draws the message and gadgets, and for a file requester, the name of the drawer.
+    fn draw(&mut self, med: &mut dyn Mediator) {
+        let (upper_left, lower_right) = self.area;
+        let paper = &med.theme().paper;
+        let d = med.borrow_mut_desktop();
+        d.filled_rectangle(upper_left, lower_right, paper);
+        let mut printer = WrappingPrinter::new(d, self.message_area(), self.font);
+        printer.print(&self.message);
+
+        if self.files.is_some() {
+            self.draw_drawer(med);
+        }
+        self.gadgets.draw(med);
+    }
+
+    /// Answers a size [[WIDTH]] wide, and as tall as the message and gadgets need.
+    fn preferred_size(&self) -> Option<Dimensions> {
+        Some(self.size())
+    }
+
+    fn set_area(&mut self, area: Rect) {
+        let ((left, top), _) = area;
+        self.area = ((left, top), (left + WIDTH, top));
+        let (placed, bottom) = self.arrange();
+        self.area = ((left, top), (left + WIDTH, bottom));
+        for (id, area) in placed {
+            self.gadgets.reflow(&Layout::stretched(id), area);
+        }
+    }
+}
+
+impl Dialog for Requester {
+    fn get_area(&self) -> Rect {
+        self.area
+    }
+
+    /// Answers the text field, if there is one; otherwise, the OK button.
+    fn initial_focus(&self) -> Option<FocusId> {
+        Some(if self.text.is_some() { TEXT_FOCUS } else { OK_FOCUS })
+    }
+}
+
+impl MouseEventSink<DialogEvent> for Requester {
+    fn pointer_moved(&mut self, med: &mut dyn Mediator, to: Point) -> DialogEvent {
+        let parts = self.gadgets.pointer_moved(med, to);
+        self.handle(med, parts)
+    }
+
+    fn button_up(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> DialogEvent {
+        let parts = self.gadgets.button_up(med, event);
+        self.handle(med, parts)
+    }
+
+    /// Handles mouse button-down events.
+    ///
+    /// Pressing in the file list gives it the focus, as pressing in the text field does.
+    /// Answers [[DialogEvent::Dismissed]] if a button was clicked,
+    /// or a file was double-clicked in the file list.
+    fn button_down(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> DialogEvent {
+        let was = med.focus();
+        if self.files.is_some() && (self.gadgets.component_at(event.at) == self.files) {
+            med.set_focus(Some(FILES_FOCUS));
+        }
+        let parts = self.gadgets.button_down(med, event);
+        self.follow_focus(med, was);
+        self.handle(med, parts)
+    }
+
+    fn wheel(&mut self, med: &mut dyn Mediator, delta: Point, modifiers: Modifiers) -> DialogEvent {
+        let parts = self.gadgets.wheel(med, delta, modifiers);
+        self.handle(med, parts)
+    }
+
+    fn enter(&mut self, med: &mut dyn Mediator, at: Point) -> DialogEvent {
+        let parts = self.gadgets.enter(med, at);
+        self.handle(med, parts)
+    }
+
+    fn leave(&mut self, med: &mut dyn Mediator) -> DialogEvent {
+        let parts = self.gadgets.leave(med);
+        self.handle(med, parts)
+    }
+}
+
+impl KeyboardEventSink<DialogEvent> for Requester {
+    /// Handles key-down events.
+    ///
+    /// Tab and Shift-Tab move the focus, and Escape cancels.
+    /// Other keys go to whichever gadget has the focus;
+    /// Return accepts the requester if that gadget doesn't want it.
+    fn key_down(&mut self, med: &mut dyn Mediator, key: KeyCode, modifiers: Modifiers) -> DialogEvent {
+        match key {
+            KeyCode::Tab => {
+                let was = med.focus();
+                self.ring.advance(med, modifiers.contains(Modifiers::SHIFT));
+                self.follow_focus(med, was);
+                return DialogEvent::None;
+            }
+            KeyCode::Escape => {
+                let result = if self.cancel.is_some() { DialogResult::Cancel } else { DialogResult::Ok };
+                return DialogEvent::Dismissed(result);
+            }
+            _ => (),
+        }
+
+        let mut parts = Vec::new();
+        for (id, map) in [(Some(self.ok), ok_part as fn(PushButtonEvent) -> Part), (self.cancel, cancel_part)] {
+            if let Some(button) = id.and_then(|id| self.gadgets.get_mut::<PushButtonView<'static, 'static>>(id)) {
+                parts.push(map(button.key_down(med, key, modifiers)));
+            }
+        }
+        if let Some(field) = self.field() {
+            parts.push(Part::Text(field.key_down(med, key, modifiers)));
+        }
+        if let Some(list) = self.list() {
+            parts.push(Part::Files(list.key_down(med, key, modifiers)));
+        }
+
+        // Each of the requester's gadgets acts on Return itself while it has the focus.
+        let wanted = med.focus().is_some_and(|id| id >= OK_FOCUS);
+        if (key == KeyCode::Return) && !wanted {
+            parts.push(Part::Ok);
+        }
+        self.handle(med, parts)
+    }
+
+    fn key_up(&mut self, _: &mut dyn Mediator, _: KeyCode, _: Modifiers) -> DialogEvent {
+        DialogEvent::None
+    }
+
+    /// Types into the text field, if it has the focus.
+    fn text_input(&mut self, med: &mut dyn Mediator, text: &str) -> DialogEvent {
+        let parts = match self.field() {
+            Some(field) => vec![Part::Text(field.text_input(med, text))],
+            None => Vec::new(),
+        };
+        self.handle(med, parts)
+    }
+}
+
+impl TimerEventSink<DialogEvent> for Requester {
+    /// Repeats the file list's scroll bar arrows while they're held down.
+    fn timer_fired(&mut self, med: &mut dyn Mediator, id: TimerId) -> DialogEvent {
+        let parts = match self.list() {
+            Some(list) => vec![Part::Files(list.timer_fired(med, id))],
+            None => Vec::new(),
+        };
+        self.handle(med, parts)
+    }
+
+    fn idle(&mut self, _: &mut dyn Mediator) -> DialogEvent {
+        DialogEvent::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::Requester;
+    use crate::dialog::{Dialog, DialogEvent, DialogResult, ModalDialog};
+    use crate::events::{ButtonEvent, KeyCode, KeyboardEventSink, Modifiers, MouseEventSink};
+    use crate::theme::CLASSIC_THEME;
+    use crate::utils::testing::TestMediator;
+
+    fn key(dialog: &mut ModalDialog, med: &mut TestMediator, key: KeyCode, modifiers: Modifiers) -> DialogEvent {
+        dialog.key_down(med, key, modifiers)
+    }
+
+    #[test]
+    fn confirmations_cancel_from_the_keyboard_or_the_mouse() {
+        let mut med = TestMediator::new(320, 200);
+        let requester = Requester::confirm(&CLASSIC_THEME, "Save changes?");
+        let ((left, top), (right, bottom)) = requester.get_area();
+        assert_eq!(right - left, super::WIDTH);
+
+        let mut dialog = ModalDialog::open(&mut med, requester);
+        assert_eq!(key(&mut dialog, &mut med, KeyCode::Escape, Modifiers::NONE), DialogEvent::Dismissed(DialogResult::Cancel));
+
+        // Tab to the Cancel button, and Return clicks it rather than accepting.
+        let mut dialog = ModalDialog::open(&mut med, Requester::confirm(&CLASSIC_THEME, "Save changes?"));
+        assert_eq!(key(&mut dialog, &mut med, KeyCode::Tab, Modifiers::NONE), DialogEvent::None);
+        assert_eq!(key(&mut dialog, &mut med, KeyCode::Return, Modifiers::NONE), DialogEvent::Dismissed(DialogResult::Cancel));
+
+        // The requester is centred, with Cancel in its bottom right-hand corner.
+        let (width, height) = (right - left, bottom - top);
+        let (left, top) = ((320 - width) / 2, (200 - height) / 2);
+        let cancel = ButtonEvent::left((left + width - 12, top + height - 12));
+        let mut dialog = ModalDialog::open(&mut med, Requester::confirm(&CLASSIC_THEME, "Save changes?"));
+        assert_eq!(dialog.button_down(&mut med, cancel), DialogEvent::None);
+        assert_eq!(dialog.button_up(&mut med, cancel), DialogEvent::Dismissed(DialogResult::Cancel));
+    }
+
+    #[test]
+    fn prompts_answer_with_the_text_typed() {
+        let mut med = TestMediator::new(320, 200);
+        let mut dialog = ModalDialog::open(&mut med, Requester::prompt(&CLASSIC_THEME, "Your name?", "Bo"));
+        assert_eq!(dialog.text_input(&mut med, "b"), DialogEvent::None);
+        assert_eq!(
+            key(&mut dialog, &mut med, KeyCode::Return, Modifiers::NONE),
+            DialogEvent::Dismissed(DialogResult::Text(String::from("Bob"))),
+        );
+    }
+
+    #[test]
+    fn file_requesters_browse_drawers() {
+        let root = std::env::temp_dir().join(format!("rose-requester-{}", std::process::id()));
+        fs::create_dir_all(root.join("inner")).unwrap();
+        fs::write(root.join("inner").join("notes.txt"), "").unwrap();
+        fs::write(root.join("top.txt"), "").unwrap();
+
+        let mut med = TestMediator::new(320, 240);
+        let mut dialog = ModalDialog::open(&mut med, Requester::file(&CLASSIC_THEME, "Open which file?", &root));
+
+        // Typing a drawer's name opens it, and naming nothing does nothing.
+        assert_eq!(dialog.text_input(&mut med, "inner"), DialogEvent::None);
+        assert_eq!(key(&mut dialog, &mut med, KeyCode::Return, Modifiers::NONE), DialogEvent::None);
+        assert_eq!(key(&mut dialog, &mut med, KeyCode::Return, Modifiers::NONE), DialogEvent::None);
+
+        // In the list, "../" leads back out, and the cursor keys pick a file.
+        assert_eq!(key(&mut dialog, &mut med, KeyCode::Tab, Modifiers::SHIFT), DialogEvent::None);
+        assert_eq!(key(&mut dialog, &mut med, KeyCode::Home, Modifiers::NONE), DialogEvent::None);
+        assert_eq!(key(&mut dialog, &mut med, KeyCode::Return, Modifiers::NONE), DialogEvent::None);
+        assert_eq!(key(&mut dialog, &mut med, KeyCode::End, Modifiers::NONE), DialogEvent::None);
+        assert_eq!(
+            key(&mut dialog, &mut med, KeyCode::Return, Modifiers::NONE),
+            DialogEvent::Dismissed(DialogResult::File(root.join("top.txt"))),
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
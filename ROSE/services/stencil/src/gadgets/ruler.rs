@@ -0,0 +1,373 @@
+//! Support for rulers.
+//!
+//! A ruler sits above a column of text,
+//! showing (and letting the operator adjust)
+//! how its paragraphs are formatted.
+//! Its left and right ends line up with the column's margins.
+//!
+//! Markers for the indents hang below the rule:
+//! the first line indent above the left indent,
+//! and the right indent at the far end.
+//! Tab stops stand above the rule.
+//! Pressing above the rule where there's no tab stop adds one there;
+//! dragging a tab stop off the ruler removes it again.
+
+use crate::events::{ButtonEvent, Modifiers, MouseButton, MouseEventSink};
+use crate::mediator::Mediator;
+use crate::paragraph::{ParagraphFormat, TabAlignment, TabStop};
+use crate::stencil::Draw;
+use crate::types::{Dimension, Point, Rect, Unit};
+use crate::utils::LINE_BLACK;
+use crate::view::{View, rect_contains};
+
+/// The narrowest the indents may leave a line.
+pub const MIN_LINE_WIDTH: Dimension = 16;
+
+/// How far apart the ruler's tick marks are.
+const TICK_INTERVAL: Dimension = 8;
+
+/// Every so many tick marks, a taller one is drawn.
+const MAJOR_TICKS: Dimension = 4;
+
+/// The parts of a ruler the operator can grab onto.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Marker {
+    FirstLine,
+    Left,
+    Right,
+    Tab(usize),
+}
+
+/// Maintains the appearance of a ruler, and the paragraph format it edits.
+pub struct RulerView {
+    /// The rectangle occupied by the ruler.
+    area: Rect,
+
+    /// The format shown by the ruler.
+    format: ParagraphFormat,
+
+    /// How tab stops added by the operator align text.
+    tab_alignment: TabAlignment,
+
+    /// The marker the operator is dragging, if any.
+    grabbed: Option<Marker>,
+
+    /// Tracks the current mouse pointer position on the screen.
+    mouse_pt: Point,
+}
+
+impl RulerView {
+    /// Creates a new ruler occupying `area`,
+    /// showing a plain paragraph format.
+    ///
+    /// Tab stops added by the operator are left-aligned by default.
+    /// Use [[RulerView::set_tab_alignment]] to change this.
+    pub fn new(area: Rect) -> Self {
+        Self {
+            area,
+
+            format: ParagraphFormat::default(),
+            tab_alignment: TabAlignment::Left,
+            grabbed: None,
+            mouse_pt: (0, 0),
+        }
+    }
+
+    /// Sets the paragraph format shown by the ruler.
+    pub fn set_format(&mut self, format: ParagraphFormat) {
+        self.format = format;
+    }
+
+    /// Retrieves the paragraph format shown by the ruler.
+    pub fn get_format(&self) -> &ParagraphFormat {
+        &self.format
+    }
+
+    /// Retrieves the rectangle occupied by the ruler.
+    pub fn get_area(&self) -> Rect {
+        self.area
+    }
+
+    /// Sets how tab stops subsequently added by the operator align text.
+    pub fn set_tab_alignment(&mut self, alignment: TabAlignment) {
+        self.tab_alignment = alignment;
+    }
+
+    /// Answers the width of the column of text the ruler measures.
+    fn width(&self) -> Dimension {
+        self.area.1.0 - self.area.0.0
+    }
+
+    /// Answers the y coordinate of the rule itself.
+    fn rule_y(&self) -> Unit {
+        (self.area.0.1 + self.area.1.1) >> 1
+    }
+
+    /// Answers where the bottom half of the ruler divides
+    /// between the first line indent marker and the left indent marker.
+    fn indent_split(&self) -> Unit {
+        let rule_y = self.rule_y();
+        rule_y + 1 + ((self.area.1.1 - rule_y - 1) >> 1).max(1)
+    }
+
+    /// Answers the x coordinate of the `marker`.
+    fn marker_x(&self, marker: Marker) -> Unit {
+        let ((left, _), (right, _)) = self.area;
+        match marker {
+            Marker::FirstLine => left + self.format.indent(true),
+            Marker::Left => left + self.format.left_indent,
+            Marker::Right => right - 1 - self.format.right_indent,
+            Marker::Tab(i) => left + self.format.tab_stops[i].position,
+        }
+    }
+
+    /// Answers the rectangle within which the operator can grab onto the `marker`.
+    fn marker_area(&self, marker: Marker) -> Rect {
+        let x = self.marker_x(marker);
+        let ((_, top), (_, bottom)) = self.area;
+        let rule_y = self.rule_y();
+        let split = self.indent_split();
+        match marker {
+            Marker::FirstLine => ((x, rule_y + 1), (x + 5, split)),
+            Marker::Left => ((x, split), (x + 5, bottom)),
+            Marker::Right => ((x - 4, rule_y + 1), (x + 1, bottom)),
+            Marker::Tab(_) => ((x - 2, top), (x + 3, rule_y)),
+        }
+    }
+
+    /// Answers the marker under the mouse pointer, if any.
+    fn marker_at_pointer(&self) -> Option<Marker> {
+        (0..self.format.tab_stops.len())
+            .map(Marker::Tab)
+            .chain([Marker::FirstLine, Marker::Left, Marker::Right])
+            .find(|&m| rect_contains(self.marker_area(m), self.mouse_pt))
+    }
+
+    /// Answers the format that results from dragging the `marker` to `x`.
+    ///
+    /// Indents are kept from leaving lines narrower than [[MIN_LINE_WIDTH]].
+    fn dragged(&self, marker: Marker, x: Unit) -> ParagraphFormat {
+        let width = self.width();
+        let position = (x - self.area.0.0).clamp(0, width - 1);
+        let mut format = self.format.clone();
+        let widest_indent = width - format.right_indent - MIN_LINE_WIDTH;
+        match marker {
+            Marker::FirstLine => {
+                format.first_line_indent = position.min(widest_indent) - format.left_indent;
+            },
+            // The first line indent is relative to the left indent, so it comes along too.
+            Marker::Left => format.left_indent = position.min(widest_indent),
+            Marker::Right => {
+                let widest = format.indent(true).max(format.left_indent) + MIN_LINE_WIDTH;
+                format.right_indent = (width - 1 - position).min(width - widest).max(0);
+            },
+            Marker::Tab(i) => format.tab_stops[i].position = position,
+        }
+        format
+    }
+
+    /// Draws a tab stop `stop`.
+    fn draw_tab_stop(&self, d: &mut dyn Draw, stop: TabStop) {
+        let x = self.area.0.0 + stop.position;
+        let top = self.area.0.1;
+        d.vertical_line((x, top), self.rule_y(), LINE_BLACK);
+        match stop.alignment {
+            TabAlignment::Left => d.horizontal_line((x, top), x + 3, LINE_BLACK),
+            TabAlignment::Right => d.horizontal_line((x - 2, top), x + 1, LINE_BLACK),
+            TabAlignment::Centre => d.horizontal_line((x - 2, top), x + 3, LINE_BLACK),
+            TabAlignment::Decimal => {
+                d.horizontal_line((x - 2, top), x + 3, LINE_BLACK);
+                d.horizontal_line((x + 2, top + 2), x + 3, LINE_BLACK);
+            },
+        }
+    }
+}
+
+/// Events unique to the ruler.
+pub enum RulerEvent {
+    /// No event recognized.
+    None,
+
+    /// The user has performed an action which requests the paragraph format change.
+    /// Use [[RulerView::set_format]] to acknowledge the change.
+    FormatChanged(ParagraphFormat),
+}
+
+impl MouseEventSink<RulerEvent> for RulerView {
+    /// Handle mouse motion events.
+    ///
+    /// While the user drags a marker,
+    /// [[RulerEvent::FormatChanged]] events will be returned.
+    /// **NOTE:** This *will not* update the ruler's format.
+    /// You will still be responsible for calling [[RulerView::set_format]]
+    /// in response to this event.
+    ///
+    /// Otherwise, [[RulerEvent::None]] is returned.
+    fn pointer_moved(&mut self, _med: &mut dyn Mediator, to: Point) -> RulerEvent {
+        self.mouse_pt = to;
+        match self.grabbed {
+            Some(marker) => {
+                let format = self.dragged(marker, to.0);
+                if format != self.format {
+                    RulerEvent::FormatChanged(format)
+                } else {
+                    RulerEvent::None
+                }
+            },
+            None => RulerEvent::None,
+        }
+    }
+
+    /// Handles mouse button-up events.
+    ///
+    /// A tab stop let go of outside the ruler is removed,
+    /// with a [[RulerEvent::FormatChanged]] event.
+    /// Otherwise, answers with [[RulerEvent::None]].
+    fn button_up(&mut self, _med: &mut dyn Mediator, event: ButtonEvent) -> RulerEvent {
+        if event.button != MouseButton::Left {
+            return RulerEvent::None;
+        }
+        self.mouse_pt = event.at;
+        let grabbed = self.grabbed.take();
+        match grabbed {
+            Some(Marker::Tab(i)) if !rect_contains(self.area, self.mouse_pt) => {
+                let mut format = self.format.clone();
+                format.tab_stops.remove(i);
+                RulerEvent::FormatChanged(format)
+            },
+            _ => RulerEvent::None,
+        }
+    }
+
+    /// Handles mouse button-down events.
+    ///
+    /// Pressing above the rule, away from any other tab stop,
+    /// adds a tab stop there with a [[RulerEvent::FormatChanged]] event.
+    /// Otherwise, answers with [[RulerEvent::None]].
+    fn button_down(&mut self, _med: &mut dyn Mediator, event: ButtonEvent) -> RulerEvent {
+        if event.button != MouseButton::Left {
+            return RulerEvent::None;
+        }
+        self.mouse_pt = event.at;
+        self.grabbed = self.marker_at_pointer();
+        if self.grabbed.is_none() && rect_contains(self.area, self.mouse_pt) && (self.mouse_pt.1 < self.rule_y()) {
+            let mut format = self.format.clone();
+            format.set_tab_stop(TabStop::new(self.mouse_pt.0 - self.area.0.0, self.tab_alignment));
+            self.grabbed = Some(Marker::Tab(format.tab_stops.len() - 1));
+            return RulerEvent::FormatChanged(format);
+        }
+        RulerEvent::None
+    }
+
+    /// Handles mouse entry events.
+    ///
+    /// Currently, always answers with [[RulerEvent::None]].
+    fn enter(&mut self, _med: &mut dyn Mediator, at: Point) -> RulerEvent {
+        self.mouse_pt = at;
+        RulerEvent::None
+    }
+
+    /// Handles mouse exit events.
+    ///
+    /// Currently, always answers with [[RulerEvent::None]].
+    fn leave(&mut self, _med: &mut dyn Mediator) -> RulerEvent {
+        RulerEvent::None
+    }
+
+    /// Handles mouse wheel events.
+    ///
+    /// Currently, always answers with [[RulerEvent::None]].
+    fn wheel(&mut self, _med: &mut dyn Mediator, _delta: Point, _modifiers: Modifiers) -> RulerEvent {
+        RulerEvent::None
+    }
+}
+
+impl View for RulerView {
+    /// Draws the ruler on the desktop stencil associated with the mediator `med`.
+    fn draw(&mut self, med: &mut dyn Mediator) {
+        let theme = med.theme();
+        let d = med.borrow_mut_desktop();
+        let ((left, top), (right, bottom)) = self.area;
+        let rule_y = self.rule_y();
+        let split = self.indent_split();
+
+        d.filled_rectangle((left, top), (right, bottom), &theme.paper);
+        d.horizontal_line((left, rule_y), right, LINE_BLACK);
+        for tick in 0..=(self.width() / TICK_INTERVAL) {
+            let height = if tick % MAJOR_TICKS == 0 { 2 } else { 1 };
+            d.vertical_line((left + tick * TICK_INTERVAL, rule_y - height), rule_y, LINE_BLACK);
+        }
+
+        let x = self.marker_x(Marker::FirstLine);
+        d.vertical_line((x, rule_y + 1), split, LINE_BLACK);
+        d.horizontal_line((x, rule_y + 1), x + 5, LINE_BLACK);
+
+        let x = self.marker_x(Marker::Left);
+        d.vertical_line((x, split), bottom, LINE_BLACK);
+        d.horizontal_line((x, bottom - 1), x + 5, LINE_BLACK);
+
+        let x = self.marker_x(Marker::Right);
+        d.vertical_line((x, rule_y + 1), bottom, LINE_BLACK);
+        d.horizontal_line((x - 4, bottom - 1), x + 1, LINE_BLACK);
+
+        for &stop in &self.format.tab_stops {
+            self.draw_tab_stop(d, stop);
+        }
+    }
+
+    fn set_area(&mut self, area: Rect) {
+        self.area = area;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RulerEvent, RulerView};
+    use crate::events::{ButtonEvent, MouseEventSink};
+    use crate::paragraph::{ParagraphFormat, TabAlignment, TabStop};
+    use crate::utils::testing::TestMediator;
+
+    /// Drags the mouse from `from` to `to` over the `ruler`,
+    /// acknowledging every format change along the way.
+    fn drag(ruler: &mut RulerView, from: (isize, isize), to: (isize, isize)) -> &ParagraphFormat {
+        let mut med = TestMediator::new(128, 32);
+        let acknowledge = |ruler: &mut RulerView, evt| {
+            if let RulerEvent::FormatChanged(format) = evt {
+                ruler.set_format(format);
+            }
+        };
+        let evt = ruler.pointer_moved(&mut med, from);
+        acknowledge(ruler, evt);
+        let evt = ruler.button_down(&mut med, ButtonEvent::left(from));
+        acknowledge(ruler, evt);
+        let evt = ruler.pointer_moved(&mut med, to);
+        acknowledge(ruler, evt);
+        let evt = ruler.button_up(&mut med, ButtonEvent::left(to));
+        acknowledge(ruler, evt);
+        ruler.get_format()
+    }
+
+    #[test]
+    fn dragging_indent_markers() {
+        // The rule lies at y=20; the first line marker just below it, the left marker under that.
+        let mut ruler = RulerView::new(((10, 16), (110, 24)));
+        assert_eq!(drag(&mut ruler, (11, 22), (30, 22)).left_indent, 20);
+        assert_eq!(ruler.get_format().indent(true), 20);
+
+        let format = drag(&mut ruler, (31, 21), (15, 21));
+        assert_eq!((format.left_indent, format.first_line_indent), (20, -15));
+
+        // Lines can't be squeezed narrower than the minimum.
+        let format = drag(&mut ruler, (108, 22), (20, 22));
+        assert_eq!(format.right_indent, 100 - 20 - 16);
+    }
+
+    #[test]
+    fn adding_moving_and_removing_tab_stops() {
+        let mut ruler = RulerView::new(((10, 16), (110, 24)));
+        ruler.set_tab_alignment(TabAlignment::Right);
+        assert_eq!(drag(&mut ruler, (50, 17), (50, 17)).tab_stops, vec![TabStop::new(40, TabAlignment::Right)]);
+        assert_eq!(drag(&mut ruler, (50, 17), (70, 17)).tab_stops, vec![TabStop::new(60, TabAlignment::Right)]);
+        assert_eq!(*drag(&mut ruler, (70, 17), (70, 40)), ParagraphFormat::default());
+    }
+}
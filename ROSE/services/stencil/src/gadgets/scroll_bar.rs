@@ -0,0 +1,489 @@
+//! Support for scroll bars.
+//!
+//! A scroll bar is a proportional gadget
+//! with a pair of arrow buttons at its bottom or right-hand end,
+//! which together let the operator move a viewport over a document.
+//! Dragging the knob scrolls to wherever it's dropped;
+//! clicking the track either side of the knob scrolls a page at a time;
+//! and pressing an arrow scrolls a line at a time,
+//! repeating for as long as the arrow is held down.
+//!
+//! Like the proportional gadget,
+//! the scroll bar only *asks* for the document to scroll.
+//! The application scrolls it,
+//! and acknowledges the new position with [[ScrollBarView::set_values]].
+
+use crate::events::{ButtonEvent, Modifiers, MouseButton, MouseEventSink, TimerEventSink};
+use crate::gadgets::proportional::{PropAxis, PropGadgetEvent, PropGadgetView};
+use crate::mediator::Mediator;
+use crate::stencil::Draw;
+use crate::theme::Theme;
+use crate::timers::TimerId;
+use crate::types::{Dimension, Dimensions, Point, Rect};
+use crate::utils::{Glyph, draw_glyph};
+use crate::view::{View, rect_contains};
+
+/// How wide a vertical scroll bar is, or how tall a horizontal one, in pixels.
+pub const THICKNESS: Dimension = 16;
+
+/// How long each arrow button is, along the scroll bar, in pixels.
+pub const ARROW_LENGTH: Dimension = 16;
+
+/// How long, in milliseconds, an arrow must be held down before it starts repeating.
+pub const REPEAT_DELAY: u32 = 400;
+
+/// How often, in milliseconds, a held arrow repeats.
+pub const REPEAT_INTERVAL: u32 = 60;
+
+/// How many lines one notch of the mouse wheel scrolls.
+pub const WHEEL_LINES: isize = 3;
+
+/// Which way a scroll bar runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Orientation {
+    /// Scrolls left and right, with its arrows at the right-hand end.
+    Horizontal,
+
+    /// Scrolls up and down, with its arrows at the bottom.
+    Vertical,
+}
+
+/// The arrow glyphs, eight pixels square; set bits are inked.
+static ARROW_UP: Glyph = [0x18, 0x18, 0x3C, 0x3C, 0x7E, 0x18, 0x18, 0x18];
+static ARROW_DOWN: Glyph = [0x18, 0x18, 0x18, 0x7E, 0x3C, 0x3C, 0x18, 0x18];
+static ARROW_LEFT: Glyph = [0x00, 0x10, 0x30, 0x7F, 0xFF, 0x30, 0x10, 0x00];
+static ARROW_RIGHT: Glyph = [0x00, 0x08, 0x0C, 0xFE, 0xFF, 0x0C, 0x08, 0x00];
+
+/// Identifies one of the arrow buttons.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Arrow {
+    /// Scrolls towards the top, or left, of the document.
+    Back,
+
+    /// Scrolls towards the bottom, or right, of the document.
+    Forward,
+}
+
+/// Maintains the appearance of a scroll bar.
+pub struct ScrollBarView {
+    /// The rectangle occupied by the whole scroll bar, arrows included.
+    area: Rect,
+
+    /// Which way the scroll bar runs.
+    orientation: Orientation,
+
+    /// The track and knob.
+    prop: PropGadgetView,
+
+    /// Where the viewport lies in the document, along the scroll bar.
+    values: PropAxis,
+
+    /// The arrow the operator is holding down, if any.
+    pressed: Option<Arrow>,
+
+    /// True while the pressed arrow is drawn inverted,
+    /// which it is only while the pointer is over it.
+    inverted: bool,
+
+    /// The timer repeating the pressed arrow, if any,
+    /// and whether it's the periodic timer rather than the initial delay.
+    repeat: Option<(TimerId, bool)>,
+
+    /// Tracks the current mouse position.
+    mouse_pt: Point,
+}
+
+impl ScrollBarView {
+    /// Creates a new scroll bar occupying `area`, running the given way.
+    ///
+    /// By default, the whole document is visible,
+    /// so the knob fills the track.
+    pub fn new(area: Rect, orientation: Orientation) -> Self {
+        let mut sb = Self {
+            area,
+            orientation,
+            prop: PropGadgetView::new(area),
+            values: PropAxis::default(),
+            pressed: None,
+            inverted: false,
+            repeat: None,
+            mouse_pt: (0, 0),
+        };
+        sb.set_area(area);
+        sb
+    }
+
+    /// Answers which way the scroll bar runs.
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    /// Retrieves the rectangle occupied by the whole scroll bar.
+    pub fn get_area(&self) -> Rect {
+        self.area
+    }
+
+    /// Describes where the viewport lies in the document,
+    /// placing the knob to match.
+    ///
+    /// Call this to acknowledge every scrolling event,
+    /// and whenever the document changes.
+    pub fn set_values(&mut self, values: PropAxis) {
+        self.values = values;
+        match self.orientation {
+            Orientation::Horizontal => self.prop.set_values(values, PropAxis::default()),
+            Orientation::Vertical => self.prop.set_values(PropAxis::default(), values),
+        }
+    }
+
+    /// Retrieves where the viewport lies in the document.
+    pub fn get_values(&self) -> PropAxis {
+        self.values
+    }
+
+    /// Answers the rectangles framing the track, the back arrow, and the forward arrow,
+    /// in that order.
+    /// Neighbouring frames share their edges.
+    fn parts(&self) -> (Rect, Rect, Rect) {
+        let ((left, top), (right, bottom)) = self.area;
+        match self.orientation {
+            Orientation::Vertical => {
+                let back_top = bottom - 2 * ARROW_LENGTH;
+                let forward_top = bottom - ARROW_LENGTH;
+                (((left, top), (right, back_top + 1)),
+                 ((left, back_top), (right, forward_top + 1)),
+                 ((left, forward_top), (right, bottom)))
+            }
+            Orientation::Horizontal => {
+                let back_left = right - 2 * ARROW_LENGTH;
+                let forward_left = right - ARROW_LENGTH;
+                (((left, top), (back_left + 1, bottom)),
+                 ((back_left, top), (forward_left + 1, bottom)),
+                 ((forward_left, top), (right, bottom)))
+            }
+        }
+    }
+
+    /// Answers the frame around the given arrow.
+    fn arrow_area(&self, arrow: Arrow) -> Rect {
+        let (_, back, forward) = self.parts();
+        match arrow {
+            Arrow::Back => back,
+            Arrow::Forward => forward,
+        }
+    }
+
+    /// Answers the arrow under `pt`, if any.
+    fn arrow_at(&self, pt: Point) -> Option<Arrow> {
+        [Arrow::Back, Arrow::Forward].into_iter().find(|&a| rect_contains(self.arrow_area(a), pt))
+    }
+
+    /// Answers `true` if the pointer is over the pressed arrow.
+    fn is_hot(&self) -> bool {
+        self.pressed.is_some() && (self.arrow_at(self.mouse_pt) == self.pressed)
+    }
+
+    /// Inverts the pressed arrow, if need be, so that it looks `pressed` or not.
+    fn show_pressed(&mut self, med: &mut dyn Mediator, pressed: bool) {
+        if let (Some(arrow), true) = (self.pressed, pressed != self.inverted) {
+            let ((left, top), (right, bottom)) = self.arrow_area(arrow);
+            med.borrow_mut_desktop().invert_rectangle((left + 1, top + 1), (right - 1, bottom - 1));
+            med.repaint_all();
+            self.inverted = pressed;
+        }
+    }
+
+    /// Answers the event asking to scroll by `lines`,
+    /// or [[ScrollBarEvent::None]] if the document can't scroll any further that way.
+    fn scroll_lines(&self, lines: isize) -> ScrollBarEvent {
+        let stuck = ((lines < 0) && (self.values.top == 0))
+            || ((lines > 0) && (self.values.top >= self.values.max_top()))
+            || (lines == 0);
+        if stuck {
+            ScrollBarEvent::None
+        } else {
+            ScrollBarEvent::ScrollLines(lines)
+        }
+    }
+
+    /// Answers the event asking to scroll by one line towards `arrow`.
+    fn step(&self, arrow: Arrow) -> ScrollBarEvent {
+        match arrow {
+            Arrow::Back => self.scroll_lines(-1),
+            Arrow::Forward => self.scroll_lines(1),
+        }
+    }
+
+    /// Picks the values along the scroll bar out of the proportional gadget's.
+    fn along(&self, h: PropAxis, v: PropAxis) -> PropAxis {
+        match self.orientation {
+            Orientation::Horizontal => h,
+            Orientation::Vertical => v,
+        }
+    }
+
+    /// Stops the pressed arrow repeating.
+    fn release(&mut self, med: &mut dyn Mediator) {
+        if let Some((id, _)) = self.repeat.take() {
+            med.cancel_timer(id);
+        }
+        self.show_pressed(med, false);
+        self.pressed = None;
+    }
+}
+
+/// Scrolling events a scroll bar may answer with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScrollBarEvent {
+    /// Nothing to scroll.
+    None,
+
+    /// The operator asked to scroll by so many lines;
+    /// negative towards the top, or left, of the document.
+    ScrollLines(isize),
+
+    /// The operator asked to scroll by so many pages;
+    /// negative towards the top, or left, of the document.
+    ScrollPages(isize),
+
+    /// The operator dragged the knob, asking to scroll so the viewport starts at the given position.
+    ScrollTo(usize),
+}
+
+impl MouseEventSink<ScrollBarEvent> for ScrollBarView {
+    /// Handles pointer motion events.
+    ///
+    /// While the knob is being dragged,
+    /// answers with [[ScrollBarEvent::ScrollTo]] whenever it moves far enough to matter.
+    /// Otherwise, answers with [[ScrollBarEvent::None]].
+    fn pointer_moved(&mut self, med: &mut dyn Mediator, to: Point) -> ScrollBarEvent {
+        self.mouse_pt = to;
+        if self.pressed.is_some() {
+            self.show_pressed(med, self.is_hot());
+            return ScrollBarEvent::None;
+        }
+        match self.prop.pointer_moved(med, to) {
+            PropGadgetEvent::ValuesChanged(h, v) => ScrollBarEvent::ScrollTo(self.along(h, v).top),
+            _ => ScrollBarEvent::None,
+        }
+    }
+
+    /// Handles mouse button-up events, letting go of the knob or the arrow.
+    ///
+    /// Currently, always answers with [[ScrollBarEvent::None]].
+    fn button_up(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> ScrollBarEvent {
+        if event.button != MouseButton::Left {
+            return ScrollBarEvent::None;
+        }
+        if self.pressed.is_some() {
+            self.release(med);
+        }
+        self.mouse_pt = event.at;
+        let _ = self.prop.button_up(med, event);
+        ScrollBarEvent::None
+    }
+
+    /// Handles mouse button-down events.
+    ///
+    /// Pressing an arrow answers with [[ScrollBarEvent::ScrollLines]] straight away,
+    /// and again every so often while it's held; see [[TimerEventSink::timer_fired]].
+    /// Pressing the track either side of the knob answers with [[ScrollBarEvent::ScrollPages]].
+    /// Otherwise, answers with [[ScrollBarEvent::None]].
+    fn button_down(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> ScrollBarEvent {
+        self.mouse_pt = event.at;
+        if event.button != MouseButton::Left {
+            return ScrollBarEvent::None;
+        }
+        if let Some(arrow) = self.arrow_at(event.at) {
+            self.pressed = Some(arrow);
+            self.show_pressed(med, true);
+            self.repeat = Some((med.start_timer(REPEAT_DELAY, false), false));
+            return self.step(arrow);
+        }
+        match self.prop.button_down(med, event) {
+            PropGadgetEvent::ValuesChanged(h, v) => {
+                let top = self.along(h, v).top;
+                if top < self.values.top {
+                    ScrollBarEvent::ScrollPages(-1)
+                } else {
+                    ScrollBarEvent::ScrollPages(1)
+                }
+            }
+            _ => ScrollBarEvent::None,
+        }
+    }
+
+    /// Handles the mouse wheel, while the pointer is over the scroll bar.
+    ///
+    /// Each notch answers with [[ScrollBarEvent::ScrollLines]] of [[WHEEL_LINES]] lines.
+    /// A horizontal scroll bar follows a sideways wheel if there is one,
+    /// or an ordinary wheel if not.
+    fn wheel(&mut self, _med: &mut dyn Mediator, delta: Point, _modifiers: Modifiers) -> ScrollBarEvent {
+        if !rect_contains(self.area, self.mouse_pt) {
+            return ScrollBarEvent::None;
+        }
+        let (dx, dy) = delta;
+        let notches = match self.orientation {
+            Orientation::Horizontal if dx != 0 => dx,
+            _ => -dy,
+        };
+        self.scroll_lines(notches * WHEEL_LINES)
+    }
+
+    /// Handles the pointer entering the scroll bar, as though it had moved.
+    fn enter(&mut self, med: &mut dyn Mediator, at: Point) -> ScrollBarEvent {
+        self.pointer_moved(med, at)
+    }
+
+    /// Handles the pointer leaving the scroll bar.
+    ///
+    /// Currently, always answers with [[ScrollBarEvent::None]].
+    fn leave(&mut self, med: &mut dyn Mediator) -> ScrollBarEvent {
+        let _ = self.prop.leave(med);
+        ScrollBarEvent::None
+    }
+}
+
+impl TimerEventSink<ScrollBarEvent> for ScrollBarView {
+    /// Repeats the pressed arrow.
+    ///
+    /// Answers with [[ScrollBarEvent::ScrollLines]] if `id` is the arrow's repeat timer
+    /// and the pointer is still over the arrow.
+    /// Otherwise, answers with [[ScrollBarEvent::None]].
+    fn timer_fired(&mut self, med: &mut dyn Mediator, id: TimerId) -> ScrollBarEvent {
+        let (Some(arrow), Some((repeat, periodic))) = (self.pressed, self.repeat) else {
+            return ScrollBarEvent::None;
+        };
+        if id != repeat {
+            return ScrollBarEvent::None;
+        }
+        if !periodic {
+            self.repeat = Some((med.start_timer(REPEAT_INTERVAL, true), true));
+        }
+        if self.is_hot() {
+            self.step(arrow)
+        } else {
+            ScrollBarEvent::None
+        }
+    }
+
+    /// Currently, always answers with [[ScrollBarEvent::None]].
+    fn idle(&mut self, _med: &mut dyn Mediator) -> ScrollBarEvent {
+        ScrollBarEvent::None
+    }
+}
+
+/// Draws an arrow button framed by `area` in the given `theme`, with `glyph` centred inside.
+fn draw_arrow(d: &mut dyn Draw, theme: &Theme, area: Rect, glyph: &Glyph) {
+    let ((left, top), (right, bottom)) = area;
+    d.filled_rectangle((left, top), (right, bottom), &theme.paper);
+    theme.frame(d, (left, top), (right, bottom));
+
+    draw_glyph(d, ((left + right - 8) >> 1, (top + bottom - 8) >> 1), glyph);
+}
+
+impl View for ScrollBarView {
+    fn draw(&mut self, med: &mut dyn Mediator) {
+        self.prop.draw(med);
+
+        let (up, down) = match self.orientation {
+            Orientation::Vertical => (&ARROW_UP, &ARROW_DOWN),
+            Orientation::Horizontal => (&ARROW_LEFT, &ARROW_RIGHT),
+        };
+        let (_, back, forward) = self.parts();
+        let theme = med.theme();
+        let d = med.borrow_mut_desktop();
+        draw_arrow(d, theme, back, up);
+        draw_arrow(d, theme, forward, down);
+
+        if self.inverted {
+            self.inverted = false;
+            self.show_pressed(med, true);
+        }
+    }
+
+    /// Answers [[THICKNESS]] across the scroll bar, and its current length along it.
+    fn preferred_size(&self) -> Option<Dimensions> {
+        let ((left, top), (right, bottom)) = self.area;
+        match self.orientation {
+            Orientation::Horizontal => Some((right - left, THICKNESS)),
+            Orientation::Vertical => Some((THICKNESS, bottom - top)),
+        }
+    }
+
+    /// Moves the scroll bar to `area`, placing the knob afresh.
+    fn set_area(&mut self, area: Rect) {
+        self.area = area;
+        let (((left, top), (right, bottom)), _, _) = self.parts();
+        self.prop.set_area(((left + 2, top + 2), (right - 2, bottom - 2)));
+        self.set_values(self.values);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{Orientation, ScrollBarEvent, ScrollBarView, ARROW_LENGTH, REPEAT_DELAY, REPEAT_INTERVAL};
+    use crate::events::{ButtonEvent, Modifiers, MouseEventSink, TimerEventSink};
+    use crate::gadgets::proportional::PropAxis;
+    use crate::utils::testing::TestMediator;
+
+    /// A vertical scroll bar 100 pixels long, over a 1000-line document showing 100 lines.
+    fn scroll_bar() -> ScrollBarView {
+        let mut sb = ScrollBarView::new(((0, 0), (16, 100)), Orientation::Vertical);
+        sb.set_values(PropAxis::new(1000, 100, 0));
+        sb
+    }
+
+    #[test]
+    fn held_arrow_repeats_until_released() {
+        let mut med = TestMediator::new(64, 128);
+        let mut sb = scroll_bar();
+        let down_arrow = (8, 100 - ARROW_LENGTH / 2);
+
+        // The up arrow does nothing at the top of the document.
+        assert_eq!(sb.button_down(&mut med, ButtonEvent::left((8, 100 - 3 * ARROW_LENGTH / 2))), ScrollBarEvent::None);
+        let _ = sb.button_up(&mut med, ButtonEvent::left((8, 100 - 3 * ARROW_LENGTH / 2)));
+
+        assert_eq!(sb.button_down(&mut med, ButtonEvent::left(down_arrow)), ScrollBarEvent::ScrollLines(1));
+        let delay = med.timers.expire(u64::from(REPEAT_DELAY));
+        assert_eq!(delay.len(), 1);
+        assert_eq!(sb.timer_fired(&mut med, delay[0]), ScrollBarEvent::ScrollLines(1));
+
+        // After the delay, the arrow repeats periodically.
+        let repeat = med.timers.expire(u64::from(REPEAT_DELAY + REPEAT_INTERVAL));
+        assert_eq!(repeat.len(), 1);
+        assert_eq!(sb.timer_fired(&mut med, repeat[0]), ScrollBarEvent::ScrollLines(1));
+
+        // Sliding off the arrow pauses it.
+        let _ = sb.pointer_moved(&mut med, (8, 10));
+        assert_eq!(sb.timer_fired(&mut med, repeat[0]), ScrollBarEvent::None);
+
+        let _ = sb.button_up(&mut med, ButtonEvent::left((8, 10)));
+        assert!(med.timers.next_deadline().is_none());
+        assert_eq!(sb.timer_fired(&mut med, repeat[0]), ScrollBarEvent::None);
+    }
+
+    #[test]
+    fn track_pages_and_knob_scrolls_to() {
+        let mut med = TestMediator::new(64, 128);
+        let mut sb = scroll_bar();
+
+        // Below the knob pages down.
+        assert_eq!(sb.button_down(&mut med, ButtonEvent::left((8, 50))), ScrollBarEvent::ScrollPages(1));
+        let _ = sb.button_up(&mut med, ButtonEvent::left((8, 50)));
+
+        // Dragging the knob to the end of its travel scrolls to the end of the document.
+        let _ = sb.button_down(&mut med, ButtonEvent::left((8, 3)));
+        assert_eq!(sb.pointer_moved(&mut med, (8, 90)), ScrollBarEvent::ScrollTo(900));
+    }
+
+    #[test]
+    fn wheel_scrolls_lines() {
+        let mut med = TestMediator::new(64, 128);
+        let mut sb = scroll_bar();
+        let _ = sb.pointer_moved(&mut med, (8, 50));
+        assert_eq!(sb.wheel(&mut med, (0, -1), Modifiers::NONE), ScrollBarEvent::ScrollLines(3));
+        assert_eq!(sb.wheel(&mut med, (0, 1), Modifiers::NONE), ScrollBarEvent::None);
+    }
+}
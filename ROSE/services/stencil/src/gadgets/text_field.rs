@@ -0,0 +1,867 @@
+//! Support for text entry gadgets.
+//!
+//! A text field holds a single line of text the operator can edit.
+//! Clicking in it places the cursor;
+//! dragging selects text,
+//! as do double clicks (a word) and triple clicks (everything).
+//! Text too long to fit scrolls sideways
+//! to keep the cursor in view.
+//!
+//! While it has the keyboard focus,
+//! keys and typed text edit it;
+//! see [[KeyboardEventSink]] for which keys do what.
+//! Editing can also be driven directly through [[TextFieldView::perform]],
+//! which takes the [[TextFieldCommand]] a keystroke stands for.
+//!
+//! Text moves in and out through the mediator's clipboard,
+//! or by dragging the selection out of the field;
+//! text dropped on a field is handed to [[TextFieldView::accept_drop]].
+
+use bitblt::BlitOp;
+
+use crate::events::{ButtonEvent, KeyCode, KeyboardEventSink, Modifiers, MouseButton, MouseEventSink};
+use crate::focus::FocusId;
+use crate::gap_buffer::GapBuffer;
+use crate::mediator::Mediator;
+use crate::pointer::{ARROW, I_BEAM};
+use crate::simple_bitmap_font::SimpleBitmapFont;
+use crate::simple_printer::paste_glyph;
+use crate::stencil::Draw;
+use crate::text_style::TextStyle;
+use crate::transfer::{DragSession, TransferData};
+use crate::types::{Dimension, Dimensions, Point, Rect, Unit};
+use crate::utils::LINE_BLACK;
+use crate::view::{View, rect_contains};
+
+/// The space between the field's frame and its text, on either side.
+const PADDING: Dimension = 2;
+
+/// How far the pointer must move, holding the selection, before it's dragged out of the field.
+const DRAG_THRESHOLD: Dimension = 3;
+
+/// The things an operator can ask a text field to do,
+/// usually by pressing a key.
+///
+/// Commands which move the cursor extend the selection if their flag is set,
+/// as they would with the shift key held down.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextFieldCommand {
+    /// Types a character, replacing any selection.
+    Insert(char),
+
+    /// Moves the cursor one character to the left.
+    Left(bool),
+
+    /// Moves the cursor one character to the right.
+    Right(bool),
+
+    /// Moves the cursor to the start of the text.
+    Home(bool),
+
+    /// Moves the cursor to the end of the text.
+    End(bool),
+
+    /// Deletes the selection, or the character before the cursor.
+    DeleteBackward,
+
+    /// Deletes the selection, or the character after the cursor.
+    DeleteForward,
+
+    /// Selects all of the text.
+    SelectAll,
+
+    /// Undoes the most recent edit.
+    Undo,
+
+    /// Redoes the most recently undone edit.
+    Redo,
+
+    /// Puts the selected text on the clipboard, and deletes it.
+    Cut,
+
+    /// Puts the selected text on the clipboard.
+    Copy,
+
+    /// Types whatever text is on the clipboard, replacing any selection.
+    Paste,
+
+    /// Accepts the text, as the return key would.
+    Commit,
+
+    /// Abandons changes made since the text was last committed, as the escape key would.
+    Cancel,
+}
+
+/// Maintains the appearance of a single-line text entry gadget, and the text it holds.
+///
+/// The lifetime `'f` corresponds to the lifetime of the font.
+pub struct TextFieldView<'f> {
+    /// The rectangle occupied by the field, including its frame.
+    area: Rect,
+
+    /// Font to print the text in.
+    font: &'f SimpleBitmapFont<'f>,
+
+    /// The text being edited, along with its cursor and selection.
+    buffer: GapBuffer,
+
+    /// The text as of the last commit, restored on cancel.
+    committed: String,
+
+    /// The most characters the field will hold, if limited.
+    max_length: Option<usize>,
+
+    /// How far the text is scrolled to the left, in pixels.
+    scroll: Dimension,
+
+    /// True while the user drags out a selection.
+    selecting: bool,
+
+    /// Where the user pressed the mouse button on the selection,
+    /// until they either drag it away or let go.
+    drag_from: Option<Point>,
+
+    /// Tracks the current mouse pointer position on the screen.
+    mouse_pt: Point,
+
+    /// Identifies the field to the mediator's focus owner, if it takes part in focus at all.
+    focus_id: Option<FocusId>,
+}
+
+impl<'f> TextFieldView<'f> {
+    /// Creates a new, empty text field occupying `area`,
+    /// which prints its text in `font`.
+    ///
+    /// By default, the field will hold any amount of text.
+    /// Use [[TextFieldView::set_max_length]] to limit it.
+    pub fn new(area: Rect, font: &'f SimpleBitmapFont<'f>) -> Self {
+        Self {
+            area,
+            font,
+
+            buffer: GapBuffer::new(),
+            committed: String::new(),
+            max_length: None,
+            scroll: 0,
+            selecting: false,
+            drag_from: None,
+            mouse_pt: (0, 0),
+            focus_id: None,
+        }
+    }
+
+    /// Identifies the field as `id` to the mediator's focus owner.
+    ///
+    /// A field without an identifier behaves as though it always has the focus.
+    /// Once it has one,
+    /// it only shows its cursor and takes keyboard input while it's the focus owner,
+    /// and clicking in it makes it the focus owner.
+    pub fn set_focus_id(&mut self, id: FocusId) {
+        self.focus_id = Some(id);
+    }
+
+    /// Answers `true` if the field has the keyboard focus.
+    fn has_focus(&self, med: &dyn Mediator) -> bool {
+        self.focus_id.is_none_or(|id| med.focus() == Some(id))
+    }
+
+    /// Replaces the field's text with `text`, with the cursor at its end.
+    /// This also becomes the text that cancelling restores.
+    ///
+    /// Text beyond the maximum length is dropped.
+    pub fn set_text(&mut self, text: &str) {
+        let text: String = match self.max_length {
+            Some(max) => text.chars().take(max).collect(),
+            None => text.to_string(),
+        };
+        self.buffer = GapBuffer::with_text(&text);
+        self.buffer.set_cursor(self.buffer.len(), false);
+        self.committed = text;
+        self.scroll = 0;
+        self.scroll_to_cursor();
+    }
+
+    /// Retrieves the field's text.
+    pub fn get_text(&self) -> String {
+        self.buffer.chars().collect()
+    }
+
+    /// Limits how many characters the field will hold.
+    /// `None` lifts the limit.
+    ///
+    /// The limit only constrains further typing;
+    /// text already in the field is left alone.
+    pub fn set_max_length(&mut self, max_length: Option<usize>) {
+        self.max_length = max_length;
+    }
+
+    /// Answers the cursor position, as a character index.
+    pub fn cursor(&self) -> usize {
+        self.buffer.cursor()
+    }
+
+    /// Answers the selected range of characters, if anything is selected.
+    pub fn selection(&self) -> Option<std::ops::Range<usize>> {
+        self.buffer.selection()
+    }
+
+    /// Answers the selected text, if anything is selected.
+    fn selected_text(&self) -> Option<String> {
+        self.buffer.selection().map(|r| self.buffer.chars().skip(r.start).take(r.len()).collect())
+    }
+
+    /// Types `text` in place of the selection,
+    /// dropping control characters and whatever would go past the maximum length.
+    ///
+    /// Answers `true` if any of it made it in.
+    fn insert_text(&mut self, text: &str) -> bool {
+        let replaced = self.buffer.selection().map_or(0, |r| r.len());
+        let room = self.max_length.map_or(usize::MAX, |max| max.saturating_sub(self.buffer.len() - replaced));
+        let text: String = text.chars().filter(|ch| !ch.is_control()).take(room).collect();
+        if text.is_empty() {
+            return false;
+        }
+        self.buffer.insert(&text);
+        true
+    }
+
+    /// Types the text of `data` where it was dropped, `at` the given point,
+    /// and takes the focus.
+    ///
+    /// Answers [[TextFieldEvent::Changed]] if any of it made it in,
+    /// and [[TextFieldEvent::None]] if it wasn't text, or wasn't dropped on the field.
+    pub fn accept_drop(&mut self, med: &mut dyn Mediator, data: &TransferData, at: Point) -> TextFieldEvent {
+        let Some(text) = data.as_text() else {
+            return TextFieldEvent::None;
+        };
+        if !rect_contains(self.area, at) {
+            return TextFieldEvent::None;
+        }
+        if let Some(id) = self.focus_id {
+            med.set_focus(Some(id));
+        }
+        self.buffer.set_cursor(self.index_at(at.0), false);
+        let evt = edited(self.insert_text(text));
+        self.scroll_to_cursor();
+        self.draw(med);
+        med.repaint_all();
+        evt
+    }
+
+    /// Carries out `command`, redrawing the field if anything changed.
+    ///
+    /// Answers [[TextFieldEvent::Changed]] if the text was edited,
+    /// [[TextFieldEvent::Committed]] or [[TextFieldEvent::Cancelled]] for those commands,
+    /// and [[TextFieldEvent::None]] otherwise.
+    /// Typing past the maximum length is refused, and answers [[TextFieldEvent::None]].
+    pub fn perform(&mut self, med: &mut dyn Mediator, command: TextFieldCommand) -> TextFieldEvent {
+        let before = (self.buffer.cursor(), self.buffer.anchor());
+        let cursor = self.buffer.cursor();
+        let evt = match command {
+            TextFieldCommand::Insert(ch) => {
+                let replaced = self.buffer.selection().map_or(0, |r| r.len());
+                let room = self.max_length.is_none_or(|max| self.buffer.len() - replaced < max);
+                if room && !ch.is_control() {
+                    self.buffer.insert(ch.encode_utf8(&mut [0; 4]));
+                    TextFieldEvent::Changed
+                } else {
+                    TextFieldEvent::None
+                }
+            },
+            TextFieldCommand::Left(extend) => {
+                match self.buffer.selection() {
+                    Some(r) if !extend => self.buffer.set_cursor(r.start, false),
+                    _ => self.buffer.set_cursor(cursor.saturating_sub(1), extend),
+                }
+                TextFieldEvent::None
+            },
+            TextFieldCommand::Right(extend) => {
+                match self.buffer.selection() {
+                    Some(r) if !extend => self.buffer.set_cursor(r.end, false),
+                    _ => self.buffer.set_cursor(cursor + 1, extend),
+                }
+                TextFieldEvent::None
+            },
+            TextFieldCommand::Home(extend) => {
+                self.buffer.set_cursor(0, extend);
+                TextFieldEvent::None
+            },
+            TextFieldCommand::End(extend) => {
+                self.buffer.set_cursor(self.buffer.len(), extend);
+                TextFieldEvent::None
+            },
+            TextFieldCommand::DeleteBackward => edited(self.buffer.delete_backward().is_some()),
+            TextFieldCommand::DeleteForward => edited(self.buffer.delete_forward().is_some()),
+            TextFieldCommand::SelectAll => {
+                self.buffer.select(0..self.buffer.len());
+                TextFieldEvent::None
+            },
+            TextFieldCommand::Undo => edited(!self.buffer.undo().is_empty()),
+            TextFieldCommand::Redo => edited(!self.buffer.redo().is_empty()),
+            TextFieldCommand::Cut => match self.selected_text() {
+                Some(text) => {
+                    med.set_clipboard(TransferData::Text(text));
+                    edited(self.buffer.delete_backward().is_some())
+                },
+                None => TextFieldEvent::None,
+            },
+            TextFieldCommand::Copy => {
+                if let Some(text) = self.selected_text() {
+                    med.set_clipboard(TransferData::Text(text));
+                }
+                TextFieldEvent::None
+            },
+            TextFieldCommand::Paste => {
+                let data = med.clipboard();
+                edited(data.as_ref().and_then(TransferData::as_text).is_some_and(|text| self.insert_text(text)))
+            },
+            TextFieldCommand::Commit => {
+                self.committed = self.get_text();
+                TextFieldEvent::Committed
+            },
+            TextFieldCommand::Cancel => {
+                let committed = std::mem::take(&mut self.committed);
+                self.set_text(&committed);
+                TextFieldEvent::Cancelled
+            },
+        };
+
+        let moved = before != (self.buffer.cursor(), self.buffer.anchor());
+        if moved || !matches!(evt, TextFieldEvent::None) {
+            self.scroll_to_cursor();
+            self.draw(med);
+            med.repaint_all();
+        }
+        evt
+    }
+
+    /// Answers the rectangle the text is printed within.
+    fn text_area(&self) -> Rect {
+        let ((left, top), (right, bottom)) = self.area;
+        ((left + PADDING, top + 1), (right - PADDING, bottom - 1))
+    }
+
+    /// Answers the rows the glyphs occupy, from top to bottom,
+    /// centred within the text area.
+    fn glyph_rows(&self) -> (Unit, Unit) {
+        let ((_, text_top), (_, text_bottom)) = self.text_area();
+        let glyph_top = text_top + ((text_bottom - text_top - self.font.height) >> 1);
+        (glyph_top, (glyph_top + self.font.height).min(text_bottom))
+    }
+
+    /// Answers the visible part of the selection, if anything is selected.
+    fn selection_area(&self) -> Option<Rect> {
+        let r = self.buffer.selection()?;
+        let ((text_left, _), (text_right, _)) = self.text_area();
+        let (glyph_top, glyph_bottom) = self.glyph_rows();
+        let offsets = self.offsets();
+        let origin = text_left - self.scroll;
+        let from = (origin + offsets[r.start]).max(text_left);
+        let to = (origin + offsets[r.end]).min(text_right);
+        Some(((from, glyph_top), (to, glyph_bottom)))
+    }
+
+    /// Answers the x offset of every cursor position from the start of the text,
+    /// from before the first character to after the last.
+    fn offsets(&self) -> Vec<Dimension> {
+        let mut offsets = vec![0];
+        let mut x = 0;
+        let mut previous = None;
+        for ch in self.buffer.chars() {
+            if let Some(p) = previous {
+                x += self.font.kerning(p, ch);
+            }
+            x += self.font.glyph_advance(ch);
+            offsets.push(x);
+            previous = Some(ch);
+        }
+        offsets
+    }
+
+    /// Scrolls the text just far enough to bring the cursor into view,
+    /// without leaving empty space past the end of the text.
+    fn scroll_to_cursor(&mut self) {
+        let ((left, _), (right, _)) = self.text_area();
+        let width = (right - left - 1).max(0);
+        let offsets = self.offsets();
+        let x = offsets[self.buffer.cursor()];
+        let widest = (offsets[offsets.len() - 1] - width).max(0);
+        self.scroll = self.scroll.clamp(x - width, x).min(widest).max(0);
+    }
+
+    /// Answers the range of the word around position `index`.
+    /// Between words, the range is empty.
+    fn word_at(&self, index: usize) -> std::ops::Range<usize> {
+        let in_word = |i: usize| self.buffer.char_at(i).is_some_and(char::is_alphanumeric);
+        let mut start = index;
+        while (start > 0) && in_word(start - 1) {
+            start -= 1;
+        }
+        let mut end = index;
+        while in_word(end) {
+            end += 1;
+        }
+        start..end
+    }
+
+    /// Answers the cursor position closest to the screen x coordinate `x`.
+    fn index_at(&self, x: Unit) -> usize {
+        let x = x - self.text_area().0.0 + self.scroll;
+        let offsets = self.offsets();
+        let after = offsets.partition_point(|&o| o <= x);
+        match after {
+            0 => 0,
+            n if n == offsets.len() => n - 1,
+            n if (x - offsets[n - 1]) < (offsets[n] - x) => n - 1,
+            n => n,
+        }
+    }
+}
+
+/// Answers [[TextFieldEvent::Changed]] if the text was `changed`.
+fn edited(changed: bool) -> TextFieldEvent {
+    if changed {
+        TextFieldEvent::Changed
+    } else {
+        TextFieldEvent::None
+    }
+}
+
+impl<'f> View for TextFieldView<'f> {
+    fn draw(&mut self, med: &mut dyn Mediator) {
+        let theme = med.theme();
+        let d = med.borrow_mut_desktop();
+        let ((left, top), (right, bottom)) = self.area;
+        let clip = self.text_area();
+        let ((text_left, _), (text_right, _)) = clip;
+        let (glyph_top, glyph_bottom) = self.glyph_rows();
+
+        d.filled_rectangle((left, top), (right, bottom), &theme.paper);
+        theme.frame(d, (left, top), (right, bottom));
+
+        let offsets = self.offsets();
+        let origin = text_left - self.scroll;
+        for (i, ch) in self.buffer.chars().enumerate() {
+            if origin + offsets[i] >= text_right {
+                break;
+            }
+            // A glyph starts after any kerning against the one before it.
+            let x = origin + offsets[i + 1] - self.font.glyph_advance(ch);
+            paste_glyph(d, self.font, ch, TextStyle::PLAIN, (x, glyph_top), clip, BlitOp::DandNotS);
+        }
+
+        if !self.has_focus(med) {
+            return;
+        }
+
+        let d = med.borrow_mut_desktop();
+        match self.selection_area() {
+            Some((upper_left, lower_right)) => {
+                if upper_left.0 < lower_right.0 {
+                    d.invert_rectangle(upper_left, lower_right);
+                }
+            },
+            None => {
+                let x = origin + offsets[self.buffer.cursor()];
+                if (text_left <= x) && (x < text_right) {
+                    d.vertical_line((x, glyph_top), glyph_bottom, LINE_BLACK);
+                }
+            },
+        }
+    }
+
+    /// Answers the field's current width,
+    /// and a height with room for a line of text and the frame around it.
+    fn preferred_size(&self) -> Option<Dimensions> {
+        let ((left, _), (right, _)) = self.area;
+        Some((right - left, self.font.height + 5))
+    }
+
+    fn set_area(&mut self, area: Rect) {
+        self.area = area;
+        self.scroll_to_cursor();
+    }
+}
+
+/// Events unique to the text field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextFieldEvent {
+    /// No event recognized.
+    None,
+
+    /// The text was edited.
+    /// Use [[TextFieldView::get_text]] to see the result.
+    Changed,
+
+    /// The user accepted the text.
+    Committed,
+
+    /// The user abandoned their changes;
+    /// the text is back to what it was when last committed.
+    Cancelled,
+}
+
+impl<'f> MouseEventSink<TextFieldEvent> for TextFieldView<'f> {
+    /// Handle mouse motion events.
+    ///
+    /// While the user drags across the field, the selection follows the pointer,
+    /// scrolling the text if the pointer leaves the field sideways.
+    /// Dragging the selection itself starts dragging a copy of its text away
+    /// (see [[Mediator::start_drag]]).
+    ///
+    /// Always answers with [[TextFieldEvent::None]].
+    fn pointer_moved(&mut self, med: &mut dyn Mediator, to: Point) -> TextFieldEvent {
+        self.mouse_pt = to;
+        if let Some(from) = self.drag_from {
+            let far_enough = ((to.0 - from.0).abs() >= DRAG_THRESHOLD) || ((to.1 - from.1).abs() >= DRAG_THRESHOLD);
+            if let (true, Some(text), Some(area)) = (far_enough, self.selected_text(), self.selection_area()) {
+                self.drag_from = None;
+                med.start_drag(DragSession::new(TransferData::Text(text), area, from));
+            }
+        } else if self.selecting {
+            let index = self.index_at(to.0);
+            if index != self.buffer.cursor() {
+                self.buffer.set_cursor(index, true);
+                self.scroll_to_cursor();
+                self.draw(med);
+                med.repaint_all();
+            }
+        }
+        TextFieldEvent::None
+    }
+
+    /// Handles mouse button-up events.
+    ///
+    /// Letting go of the selection without dragging it places the cursor under the pointer.
+    ///
+    /// Always answers with [[TextFieldEvent::None]].
+    fn button_up(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> TextFieldEvent {
+        if event.button == MouseButton::Left {
+            self.selecting = false;
+            if self.drag_from.take().is_some() {
+                self.buffer.set_cursor(self.index_at(event.at.0), false);
+                self.scroll_to_cursor();
+                self.draw(med);
+                med.repaint_all();
+            }
+        }
+        TextFieldEvent::None
+    }
+
+    /// Handles mouse button-down events.
+    ///
+    /// Pressing the left button inside the field places the cursor under the pointer,
+    /// and starts selecting text;
+    /// unless it's pressed on the selection, which might be about to be dragged away.
+    /// Double clicking selects the word under the pointer instead,
+    /// and triple clicking selects everything.
+    ///
+    /// Always answers with [[TextFieldEvent::None]].
+    fn button_down(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> TextFieldEvent {
+        self.mouse_pt = event.at;
+        if (event.button == MouseButton::Left) && rect_contains(self.area, self.mouse_pt) {
+            // The selection only shows, and so can only be grabbed, while the field has the focus.
+            let on_selection = self.has_focus(med)
+                && self.selection_area().is_some_and(|area| rect_contains(area, self.mouse_pt));
+            if let Some(id) = self.focus_id {
+                med.set_focus(Some(id));
+            }
+            let index = self.index_at(self.mouse_pt.0);
+            match event.clicks {
+                1 if on_selection => self.drag_from = Some(self.mouse_pt),
+                1 => {
+                    self.selecting = true;
+                    self.buffer.set_cursor(index, false);
+                },
+                2 => self.buffer.select(self.word_at(index)),
+                _ => self.buffer.select(0..self.buffer.len()),
+            }
+            self.scroll_to_cursor();
+            self.draw(med);
+            med.repaint_all();
+        }
+        TextFieldEvent::None
+    }
+
+    /// Handles mouse entry events,
+    /// turning the pointer into an I-beam.
+    ///
+    /// Currently, always answers with [[TextFieldEvent::None]].
+    fn enter(&mut self, med: &mut dyn Mediator, at: Point) -> TextFieldEvent {
+        self.mouse_pt = at;
+        med.set_pointer(&I_BEAM);
+        TextFieldEvent::None
+    }
+
+    /// Handles mouse exit events,
+    /// turning the pointer back into an arrow.
+    ///
+    /// Currently, always answers with [[TextFieldEvent::None]].
+    fn leave(&mut self, med: &mut dyn Mediator) -> TextFieldEvent {
+        med.set_pointer(&ARROW);
+        TextFieldEvent::None
+    }
+
+    /// Handles mouse wheel events.
+    ///
+    /// Currently, always answers with [[TextFieldEvent::None]].
+    fn wheel(&mut self, _med: &mut dyn Mediator, _delta: Point, _modifiers: Modifiers) -> TextFieldEvent {
+        TextFieldEvent::None
+    }
+}
+
+impl<'f> KeyboardEventSink<TextFieldEvent> for TextFieldView<'f> {
+    /// Handles key-down events while the field has the focus.
+    ///
+    /// The arrow keys, Home and End move the cursor,
+    /// extending the selection with Shift held down.
+    /// Backspace and Delete delete,
+    /// Return commits and Escape cancels.
+    /// With Control held down,
+    /// A selects everything, Z undoes and Y redoes,
+    /// and X, C and V cut, copy and paste.
+    ///
+    /// Answers as [[TextFieldView::perform]] does for the command the key stands for,
+    /// or [[TextFieldEvent::None]] if it stands for none.
+    fn key_down(&mut self, med: &mut dyn Mediator, key: KeyCode, modifiers: Modifiers) -> TextFieldEvent {
+        if !self.has_focus(med) {
+            return TextFieldEvent::None;
+        }
+
+        let extend = modifiers.contains(Modifiers::SHIFT);
+        let command = match key {
+            KeyCode::Left => TextFieldCommand::Left(extend),
+            KeyCode::Right => TextFieldCommand::Right(extend),
+            KeyCode::Home | KeyCode::Up => TextFieldCommand::Home(extend),
+            KeyCode::End | KeyCode::Down => TextFieldCommand::End(extend),
+            KeyCode::Backspace => TextFieldCommand::DeleteBackward,
+            KeyCode::Delete => TextFieldCommand::DeleteForward,
+            KeyCode::Return => TextFieldCommand::Commit,
+            KeyCode::Escape => TextFieldCommand::Cancel,
+            KeyCode::Char(ch) if modifiers.contains(Modifiers::CONTROL) => match ch {
+                'a' => TextFieldCommand::SelectAll,
+                'z' => TextFieldCommand::Undo,
+                'y' => TextFieldCommand::Redo,
+                'x' => TextFieldCommand::Cut,
+                'c' => TextFieldCommand::Copy,
+                'v' => TextFieldCommand::Paste,
+                _ => return TextFieldEvent::None,
+            },
+            _ => return TextFieldEvent::None,
+        };
+        self.perform(med, command)
+    }
+
+    /// Handles key-up events.
+    ///
+    /// Currently, always answers with [[TextFieldEvent::None]].
+    fn key_up(&mut self, _med: &mut dyn Mediator, _key: KeyCode, _modifiers: Modifiers) -> TextFieldEvent {
+        TextFieldEvent::None
+    }
+
+    /// Handles text input events while the field has the focus,
+    /// typing the `text` into the field.
+    ///
+    /// Answers [[TextFieldEvent::Changed]] if any of it made it in.
+    fn text_input(&mut self, med: &mut dyn Mediator, text: &str) -> TextFieldEvent {
+        if !self.has_focus(med) {
+            return TextFieldEvent::None;
+        }
+
+        let mut evt = TextFieldEvent::None;
+        for ch in text.chars() {
+            if self.perform(med, TextFieldCommand::Insert(ch)) == TextFieldEvent::Changed {
+                evt = TextFieldEvent::Changed;
+            }
+        }
+        evt
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TextFieldCommand, TextFieldEvent, TextFieldView};
+    use crate::events::{ButtonEvent, KeyCode, KeyboardEventSink, Modifiers, MouseEventSink};
+    use crate::mediator::Mediator;
+    use crate::pointer::{ARROW, I_BEAM};
+    use crate::sysfont_bsw_9::SYSTEM_BITMAP_FONT;
+    use crate::transfer::TransferData;
+    use crate::utils::testing::{TestMediator, inked_pixels};
+
+    fn type_text(field: &mut TextFieldView, med: &mut TestMediator, text: &str) {
+        for ch in text.chars() {
+            field.perform(med, TextFieldCommand::Insert(ch));
+        }
+    }
+
+    #[test]
+    fn editing_emits_events() {
+        let mut med = TestMediator::new(128, 32);
+        let mut field = TextFieldView::new(((4, 4), (124, 18)), &SYSTEM_BITMAP_FONT);
+        assert_eq!(field.perform(&mut med, TextFieldCommand::Insert('a')), TextFieldEvent::Changed);
+        type_text(&mut field, &mut med, "bcd");
+        assert_eq!(field.perform(&mut med, TextFieldCommand::Left(false)), TextFieldEvent::None);
+        assert_eq!(field.perform(&mut med, TextFieldCommand::DeleteBackward), TextFieldEvent::Changed);
+        assert_eq!(field.get_text(), "abd");
+
+        field.perform(&mut med, TextFieldCommand::Home(false));
+        field.perform(&mut med, TextFieldCommand::End(true));
+        assert_eq!(field.selection(), Some(0..3));
+        assert_eq!(field.perform(&mut med, TextFieldCommand::Insert('x')), TextFieldEvent::Changed);
+        assert_eq!(field.perform(&mut med, TextFieldCommand::Commit), TextFieldEvent::Committed);
+
+        type_text(&mut field, &mut med, "yz");
+        assert_eq!(field.perform(&mut med, TextFieldCommand::Cancel), TextFieldEvent::Cancelled);
+        assert_eq!(field.get_text(), "x");
+        assert_eq!(field.perform(&mut med, TextFieldCommand::DeleteForward), TextFieldEvent::None);
+        assert!(med.repaints > 0);
+        assert!(!inked_pixels(&med.desktop).is_empty());
+    }
+
+    #[test]
+    fn max_length_refuses_typing() {
+        let mut med = TestMediator::new(128, 32);
+        let mut field = TextFieldView::new(((4, 4), (124, 18)), &SYSTEM_BITMAP_FONT);
+        field.set_max_length(Some(3));
+        type_text(&mut field, &mut med, "abc");
+        assert_eq!(field.perform(&mut med, TextFieldCommand::Insert('d')), TextFieldEvent::None);
+        assert_eq!(field.get_text(), "abc");
+
+        // Replacing a selection makes room.
+        field.perform(&mut med, TextFieldCommand::Left(true));
+        assert_eq!(field.perform(&mut med, TextFieldCommand::Insert('d')), TextFieldEvent::Changed);
+        assert_eq!(field.get_text(), "abd");
+    }
+
+    #[test]
+    fn long_text_scrolls_to_keep_the_cursor_in_view() {
+        let mut med = TestMediator::new(128, 32);
+        let mut field = TextFieldView::new(((4, 4), (44, 18)), &SYSTEM_BITMAP_FONT);
+        type_text(&mut field, &mut med, "the quick brown fox");
+        assert!(field.scroll > 0);
+        let offsets = field.offsets();
+        assert_eq!(field.scroll, offsets[offsets.len() - 1] - (44 - 4 - 4 - 1));
+
+        field.perform(&mut med, TextFieldCommand::Home(false));
+        assert_eq!(field.scroll, 0);
+
+        // Nothing is drawn outside of the field.
+        assert!(inked_pixels(&med.desktop).iter().all(|&(x, y)| (4..44).contains(&x) && (4..18).contains(&y)));
+    }
+
+    #[test]
+    fn dragging_selects_text() {
+        let mut med = TestMediator::new(128, 32);
+        let mut field = TextFieldView::new(((4, 4), (124, 18)), &SYSTEM_BITMAP_FONT);
+        field.set_text("hello world");
+        let offsets = field.offsets();
+        let x = |i: usize| 4 + 2 + offsets[i];
+
+        field.pointer_moved(&mut med, (x(1), 10));
+        field.button_down(&mut med, ButtonEvent::left((x(1), 10)));
+        assert_eq!(field.cursor(), 1);
+        field.pointer_moved(&mut med, (x(5), 10));
+        field.button_up(&mut med, ButtonEvent::left((x(5), 10)));
+        assert_eq!(field.selection(), Some(1..5));
+
+        // Once released, moving the pointer leaves the selection alone.
+        field.pointer_moved(&mut med, (x(8), 10));
+        assert_eq!(field.selection(), Some(1..5));
+
+        // Double clicks select a word, and triple clicks everything.
+        let click = |clicks| ButtonEvent { clicks, ..ButtonEvent::left((x(8), 10)) };
+        field.button_down(&mut med, click(2));
+        field.button_up(&mut med, click(2));
+        assert_eq!(field.selection(), Some(6..11));
+        field.button_down(&mut med, click(3));
+        assert_eq!(field.selection(), Some(0..11));
+    }
+
+    #[test]
+    fn keys_edit_only_with_the_focus() {
+        let mut med = TestMediator::new(128, 32);
+        let mut field = TextFieldView::new(((4, 4), (124, 18)), &SYSTEM_BITMAP_FONT);
+        field.set_focus_id(7);
+        assert_eq!(field.text_input(&mut med, "ab"), TextFieldEvent::None);
+
+        // Clicking in the field takes the focus.
+        field.button_down(&mut med, ButtonEvent::left((10, 10)));
+        field.button_up(&mut med, ButtonEvent::left((10, 10)));
+        assert_eq!(med.focus(), Some(7));
+
+        assert_eq!(field.text_input(&mut med, "abc"), TextFieldEvent::Changed);
+        field.key_down(&mut med, KeyCode::Left, Modifiers::SHIFT);
+        assert_eq!(field.key_down(&mut med, KeyCode::Backspace, Modifiers::NONE), TextFieldEvent::Changed);
+        assert_eq!(field.get_text(), "ab");
+        assert_eq!(field.key_down(&mut med, KeyCode::Char('z'), Modifiers::CONTROL), TextFieldEvent::Changed);
+        assert_eq!(field.get_text(), "abc");
+        assert_eq!(field.key_down(&mut med, KeyCode::Return, Modifiers::NONE), TextFieldEvent::Committed);
+
+        med.set_focus(None);
+        assert_eq!(field.key_down(&mut med, KeyCode::Backspace, Modifiers::NONE), TextFieldEvent::None);
+        assert_eq!(field.get_text(), "abc");
+    }
+
+    #[test]
+    fn pointer_is_an_i_beam_over_the_field() {
+        let mut med = TestMediator::new(128, 32);
+        let mut field = TextFieldView::new(((4, 4), (124, 18)), &SYSTEM_BITMAP_FONT);
+        field.enter(&mut med, (10, 10));
+        assert!(std::ptr::eq(med.pointer, &I_BEAM));
+        field.leave(&mut med);
+        assert!(std::ptr::eq(med.pointer, &ARROW));
+    }
+
+    #[test]
+    fn cut_copy_and_paste_use_the_clipboard() {
+        let mut med = TestMediator::new(128, 32);
+        let mut field = TextFieldView::new(((4, 4), (124, 18)), &SYSTEM_BITMAP_FONT);
+        field.set_max_length(Some(8));
+        type_text(&mut field, &mut med, "hello");
+        assert_eq!(field.perform(&mut med, TextFieldCommand::Copy), TextFieldEvent::None);
+        assert_eq!(med.clipboard, None);
+
+        field.perform(&mut med, TextFieldCommand::Left(true));
+        field.perform(&mut med, TextFieldCommand::Left(true));
+        assert_eq!(field.perform(&mut med, TextFieldCommand::Cut), TextFieldEvent::Changed);
+        assert_eq!(field.get_text(), "hel");
+        assert_eq!(med.clipboard, Some(TransferData::Text("lo".to_string())));
+
+        field.perform(&mut med, TextFieldCommand::Home(false));
+        assert_eq!(field.perform(&mut med, TextFieldCommand::Paste), TextFieldEvent::Changed);
+        assert_eq!(field.get_text(), "lohel");
+
+        // Pasting stops at the maximum length.
+        med.set_clipboard(TransferData::Text("world".to_string()));
+        assert_eq!(field.perform(&mut med, TextFieldCommand::Paste), TextFieldEvent::Changed);
+        assert_eq!(field.get_text(), "loworhel");
+    }
+
+    #[test]
+    fn dragging_the_selection_drops_a_copy() {
+        let mut med = TestMediator::new(128, 32);
+        let mut field = TextFieldView::new(((4, 4), (124, 18)), &SYSTEM_BITMAP_FONT);
+        type_text(&mut field, &mut med, "drag me");
+        field.perform(&mut med, TextFieldCommand::SelectAll);
+
+        // Grabbing the selection and letting go just places the cursor.
+        field.button_down(&mut med, ButtonEvent::left((8, 10)));
+        field.button_up(&mut med, ButtonEvent::left((8, 10)));
+        assert_eq!(field.selection(), None);
+        assert_eq!(med.drag(), None);
+
+        field.perform(&mut med, TextFieldCommand::SelectAll);
+        field.button_down(&mut med, ButtonEvent::left((8, 10)));
+        field.pointer_moved(&mut med, (9, 10));
+        assert_eq!(med.drag(), None);
+        field.pointer_moved(&mut med, (20, 10));
+        let session = med.end_drag().expect("dragging the selection starts a drag");
+        assert_eq!(session.data().as_text(), Some("drag me"));
+        field.button_up(&mut med, ButtonEvent::left((20, 10)));
+        assert_eq!(field.selection(), Some(0..7));
+
+        assert_eq!(field.accept_drop(&mut med, session.data(), (200, 10)), TextFieldEvent::None);
+        assert_eq!(field.accept_drop(&mut med, session.data(), (5, 10)), TextFieldEvent::Changed);
+        assert_eq!(field.get_text(), "drag medrag me");
+    }
+}
@@ -0,0 +1,546 @@
+//! Editable text storage.
+//!
+//! A gap buffer keeps its text in a single array
+//! with a hole (the *gap*) where the next edit is expected to happen.
+//! Typing fills the gap from the left;
+//! backspacing widens it again.
+//! Only when the cursor jumps elsewhere
+//! does any text need to move,
+//! and then only the text between the old and new gap locations.
+//! This is the same trick Emacs and many 8-bit word processors use,
+//! and it suits ROSE well:
+//! it's simple, compact, and edits near the cursor are cheap.
+//!
+//! [[GapBuffer]] also tracks a cursor and selection anchor,
+//! and records every edit so that it can be undone and redone.
+//! Each edit is reported as a [[chase::Edit]],
+//! ready to hand to [[Forme::relayout_from_chars]].
+
+use std::ops::Range;
+
+use crate::chase::{Edit, Line, is_paragraph_break};
+
+/// How much room the gap is given whenever it has to grow.
+const GAP_GROWTH: usize = 64;
+
+/// A single, undoable change to the text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Change {
+    /// Where the change took place.
+    at: usize,
+
+    /// The text which was removed.
+    deleted: Vec<char>,
+
+    /// The text which was put in its place.
+    inserted: Vec<char>,
+}
+
+impl Change {
+    /// Answers the edit which applies this change.
+    fn edit(&self) -> Edit {
+        Edit { at: self.at, deleted: self.deleted.len(), inserted: self.inserted.len() }
+    }
+
+    /// Answers the change which reverts this one.
+    fn inverse(&self) -> Change {
+        Change { at: self.at, deleted: self.inserted.clone(), inserted: self.deleted.clone() }
+    }
+}
+
+/// A group of changes which are undone and redone together.
+#[derive(Clone, Debug, Default)]
+struct UndoGroup {
+    /// The changes, in the order they were made.
+    changes: Vec<Change>,
+
+    /// Where the cursor and anchor were before the first change.
+    cursor_before: usize,
+    anchor_before: Option<usize>,
+
+    /// Where the cursor and anchor were after the last change.
+    cursor_after: usize,
+    anchor_after: Option<usize>,
+}
+
+/// A buffer of editable text, with a cursor, an optional selection, and undo history.
+///
+/// All positions are character indices, like those of a [[Line]].
+/// The cursor sits *between* characters;
+/// position 0 is before the first character,
+/// and position `len()` is after the last.
+#[derive(Clone, Debug, Default)]
+pub struct GapBuffer {
+    /// Storage for the text, including the gap.
+    text: Vec<char>,
+
+    /// Where the gap starts in `text`.
+    gap_start: usize,
+
+    /// Where the gap ends in `text` (exclusive).
+    gap_end: usize,
+
+    /// The insertion point.
+    cursor: usize,
+
+    /// The other end of the selection, if there is one.
+    /// The selection spans from the anchor to the cursor.
+    anchor: Option<usize>,
+
+    /// Changes which may be undone, oldest first.
+    undo: Vec<UndoGroup>,
+
+    /// Changes which were undone and may be redone, most recently undone last.
+    redo: Vec<UndoGroup>,
+
+    /// How many times [[GapBuffer::begin_group]] was called without a matching
+    /// [[GapBuffer::end_group]].
+    group_depth: usize,
+
+    /// True if the outermost group has begun, but no change has been recorded in it yet.
+    group_fresh: bool,
+
+    /// True if the next change outside of a group must start a new undo group,
+    /// rather than coalescing with the most recent one.
+    sealed: bool,
+}
+
+impl GapBuffer {
+    /// Creates an empty buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a buffer holding `s`, with the cursor at the start.
+    /// Loading the text isn't an undoable change.
+    pub fn with_text(s: &str) -> Self {
+        let text: Vec<char> = s.chars().collect();
+        let len = text.len();
+        Self { text, gap_start: len, gap_end: len, ..Default::default() }
+    }
+
+    /// Answers the number of characters in the buffer.
+    pub fn len(&self) -> usize {
+        self.text.len() - (self.gap_end - self.gap_start)
+    }
+
+    /// Answers `true` if the buffer holds no text.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Answers the character at position `index`, if there is one.
+    pub fn char_at(&self, index: usize) -> Option<char> {
+        if index < self.gap_start {
+            Some(self.text[index])
+        } else {
+            self.text.get(index + self.gap_end - self.gap_start).copied()
+        }
+    }
+
+    /// Answers every character in the buffer, in order.
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.text[..self.gap_start].iter().chain(&self.text[self.gap_end..]).copied()
+    }
+
+    /// Answers the characters in `range`.
+    pub fn slice(&self, range: Range<usize>) -> String {
+        self.chars().skip(range.start).take(range.end - range.start).collect()
+    }
+
+    /// Answers the insertion point.
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Answers the selection anchor, if any.
+    pub fn anchor(&self) -> Option<usize> {
+        self.anchor
+    }
+
+    /// Moves the cursor to `position`, clamped to the text.
+    ///
+    /// If `extend` is set, the selection is extended to the new position,
+    /// anchored wherever the cursor was if nothing was selected yet.
+    /// Otherwise, any selection is dropped.
+    ///
+    /// Moving the cursor ends the current run of typing for undo purposes.
+    pub fn set_cursor(&mut self, position: usize, extend: bool) {
+        let position = position.min(self.len());
+        if extend {
+            if self.anchor.is_none() {
+                self.anchor = Some(self.cursor);
+            }
+        } else {
+            self.anchor = None;
+        }
+        if self.anchor == Some(position) {
+            self.anchor = None;
+        }
+        self.cursor = position;
+        self.sealed = true;
+    }
+
+    /// Selects the text in `range`, leaving the cursor at its end.
+    pub fn select(&mut self, range: Range<usize>) {
+        self.set_cursor(range.start, false);
+        self.set_cursor(range.end, true);
+    }
+
+    /// Answers the selected range, if anything is selected.
+    pub fn selection(&self) -> Option<Range<usize>> {
+        self.anchor.map(|anchor| anchor.min(self.cursor)..anchor.max(self.cursor))
+    }
+
+    /// Inserts `s` at the cursor, replacing the selection if there is one.
+    /// The cursor ends up after the inserted text.
+    pub fn insert(&mut self, s: &str) -> Edit {
+        let range = self.selection().unwrap_or(self.cursor..self.cursor);
+        self.replace(range, s)
+    }
+
+    /// Deletes the selection, or the character before the cursor if nothing is selected.
+    /// Answers `None` if there was nothing to delete.
+    pub fn delete_backward(&mut self) -> Option<Edit> {
+        let range = match self.selection() {
+            Some(range) => range,
+            None if self.cursor > 0 => (self.cursor - 1)..self.cursor,
+            None => return None,
+        };
+        Some(self.replace(range, ""))
+    }
+
+    /// Deletes the selection, or the character after the cursor if nothing is selected.
+    /// Answers `None` if there was nothing to delete.
+    pub fn delete_forward(&mut self) -> Option<Edit> {
+        let range = match self.selection() {
+            Some(range) => range,
+            None if self.cursor < self.len() => self.cursor..(self.cursor + 1),
+            None => return None,
+        };
+        Some(self.replace(range, ""))
+    }
+
+    /// Replaces the text in `range` with `s`, leaving the cursor after it and nothing selected.
+    ///
+    /// Consecutive replacements which pick up where the previous one left off,
+    /// such as typing or backspacing a character at a time,
+    /// are undone together.
+    pub fn replace(&mut self, range: Range<usize>, s: &str) -> Edit {
+        let end = range.end.min(self.len());
+        let start = range.start.min(end);
+        let change = Change { at: start, deleted: self.slice(start..end).chars().collect(), inserted: s.chars().collect() };
+
+        let cursor_before = self.cursor;
+        let anchor_before = self.anchor;
+        self.apply(&change);
+        self.cursor = start + change.inserted.len();
+        self.anchor = None;
+        self.record(change.clone(), cursor_before, anchor_before);
+        change.edit()
+    }
+
+    /// Starts a group of changes which will be undone and redone as one.
+    /// Groups may nest; only the outermost group counts.
+    pub fn begin_group(&mut self) {
+        if self.group_depth == 0 {
+            self.group_fresh = true;
+        }
+        self.group_depth += 1;
+    }
+
+    /// Ends a group started by [[GapBuffer::begin_group]].
+    pub fn end_group(&mut self) {
+        self.group_depth = self.group_depth.saturating_sub(1);
+        if self.group_depth == 0 {
+            self.sealed = true;
+        }
+    }
+
+    /// Answers `true` if there's a change to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Answers `true` if there's an undone change to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Undoes the most recent group of changes,
+    /// restoring the cursor and selection to how they were beforehand.
+    ///
+    /// Answers the edits made to the text, in the order they were applied,
+    /// or an empty vector if there was nothing to undo.
+    pub fn undo(&mut self) -> Vec<Edit> {
+        let group = match self.undo.pop() {
+            Some(group) => group,
+            None => return Vec::new(),
+        };
+        let mut edits = Vec::new();
+        for change in group.changes.iter().rev() {
+            let inverse = change.inverse();
+            self.apply(&inverse);
+            edits.push(inverse.edit());
+        }
+        self.cursor = group.cursor_before;
+        self.anchor = group.anchor_before;
+        self.sealed = true;
+        self.redo.push(group);
+        edits
+    }
+
+    /// Redoes the most recently undone group of changes.
+    ///
+    /// Answers the edits made to the text, in the order they were applied,
+    /// or an empty vector if there was nothing to redo.
+    pub fn redo(&mut self) -> Vec<Edit> {
+        let group = match self.redo.pop() {
+            Some(group) => group,
+            None => return Vec::new(),
+        };
+        let mut edits = Vec::new();
+        for change in &group.changes {
+            self.apply(change);
+            edits.push(change.edit());
+        }
+        self.cursor = group.cursor_after;
+        self.anchor = group.anchor_after;
+        self.sealed = true;
+        self.undo.push(group);
+        edits
+    }
+
+    /// Answers the line and column of position `index`,
+    /// given the `lines` a [[Forme]] laid this buffer's text out into.
+    ///
+    /// A position on the boundary between two lines belongs to the later line.
+    /// The position after a final paragraph break sits on a line of its own,
+    /// one past the end of `lines`.
+    pub fn line_and_column(&self, index: usize, lines: &[Line]) -> (usize, usize) {
+        let n = lines.partition_point(|&(_, end)| end <= index);
+        match lines.get(n) {
+            Some(&(start, _)) => (n, index.saturating_sub(start)),
+            None => match lines.last() {
+                Some(&(start, end)) if !self.ends_paragraph(end) => (n - 1, index - start),
+                _ => (n, 0),
+            },
+        }
+    }
+
+    /// Answers the position at `column` of `line`,
+    /// given the `lines` a [[Forme]] laid this buffer's text out into.
+    ///
+    /// Columns past the end of a line are clamped to the last position on it;
+    /// that's just before the paragraph break (or wrap) which ends it.
+    /// Lines past the end of `lines` clamp to the end of the text.
+    pub fn index_at(&self, line: usize, column: usize, lines: &[Line]) -> usize {
+        match lines.get(line) {
+            Some(&(start, end)) => {
+                let last_line = line + 1 == lines.len();
+                let limit = if last_line && !self.ends_paragraph(end) { end } else { end - 1 };
+                (start + column).min(limit.max(start))
+            },
+            None => self.len(),
+        }
+    }
+
+    /// Answers `true` if the character just before position `end` is a paragraph break.
+    fn ends_paragraph(&self, end: usize) -> bool {
+        end > 0 && self.char_at(end - 1).is_some_and(is_paragraph_break)
+    }
+
+    /// Applies `change` to the text, without touching the cursor or history.
+    fn apply(&mut self, change: &Change) {
+        self.move_gap(change.at);
+        self.gap_end += change.deleted.len();
+        if self.gap_end - self.gap_start < change.inserted.len() {
+            self.grow_gap(change.inserted.len());
+        }
+        self.text[self.gap_start..self.gap_start + change.inserted.len()].copy_from_slice(&change.inserted);
+        self.gap_start += change.inserted.len();
+    }
+
+    /// Adds `change` to the undo history, coalescing it with the current group if appropriate.
+    fn record(&mut self, change: Change, cursor_before: usize, anchor_before: Option<usize>) {
+        self.redo.clear();
+
+        let coalesce = if self.group_depth > 0 {
+            !std::mem::replace(&mut self.group_fresh, false)
+        } else {
+            let last = self.undo.last().and_then(|g| g.changes.last());
+            !self.sealed && last.is_some_and(|last| continues(last, &change))
+        };
+
+        if !coalesce {
+            self.undo.push(UndoGroup {
+                cursor_before,
+                anchor_before,
+                ..Default::default()
+            });
+        }
+        let group = self.undo.last_mut().unwrap();
+        group.changes.push(change);
+        group.cursor_after = self.cursor;
+        group.anchor_after = self.anchor;
+        self.sealed = false;
+    }
+
+    /// Moves the gap so that it starts at position `at`.
+    fn move_gap(&mut self, at: usize) {
+        let at = at.min(self.len());
+        if at < self.gap_start {
+            let count = self.gap_start - at;
+            self.text.copy_within(at..self.gap_start, self.gap_end - count);
+            self.gap_start = at;
+            self.gap_end -= count;
+        } else if at > self.gap_start {
+            let count = at - self.gap_start;
+            self.text.copy_within(self.gap_end..self.gap_end + count, self.gap_start);
+            self.gap_start += count;
+            self.gap_end += count;
+        }
+    }
+
+    /// Widens the gap so that it can hold at least `needed` characters.
+    fn grow_gap(&mut self, needed: usize) {
+        let extra = needed + GAP_GROWTH;
+        let after = self.text.len() - self.gap_end;
+        self.text.resize(self.text.len() + extra, '\0');
+        let new_gap_end = self.text.len() - after;
+        self.text.copy_within(self.gap_end..self.gap_end + after, new_gap_end);
+        self.gap_end = new_gap_end;
+    }
+}
+
+/// Answers `true` if `next` picks up where `last` left off,
+/// as successive keystrokes do while typing or deleting.
+fn continues(last: &Change, next: &Change) -> bool {
+    let typing = last.deleted.is_empty() && next.deleted.is_empty() && (next.at == last.at + last.inserted.len());
+    let backspacing = last.inserted.is_empty() && next.inserted.is_empty() && (next.at + next.deleted.len() == last.at);
+    let deleting = last.inserted.is_empty() && next.inserted.is_empty() && (next.at == last.at);
+    typing || backspacing || deleting
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GapBuffer;
+    use crate::chase::{Edit, FontProvider, Forme};
+
+    struct MyFont {}
+
+    impl FontProvider for MyFont {
+        fn get_glyph_width(&self, _ch: char) -> usize {
+            1
+        }
+    }
+
+    fn text(b: &GapBuffer) -> String {
+        b.chars().collect()
+    }
+
+    #[test]
+    fn edits_anywhere_in_the_buffer() {
+        let mut b = GapBuffer::with_text("pasta al dente");
+        b.set_cursor(6, false);
+        assert_eq!(b.insert("carbonara "), Edit::insert(6, 10));
+        assert_eq!(text(&b), "pasta carbonara al dente");
+        assert_eq!(b.cursor(), 16);
+
+        b.set_cursor(0, false);
+        assert_eq!(b.delete_forward(), Some(Edit::delete(0, 1)));
+        assert_eq!(b.delete_backward(), None);
+        assert_eq!(text(&b), "asta carbonara al dente");
+        assert_eq!(b.char_at(1), Some('s'));
+        assert_eq!(b.char_at(23), None);
+
+        // Enough text to make the gap grow.
+        let long = "x".repeat(200);
+        b.set_cursor(b.len(), false);
+        b.insert(&long);
+        assert_eq!(b.len(), 223);
+        assert!(text(&b).ends_with(&long));
+    }
+
+    #[test]
+    fn typing_replaces_the_selection() {
+        let mut b = GapBuffer::with_text("pasta carbonara");
+        b.set_cursor(15, false);
+        b.set_cursor(6, true);
+        assert_eq!(b.selection(), Some(6..15));
+        assert_eq!(b.slice(6..15), "carbonara");
+
+        assert_eq!(b.insert("puttanesca"), Edit { at: 6, deleted: 9, inserted: 10 });
+        assert_eq!(text(&b), "pasta puttanesca");
+        assert_eq!(b.selection(), None);
+
+        b.select(0..5);
+        assert_eq!(b.delete_backward(), Some(Edit::delete(0, 5)));
+        assert_eq!(text(&b), " puttanesca");
+    }
+
+    #[test]
+    fn undo_groups_runs_of_typing() {
+        let mut b = GapBuffer::new();
+        for ch in ["p", "a", "s", "t", "a"] {
+            b.insert(ch);
+        }
+        b.set_cursor(0, false);
+        b.insert("la ");
+        assert_eq!(text(&b), "la pasta");
+
+        // Moving the cursor split the typing into two groups.
+        assert_eq!(b.undo(), vec![Edit::delete(0, 3)]);
+        assert_eq!(text(&b), "pasta");
+        assert_eq!(b.cursor(), 0);
+        b.undo();
+        assert_eq!(text(&b), "");
+        assert!(!b.can_undo());
+
+        assert_eq!(b.redo().len(), 5);
+        assert_eq!(text(&b), "pasta");
+        assert_eq!(b.cursor(), 5);
+        assert!(b.can_redo());
+
+        // A fresh change forgets what could have been redone.
+        b.delete_backward();
+        assert!(!b.can_redo());
+    }
+
+    #[test]
+    fn explicit_groups_undo_together() {
+        let mut b = GapBuffer::with_text("pasta");
+        b.begin_group();
+        b.set_cursor(0, false);
+        b.insert("la ");
+        b.set_cursor(8, false);
+        b.insert("!");
+        b.end_group();
+        assert_eq!(text(&b), "la pasta!");
+
+        b.undo();
+        assert_eq!(text(&b), "pasta");
+        b.redo();
+        assert_eq!(text(&b), "la pasta!");
+        assert_eq!(b.cursor(), 9);
+    }
+
+    #[test]
+    fn maps_positions_through_line_table() {
+        let b = GapBuffer::with_text("pasta carbonara\nal dente\n");
+        let fp = MyFont{};
+        let mut f = Forme::new(10, &fp);
+        f.layout_from_chars(b.chars());
+        let lines = f.borrow_lines();
+        assert_eq!(*lines, vec![(0, 6), (6, 16), (16, 25)]);
+
+        assert_eq!(b.line_and_column(0, lines), (0, 0));
+        assert_eq!(b.line_and_column(6, lines), (1, 0));
+        assert_eq!(b.line_and_column(20, lines), (2, 4));
+        assert_eq!(b.line_and_column(25, lines), (3, 0));
+
+        assert_eq!(b.index_at(1, 3, lines), 9);
+        assert_eq!(b.index_at(1, 99, lines), 15);
+        assert_eq!(b.index_at(3, 0, lines), 25);
+    }
+}
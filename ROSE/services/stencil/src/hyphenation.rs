@@ -0,0 +1,159 @@
+//! Pattern-based hyphenation.
+//!
+//! This implements Frank Liang's hyphenation algorithm,
+//! as used by TeX.
+//! A language is described by a list of *patterns*,
+//! each a fragment of a word with digits between its letters,
+//! such as `hy3ph` or `.ex5am` (where `.` marks the edge of a word).
+//! To hyphenate a word,
+//! every pattern found anywhere in it casts a vote between each pair of letters it covers;
+//! the highest vote wins.
+//! Odd votes permit a hyphen, and even votes forbid one.
+//!
+//! Words the patterns get wrong can be listed as exceptions,
+//! spelled out with their hyphens, like `ta-ble`.
+//!
+//! ROSE does not ship any pattern dictionaries;
+//! the TeX distributions carry patterns for dozens of languages,
+//! which can be handed to [[Hyphenator::new]] as-is.
+
+use std::collections::HashMap;
+
+/// Finds the places a word may be hyphenated.
+#[derive(Clone, Debug, Default)]
+pub struct Hyphenator {
+    /// Patterns, keyed by their letters, with the votes between (and around) them.
+    patterns: HashMap<String, Vec<u8>>,
+
+    /// Words whose hyphenation is given outright, keyed by the word without hyphens.
+    exceptions: HashMap<String, Vec<usize>>,
+
+    /// The length of the longest pattern, in characters.
+    longest: usize,
+
+    /// The fewest characters allowed before a hyphen.
+    pub left_min: usize,
+
+    /// The fewest characters allowed after a hyphen.
+    pub right_min: usize,
+}
+
+impl Hyphenator {
+    /// Creates a hyphenator from a whitespace-separated list of Liang `patterns`.
+    ///
+    /// Like TeX, at least two characters are kept before a hyphen, and three after.
+    pub fn new(patterns: &str) -> Self {
+        let mut h = Self { left_min: 2, right_min: 3, ..Default::default() };
+        for pattern in patterns.split_whitespace() {
+            let mut letters = String::new();
+            let mut votes = vec![0];
+            for ch in pattern.chars() {
+                match ch.to_digit(10) {
+                    Some(d) => *votes.last_mut().unwrap() = d as u8,
+                    None => {
+                        letters.push(ch);
+                        votes.push(0);
+                    },
+                }
+            }
+            h.longest = h.longest.max(letters.chars().count());
+            h.patterns.insert(letters, votes);
+        }
+        h
+    }
+
+    /// Adds a whitespace-separated list of exceptions,
+    /// each spelled with hyphens where they're allowed,
+    /// to the hyphenator.
+    pub fn with_exceptions(mut self, exceptions: &str) -> Self {
+        for exception in exceptions.split_whitespace() {
+            let mut word = String::new();
+            let mut points = Vec::new();
+            for ch in exception.chars() {
+                if ch == '-' {
+                    points.push(word.chars().count());
+                } else {
+                    word.extend(ch.to_lowercase());
+                }
+            }
+            self.exceptions.insert(word, points);
+        }
+        self
+    }
+
+    /// Answers the places `word` may be hyphenated,
+    /// as counts of characters preceding each hyphen.
+    ///
+    /// For example, with suitable patterns,
+    /// `"hyphenation"` answers `[2, 6]`, for "hy-phen-ation".
+    pub fn hyphenate(&self, word: &str) -> Vec<usize> {
+        let letters: Vec<char> = word.chars().flat_map(char::to_lowercase).collect();
+        let count = letters.len();
+
+        // A hyphen at either end of a word wouldn't break it at all.
+        let left_min = self.left_min.max(1);
+        let right_min = self.right_min.max(1);
+        if count < left_min + right_min {
+            return Vec::new();
+        }
+        let lowered: String = letters.iter().collect();
+        if let Some(points) = self.exceptions.get(&lowered) {
+            return points.iter().copied().filter(|&n| (n > 0) && (n < count)).collect();
+        }
+
+        // votes[i] is the vote for a hyphen before letters[i-1], since the word gains a leading '.'.
+        let mut dotted = Vec::with_capacity(count + 2);
+        dotted.push('.');
+        dotted.extend(&letters);
+        dotted.push('.');
+        let mut votes = vec![0u8; dotted.len() + 1];
+
+        let mut key = String::new();
+        for i in 0..dotted.len() {
+            key.clear();
+            for &ch in &dotted[i..dotted.len().min(i + self.longest)] {
+                key.push(ch);
+                if let Some(pattern) = self.patterns.get(&key) {
+                    for (k, &vote) in pattern.iter().enumerate() {
+                        votes[i + k] = votes[i + k].max(vote);
+                    }
+                }
+            }
+        }
+
+        (left_min..=(count - right_min))
+            .filter(|&n| votes[n + 1] % 2 == 1)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Hyphenator;
+
+    // Just enough of the original TeX patterns for the classic example.
+    static PATTERNS: &str = "hy3ph he2n hena4 hen5at 1na n2at 1tio 2io o2n";
+
+    #[test]
+    fn hyphenates_with_patterns() {
+        let h = Hyphenator::new(PATTERNS);
+        assert_eq!(h.hyphenate("hyphenation"), vec![2, 6]);
+        assert_eq!(h.hyphenate("Hyphenation"), vec![2, 6]);
+    }
+
+    #[test]
+    fn respects_minimum_fragments() {
+        let mut h = Hyphenator::new("1b");
+        assert_eq!(h.hyphenate("ababab"), vec![3]);
+        h.left_min = 0;
+        h.right_min = 0;
+        assert_eq!(h.hyphenate("ababab"), vec![1, 3, 5]);
+        assert_eq!(h.hyphenate("ab"), vec![1]);
+    }
+
+    #[test]
+    fn exceptions_override_patterns() {
+        let h = Hyphenator::new(PATTERNS).with_exceptions("hyphen-ation");
+        assert_eq!(h.hyphenate("hyphenation"), vec![6]);
+    }
+}
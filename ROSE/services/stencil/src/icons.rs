@@ -0,0 +1,495 @@
+//! Desktop icons
+//!
+//! An [[IconLayer]] shows a collection of icons,
+//! each a small picture with a label beneath it,
+//! over a background pattern;
+//! such as the files in a drawer, or the drawers on the desktop itself.
+//!
+//! Clicking an icon selects it, and only it,
+//! unless it's already selected;
+//! clicking with Shift held down adds it to (or takes it from) the selection,
+//! and clicking between icons selects nothing.
+//! Double-clicking an icon opens it.
+//! Dragging an icon moves it somewhere else in the layer;
+//! while it's dragged, the host shows its outline following the pointer
+//! (see [[Mediator::start_drag]]).
+//!
+//! The layer redraws all of itself whenever anything changes,
+//! so it should be given the whole of whatever it sits in.
+
+use crate::events::{ButtonEvent, Modifiers, MouseButton, MouseEventSink};
+use crate::mediator::Mediator;
+use crate::simple_bitmap_font::text_width;
+use crate::simple_printer::SimplePrinter;
+use crate::stencil::{Draw, Stencil};
+use crate::theme::Theme;
+use crate::transfer::{DragSession, TransferData};
+use crate::types::{Dimension, Point, Rect};
+use crate::utils::draw_masked_image;
+use crate::view::{View, rect_contains};
+
+/// A 16x16 pixel icon picture, one `u16` per row,
+/// with the leftmost pixel in the most significant bit.
+/// Set bits are black; the rest are white.
+pub type IconImage = [u16; 16];
+
+/// Identifies an icon within its layer.
+pub type IconId = usize;
+
+/// The width and height of an icon's picture.
+pub const ICON_SIZE: Dimension = 16;
+
+/// The width of the space each icon is given, label and all, when icons are arranged.
+pub const CELL_WIDTH: Dimension = 64;
+
+/// The height of the space each icon is given, label and all, when icons are arranged.
+pub const CELL_HEIGHT: Dimension = 32;
+
+/// How far the pointer must move, holding an icon, before the icon is dragged.
+const DRAG_THRESHOLD: Dimension = 3;
+
+/// The gap between an icon's picture and its label.
+const LABEL_GAP: Dimension = 2;
+
+/// A drawer, holding other files and drawers.
+pub static DRAWER_ICON: IconImage = [
+    0x0000, 0x3C00, 0x4200, 0x7FFE, 0x4002, 0x4002, 0x4002, 0x4002,
+    0x4002, 0x4002, 0x4002, 0x4002, 0x4002, 0x4002, 0x7FFE, 0x0000,
+];
+
+/// A file of no particular kind.
+pub static DOCUMENT_ICON: IconImage = [
+    0x0000, 0x1FC0, 0x1060, 0x1050, 0x1078, 0x1008, 0x1008, 0x1008,
+    0x1008, 0x1008, 0x1008, 0x1008, 0x1008, 0x1008, 0x1FF8, 0x0000,
+];
+
+/// A file of text.
+pub static TEXT_ICON: IconImage = [
+    0x0000, 0x1FC0, 0x1060, 0x1050, 0x1078, 0x1008, 0x17E8, 0x1008,
+    0x17E8, 0x1008, 0x17E8, 0x1008, 0x1628, 0x1008, 0x1FF8, 0x0000,
+];
+
+/// Every pixel of an icon's picture is drawn.
+static OPAQUE: [u16; 16] = [0xFFFF; 16];
+
+/// An icon in a layer.
+struct Icon {
+    id: IconId,
+
+    /// The top left corner of the space the icon is given.
+    at: Point,
+
+    label: String,
+    image: &'static IconImage,
+    selected: bool,
+}
+
+/// Events an icon layer may answer with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IconEvent {
+    /// Nothing of interest happened.
+    None,
+
+    /// Icons were selected or deselected.
+    /// Use [[IconLayer::selection]] to see which are selected now.
+    SelectionChanged,
+
+    /// The operator double-clicked the icon, to open it.
+    Opened(IconId),
+
+    /// The operator dragged the icon somewhere else in the layer.
+    Moved(IconId),
+
+    /// The operator dragged the icon out of the layer, and let go at this point.
+    /// The icon stays where it was.
+    Dropped(IconId, Point),
+}
+
+/// Shows icons over a background pattern, and lets the operator pick and arrange them.
+pub struct IconLayer {
+    /// The rectangle the layer occupies.
+    area: Rect,
+
+    /// The icons, from back to front.
+    icons: Vec<Icon>,
+
+    /// The identifier the next icon added will receive.
+    next_id: IconId,
+
+    /// Set if the theme's desktop pattern shows between icons, rather than its paper.
+    on_desktop: bool,
+
+    /// The icon the mouse button was pressed on, and where, until it's let go.
+    pressed: Option<(IconId, Point)>,
+
+    /// Set once the pressed icon is being dragged.
+    dragging: bool,
+}
+
+impl IconLayer {
+    /// Creates an empty layer occupying `area`, with the theme's paper showing between icons.
+    pub fn new(area: Rect) -> Self {
+        Self {
+            area,
+            icons: Vec::new(),
+            next_id: 0,
+            on_desktop: false,
+            pressed: None,
+            dragging: false,
+        }
+    }
+
+    /// Shows the theme's desktop pattern between the icons if `on_desktop` is set,
+    /// as for a layer filling the desktop; otherwise, its paper.
+    pub fn set_on_desktop(&mut self, on_desktop: bool) {
+        self.on_desktop = on_desktop;
+    }
+
+    /// Adds an icon labelled `label`, with the picture `image`,
+    /// in the first space left free by the icons already there.
+    ///
+    /// Answers the identifier of the icon.
+    pub fn add(&mut self, label: &str, image: &'static IconImage) -> IconId {
+        let at = self.free_cell();
+        self.add_at(at, label, image)
+    }
+
+    /// Adds an icon labelled `label`, with the picture `image`,
+    /// in the space whose top left corner is `at`.
+    ///
+    /// Answers the identifier of the icon.
+    pub fn add_at(&mut self, at: Point, label: &str, image: &'static IconImage) -> IconId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.icons.push(Icon { id, at, label: label.to_string(), image, selected: false });
+        id
+    }
+
+    /// Removes every icon.
+    pub fn clear(&mut self) {
+        self.icons.clear();
+        self.pressed = None;
+        self.dragging = false;
+    }
+
+    /// Answers every icon, in the order they were added.
+    pub fn icons(&self) -> Vec<IconId> {
+        let mut ids: Vec<IconId> = self.icons.iter().map(|i| i.id).collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Answers the label of icon `id`.
+    pub fn label(&self, id: IconId) -> Option<&str> {
+        self.icon(id).map(|i| i.label.as_str())
+    }
+
+    /// Answers the space icon `id` is given, picture and label.
+    pub fn get_cell(&self, id: IconId) -> Option<Rect> {
+        self.icon(id).map(|i| cell(i.at))
+    }
+
+    /// Answers the selected icons, in the order they were added.
+    pub fn selection(&self) -> Vec<IconId> {
+        let mut ids: Vec<IconId> = self.icons.iter().filter(|i| i.selected).map(|i| i.id).collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Selects icon `id`, or deselects it.
+    ///
+    /// **NOTE:** This does not redraw anything.
+    pub fn set_selected(&mut self, id: IconId, selected: bool) {
+        if let Some(icon) = self.icons.iter_mut().find(|i| i.id == id) {
+            icon.selected = selected;
+        }
+    }
+
+    /// Answers the frontmost icon under the point `pt`, if any.
+    pub fn icon_at(&self, pt: Point) -> Option<IconId> {
+        self.icons.iter().rev().find(|i| rect_contains(cell(i.at), pt)).map(|i| i.id)
+    }
+
+    /// Lines the icons up in rows across the layer, in the order they were added.
+    ///
+    /// **NOTE:** This does not redraw anything.
+    pub fn arrange(&mut self) {
+        self.icons.sort_by_key(|i| i.id);
+        let ((left, top), _) = self.area;
+        let per_row = self.per_row();
+        for (n, icon) in self.icons.iter_mut().enumerate() {
+            let (column, row) = ((n % per_row) as Dimension, (n / per_row) as Dimension);
+            icon.at = (left + column * CELL_WIDTH, top + row * CELL_HEIGHT);
+        }
+    }
+
+    /// Answers how many icons fit across the layer, if they're arranged.
+    fn per_row(&self) -> usize {
+        let ((left, _), (right, _)) = self.area;
+        ((right - left) / CELL_WIDTH).max(1) as usize
+    }
+
+    /// Answers the top left corner of the first arranged space no icon overlaps.
+    fn free_cell(&self) -> Point {
+        let ((left, top), _) = self.area;
+        let per_row = self.per_row();
+        (0..)
+            .map(|n| {
+                let (column, row) = ((n % per_row) as Dimension, (n / per_row) as Dimension);
+                (left + column * CELL_WIDTH, top + row * CELL_HEIGHT)
+            })
+            .find(|&at| !self.icons.iter().any(|i| overlaps(cell(i.at), cell(at))))
+            .expect("there's always more room further down")
+    }
+
+    fn icon(&self, id: IconId) -> Option<&Icon> {
+        self.icons.iter().find(|i| i.id == id)
+    }
+
+    /// Draws `icon` over whatever is there, with its label in the `theme`'s font.
+    fn draw_icon(&self, d: &mut Stencil, theme: &Theme, icon: &Icon) {
+        let ((left, top), (_, bottom)) = cell(icon.at);
+        let picture_left = left + ((CELL_WIDTH - ICON_SIZE) >> 1);
+        draw_masked_image(d, (picture_left, top), icon.image, &OPAQUE);
+
+        // The label sits on a box of paper, centred under the picture,
+        // cut short if it's wider than the space the icon is given.
+        let font = theme.font;
+        let label_top = top + ICON_SIZE + LABEL_GAP;
+        let label_width = (text_width(&icon.label, font) + 2).min(CELL_WIDTH);
+        let label_left = left + ((CELL_WIDTH - label_width) >> 1);
+        let label_right = label_left + label_width;
+        d.filled_rectangle((label_left, label_top), (label_right, bottom), &theme.paper);
+        let mut p = SimplePrinter::new(d, ((label_left + 1, label_top), (label_right, bottom)), font);
+        for ch in icon.label.chars() {
+            if p.head.0 + font.glyph_advance(ch) > label_right - 1 {
+                break;
+            }
+            p.print_char(ch);
+        }
+
+        if icon.selected {
+            d.invert_rectangle((picture_left, top), (picture_left + ICON_SIZE, top + ICON_SIZE));
+            d.invert_rectangle((label_left, label_top), (label_right, bottom));
+        }
+    }
+
+    /// Selects whatever a click `at` the given point should select,
+    /// and answers `true` if the selection changed.
+    fn click(&mut self, at: Point, modifiers: Modifiers) -> bool {
+        let before = self.selection();
+        let hit = self.icon_at(at);
+        let hit_selected = hit.and_then(|id| self.icon(id)).is_some_and(|i| i.selected);
+        let toggle = modifiers.contains(Modifiers::SHIFT);
+        for icon in self.icons.iter_mut() {
+            let is_hit = Some(icon.id) == hit;
+            if toggle {
+                icon.selected ^= is_hit;
+            } else if !hit_selected {
+                icon.selected = is_hit;
+            }
+        }
+        self.selection() != before
+    }
+}
+
+/// Answers the space an icon is given, with its top left corner `at` the given point.
+fn cell(at: Point) -> Rect {
+    let (left, top) = at;
+    ((left, top), (left + CELL_WIDTH, top + CELL_HEIGHT))
+}
+
+/// Answers `true` if rectangles `a` and `b` overlap.
+fn overlaps(a: Rect, b: Rect) -> bool {
+    let ((a_left, a_top), (a_right, a_bottom)) = a;
+    let ((b_left, b_top), (b_right, b_bottom)) = b;
+    (a_left < b_right) && (b_left < a_right) && (a_top < b_bottom) && (b_top < a_bottom)
+}
+
+impl View for IconLayer {
+    /// Draws the background, then every icon from back to front.
+    fn draw(&mut self, med: &mut dyn Mediator) {
+        let theme = med.theme();
+        let background = if self.on_desktop { &theme.desktop } else { &theme.paper };
+        let d = med.borrow_mut_desktop();
+        let ((left, top), (right, bottom)) = self.area;
+        d.filled_rectangle((left, top), (right, bottom), background);
+        for icon in self.icons.iter() {
+            self.draw_icon(d, theme, icon);
+        }
+        med.repaint_all();
+    }
+
+    fn set_area(&mut self, area: Rect) {
+        // The icons keep their places relative to the layer's top left corner.
+        let ((old_left, old_top), _) = self.area;
+        let ((left, top), _) = area;
+        for icon in self.icons.iter_mut() {
+            icon.at = (icon.at.0 - old_left + left, icon.at.1 - old_top + top);
+        }
+        self.area = area;
+    }
+}
+
+impl MouseEventSink<IconEvent> for IconLayer {
+    /// Handles mouse motion events.
+    ///
+    /// Moving the pointer far enough while holding the button down on an icon
+    /// starts dragging it.
+    ///
+    /// Always answers with [[IconEvent::None]].
+    fn pointer_moved(&mut self, med: &mut dyn Mediator, to: Point) -> IconEvent {
+        let Some((id, from)) = self.pressed else {
+            return IconEvent::None;
+        };
+        let far_enough = ((to.0 - from.0).abs() >= DRAG_THRESHOLD) || ((to.1 - from.1).abs() >= DRAG_THRESHOLD);
+        if far_enough && !self.dragging {
+            if let Some(icon) = self.icon(id) {
+                let label = TransferData::Text(icon.label.clone());
+                med.start_drag(DragSession::new(label, cell(icon.at), from));
+                self.dragging = true;
+            }
+        }
+        IconEvent::None
+    }
+
+    /// Handles mouse button-up events.
+    ///
+    /// Letting go of an icon being dragged moves it,
+    /// answering with [[IconEvent::Moved]];
+    /// unless it's let go outside of the layer,
+    /// in which case it stays put, answering with [[IconEvent::Dropped]].
+    /// Otherwise, answers with [[IconEvent::None]].
+    fn button_up(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> IconEvent {
+        if event.button != MouseButton::Left {
+            return IconEvent::None;
+        }
+        let Some((id, (from_x, from_y))) = self.pressed.take() else {
+            return IconEvent::None;
+        };
+        if !std::mem::take(&mut self.dragging) {
+            return IconEvent::None;
+        }
+        let _ = med.end_drag();
+        med.repaint_all();
+
+        if !rect_contains(self.area, event.at) {
+            return IconEvent::Dropped(id, event.at);
+        }
+        let (dx, dy) = (event.at.0 - from_x, event.at.1 - from_y);
+        if let Some(i) = self.icons.iter().position(|i| i.id == id) {
+            // A moved icon comes to the front.
+            let mut icon = self.icons.remove(i);
+            icon.at = (icon.at.0 + dx, icon.at.1 + dy);
+            self.icons.push(icon);
+        }
+        self.draw(med);
+        IconEvent::Moved(id)
+    }
+
+    /// Handles mouse button-down events.
+    ///
+    /// Pressing the left button selects as described for [[IconLayer]],
+    /// answering with [[IconEvent::SelectionChanged]] if the selection changed;
+    /// but a double click on an icon answers with [[IconEvent::Opened]].
+    /// Otherwise, answers with [[IconEvent::None]].
+    fn button_down(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> IconEvent {
+        if (event.button != MouseButton::Left) || !rect_contains(self.area, event.at) {
+            return IconEvent::None;
+        }
+        let hit = self.icon_at(event.at);
+        self.pressed = hit.map(|id| (id, event.at));
+        if let (Some(id), 2) = (hit, event.clicks) {
+            return IconEvent::Opened(id);
+        }
+        if self.click(event.at, event.modifiers) {
+            self.draw(med);
+            IconEvent::SelectionChanged
+        } else {
+            IconEvent::None
+        }
+    }
+
+    /// Handles mouse wheel events.
+    ///
+    /// Currently, always answers with [[IconEvent::None]].
+    fn wheel(&mut self, _med: &mut dyn Mediator, _delta: Point, _modifiers: Modifiers) -> IconEvent {
+        IconEvent::None
+    }
+
+    /// Handles mouse entry events.
+    ///
+    /// Currently, always answers with [[IconEvent::None]].
+    fn enter(&mut self, _med: &mut dyn Mediator, _at: Point) -> IconEvent {
+        IconEvent::None
+    }
+
+    /// Handles mouse exit events.
+    ///
+    /// Currently, always answers with [[IconEvent::None]].
+    fn leave(&mut self, _med: &mut dyn Mediator) -> IconEvent {
+        IconEvent::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IconEvent, IconLayer, CELL_HEIGHT, CELL_WIDTH, DOCUMENT_ICON, DRAWER_ICON};
+    use crate::events::{ButtonEvent, Modifiers, MouseEventSink};
+    use crate::mediator::Mediator;
+    use crate::utils::testing::TestMediator;
+
+    fn layer() -> IconLayer {
+        let mut layer = IconLayer::new(((0, 0), (200, 100)));
+        layer.add("Drawer", &DRAWER_ICON);
+        layer.add("Letter", &DOCUMENT_ICON);
+        layer.add("Notes", &DOCUMENT_ICON);
+        layer
+    }
+
+    #[test]
+    fn clicks_select_and_open() {
+        let mut med = TestMediator::new(256, 128);
+        let mut layer = layer();
+        assert_eq!(layer.get_cell(2), Some(((2 * CELL_WIDTH, 0), (3 * CELL_WIDTH, CELL_HEIGHT))));
+        assert_eq!(layer.icon_at((70, 10)), Some(1));
+
+        assert_eq!(layer.button_down(&mut med, ButtonEvent::left((10, 10))), IconEvent::SelectionChanged);
+        assert_eq!(layer.selection(), vec![0]);
+        let mut shifted = ButtonEvent::left((140, 10));
+        shifted.modifiers = Modifiers::SHIFT;
+        assert_eq!(layer.button_down(&mut med, shifted), IconEvent::SelectionChanged);
+        assert_eq!(layer.selection(), vec![0, 2]);
+
+        // Clicking a selected icon keeps the selection; clicking between icons clears it.
+        assert_eq!(layer.button_down(&mut med, ButtonEvent::left((10, 10))), IconEvent::None);
+        assert_eq!(layer.selection(), vec![0, 2]);
+        assert_eq!(layer.button_down(&mut med, ButtonEvent::left((10, 80))), IconEvent::SelectionChanged);
+        assert_eq!(layer.selection(), vec![]);
+
+        let mut double = ButtonEvent::left((70, 10));
+        double.clicks = 2;
+        assert_eq!(layer.button_down(&mut med, double), IconEvent::Opened(1));
+    }
+
+    #[test]
+    fn dragging_moves_icons() {
+        let mut med = TestMediator::new(256, 128);
+        let mut layer = layer();
+        layer.button_down(&mut med, ButtonEvent::left((70, 10)));
+        layer.pointer_moved(&mut med, (71, 11));
+        assert_eq!(med.drag(), None);
+        layer.pointer_moved(&mut med, (90, 50));
+        assert_eq!(med.drag().and_then(|s| s.data().as_text()), Some("Letter"));
+
+        assert_eq!(layer.button_up(&mut med, ButtonEvent::left((90, 50))), IconEvent::Moved(1));
+        assert_eq!(med.drag(), None);
+        assert_eq!(layer.get_cell(1), Some(((CELL_WIDTH + 20, 40), (2 * CELL_WIDTH + 20, 40 + CELL_HEIGHT))));
+
+        // Dropping an icon outside of the layer leaves it where it was.
+        layer.button_down(&mut med, ButtonEvent::left((10, 10)));
+        layer.pointer_moved(&mut med, (230, 10));
+        assert_eq!(layer.button_up(&mut med, ButtonEvent::left((230, 10))), IconEvent::Dropped(0, (230, 10)));
+        assert_eq!(layer.get_cell(0), Some(((0, 0), (CELL_WIDTH, CELL_HEIGHT))));
+    }
+}
@@ -0,0 +1,306 @@
+//! Layout managers
+//!
+//! Rather than placing gadgets at literal coordinates,
+//! an application can describe how they relate to each other with a [[Layout]]:
+//! stacked in a column, lined up in a row, or arranged in a grid.
+//! [[Layout::arrange]] then works out a rectangle for every item
+//! from the size each one would like to be,
+//! and [[Container::reflow]] moves a container's views there.
+//! When the space available changes,
+//! reflowing again is all it takes to follow it.
+//!
+//! Within the space a layout gives it,
+//! an item may be anchored to either edge or centred,
+//! or stretched to fill the space.
+//! Items which stretch along the length of a row or column
+//! also share out whatever space the row or column has left over;
+//! [[Layout::Glue]] stretches without occupying any space of its own,
+//! pushing its neighbours apart.
+//! When nothing stretches, the space left over is left at the end.
+//! When there isn't enough space to go around,
+//! items still get the size they asked for, and overflow the area given.
+
+use crate::container::ComponentId;
+use crate::types::{Dimension, Dimensions, Rect, Unit};
+
+/// Where an item sits within the space a layout gives it, along one axis.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    /// Against the left or top edge.
+    Start,
+
+    /// Midway between the edges.
+    Center,
+
+    /// Against the right or bottom edge.
+    End,
+
+    /// Filling the space from edge to edge.
+    Stretch,
+}
+
+/// Describes how to arrange a collection of views.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Layout {
+    /// A single view, aligned horizontally by `h` and vertically by `v`.
+    Item { id: ComponentId, h: Align, v: Align },
+
+    /// Empty space which stretches in both directions.
+    Glue,
+
+    /// Items side by side, left to right, `spacing` pixels apart.
+    Row { spacing: Dimension, items: Vec<Layout> },
+
+    /// Items one above another, top to bottom, `spacing` pixels apart.
+    Column { spacing: Dimension, items: Vec<Layout> },
+
+    /// Items in a grid `columns` wide, filled a row at a time.
+    /// Columns are `spacing.0` pixels apart, and rows `spacing.1` pixels apart.
+    /// Every item in a column is as wide as the widest item,
+    /// and every item in a row as tall as the tallest.
+    Grid { columns: usize, spacing: Dimensions, items: Vec<Layout> },
+}
+
+impl Layout {
+    /// Answers a view which keeps its preferred size, in the top-left corner of its space.
+    pub fn item(id: ComponentId) -> Self {
+        Layout::Item { id, h: Align::Start, v: Align::Start }
+    }
+
+    /// Answers a view which fills all of its space.
+    pub fn stretched(id: ComponentId) -> Self {
+        Layout::Item { id, h: Align::Stretch, v: Align::Stretch }
+    }
+
+    /// Answers a view aligned horizontally by `h` and vertically by `v`.
+    pub fn aligned(id: ComponentId, h: Align, v: Align) -> Self {
+        Layout::Item { id, h, v }
+    }
+
+    /// Answers items in a row, `spacing` pixels apart.
+    pub fn row(spacing: Dimension, items: Vec<Layout>) -> Self {
+        Layout::Row { spacing, items }
+    }
+
+    /// Answers items in a column, `spacing` pixels apart.
+    pub fn column(spacing: Dimension, items: Vec<Layout>) -> Self {
+        Layout::Column { spacing, items }
+    }
+
+    /// Answers items in a grid `columns` wide.
+    pub fn grid(columns: usize, spacing: Dimensions, items: Vec<Layout>) -> Self {
+        Layout::Grid { columns: columns.max(1), spacing, items }
+    }
+
+    /// Answers the size the layout would like to be,
+    /// given the preferred size of each view.
+    pub fn preferred_size(&self, preferred: &dyn Fn(ComponentId) -> Dimensions) -> Dimensions {
+        match self {
+            Layout::Item { id, .. } => preferred(*id),
+            Layout::Glue => (0, 0),
+            Layout::Row { spacing, items } => {
+                let sizes: Vec<Dimensions> = items.iter().map(|i| i.preferred_size(preferred)).collect();
+                (total(sizes.iter().map(|s| s.0), *spacing), sizes.iter().map(|s| s.1).max().unwrap_or(0))
+            }
+            Layout::Column { spacing, items } => {
+                let sizes: Vec<Dimensions> = items.iter().map(|i| i.preferred_size(preferred)).collect();
+                (sizes.iter().map(|s| s.0).max().unwrap_or(0), total(sizes.iter().map(|s| s.1), *spacing))
+            }
+            Layout::Grid { columns, spacing, items } => {
+                let (widths, heights) = grid_tracks(*columns, items, preferred);
+                (total(widths.into_iter(), spacing.0), total(heights.into_iter(), spacing.1))
+            }
+        }
+    }
+
+    /// Answers `true` if the layout can grow to fill extra space,
+    /// horizontally if `horizontal` is set, otherwise vertically.
+    pub fn stretches(&self, horizontal: bool) -> bool {
+        match self {
+            Layout::Item { h, v, .. } => (if horizontal { *h } else { *v }) == Align::Stretch,
+            Layout::Glue => true,
+            Layout::Row { items, .. } | Layout::Column { items, .. } | Layout::Grid { items, .. } => {
+                items.iter().any(|i| i.stretches(horizontal))
+            }
+        }
+    }
+
+    /// Works out where every view goes when the layout occupies `area`,
+    /// given the preferred size of each view.
+    ///
+    /// Answers each view's identifier with its rectangle.
+    pub fn arrange(&self, area: Rect, preferred: &dyn Fn(ComponentId) -> Dimensions) -> Vec<(ComponentId, Rect)> {
+        let mut placed = Vec::new();
+        self.arrange_into(area, preferred, &mut placed);
+        placed
+    }
+
+    fn arrange_into(
+        &self,
+        area: Rect,
+        preferred: &dyn Fn(ComponentId) -> Dimensions,
+        placed: &mut Vec<(ComponentId, Rect)>,
+    ) {
+        let ((left, top), (right, bottom)) = area;
+        match self {
+            Layout::Item { id, h, v } => {
+                let (w, ht) = preferred(*id);
+                let (l, r) = align(left, right, w, *h);
+                let (t, b) = align(top, bottom, ht, *v);
+                placed.push((*id, ((l, t), (r, b))));
+            }
+
+            Layout::Glue => (),
+
+            Layout::Row { spacing, items } => {
+                let sizes: Vec<Dimension> = items.iter().map(|i| i.preferred_size(preferred).0).collect();
+                let stretchy: Vec<bool> = items.iter().map(|i| i.stretches(true)).collect();
+                let spans = divide(left, right, &sizes, &stretchy, *spacing);
+                for (item, (l, r)) in items.iter().zip(spans) {
+                    item.arrange_into(((l, top), (r, bottom)), preferred, placed);
+                }
+            }
+
+            Layout::Column { spacing, items } => {
+                let sizes: Vec<Dimension> = items.iter().map(|i| i.preferred_size(preferred).1).collect();
+                let stretchy: Vec<bool> = items.iter().map(|i| i.stretches(false)).collect();
+                let spans = divide(top, bottom, &sizes, &stretchy, *spacing);
+                for (item, (t, b)) in items.iter().zip(spans) {
+                    item.arrange_into(((left, t), (right, b)), preferred, placed);
+                }
+            }
+
+            Layout::Grid { columns, spacing, items } => {
+                let (widths, heights) = grid_tracks(*columns, items, preferred);
+                let stretchy_columns: Vec<bool> = (0..widths.len())
+                    .map(|c| items.iter().skip(c).step_by(*columns).any(|i| i.stretches(true)))
+                    .collect();
+                let stretchy_rows: Vec<bool> = items.chunks(*columns)
+                    .map(|row| row.iter().any(|i| i.stretches(false)))
+                    .collect();
+                let xs = divide(left, right, &widths, &stretchy_columns, spacing.0);
+                let ys = divide(top, bottom, &heights, &stretchy_rows, spacing.1);
+                for (n, item) in items.iter().enumerate() {
+                    let (l, r) = xs[n % columns];
+                    let (t, b) = ys[n / columns];
+                    item.arrange_into(((l, t), (r, b)), preferred, placed);
+                }
+            }
+        }
+    }
+}
+
+/// Answers the total length of `lengths` laid end to end, `spacing` apart.
+fn total(lengths: impl Iterator<Item = Dimension>, spacing: Dimension) -> Dimension {
+    let mut count = 0;
+    let mut sum = 0;
+    for length in lengths {
+        count += 1;
+        sum += length;
+    }
+    sum + spacing * (count - 1).max(0)
+}
+
+/// Answers the widths of a grid's columns and the heights of its rows.
+fn grid_tracks(
+    columns: usize,
+    items: &[Layout],
+    preferred: &dyn Fn(ComponentId) -> Dimensions,
+) -> (Vec<Dimension>, Vec<Dimension>) {
+    let mut widths = vec![0; columns.min(items.len())];
+    let mut heights = vec![0; items.len().div_ceil(columns)];
+    for (n, item) in items.iter().enumerate() {
+        let (w, h) = item.preferred_size(preferred);
+        widths[n % columns] = widths[n % columns].max(w);
+        heights[n / columns] = heights[n / columns].max(h);
+    }
+    (widths, heights)
+}
+
+/// Splits the span from `start` to `end` into consecutive spans `spacing` apart,
+/// one for each of `sizes`.
+/// Space left over is shared between the spans marked `stretchy`,
+/// the first few getting a pixel more when it doesn't divide evenly.
+fn divide(start: Unit, end: Unit, sizes: &[Dimension], stretchy: &[bool], spacing: Dimension) -> Vec<(Unit, Unit)> {
+    let extra = (end - start - total(sizes.iter().copied(), spacing)).max(0);
+    let stretchers = stretchy.iter().filter(|&&s| s).count() as Dimension;
+    let mut spans = Vec::with_capacity(sizes.len());
+    let mut at = start;
+    let mut nth = 0;
+    for (&size, &stretch) in sizes.iter().zip(stretchy) {
+        let mut length = size;
+        if stretch {
+            length += (extra / stretchers) + Dimension::from(nth < extra % stretchers);
+            nth += 1;
+        }
+        spans.push((at, at + length));
+        at += length + spacing;
+    }
+    spans
+}
+
+/// Answers where something `length` long goes between `start` and `end`, aligned by `a`.
+fn align(start: Unit, end: Unit, length: Dimension, a: Align) -> (Unit, Unit) {
+    let length = length.min(end - start);
+    match a {
+        Align::Start => (start, start + length),
+        Align::Center => {
+            let offset = (end - start - length) >> 1;
+            (start + offset, start + offset + length)
+        }
+        Align::End => (end - length, end),
+        Align::Stretch => (start, end),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Align, Layout};
+    use crate::types::Dimensions;
+
+    fn preferred(id: usize) -> Dimensions {
+        [(40, 20), (60, 10), (20, 20)][id]
+    }
+
+    #[test]
+    fn columns_stack_and_stretch() {
+        let column = Layout::column(2, vec![
+            Layout::item(0),
+            Layout::Glue,
+            Layout::aligned(1, Align::End, Align::Start),
+            Layout::aligned(2, Align::Stretch, Align::Center),
+        ]);
+        assert_eq!(column.preferred_size(&preferred), (60, 56));
+        assert_eq!(column.arrange(((10, 10), (110, 100)), &preferred), vec![
+            (0, ((10, 10), (50, 30))),
+            // The glue takes up the 34 spare pixels.
+            (1, ((50, 68), (110, 78))),
+            (2, ((10, 80), (110, 100))),
+        ]);
+    }
+
+    #[test]
+    fn rows_share_extra_space_between_stretchy_items() {
+        let row = Layout::row(0, vec![Layout::stretched(0), Layout::item(1), Layout::stretched(2)]);
+        assert_eq!(row.arrange(((0, 0), (125, 20)), &preferred), vec![
+            (0, ((0, 0), (43, 20))),
+            (1, ((43, 0), (103, 10))),
+            (2, ((103, 0), (125, 20))),
+        ]);
+    }
+
+    #[test]
+    fn grids_line_up_columns_and_rows() {
+        let grid = Layout::grid(2, (4, 4), vec![
+            Layout::item(0),
+            Layout::item(1),
+            Layout::aligned(2, Align::Center, Align::End),
+        ]);
+        assert_eq!(grid.preferred_size(&preferred), (104, 44));
+        assert_eq!(grid.arrange(((0, 0), (200, 200)), &preferred), vec![
+            (0, ((0, 0), (40, 20))),
+            (1, ((44, 0), (104, 10))),
+            (2, ((10, 24), (30, 44))),
+        ]);
+    }
+}
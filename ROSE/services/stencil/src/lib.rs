@@ -4,12 +4,36 @@ pub mod simple_bitmap_font;
 pub mod sysfont_bsw_9;
 pub mod utils;
 pub mod simple_printer;
+pub mod wrapping_printer;
+pub mod text_style;
 pub mod chase;
+pub mod hyphenation;
+pub mod paragraph;
+pub mod gap_buffer;
 pub mod events;
+pub mod focus;
+pub mod timers;
 pub mod view;
 pub mod mediator;
+pub mod container;
+pub mod layout;
+pub mod dialog;
+pub mod windows;
+pub mod pointer;
+pub mod transfer;
+pub mod icons;
+pub mod theme;
 
 pub mod gadgets {
     pub mod proportional;
     pub mod boolean;
+    pub mod ruler;
+    pub mod text_field;
+    pub mod menu;
+    pub mod scroll_bar;
+    pub mod checkbox;
+    pub mod radio;
+    pub mod cycle;
+    pub mod list;
+    pub mod requester;
 }
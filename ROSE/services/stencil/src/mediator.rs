@@ -1,6 +1,11 @@
 //! Mediator
 
+use crate::focus::FocusId;
+use crate::pointer::PointerShape;
 use crate::stencil::Stencil;
+use crate::theme::Theme;
+use crate::timers::TimerId;
+use crate::transfer::{DragSession, TransferData};
 
 /// Provides an interface
 /// to the host application environment.
@@ -29,5 +34,73 @@ pub trait Mediator {
     /// query its dimensions,
     /// etc.
     fn borrow_mut_desktop(&mut self) -> &mut Stencil;
-}
 
+    /// Answers the focus owner:
+    /// the gadget keyboard input should go to, if any.
+    fn focus(&self) -> Option<FocusId>;
+
+    /// Makes `focus` the focus owner.
+    /// `None` leaves nothing with the focus.
+    ///
+    /// **NOTE:** This does not redraw anything.
+    /// Gadgets gaining or losing the focus must be redrawn by the caller.
+    fn set_focus(&mut self, focus: Option<FocusId>);
+
+    /// Starts a timer which fires `delay` milliseconds from now.
+    /// If `periodic` is set, it keeps firing every `delay` milliseconds
+    /// until cancelled.
+    ///
+    /// Answers the timer's identifier,
+    /// which [[TimerEventSink::timer_fired]] reports whenever it fires.
+    fn start_timer(&mut self, delay: u32, periodic: bool) -> TimerId;
+
+    /// Cancels the timer `id`, so that it won't fire again.
+    fn cancel_timer(&mut self, id: TimerId);
+
+    /// Requests a single [[TimerEventSink::idle]] event,
+    /// delivered once no other events are waiting.
+    /// Request another from the idle event to keep them coming.
+    fn request_idle(&mut self);
+
+    /// Changes the shape of the mouse pointer,
+    /// as when it moves over editable text, or the application gets busy.
+    ///
+    /// The host draws the pointer over the desktop only as it's shown;
+    /// it never appears in [[Mediator::borrow_mut_desktop]].
+    fn set_pointer(&mut self, shape: &'static PointerShape);
+
+    /// Answers a copy of whatever is on the clipboard, if anything.
+    fn clipboard(&self) -> Option<TransferData>;
+
+    /// Puts `data` on the clipboard, replacing whatever was there.
+    ///
+    /// Hosts which share a clipboard with other programs share the text put on it;
+    /// bitmaps may stay within ROSE.
+    fn set_clipboard(&mut self, data: TransferData);
+
+    /// Starts dragging something about the desktop.
+    ///
+    /// Until the drag ends, the host moves the session with the pointer,
+    /// drawing its outline inverted over the desktop as it's shown.
+    /// Starting another drag abandons the first.
+    fn start_drag(&mut self, session: DragSession);
+
+    /// Answers the drag in progress, if any.
+    fn drag(&self) -> Option<&DragSession>;
+
+    /// Ends the drag in progress, rubbing out its outline.
+    ///
+    /// Answers the session,
+    /// so its data can be handed to the view it was dropped on;
+    /// or `None` if nothing was being dragged.
+    fn end_drag(&mut self) -> Option<DragSession>;
+
+    /// Answers the theme gadgets draw themselves in.
+    fn theme(&self) -> &'static Theme;
+
+    /// Makes `theme` the one gadgets draw themselves in from now on.
+    ///
+    /// **NOTE:** This does not redraw anything.
+    /// To restyle what's already on the desktop, the application must redraw it.
+    fn set_theme(&mut self, theme: &'static Theme);
+}
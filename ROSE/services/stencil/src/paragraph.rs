@@ -0,0 +1,210 @@
+//! Paragraph formatting.
+//!
+//! A [[ParagraphFormat]] describes how the lines of a paragraph sit between the margins,
+//! much like the ruler of a word processor does:
+//! how far they're set in from either margin,
+//! where tabs take the text following them,
+//! and how far apart the lines are.
+//!
+//! [[Forme]] honours the format while laying text out,
+//! and [[WrappingPrinter]] while printing it.
+//! The [[RulerView]] gadget lets the operator edit one.
+
+use crate::types::Dimension;
+
+/// Decimal tab stops align the first of these characters in the text following them.
+pub const DECIMAL_POINT: char = '.';
+
+/// How a tab stop positions the text following a tab.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TabAlignment {
+    /// The text starts at the stop.
+    Left,
+
+    /// The text is centered on the stop.
+    Centre,
+
+    /// The text ends at the stop.
+    Right,
+
+    /// The text's decimal point sits at the stop.
+    /// Text without one ends at the stop instead.
+    Decimal,
+}
+
+/// A place for tabs to take the text following them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TabStop {
+    /// Where the stop lies, measured from the left-hand margin.
+    pub position: Dimension,
+
+    /// How the text following a tab is aligned against the stop.
+    pub alignment: TabAlignment,
+}
+
+impl TabStop {
+    /// Creates a new tab stop at `position`, which aligns text according to `alignment`.
+    pub fn new(position: Dimension, alignment: TabAlignment) -> Self {
+        Self { position, alignment }
+    }
+
+    /// Answers how far a tab at `from` advances to reach this stop.
+    ///
+    /// The `width` is that of the text following the tab,
+    /// and `to_point` its width up to the decimal point.
+    /// A tab never moves backwards;
+    /// text too wide to align simply follows the tab directly.
+    pub fn advance(&self, from: Dimension, width: Dimension, to_point: Dimension) -> Dimension {
+        let anchor = match self.alignment {
+            TabAlignment::Left => 0,
+            TabAlignment::Centre => width / 2,
+            TabAlignment::Right => width,
+            TabAlignment::Decimal => to_point,
+        };
+        (self.position - from - anchor).max(0)
+    }
+}
+
+/// How far apart successive lines of a paragraph are.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineSpacing {
+    /// Lines follow each other directly.
+    Single,
+
+    /// Lines are half a line apart.
+    OneAndAHalf,
+
+    /// Lines are a whole line apart.
+    Double,
+
+    /// Lines start exactly this many pixels apart, whatever the font.
+    Exactly(Dimension),
+}
+
+impl LineSpacing {
+    /// Answers the distance from the top of one line to the top of the next,
+    /// for a font `height` pixels tall.
+    pub fn line_height(&self, height: Dimension) -> Dimension {
+        match *self {
+            LineSpacing::Single => height,
+            LineSpacing::OneAndAHalf => height + (height >> 1),
+            LineSpacing::Double => height << 1,
+            LineSpacing::Exactly(pixels) => pixels,
+        }
+    }
+}
+
+/// Describes how the lines of a paragraph are laid out.
+///
+/// All measurements are in pixels.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParagraphFormat {
+    /// How far lines are set in from the left-hand margin.
+    pub left_indent: Dimension,
+
+    /// How far lines are set in from the right-hand margin.
+    pub right_indent: Dimension,
+
+    /// How much further the first line of a paragraph is set in than the rest.
+    /// Negative values make a hanging indent.
+    pub first_line_indent: Dimension,
+
+    /// The tab stops, in no particular order.
+    pub tab_stops: Vec<TabStop>,
+
+    /// The distance between the evenly spaced, left-aligned default tab stops
+    /// which take over past the last of the `tab_stops`.
+    /// If zero, tabs past the last stop are treated as ordinary whitespace.
+    pub default_tab_interval: Dimension,
+
+    /// How far apart successive lines are.
+    pub line_spacing: LineSpacing,
+}
+
+/// The format used when no other is given:
+/// no indents, no tab stops, and single spacing.
+pub static PLAIN_PARAGRAPH: ParagraphFormat = ParagraphFormat {
+    left_indent: 0,
+    right_indent: 0,
+    first_line_indent: 0,
+    tab_stops: Vec::new(),
+    default_tab_interval: 0,
+    line_spacing: LineSpacing::Single,
+};
+
+impl Default for ParagraphFormat {
+    fn default() -> Self {
+        PLAIN_PARAGRAPH.clone()
+    }
+}
+
+impl ParagraphFormat {
+    /// Answers how far a line is set in from the left-hand margin,
+    /// depending on whether or not it's the `first_line` of its paragraph.
+    pub fn indent(&self, first_line: bool) -> Dimension {
+        let indent = if first_line { self.left_indent + self.first_line_indent } else { self.left_indent };
+        indent.max(0)
+    }
+
+    /// Answers the first tab stop lying past `from`, if any.
+    pub fn next_tab_stop(&self, from: Dimension) -> Option<TabStop> {
+        let stop = self.tab_stops.iter().filter(|t| t.position > from).min_by_key(|t| t.position).copied();
+        stop.or_else(|| {
+            let interval = self.default_tab_interval;
+            (interval > 0).then(|| TabStop::new((from.max(0) / interval + 1) * interval, TabAlignment::Left))
+        })
+    }
+
+    /// Adds a tab `stop`, replacing any other stop at the same position.
+    pub fn set_tab_stop(&mut self, stop: TabStop) {
+        self.remove_tab_stop(stop.position);
+        self.tab_stops.push(stop);
+    }
+
+    /// Removes the tab stop at `position`, answering with it if there was one.
+    pub fn remove_tab_stop(&mut self, position: Dimension) -> Option<TabStop> {
+        let i = self.tab_stops.iter().position(|t| t.position == position)?;
+        Some(self.tab_stops.remove(i))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LineSpacing, ParagraphFormat, TabAlignment, TabStop};
+
+    #[test]
+    fn tabs_align_text_against_stops() {
+        let stop = |alignment| TabStop::new(40, alignment);
+        assert_eq!(stop(TabAlignment::Left).advance(10, 12, 4), 30);
+        assert_eq!(stop(TabAlignment::Centre).advance(10, 12, 4), 24);
+        assert_eq!(stop(TabAlignment::Right).advance(10, 12, 4), 18);
+        assert_eq!(stop(TabAlignment::Decimal).advance(10, 12, 4), 26);
+
+        // Text too wide to align follows the tab directly.
+        assert_eq!(stop(TabAlignment::Right).advance(10, 50, 50), 0);
+    }
+
+    #[test]
+    fn next_tab_stop_falls_back_to_default_interval() {
+        let mut f = ParagraphFormat { default_tab_interval: 16, ..Default::default() };
+        f.set_tab_stop(TabStop::new(40, TabAlignment::Right));
+        f.set_tab_stop(TabStop::new(20, TabAlignment::Left));
+        assert_eq!(f.next_tab_stop(0), Some(TabStop::new(20, TabAlignment::Left)));
+        assert_eq!(f.next_tab_stop(20), Some(TabStop::new(40, TabAlignment::Right)));
+        assert_eq!(f.next_tab_stop(40), Some(TabStop::new(48, TabAlignment::Left)));
+
+        f.default_tab_interval = 0;
+        assert_eq!(f.next_tab_stop(40), None);
+        assert_eq!(f.remove_tab_stop(20), Some(TabStop::new(20, TabAlignment::Left)));
+        assert_eq!(f.tab_stops.len(), 1);
+    }
+
+    #[test]
+    fn indents_and_spacing() {
+        let f = ParagraphFormat { left_indent: 8, first_line_indent: -12, ..Default::default() };
+        assert_eq!(f.indent(false), 8);
+        assert_eq!(f.indent(true), 0);
+        assert_eq!(LineSpacing::OneAndAHalf.line_height(9), 13);
+        assert_eq!(LineSpacing::Double.line_height(9), 18);
+    }
+}
@@ -0,0 +1,144 @@
+//! Mouse pointers
+//!
+//! Not every machine ROSE runs on has a hardware cursor,
+//! so the host draws the pointer itself,
+//! straight onto the desktop just before it's shown,
+//! and takes it off again straight after.
+//! Applications never see it in [[Mediator::borrow_mut_desktop]];
+//! they only choose which [[PointerShape]] it wears,
+//! through [[Mediator::set_pointer]].
+
+use crate::stencil::{SavedArea, Stencil};
+use crate::types::{Point, Rect, Unit};
+use crate::utils::draw_masked_image;
+
+/// The width and height of every pointer, in pixels.
+pub const POINTER_SIZE: Unit = 16;
+
+/// A 16x16 pixel pointer image, one `u16` per row,
+/// with the leftmost pixel in the most significant bit.
+///
+/// Pixels set in the `mask` are drawn:
+/// black where they're also set in the `image`, white where they're not.
+/// The rest of the desktop shows through.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PointerShape {
+    /// The black pixels of the pointer.
+    pub image: [u16; 16],
+
+    /// The pixels of the pointer which hide the desktop.
+    pub mask: [u16; 16],
+
+    /// The pixel which sits exactly at the mouse position.
+    pub hotspot: Point,
+}
+
+impl PointerShape {
+    /// Creates a pointer from its black pixels,
+    /// outlined in white one pixel thick so it shows up against any background.
+    pub const fn outlined(image: [u16; 16], hotspot: Point) -> Self {
+        let mut mask = [0u16; 16];
+        let mut y = 0;
+        while y < 16 {
+            let row = image[y] | (image[y] << 1) | (image[y] >> 1);
+            mask[y] |= row;
+            if y > 0 {
+                mask[y - 1] |= row;
+            }
+            if y < 15 {
+                mask[y + 1] |= row;
+            }
+            y += 1;
+        }
+        Self { image, mask, hotspot }
+    }
+
+    /// Answers the rectangle the pointer covers when the mouse is `at` the given point.
+    pub fn area(&self, at: Point) -> Rect {
+        let (left, top) = (at.0 - self.hotspot.0, at.1 - self.hotspot.1);
+        ((left, top), (left + POINTER_SIZE, top + POINTER_SIZE))
+    }
+
+    /// Draws the pointer onto `st` with its hotspot `at` the given point.
+    ///
+    /// Answers the pixels it covered,
+    /// so that [[Stencil::restore_area]] can take it off again.
+    pub fn draw(&self, st: &mut Stencil, at: Point) -> SavedArea {
+        let ((left, top), (right, bottom)) = self.area(at);
+        let saved = st.save_area((left, top), (right, bottom));
+
+        draw_masked_image(st, (left, top), &self.image, &self.mask);
+        saved
+    }
+}
+
+/// The usual pointer, for pointing and clicking.
+pub static ARROW: PointerShape = PointerShape::outlined(
+    [
+        0x0000, 0x4000, 0x6000, 0x7000, 0x7800, 0x7C00, 0x7E00, 0x7F00,
+        0x7F80, 0x7C00, 0x6C00, 0x4600, 0x0600, 0x0300, 0x0300, 0x0000,
+    ],
+    (1, 1),
+);
+
+/// An hourglass, shown while the application is too busy to respond.
+pub static BUSY: PointerShape = PointerShape::outlined(
+    [
+        0x0000, 0x7FFC, 0x2008, 0x2008, 0x17D0, 0x0BA0, 0x0540, 0x0280,
+        0x0280, 0x0440, 0x0920, 0x1290, 0x2548, 0x2AA8, 0x7FFC, 0x0000,
+    ],
+    (7, 7),
+);
+
+/// An I-beam, shown over text which can be edited.
+pub static I_BEAM: PointerShape = PointerShape::outlined(
+    [
+        0x0000, 0x06C0, 0x0100, 0x0100, 0x0100, 0x0100, 0x0100, 0x0100,
+        0x0380, 0x0100, 0x0100, 0x0100, 0x0100, 0x0100, 0x06C0, 0x0000,
+    ],
+    (7, 8),
+);
+
+/// Crosshairs, for picking out a single pixel.
+pub static CROSSHAIR: PointerShape = PointerShape::outlined(
+    [
+        0x0000, 0x0100, 0x0100, 0x0100, 0x0100, 0x0100, 0x0000, 0x7D7C,
+        0x0000, 0x0100, 0x0100, 0x0100, 0x0100, 0x0100, 0x0000, 0x0000,
+    ],
+    (7, 7),
+);
+
+#[cfg(test)]
+mod tests {
+    use super::{ARROW, CROSSHAIR, I_BEAM};
+    use crate::theme::CLASSIC_THEME;
+    use crate::utils::draw_desktop;
+    use crate::utils::testing::{blank_stencil, inked_pixels};
+
+    #[test]
+    fn drawing_saves_what_it_covers() {
+        let mut st = blank_stencil(64, 32);
+        draw_desktop(&mut st, &CLASSIC_THEME);
+        let before = st.borrow_bits().to_vec();
+
+        // Partly off the edge of the desktop, too.
+        for at in [(20, 10), (60, 30), (0, 0)] {
+            let saved = I_BEAM.draw(&mut st, at);
+            assert_ne!(st.borrow_bits(), &before[..]);
+            st.restore_area(&saved);
+            assert_eq!(st.borrow_bits(), &before[..]);
+        }
+    }
+
+    #[test]
+    fn hotspot_sits_at_the_mouse_position() {
+        let mut st = blank_stencil(64, 32);
+        let _ = ARROW.draw(&mut st, (30, 20));
+        let inked = inked_pixels(&st);
+        assert_eq!(inked.first(), Some(&(30, 20)));
+
+        let mut st = blank_stencil(64, 32);
+        let _ = CROSSHAIR.draw(&mut st, (30, 20));
+        assert!(inked_pixels(&st).contains(&(30, 20)));
+    }
+}
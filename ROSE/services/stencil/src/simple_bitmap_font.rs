@@ -1,4 +1,65 @@
 use crate::types::{Unit, Dimension};
+use crate::chase::FontProvider;
+
+/// A contiguous run of Unicode code points,
+/// every one of which has a glyph in a font.
+///
+/// Both `first` and `last` are inclusive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GlyphRange {
+    /// The first code point covered by this range.
+    pub first: char,
+    /// The last code point covered by this range.
+    pub last: char,
+}
+
+impl GlyphRange {
+    /// Answers the number of glyphs covered by this range.
+    pub fn len(&self) -> usize {
+        (self.last as usize + 1).saturating_sub(self.first as usize)
+    }
+
+    /// Answers `true` if the range covers no code points at all.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Answers `true` if the code point `ch` falls within this range.
+    pub fn contains(&self, ch: char) -> bool {
+        (self.first <= ch) && (ch <= self.last)
+    }
+}
+
+/// Placement metrics for a single glyph.
+///
+/// A glyph's bitmap need not coincide with the space it occupies on a line of text.
+/// The *bearing* places the bitmap relative to the print head,
+/// while the *advance* says how far the print head moves afterwards.
+/// This allows glyphs to overhang their neighbors (e.g., an italic `f`)
+/// or to have space built in to either side.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GlyphMetrics {
+    /// Horizontal offset from the print head to the left edge of the glyph's bitmap.
+    /// Negative values place the bitmap to the left of the print head.
+    pub bearing: Unit,
+
+    /// How far the print head moves after printing the glyph.
+    pub advance: Dimension,
+}
+
+/// An adjustment to the space between a specific pair of adjacent characters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KerningPair {
+    /// The character on the left-hand side of the pair.
+    pub left: char,
+
+    /// The character on the right-hand side of the pair.
+    pub right: char,
+
+    /// Pixels to add to the advance of `left` when it's immediately followed by `right`.
+    /// This is usually negative, to tuck pairs like `AV` closer together.
+    pub adjust: Unit,
+}
 
 /// A very simple bitmapped font descriptor.
 pub struct SimpleBitmapFont<'a> {
@@ -13,19 +74,22 @@ pub struct SimpleBitmapFont<'a> {
     pub bits: &'a [u8],
     /// Reference to an array of horizontal coordinates.
     /// Each coordinate indicates the left edge
-    /// of the glyph for the corresponding character index,
-    /// adjusted for the lowest character.
+    /// of the glyph for the corresponding glyph index.
     ///
-    /// Note that index 0 corresponds to character `lowest_char`,
-    /// and increases monotonically from there.
+    /// Glyph indices are assigned consecutively
+    /// to the code points covered by `ranges`,
+    /// in the order the ranges are listed.
+    /// For example,
+    /// if `ranges` covers `' '..='~'` followed by `'À'..='ÿ'`,
+    /// then index 0 corresponds to `' '`,
+    /// index 94 corresponds to `'~'`,
+    /// and index 95 corresponds to `'À'`.
     ///
-    /// The largest index supported is `highest_char`-`lowest_char`+2.
-    /// At `highest_char`-`lowest_char`+1,
+    /// Immediately after the last covered glyph,
     /// you'll find the left edge of the *unknown* glyph
     /// (usually a non-descript rectangle).
-    ///
-    /// At `highest_char`-`lowest_char`+2,
-    /// you'll find the pixel coordinate of the right-hand edge of the
+    /// The entry after that
+    /// holds the pixel coordinate of the right-hand edge of the
     /// font bitmap.
     ///
     /// The *width of any glyph*
@@ -33,43 +97,239 @@ pub struct SimpleBitmapFont<'a> {
     /// `left_edges[n+1] - left_edges[n]`,
     /// assuming `n` is scoped accordingly.
     pub left_edges: &'a [u16],
-    /// Indicates the lowest character index supported in this font.
-    /// Usually, this will be set to 32 (the ASCII space character).
-    pub lowest_char: u8,
-    /// Indicates the largest character index supported in this font.
-    /// Usually, this will be set to 127 for fonts that cover the
-    /// 96 graphic characters in the ASCII standard.
-    pub highest_char: u8,
+    /// The ranges of Unicode code points this font supplies glyphs for.
+    /// Ranges need not be contiguous with each other,
+    /// which allows a font to cover, e.g., ASCII and a handful of Latin-1 letters
+    /// without having to carry glyphs for everything in between.
+    ///
+    /// Ranges should not overlap.
+    /// Any code point not covered by a range
+    /// is rendered with the unknown glyph.
+    pub ranges: &'a [GlyphRange],
+    /// Per-glyph placement metrics, indexed by glyph index,
+    /// including an entry for the unknown glyph.
+    ///
+    /// If empty,
+    /// every glyph has a bearing of zero
+    /// and advances by exactly the width of its bitmap.
+    pub metrics: &'a [GlyphMetrics],
+    /// Kerning pairs, sorted by `left` and then by `right`.
+    ///
+    /// Pairs not listed here receive no adjustment.
+    pub kerning: &'a [KerningPair],
+}
+
+impl<'a> SimpleBitmapFont<'a> {
+    /// Answers the number of glyphs covered by the font's ranges,
+    /// not counting the unknown glyph.
+    pub fn glyph_count(&self) -> usize {
+        self.ranges.iter().map(GlyphRange::len).sum()
+    }
+
+    /// Answers the glyph index for the code point `ch`.
+    ///
+    /// If the font has no glyph for `ch`,
+    /// the index of the unknown glyph is returned instead.
+    pub fn glyph_index(&self, ch: char) -> usize {
+        let mut base = 0;
+        for r in self.ranges {
+            if r.contains(ch) {
+                return base + (ch as usize - r.first as usize);
+            }
+            base += r.len();
+        }
+        base
+    }
+
+    /// Answers `true` if the font has a glyph for the code point `ch`.
+    pub fn has_glyph(&self, ch: char) -> bool {
+        self.ranges.iter().any(|r| r.contains(ch))
+    }
+
+    /// Answers the left and right edges of the glyph for `ch` in the font bitmap.
+    /// Like other measurements in ROSE, the left edge is inclusive, while the right edge is
+    /// exclusive.
+    pub fn glyph_edges(&self, ch: char) -> (Unit, Unit) {
+        let glyph_index = self.glyph_index(ch);
+        let left_edge = self.left_edges[glyph_index];
+        let right_edge = self.left_edges[glyph_index + 1];
+
+        (left_edge as Unit, right_edge as Unit)
+    }
+
+    /// Answers the pixel width of the bitmap for the glyph for `ch`.
+    ///
+    /// This is not necessarily how far the print head moves after printing the glyph;
+    /// see [[SimpleBitmapFont::glyph_advance]] for that.
+    pub fn glyph_width(&self, ch: char) -> Dimension {
+        let (left_edge, right_edge) = self.glyph_edges(ch);
+
+        right_edge - left_edge
+    }
+
+    /// Answers the placement metrics for the glyph for `ch`.
+    ///
+    /// Fonts without explicit metrics derive them from the glyph's bitmap.
+    pub fn glyph_metrics(&self, ch: char) -> GlyphMetrics {
+        match self.metrics.get(self.glyph_index(ch)) {
+            Some(m) => *m,
+            None => GlyphMetrics {
+                bearing: 0,
+                advance: self.glyph_width(ch),
+            },
+        }
+    }
+
+    /// Answers how far the print head moves after printing the glyph for `ch`.
+    pub fn glyph_advance(&self, ch: char) -> Dimension {
+        self.glyph_metrics(ch).advance
+    }
+
+    /// Answers the adjustment to apply between `left` and `right`
+    /// when they're printed next to each other.
+    pub fn kerning(&self, left: char, right: char) -> Unit {
+        self.kerning
+            .binary_search_by(|p| (p.left, p.right).cmp(&(left, right)))
+            .map(|i| self.kerning[i].adjust)
+            .unwrap_or(0)
+    }
+}
+
+impl<'a> FontProvider for SimpleBitmapFont<'a> {
+    fn get_glyph_width(&self, ch: char) -> usize {
+        self.glyph_advance(ch) as usize
+    }
+
+    fn get_kerning(&self, left: char, right: char) -> isize {
+        self.kerning(left, right)
+    }
 }
 
 /// Compute the pixel width of the string,
 /// if a printer were to draw the string onto a stencil.
+/// This is the sum of the advances of every glyph,
+/// adjusted for kerning between adjacent characters.
+///
+/// The `font` may be a plain [[SimpleBitmapFont]],
+/// or a [[StyledFont]] if the text will be printed in a style
+/// which affects glyph widths.
 ///
 /// The `text` argument should not include control characters
 /// like new-lines or carriage-returns.
 /// All control characters will be treated as glyphs to be printed.
 /// If the font doesn't support these glyphs,
 /// the unknown glyph will be used.
-pub fn text_width(text: &str, font: &SimpleBitmapFont) -> Dimension {
-    text.bytes().map(|b| {
-        // If not representable in the glyph set of the font, assume the undefined character glyph,
-        // which by definition, is always at highest_char+1 mod 256.
-        let highest_character = font.highest_char;
-        let lowest_character = font.lowest_char;
-        let mut glyph_index = b as usize;
-
-        if (b < lowest_character) || (b > highest_character) {
-            glyph_index = (highest_character as usize).overflowing_add(1).0;
+pub fn text_width(text: &str, font: &dyn FontProvider) -> Dimension {
+    let mut width = 0;
+    let mut previous = None;
+    for ch in text.chars() {
+        if let Some(p) = previous {
+            width += font.get_kerning(p, ch);
+        }
+        width += font.get_glyph_width(ch) as Dimension;
+        previous = Some(ch);
+    }
+    width
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GlyphMetrics, GlyphRange, KerningPair, SimpleBitmapFont, text_width};
+
+    // A font with a gap between its two ranges.
+    // Glyph n is n+1 pixels wide, so widths double as identities.
+    static RANGES: [GlyphRange; 2] = [
+        GlyphRange { first: 'a', last: 'c' },
+        GlyphRange { first: 'é', last: 'é' },
+    ];
+
+    static LEFT_EDGES: [u16; 6] = [0, 1, 3, 6, 10, 15];
+
+    fn sparse_font() -> SimpleBitmapFont<'static> {
+        SimpleBitmapFont {
+            span: 2,
+            height: 1,
+            baseline: 1,
+            bits: &[0, 0],
+            left_edges: &LEFT_EDGES,
+            ranges: &RANGES,
+            metrics: &[],
+            kerning: &[],
         }
-        glyph_index -= lowest_character as usize;
+    }
 
-        // Let's expand this to a valid array index.
+    static METRICS: [GlyphMetrics; 5] = [
+        GlyphMetrics { bearing: 0, advance: 1 },
+        GlyphMetrics { bearing: -1, advance: 1 },
+        GlyphMetrics { bearing: 1, advance: 5 },
+        GlyphMetrics { bearing: 0, advance: 4 },
+        GlyphMetrics { bearing: 0, advance: 5 },
+    ];
 
-        let left_edge = font.left_edges[glyph_index];
-        let right_edge = font.left_edges[glyph_index + 1];
-        let glyph_width = right_edge - left_edge;
+    static KERNING: [KerningPair; 2] = [
+        KerningPair { left: 'a', right: 'b', adjust: -1 },
+        KerningPair { left: 'c', right: 'a', adjust: 2 },
+    ];
 
-        glyph_width as Dimension
-    }).sum()
-}
+    fn kerned_font() -> SimpleBitmapFont<'static> {
+        SimpleBitmapFont {
+            metrics: &METRICS,
+            kerning: &KERNING,
+            ..sparse_font()
+        }
+    }
 
+    #[test]
+    fn glyph_indices_follow_ranges() {
+        let f = sparse_font();
+        assert_eq!(f.glyph_count(), 4);
+        assert_eq!(f.glyph_index('a'), 0);
+        assert_eq!(f.glyph_index('c'), 2);
+        assert_eq!(f.glyph_index('é'), 3);
+    }
+
+    #[test]
+    fn unmapped_code_points_use_unknown_glyph() {
+        let f = sparse_font();
+        assert_eq!(f.glyph_index('d'), 4);
+        assert_eq!(f.glyph_index('€'), 4);
+        assert!(!f.has_glyph('d'));
+        assert_eq!(f.glyph_width('d'), 5);
+    }
+
+    #[test]
+    fn width_counts_chars_not_bytes() {
+        let f = sparse_font();
+        // 'é' is two bytes in UTF-8, but one glyph.
+        assert_eq!(text_width("é", &f), 4);
+        assert_eq!(text_width("abé", &f), 1 + 2 + 4);
+        // '€' is three bytes in UTF-8, but one unknown glyph.
+        assert_eq!(text_width("€", &f), 5);
+    }
+
+    #[test]
+    fn derived_metrics_match_bitmap() {
+        let f = sparse_font();
+        assert_eq!(f.glyph_metrics('c'), GlyphMetrics { bearing: 0, advance: 3 });
+        assert_eq!(f.kerning('a', 'b'), 0);
+    }
+
+    #[test]
+    fn explicit_metrics_override_bitmap() {
+        let f = kerned_font();
+        // 'b' is two pixels wide, but overhangs its one-pixel advance to the left.
+        assert_eq!(f.glyph_width('b'), 2);
+        assert_eq!(f.glyph_metrics('b'), GlyphMetrics { bearing: -1, advance: 1 });
+        assert_eq!(text_width("b", &f), 1);
+    }
+
+    #[test]
+    fn kerning_adjusts_adjacent_pairs_only() {
+        let f = kerned_font();
+        assert_eq!(f.kerning('a', 'b'), -1);
+        assert_eq!(f.kerning('b', 'a'), 0);
+        assert_eq!(text_width("ab", &f), 1 + 1 - 1);
+        assert_eq!(text_width("ca", &f), 5 + 1 + 2);
+        assert_eq!(text_width("cab", &f), 5 + 1 + 1 + 2 - 1);
+    }
+}
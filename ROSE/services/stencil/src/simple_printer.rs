@@ -1,7 +1,9 @@
 use bitblt::{BlitOp, BlitContext, blit_rect};
-use crate::types::{Rect, Unit, Point};
+use crate::types::{Rect, Point, Unit, Dimension};
+use crate::chase::SOFT_HYPHEN;
 use crate::simple_bitmap_font::SimpleBitmapFont;
 use crate::stencil::Stencil;
+use crate::text_style::{TextStyle, italic_shift};
 
 /// This structure is an attempt to emulate a very basic dot-matrix printer,
 /// which prints onto a Stencil instead of actual paper.  This is a useful
@@ -23,6 +25,13 @@ pub struct SimplePrinter<'a, 'b> {
 
     /// The blitter operation to perform when pasting glyphs to the stencil.
     pub blit_op: BlitOp,
+
+    /// The style synthesized onto every glyph printed.
+    pub style: TextStyle,
+
+    /// The character most recently printed on the current line, if any.
+    /// Used to kern the next character against it.
+    previous: Option<char>,
 }
 
 impl<'a, 'b> SimplePrinter<'a, 'b> {
@@ -38,7 +47,8 @@ impl<'a, 'b> SimplePrinter<'a, 'b> {
     /// After creation, the print head will be placed in the upper left-hand corner of the margins
     /// rectangle.  The default `blit_op` will be set to render black text on a white surface,
     /// which is usually correct.  If a different blitter operation is required, `blit_op` may be
-    /// adjusted prior to the next print.
+    /// adjusted prior to the next print.  Likewise, text prints in plain style until `style` is
+    /// changed.
     pub fn new(stencil: &'a mut Stencil, margins: Rect, font: &'b SimpleBitmapFont<'b>) -> Self {
         let (left, top) = (margins.0.0, margins.0.1 + font.baseline);
 
@@ -49,12 +59,15 @@ impl<'a, 'b> SimplePrinter<'a, 'b> {
 
             head: (left, top),
             blit_op: BlitOp::DandNotS,
+            style: TextStyle::PLAIN,
+            previous: None,
         }
     }
 
     /// Emulate a carriage return.
     fn carriage_return(&mut self) {
         self.head.0 = self.margins.0.0;
+        self.previous = None;
     }
 
     /// Emulate a line feed operation.
@@ -62,63 +75,57 @@ impl<'a, 'b> SimplePrinter<'a, 'b> {
         self.head.1 += self.font.height;
     }
 
-    /// Wrap a character `b` from the right-hand side of the margin rectangle to the left-hand side
-    /// on the next line.
-    fn line_wrap(&mut self, b: u8) {
+    /// Wrap a character `ch` from the right-hand side of the margin rectangle to the left-hand
+    /// side on the next line.
+    fn line_wrap(&mut self, ch: char) {
         self.carriage_return();
         self.line_feed();
-        self.print_byte(b)
+        self.print_char(ch)
     }
 
-    /// Print a single byte, including control codes.
-    fn handle_byte(&mut self, b: u8) {
-        match b {
-            0x0D => self.carriage_return(),
-            0x0A => { self.carriage_return(); self.line_feed(); },
-            _ => self.print_byte(b),
+    /// Print a single character, including control codes.
+    fn handle_char(&mut self, ch: char) {
+        match ch {
+            '\r' => self.carriage_return(),
+            '\n' => { self.carriage_return(); self.line_feed(); },
+            // This printer never hyphenates, so soft hyphens never show.
+            SOFT_HYPHEN => {},
+            _ => self.print_char(ch),
         }
     }
 
     /// Print a `text` string.  The layout of the text will depend on the specific printer you're
     /// using.
+    ///
+    /// Text is processed one Unicode character at a time; multi-byte UTF-8 sequences are printed
+    /// as a single glyph.
     pub fn print(&mut self, text: &str) {
-        let mut text_iter = text.bytes();
-        loop {
-            let some_b = text_iter.next();
-            match some_b {
-                None => break,
-                Some(b) => self.handle_byte(b),
-            }
+        for ch in text.chars() {
+            self.handle_char(ch);
         }
     }
 
-    /// Print a single byte `b`, handling only simple control codes like carriage return, etc.
-    pub fn print_byte(&mut self, b: u8) {
-        // If not representable in the glyph set of the font, assume the undefined character glyph,
-        // which by definition, is always at highest_char+1 mod 256.
-        let highest_character = self.font.highest_char;
-        let lowest_character = self.font.lowest_char;
-        let mut glyph_index = b as usize;
-
-        if (b < lowest_character) || (b > highest_character) {
-            glyph_index = (highest_character as usize).overflowing_add(1).0;
-        }
-        glyph_index -= lowest_character as usize;
-
-        // Let's expand this to a valid array index.
-
-        let left_edge = self.font.left_edges[glyph_index];
-        let right_edge = self.font.left_edges[glyph_index + 1];
-        let glyph_width = right_edge - left_edge;
+    /// Print a single character `ch`, handling only simple control codes like carriage return,
+    /// etc.
+    pub fn print_char(&mut self, ch: char) {
+        // If not representable in the glyph set of the font, the font will answer with the
+        // undefined character glyph instead.
+        let advance = self.font.glyph_advance(ch) + self.style.extra_width();
+        let kern = match self.previous {
+            Some(p) => self.font.kerning(p, ch),
+            None => 0,
+        };
 
         // Check to see if we can print this character on the stencil
         // completely.  If not, defer to a callback, which may or may not
-        // re-enter print_byte.
+        // re-enter print_char.  A glyph too wide to fit even on an empty line
+        // is printed anyway (and clipped), lest we wrap forever.
         let (x, y) = self.head;
-        let new_cursor_position = x + glyph_width as Unit;
+        let x = x + kern;
+        let new_cursor_position = x + advance;
         let right_hand_margin = self.margins.1.0;
-        if new_cursor_position >= right_hand_margin {
-            self.line_wrap(b);
+        if (new_cursor_position >= right_hand_margin) && (x > self.margins.0.0) {
+            self.line_wrap(ch);
             return;
         }
 
@@ -128,51 +135,425 @@ impl<'a, 'b> SimplePrinter<'a, 'b> {
             return;
         }
 
-        // Blit the character glyph onto the stencil.
+        paste_glyph(self.stencil, self.font, ch, self.style, (x, y - self.font.baseline), self.margins, self.blit_op);
+
+        self.head.0 = new_cursor_position;
+        self.previous = Some(ch);
+    }
+}
+
+/// Pastes the glyph for `ch`, printed in `style`, onto the `stencil`,
+/// such that the top-left corner of the font's cell lands on `at`.
+/// Nothing is drawn outside of the `clip` rectangle or the stencil.
+///
+/// Plain glyphs without a bearing are blitted more or less straight from the font,
+/// through a small buffer on the stack.
+/// Anything else is rendered into a [[GlyphImage]] first.
+pub(crate) fn paste_glyph(
+    stencil: &mut Stencil,
+    font: &SimpleBitmapFont,
+    ch: char,
+    style: TextStyle,
+    at: Point,
+    clip: Rect,
+    op: BlitOp,
+) {
+    let (left_edge, right_edge) = font.glyph_edges(ch);
+    let glyph_width = right_edge - left_edge;
+    let fits = (glyph_width <= 16) && (font.height <= 16);
+
+    if !style.is_plain() || (font.glyph_metrics(ch).bearing != 0) || !fits {
+        GlyphImage::render(font, ch, style, at.0).paste(stencil, at, clip, op);
+        return;
+    }
+
+    // Because of a bug in bitblt that I do not know how to resolve yet, we must do this in two
+    // steps.  First, we must blit the glyph into a temporary holding stencil that is sized large
+    // enough for the glyph, but which holds the *pre-shifted* version of the glyph.
+    let shift = at.0 & 7;
+    let mut tmp_buf: [u8; 64] = [0; 64]; // Big enough for fonts up to 16 pixels tall, and with characters no wider than 16px.
+    let mut bc = BlitContext::new(font.bits, font.span, &mut tmp_buf, 4);
+    blit_rect(
+        &mut bc,
+        left_edge as usize,
+        0,
+        right_edge as usize,
+        font.height as usize,
+        shift as usize,
+        0,
+        BlitOp::S,
+    );
+
+    let (x, y) = at;
+    let area = ((x, y), (x + glyph_width, y + font.height));
+    blit_clipped(&tmp_buf, 4, shift, area, stencil, clip, op);
+}
+
+/// Blits a pre-shifted image onto the `area` of the `stencil`.
+/// The image's left-most column sits `shift` pixels into each row of `bits`.
+/// Nothing is drawn outside of the `clip` rectangle or the stencil.
+fn blit_clipped(
+    bits: &[u8],
+    span: usize,
+    shift: Unit,
+    area: Rect,
+    stencil: &mut Stencil,
+    clip: Rect,
+    op: BlitOp,
+) {
+    let ((x, y), (x_end, y_end)) = area;
+    let (stencil_width, stencil_height) = stencil.dimensions;
+    let ((clip_left, clip_top), (clip_right, clip_bottom)) = clip;
+
+    let left = x.max(clip_left).max(0);
+    let right = x_end.min(clip_right).min(stencil_width);
+    let upper = y.max(clip_top).max(0);
+    let lower = y_end.min(clip_bottom).min(stencil_height);
+    if (left >= right) || (upper >= lower) {
+        return;
+    }
+
+    // Next, blit the pre-shifted glyph onto the destination bitmap with the operation
+    // requested by the caller.
+    let stencil_span = stencil.get_span();
+    let mut bc = BlitContext::new(bits, span, &mut stencil.bits, stencil_span);
+    blit_rect(
+        &mut bc,
+        (shift + left - x) as usize,
+        (upper - y) as usize,
+        (shift + right - x) as usize,
+        (lower - y) as usize,
+        left as usize,
+        upper as usize,
+        op,
+    );
+}
+
+/// A single glyph, rendered with all of its styles applied,
+/// into a scratch bitmap from which it can be pasted onto a stencil.
+///
+/// The scratch bitmap reserves one row above and one row below the font's cell,
+/// so that outlined glyphs have somewhere to put their outline.
+struct GlyphImage {
+    /// The raw bits of the scratch bitmap.  As with fonts, 1 bits are ink.
+    bits: Vec<u8>,
+
+    /// How many bytes wide the scratch bitmap is.
+    span: usize,
+
+    /// How far, in pixels, the glyph is pre-shifted to the right within the scratch bitmap.
+    shift: Unit,
+
+    /// Where the left edge of the rendered glyph sits, relative to the print head.
+    /// This is negative for glyphs which overhang to the left of the print head.
+    origin: Unit,
+
+    /// The width of the rendered glyph, in pixels, including any overhang.
+    width: Dimension,
+
+    /// The height of the rendered glyph, in pixels, including the padding rows.
+    height: Dimension,
+}
+
+impl GlyphImage {
+    /// Renders the glyph for `ch` in the given `font` and `style`.
+    ///
+    /// The glyph is pre-shifted to suit being pasted with the print head at horizontal
+    /// coordinate `x`.
+    fn render(font: &SimpleBitmapFont, ch: char, style: TextStyle, x: Unit) -> Self {
+        let (left_edge, right_edge) = font.glyph_edges(ch);
+        let glyph_width = right_edge - left_edge;
+        let metrics = font.glyph_metrics(ch);
+        let extra = style.extra_width();
+
+        // The rendered glyph must cover both the glyph's cell (which underlines and reverse video
+        // fill) and its bitmap (which may lie partly outside the cell).
+        let origin = metrics.bearing.min(0);
+        let advance = metrics.advance + extra;
+        let ink_right = metrics.bearing + glyph_width + extra;
+        let width = advance.max(ink_right) + style.overhang(font) - origin;
+        let cell_left = -origin;
+        let glyph_left = metrics.bearing - origin;
+
+        let height = font.height + 2;
+        let shift = (x + origin) & 7;
+        // The blitter may touch one more byte than the glyph strictly needs; allow for it.
+        let span = ((shift + width + 7) >> 3) as usize + 1;
+
+        let mut img = Self {
+            bits: vec![0; span * height as usize],
+            span,
+            shift,
+            origin,
+            width,
+            height,
+        };
+
+        // Blit the character glyph into the scratch bitmap.
         //
         // Because of a bug in bitblt that I do not know how to resolve yet, we must do this in two
         // steps.  First, we must blit the glyph into a temporary holding stencil that is sized large
         // enough for the glyph, but which holds the *pre-shifted* version of the glyph.
-
-        let x_in_byte = (x & 7) as usize;
-
-        // TODO: replace this with a higher-level interface.
-        let mut tmp_buf: [u8; 48] = [0; 48]; // Big enough for fonts up to 16 pixels tall, and with characters no wider than 16px.
-        let mut bc = BlitContext::new(self.font.bits, self.font.span, &mut tmp_buf, 3);
+        // Styles are applied to the pre-shifted copy before it's pasted anywhere.
+        let ox = glyph_left + if style.contains(TextStyle::OUTLINE) { 1 } else { 0 };
+        let mut bc = BlitContext::new(font.bits, font.span, &mut img.bits, span);
         blit_rect(
             &mut bc,
             left_edge as usize,
             0,
             right_edge as usize,
-            self.font.height as usize,
-            x_in_byte,
-            0,
+            font.height as usize,
+            (shift + ox) as usize,
+            1,
             BlitOp::S,
         );
 
-        // Next, blit the pre-shifted glyph onto the destination bitmap with the operation requested by
-        // the caller.  This requires resetting the BlitContext to refer to the correct stencil
-        // bitmaps.
-        bc.s_bits = &(*bc.d_bits);
-        bc.s_span = 3;
+        // The blitter works in whole bytes, so it may have dragged in pixels from neighboring
+        // glyphs in the font.  These would show through once a style widens the glyph.
+        for row in 0..height {
+            for col in (0..ox).chain((ox + glyph_width)..width) {
+                img.set(col, row, false);
+            }
+        }
 
-        let stencil_width = self.stencil.dimensions.0;
-        bc.d_bits = &mut self.stencil.bits;
-        bc.d_span = ((stencil_width + 7) >> 3) as usize;
+        if style.contains(TextStyle::BOLD) {
+            img.smear();
+        }
+        if style.contains(TextStyle::ITALIC) {
+            img.shear(font);
+        }
+        if style.contains(TextStyle::OUTLINE) {
+            img.outline();
+        }
+        if style.contains(TextStyle::UNDERLINE) {
+            let row = (font.baseline + 1).min(font.height - 1) + 1;
+            for col in cell_left..(cell_left + advance) {
+                img.set(col, row, true);
+            }
+        }
+        if style.contains(TextStyle::REVERSE) {
+            // Reverse video fills the cell exactly; an outline mustn't poke out above or below it.
+            for col in 0..width {
+                img.set(col, 0, false);
+                img.set(col, height - 1, false);
+            }
+            for row in 1..=font.height {
+                for col in cell_left..(cell_left + advance) {
+                    let ink = img.get(col, row);
+                    img.set(col, row, !ink);
+                }
+            }
+        }
 
-        let top = (y - self.font.baseline) as usize;
-        blit_rect(
-            &mut bc,
-            x_in_byte,
-            0,
-            x_in_byte + glyph_width as usize,
-            self.font.height as usize,
-            x as usize,
-            top,
-            self.blit_op,
-        );
+        img
+    }
 
-        self.head.0 = new_cursor_position;
+    /// Answers `true` if the pixel at (`col`, `row`) is inked.
+    /// Pixels outside the glyph are never inked.
+    fn get(&self, col: Unit, row: Unit) -> bool {
+        if (col < 0) || (col >= self.width) || (row < 0) || (row >= self.height) {
+            return false;
+        }
+        let c = (col + self.shift) as usize;
+        let offset = row as usize * self.span + (c >> 3);
+        (self.bits[offset] & (0x80 >> (c & 7))) != 0
+    }
+
+    /// Inks (or clears) the pixel at (`col`, `row`).
+    fn set(&mut self, col: Unit, row: Unit, ink: bool) {
+        let c = (col + self.shift) as usize;
+        let offset = row as usize * self.span + (c >> 3);
+        let mask = 0x80 >> (c & 7);
+        if ink {
+            self.bits[offset] |= mask;
+        } else {
+            self.bits[offset] &= !mask;
+        }
+    }
+
+    /// Emboldens the glyph by smearing every pixel one pixel to the right.
+    fn smear(&mut self) {
+        for row in 0..self.height {
+            for col in (1..self.width).rev() {
+                let ink = self.get(col, row) || self.get(col - 1, row);
+                self.set(col, row, ink);
+            }
+        }
+    }
+
+    /// Italicizes the glyph by shearing rows above the baseline to the right.
+    fn shear(&mut self, font: &SimpleBitmapFont) {
+        for font_row in 0..font.height {
+            let displacement = italic_shift(font, font_row);
+            if displacement == 0 {
+                continue;
+            }
+            let row = font_row + 1;
+            for col in (0..self.width).rev() {
+                let ink = self.get(col - displacement, row);
+                self.set(col, row, ink);
+            }
+        }
+    }
+
+    /// Replaces the glyph with its one-pixel outline.
+    fn outline(&mut self) {
+        let mut outlined = vec![false; (self.width * self.height) as usize];
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let mut near = false;
+                for (dx, dy) in [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)] {
+                    near |= self.get(col + dx, row + dy);
+                }
+                outlined[(row * self.width + col) as usize] = near && !self.get(col, row);
+            }
+        }
+        for row in 0..self.height {
+            for col in 0..self.width {
+                self.set(col, row, outlined[(row * self.width + col) as usize]);
+            }
+        }
+    }
+
+    /// Pastes the glyph onto the `stencil`, such that the top-left corner of the font's cell
+    /// lands on `at`.  Nothing is drawn outside of the `clip` rectangle or the stencil.
+    fn paste(&self, stencil: &mut Stencil, at: Point, clip: Rect, op: BlitOp) {
+        let (head, top) = at;
+        let (x, y) = (head + self.origin, top - 1);
+        let area = ((x, y), (x + self.width, y + self.height));
+        blit_clipped(&self.bits, self.span, self.shift, area, stencil, clip, op);
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::{GlyphImage, SimplePrinter};
+    use crate::simple_bitmap_font::{KerningPair, SimpleBitmapFont};
+    use crate::sysfont_bsw_9::SYSTEM_BITMAP_FONT;
+    use crate::text_style::{TextStyle, italic_shift};
+    use crate::types::{Point, Rect};
+    use crate::utils::testing::{blank_stencil, inked_pixels};
+
+    const PAGE: Rect = ((0, 0), (64, 12));
+
+    /// Prints `text` in `style` onto a blank page, and answers with the inked pixels.
+    fn print_styled(text: &str, style: TextStyle) -> Vec<Point> {
+        print_with(text, &SYSTEM_BITMAP_FONT, style, PAGE)
+    }
+
+    /// Prints `text` in `font` and `style` between the `margins` of a blank page,
+    /// and answers with the inked pixels.
+    fn print_with(text: &str, font: &SimpleBitmapFont, style: TextStyle, margins: Rect) -> Vec<Point> {
+        let mut s = blank_stencil(64, 16);
+        {
+            let mut p = SimplePrinter::new(&mut s, margins, font);
+            p.style = style;
+            p.print(text);
+        }
+        inked_pixels(&s)
+    }
+
+    #[test]
+    fn bold_smears_one_pixel_right() {
+        let plain = print_styled("l", TextStyle::PLAIN);
+        let bold = print_styled("l", TextStyle::BOLD);
+        assert!(!plain.is_empty());
+        for &(x, y) in &plain {
+            assert!(bold.contains(&(x, y)));
+            assert!(bold.contains(&(x + 1, y)));
+        }
+        assert_eq!(bold.len(), plain.len() * 2);
+    }
+
+    #[test]
+    fn italic_shears_rows_above_baseline() {
+        let font = &SYSTEM_BITMAP_FONT;
+        let plain = print_styled("l", TextStyle::PLAIN);
+        let italic = print_styled("l", TextStyle::ITALIC);
+        let mut expected: Vec<Point> = plain.iter().map(|&(x, y)| (x + italic_shift(font, y), y)).collect();
+        expected.sort_by_key(|&(x, y)| (y, x));
+        assert_eq!(italic, expected);
+
+        // The top of the 'l' leans right; its foot stays put.
+        assert!(italic_shift(font, 0) > 0);
+        assert_eq!(italic_shift(font, font.baseline - 1), 0);
+    }
+
+    #[test]
+    fn outline_traces_border_around_glyph() {
+        // The outlined glyph moves one pixel right, to leave room for the left-hand border.
+        let glyph: Vec<Point> = print_styled("l", TextStyle::PLAIN).iter().map(|&(x, y)| (x + 1, y)).collect();
+        let outlined = print_styled("l", TextStyle::OUTLINE);
+
+        let mut expected = Vec::new();
+        for y in 0..12 {
+            for x in 0..64 {
+                let near = glyph.iter().any(|&(gx, gy)| ((gx - x).abs() <= 1) && ((gy - y).abs() <= 1));
+                if near && !glyph.contains(&(x, y)) {
+                    expected.push((x, y));
+                }
+            }
+        }
+        assert!(!expected.is_empty());
+        assert_eq!(outlined, expected);
+    }
+
+    #[test]
+    fn reverse_inverts_exactly_the_cell() {
+        let font = &SYSTEM_BITMAP_FONT;
+        let advance = font.glyph_advance('l');
+        let margins: Rect = ((0, 2), (64, 14));
+        let plain = print_with("l", font, TextStyle::PLAIN, margins);
+        let reversed = print_with("l", font, TextStyle::REVERSE, margins);
+
+        let mut expected = Vec::new();
+        for y in 2..(2 + font.height) {
+            for x in 0..advance {
+                if !plain.contains(&(x, y)) {
+                    expected.push((x, y));
+                }
+            }
+        }
+        assert_eq!(reversed, expected);
+
+        // An outline would otherwise poke out of the padding rows above and below the cell.
+        let outlined = GlyphImage::render(font, 'l', TextStyle::OUTLINE | TextStyle::REVERSE, 0);
+        for col in 0..outlined.width {
+            assert!(!outlined.get(col, 0));
+            assert!(!outlined.get(col, outlined.height - 1));
+        }
+    }
+
+    #[test]
+    fn underline_spans_advance_below_baseline() {
+        let underlined = print_styled("  ", TextStyle::UNDERLINE);
+        let advance = SYSTEM_BITMAP_FONT.glyph_width(' ') * 2;
+        let row = SYSTEM_BITMAP_FONT.baseline + 1;
+        let expected: Vec<Point> = (0..advance).map(|x| (x, row)).collect();
+        assert_eq!(underlined, expected);
+    }
+
+    #[test]
+    fn multibyte_characters_print_one_glyph() {
+        // Both render the unknown glyph exactly once.
+        assert_eq!(print_styled("\u{80}", TextStyle::PLAIN), print_styled("€", TextStyle::PLAIN));
+    }
+
+    #[test]
+    fn kerning_pulls_pairs_together() {
+        static KERNING: [KerningPair; 1] = [KerningPair { left: 'l', right: 'l', adjust: -1 }];
+        let kerned = SimpleBitmapFont { kerning: &KERNING, ..SYSTEM_BITMAP_FONT };
+
+        let plain = print_styled("ll", TextStyle::PLAIN);
+        let tight = print_with("ll", &kerned, TextStyle::PLAIN, PAGE);
+        let first = print_styled("l", TextStyle::PLAIN);
+        let second: Vec<Point> = plain.iter().filter(|p| !first.contains(p)).copied().collect();
+
+        for &(x, y) in &first {
+            assert!(tight.contains(&(x, y)));
+        }
+        for &(x, y) in &second {
+            assert!(tight.contains(&(x - 1, y)));
+        }
+    }
+}
@@ -87,9 +87,15 @@ pub trait Draw {
 
     /// Inverts a horizontal line.
     fn invert_horizontal_line(&mut self, left: Point, right: Unit);
+
+    /// Lightens a rectangle by whitening every other pixel, in a checkerboard,
+    /// leaving the rest alone.
+    /// This is how disabled gadgets and menu items are drawn.
+    fn lighten_rectangle(&mut self, upper_left: Point, lower_right: Point);
 }
 
 /// A Stencil encapsulates a bitmapped image.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Stencil {
     /// (Width, Height) of the stencil, in dots.
     pub dimensions: (Dimension, Dimension),
@@ -184,6 +190,57 @@ impl Stencil {
     pub fn borrow_bits(&self) -> &[u8] {
         &self.bits
     }
+
+    /// Saves the pixels within a rectangle, so they can be put back later,
+    /// as when a menu or other transient view is drawn on top of them.
+    ///
+    /// The rectangle is clipped to the stencil,
+    /// and widened to whole bytes;
+    /// so up to seven pixels either side of it are saved as well.
+    pub fn save_area(&self, upper_left: Point, lower_right: Point) -> SavedArea {
+        let (upper_left, lower_right) = canonize_rectangle(upper_left, lower_right);
+        let (width, height) = self.dimensions;
+        let span = self.get_span();
+        let top = upper_left.1.clamp(0, height) as usize;
+        let bottom = lower_right.1.clamp(0, height) as usize;
+        let left_byte = (upper_left.0.clamp(0, width) >> 3) as usize;
+        let right_byte = ((lower_right.0.clamp(0, width) + 7) >> 3) as usize;
+        let row_bytes = right_byte.saturating_sub(left_byte);
+
+        let mut bits = Vec::with_capacity(row_bytes * bottom.saturating_sub(top));
+        for y in top..bottom {
+            let row = (y * span) + left_byte;
+            bits.extend_from_slice(&self.bits[row..(row + row_bytes)]);
+        }
+        SavedArea { left_byte, top, row_bytes, bits }
+    }
+
+    /// Puts back pixels saved by [[Stencil::save_area]].
+    pub fn restore_area(&mut self, saved: &SavedArea) {
+        let span = self.get_span();
+        if saved.row_bytes == 0 {
+            return;
+        }
+        for (i, saved_row) in saved.bits.chunks(saved.row_bytes).enumerate() {
+            let row = ((saved.top + i) * span) + saved.left_byte;
+            self.bits[row..(row + saved.row_bytes)].copy_from_slice(saved_row);
+        }
+    }
+}
+
+/// Pixels saved from a stencil by [[Stencil::save_area]].
+pub struct SavedArea {
+    /// The first byte of each row saved.
+    left_byte: usize,
+
+    /// The first row saved.
+    top: usize,
+
+    /// How many bytes of each row were saved.
+    row_bytes: usize,
+
+    /// The bytes saved, row by row.
+    bits: Vec<u8>,
 }
 
 /// Canonize a rectangle's coordinates.
@@ -394,6 +451,27 @@ impl Draw for Stencil {
     }
 
     fn invert_horizontal_line(&mut self, left_pt: Point, right: Unit) {
+        self.combine_horizontal_line(left_pt, right, |original, mask| original ^ mask);
+    }
+
+    fn lighten_rectangle(&mut self, upper_left: Point, lower_right: Point) {
+        let (upper_left, lower_right) = canonize_rectangle(upper_left, lower_right);
+        let (left, top) = upper_left;
+        let (right, bottom) = lower_right;
+
+        for y in top..bottom {
+            let pattern = if y & 1 == 0 { 0xAA } else { 0x55 };
+            self.combine_horizontal_line((left, y), right, |original, mask| original | (mask & pattern));
+        }
+    }
+}
+
+impl Stencil {
+    /// Combines each byte a horizontal line covers with a mask of the pixels covered,
+    /// using `combine`.
+    ///
+    /// The left point and right coordinate are clipped to the stencil as necessary.
+    fn combine_horizontal_line(&mut self, left_pt: Point, right: Unit, combine: impl Fn(u8, u8) -> u8) {
         let (left_pt, mut right) = canonize_hline(left_pt, right);
         let (mut left, top) = left_pt;
         let (width, height) = self.dimensions;
@@ -435,7 +513,7 @@ impl Draw for Stencil {
             }
 
             let original_byte = self.bits[x];
-            let new_byte = original_byte ^ combined_mask;
+            let new_byte = combine(original_byte, combined_mask);
             self.bits[x] = new_byte;
 
             x = x + 1;
@@ -443,3 +521,26 @@ impl Draw for Stencil {
     }
 }
 
+#[cfg(test)]
+mod save_area_tests {
+    use super::{Draw, Stencil};
+
+    #[test]
+    fn restoring_puts_back_saved_pixels() {
+        let mut s = Stencil::new_with_dimensions(24, 4);
+        s.invert_rectangle((3, 1), (9, 3));
+        let before = s.bits.clone();
+
+        let saved = s.save_area((10, 0), (12, 4));
+        s.invert_rectangle((8, 0), (14, 4));
+        s.restore_area(&saved);
+        assert_eq!(s.bits, before);
+    }
+
+    #[test]
+    fn lightening_whitens_a_checkerboard() {
+        let mut s = Stencil::new_with_dimensions(8, 2);
+        s.lighten_rectangle((0, 0), (4, 2));
+        assert_eq!(s.bits, vec![0xA0, 0x50]);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::simple_bitmap_font::SimpleBitmapFont;
+use crate::simple_bitmap_font::{SimpleBitmapFont, GlyphRange};
 
 
 // This font was built by running GEOS on a Commodore 64 emulator,
@@ -12,8 +12,9 @@ pub const SYSTEM_BITMAP_FONT: SimpleBitmapFont<'static> = SimpleBitmapFont {
     span: 60,
     height: 9,
     baseline: 7,
-    lowest_char: 32,
-    highest_char: 127,
+    ranges: &[GlyphRange { first: ' ', last: '\u{7F}' }],
+    metrics: &[],
+    kerning: &[],
 
     bits: &[
         0x02, 0xA5, 0x08, 0xC1, 0x04, 0x50, 0x40, 0x00, 0x00, 0x06, 0x2C, 0xC1, 0x71, 0x3C, 0xC6, 0x00, 
@@ -0,0 +1,175 @@
+//! Synthesized text styles.
+//!
+//! Like GEOS,
+//! ROSE doesn't need a separate font for every style of a typeface.
+//! Bold, italic, underlined, outlined, and reverse-video text
+//! are all synthesized at print time
+//! from whatever plain bitmap font is at hand.
+//!
+//! Because some styles change how wide a glyph is,
+//! anything that measures text for layout purposes
+//! must measure it *with* the style applied.
+//! [[StyledFont]] pairs a font with a style
+//! so that it can be handed to [[text_width]] or to a [[Forme]]
+//! wherever a plain font would otherwise go.
+
+use std::ops::{BitOr, BitOrAssign, Sub};
+
+use crate::chase::FontProvider;
+use crate::simple_bitmap_font::SimpleBitmapFont;
+use crate::types::Dimension;
+
+/// A set of synthesized text styles.
+///
+/// Styles combine freely with `|`;
+/// for example, `TextStyle::BOLD | TextStyle::UNDERLINE`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TextStyle(u8);
+
+impl TextStyle {
+    /// Plain text; no styles applied.
+    pub const PLAIN: TextStyle = TextStyle(0);
+
+    /// Bold text is synthesized by smearing each glyph one pixel to the right.
+    /// This widens every glyph by one pixel.
+    pub const BOLD: TextStyle = TextStyle(0x01);
+
+    /// Italic text is synthesized by shearing the portion of each glyph above the baseline to the
+    /// right.  Glyph advances are unchanged; the sheared pixels overhang into the next glyph.
+    pub const ITALIC: TextStyle = TextStyle(0x02);
+
+    /// Underlined text has a line drawn one pixel below the baseline.
+    pub const UNDERLINE: TextStyle = TextStyle(0x04);
+
+    /// Outlined text draws only the one-pixel outline around each glyph.
+    /// This widens every glyph by two pixels.
+    pub const OUTLINE: TextStyle = TextStyle(0x08);
+
+    /// Reverse-video text inverts the entire glyph cell.
+    pub const REVERSE: TextStyle = TextStyle(0x10);
+
+    /// Answers `true` if every style in `other` is also set in `self`.
+    pub fn contains(self, other: TextStyle) -> bool {
+        (self.0 & other.0) == other.0
+    }
+
+    /// Answers `true` if no styles are set.
+    pub fn is_plain(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Answers the number of pixels this style adds to the advance of every glyph.
+    pub fn extra_width(self) -> Dimension {
+        let mut extra = 0;
+        if self.contains(TextStyle::BOLD) {
+            extra += 1;
+        }
+        if self.contains(TextStyle::OUTLINE) {
+            extra += 2;
+        }
+        extra
+    }
+
+    /// Answers how far, in pixels, a glyph printed in this style
+    /// may spill past its advance on the right-hand side.
+    ///
+    /// Only italics overhang.
+    pub fn overhang(self, font: &SimpleBitmapFont) -> Dimension {
+        if self.contains(TextStyle::ITALIC) {
+            italic_shift(font, 0)
+        } else {
+            0
+        }
+    }
+}
+
+impl BitOr for TextStyle {
+    type Output = TextStyle;
+
+    fn bitor(self, rhs: TextStyle) -> TextStyle {
+        TextStyle(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for TextStyle {
+    fn bitor_assign(&mut self, rhs: TextStyle) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl Sub for TextStyle {
+    type Output = TextStyle;
+
+    /// Removes the styles in `rhs` from `self`.
+    fn sub(self, rhs: TextStyle) -> TextStyle {
+        TextStyle(self.0 & !rhs.0)
+    }
+}
+
+/// Answers how far to the right the italic shear displaces font row `row`.
+///
+/// Every two rows above the baseline shift the row one more pixel to the right.
+/// Rows at or below the baseline are not displaced.
+pub(crate) fn italic_shift(font: &SimpleBitmapFont, row: Dimension) -> Dimension {
+    ((font.baseline - 1 - row) / 2).max(0)
+}
+
+/// A font paired with the style it will be printed in.
+///
+/// Use this wherever a [[FontProvider]] is expected
+/// to measure text the way it will actually appear once printed.
+pub struct StyledFont<'f> {
+    /// The underlying bitmap font.
+    pub font: &'f SimpleBitmapFont<'f>,
+
+    /// The style the font will be printed in.
+    pub style: TextStyle,
+}
+
+impl<'f> StyledFont<'f> {
+    /// Pairs a `font` with a `style`.
+    pub fn new(font: &'f SimpleBitmapFont<'f>, style: TextStyle) -> Self {
+        Self { font, style }
+    }
+}
+
+impl<'f> FontProvider for StyledFont<'f> {
+    fn get_glyph_width(&self, ch: char) -> usize {
+        (self.font.glyph_advance(ch) + self.style.extra_width()) as usize
+    }
+
+    fn get_kerning(&self, left: char, right: char) -> isize {
+        self.font.kerning(left, right)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StyledFont, TextStyle};
+    use crate::simple_bitmap_font::text_width;
+    use crate::sysfont_bsw_9::SYSTEM_BITMAP_FONT;
+
+    #[test]
+    fn styles_combine() {
+        let s = TextStyle::BOLD | TextStyle::UNDERLINE;
+        assert!(s.contains(TextStyle::BOLD));
+        assert!(s.contains(TextStyle::UNDERLINE));
+        assert!(!s.contains(TextStyle::ITALIC));
+        assert!((s - TextStyle::BOLD - TextStyle::UNDERLINE).is_plain());
+    }
+
+    #[test]
+    fn styled_width_accounts_for_style() {
+        let font = &SYSTEM_BITMAP_FONT;
+        let plain = text_width("Hello", font);
+
+        let bold = StyledFont::new(font, TextStyle::BOLD);
+        assert_eq!(text_width("Hello", &bold), plain + 5);
+
+        let outline = StyledFont::new(font, TextStyle::OUTLINE | TextStyle::BOLD);
+        assert_eq!(text_width("Hello", &outline), plain + 15);
+
+        let italic = StyledFont::new(font, TextStyle::ITALIC | TextStyle::UNDERLINE);
+        assert_eq!(text_width("Hello", &italic), plain);
+    }
+}
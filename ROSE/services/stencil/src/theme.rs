@@ -0,0 +1,120 @@
+//! Themes
+//!
+//! A [[Theme]] decides how gadgets look:
+//! the patterns they're filled with,
+//! how thick their frames are,
+//! how far their shadows fall,
+//! and which font text is printed in when nobody chose one.
+//! Gadgets ask [[Mediator::theme]] for it every time they draw,
+//! so an application restyles every gadget at once
+//! by handing another theme to [[Mediator::set_theme]]
+//! and redrawing.
+
+use crate::simple_bitmap_font::SimpleBitmapFont;
+use crate::stencil::{Draw, Pattern};
+use crate::sysfont_bsw_9::SYSTEM_BITMAP_FONT;
+use crate::types::{Dimension, Point};
+use crate::utils::{BLACK_PATTERN, DESKTOP_PATTERN, LINE_BLACK, WHITE_PATTERN};
+
+/// The look shared by every gadget.
+pub struct Theme {
+    /// The pattern filling the desktop, behind every window and dialog box.
+    pub desktop: Pattern,
+
+    /// The pattern filling gadgets, windows and dialog boxes, behind their text.
+    pub paper: Pattern,
+
+    /// The pattern filling a proportional gadget's track, behind its knob.
+    pub track: Pattern,
+
+    /// The font for text whose owner didn't choose one,
+    /// such as window titles, icon labels and requesters.
+    pub font: &'static SimpleBitmapFont<'static>,
+
+    /// How thick the frames around gadgets, windows and dialog boxes are, in pixels.
+    pub border: Dimension,
+
+    /// How far the shadows of buttons, menus and dialog boxes fall, in pixels,
+    /// below and to the right of whatever casts them.
+    pub shadow: Dimension,
+
+    /// How much room requesters leave around and between their gadgets, in pixels.
+    pub spacing: Dimension,
+}
+
+/// The theme every host starts with:
+/// a grey desktop, and gadgets framed in thin black lines.
+pub static CLASSIC_THEME: Theme = Theme {
+    desktop: DESKTOP_PATTERN,
+    paper: WHITE_PATTERN,
+    track: [
+        0b11101110,
+        0b11011101,
+        0b10111011,
+        0b01110111,
+        0b11101110,
+        0b11011101,
+        0b10111011,
+        0b01110111,
+    ],
+    font: &SYSTEM_BITMAP_FONT,
+    border: 1,
+    shadow: 1,
+    spacing: 8,
+};
+
+/// A theme for those who find the classic one hard to make out:
+/// a solid black desktop, no stipples behind anything,
+/// and heavier frames and shadows.
+pub static HIGH_CONTRAST_THEME: Theme = Theme {
+    desktop: BLACK_PATTERN,
+    paper: WHITE_PATTERN,
+    track: WHITE_PATTERN,
+    font: &SYSTEM_BITMAP_FONT,
+    border: 2,
+    shadow: 2,
+    spacing: 10,
+};
+
+impl Theme {
+    /// Draws a frame [[Theme::border]] pixels thick,
+    /// just inside the rectangle from `upper_left` to `lower_right`.
+    pub fn frame(&self, d: &mut dyn Draw, upper_left: Point, lower_right: Point) {
+        let ((left, top), (right, bottom)) = (upper_left, lower_right);
+        for inset in 0..self.border {
+            d.framed_rectangle((left + inset, top + inset), (right - inset, bottom - inset), LINE_BLACK);
+        }
+    }
+
+    /// Draws the shadow of the rectangle from `upper_left` to `lower_right`,
+    /// [[Theme::shadow]] pixels deep, along its bottom and right edges.
+    pub fn drop_shadow(&self, d: &mut dyn Draw, upper_left: Point, lower_right: Point) {
+        let ((left, top), (right, bottom)) = (upper_left, lower_right);
+        let depth = self.shadow;
+        d.filled_rectangle((left + depth, bottom), (right + depth, bottom + depth), &BLACK_PATTERN);
+        d.filled_rectangle((right, top + depth), (right + depth, bottom), &BLACK_PATTERN);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CLASSIC_THEME, HIGH_CONTRAST_THEME};
+    use crate::utils::testing::{blank_stencil, inked_pixels};
+
+    #[test]
+    fn frames_are_as_thick_as_the_border() {
+        let mut st = blank_stencil(8, 8);
+        CLASSIC_THEME.frame(&mut st, (2, 2), (6, 6));
+        let inked = inked_pixels(&st);
+        assert_eq!(inked.len(), 12);
+        assert!(inked.contains(&(2, 3)) && inked.contains(&(5, 5)));
+        assert!(!inked.contains(&(3, 3)));
+
+        let mut st = blank_stencil(8, 8);
+        HIGH_CONTRAST_THEME.frame(&mut st, (1, 1), (7, 7));
+        let inked = inked_pixels(&st);
+        assert_eq!(inked.len(), 20 + 12);
+        assert!(inked.contains(&(2, 4)) && inked.contains(&(5, 5)));
+        assert!(!inked.contains(&(3, 3)));
+    }
+}
@@ -0,0 +1,115 @@
+//! Timers.
+//!
+//! Applications ask the [[Mediator]] for timers,
+//! and the host loop delivers them as [[TimerEventSink::timer_fired]] events
+//! once they expire.
+//! [[TimerQueue]] does the bookkeeping for the host:
+//! it remembers when each timer is due,
+//! so the host knows how long it may wait for other events,
+//! and which timers to deliver when it wakes up.
+//!
+//! Times are in milliseconds since some moment the host chooses,
+//! such as when it started.
+
+/// Identifies a timer.
+pub type TimerId = usize;
+
+/// A timer waiting to fire.
+#[derive(Clone, Copy, Debug)]
+struct Timer {
+    id: TimerId,
+
+    /// When the timer is next due.
+    deadline: u64,
+
+    /// How long after each firing a periodic timer fires again.
+    period: Option<u64>,
+}
+
+/// Keeps track of pending timers on behalf of a host.
+#[derive(Clone, Debug, Default)]
+pub struct TimerQueue {
+    /// Pending timers, in no particular order.
+    timers: Vec<Timer>,
+
+    /// The identifier the next timer started will receive.
+    next_id: TimerId,
+}
+
+impl TimerQueue {
+    /// Creates an empty timer queue.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a timer at time `now`, which fires `delay` milliseconds later.
+    /// If `periodic` is set, it fires again every `delay` milliseconds after that,
+    /// until cancelled.
+    ///
+    /// Periodic timers fire at most once per millisecond, however short their delay.
+    pub fn start(&mut self, now: u64, delay: u32, periodic: bool) -> TimerId {
+        let id = self.next_id;
+        self.next_id += 1;
+        let period = periodic.then_some(u64::from(delay.max(1)));
+        self.timers.push(Timer { id, deadline: now + u64::from(delay), period });
+        id
+    }
+
+    /// Cancels the timer `id`.
+    /// Cancelling a one-shot timer which already fired does nothing.
+    pub fn cancel(&mut self, id: TimerId) {
+        self.timers.retain(|t| t.id != id);
+    }
+
+    /// Answers `true` if no timers are pending.
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+
+    /// Answers when the earliest pending timer is due, if any timer is pending.
+    pub fn next_deadline(&self) -> Option<u64> {
+        self.timers.iter().map(|t| t.deadline).min()
+    }
+
+    /// Answers the timers due at or before time `now`, earliest first.
+    ///
+    /// One-shot timers are forgotten.
+    /// Periodic timers are scheduled to fire again;
+    /// a periodic timer which fell several periods behind fires only once,
+    /// rather than once for every period it missed.
+    pub fn expire(&mut self, now: u64) -> Vec<TimerId> {
+        let mut due: Vec<Timer> = self.timers.iter().copied().filter(|t| t.deadline <= now).collect();
+        due.sort_by_key(|t| (t.deadline, t.id));
+
+        self.timers.retain(|t| (t.deadline > now) || t.period.is_some());
+        for t in self.timers.iter_mut().filter(|t| t.deadline <= now) {
+            let period = t.period.unwrap_or(1);
+            t.deadline += ((now - t.deadline) / period + 1) * period;
+        }
+        due.iter().map(|t| t.id).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TimerQueue;
+
+    #[test]
+    fn timers_fire_in_deadline_order() {
+        let mut q = TimerQueue::new();
+        let slow = q.start(0, 300, false);
+        let fast = q.start(0, 100, true);
+        assert_eq!(q.next_deadline(), Some(100));
+        assert_eq!(q.expire(99), vec![]);
+        assert_eq!(q.expire(100), vec![fast]);
+        assert_eq!(q.next_deadline(), Some(200));
+
+        // Falling behind fires a periodic timer just once.
+        assert_eq!(q.expire(350), vec![fast, slow]);
+        assert_eq!(q.next_deadline(), Some(400));
+
+        q.cancel(fast);
+        assert!(q.is_empty());
+        assert_eq!(q.expire(1000), vec![]);
+    }
+}
@@ -0,0 +1,121 @@
+//! Data transfer between views
+//!
+//! Views hand data to one another in two ways.
+//! They can put it on the clipboard,
+//! through [[Mediator::set_clipboard]],
+//! for any view (or, on some hosts, any other program) to paste later.
+//! Or the operator can drag it from one view and drop it on another:
+//! the view it's dragged from starts a [[DragSession]]
+//! with [[Mediator::start_drag]],
+//! and whoever hears the mouse button go up ends it
+//! with [[Mediator::end_drag]],
+//! handing the data to the view under the pointer.
+//!
+//! While a drag is in progress,
+//! the host draws its outline inverted over the desktop,
+//! following the pointer;
+//! like the pointer itself,
+//! it never appears in [[Mediator::borrow_mut_desktop]].
+
+use crate::stencil::Stencil;
+use crate::types::{Point, Rect};
+
+/// Something which can be copied, pasted or dragged.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TransferData {
+    /// Plain text.
+    Text(String),
+
+    /// A picture.
+    Bitmap(Stencil),
+}
+
+impl TransferData {
+    /// Answers the text, if this is text.
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            TransferData::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Answers the picture, if this is a picture.
+    pub fn as_bitmap(&self) -> Option<&Stencil> {
+        match self {
+            TransferData::Bitmap(bitmap) => Some(bitmap),
+            _ => None,
+        }
+    }
+}
+
+/// Data being dragged from one view to another.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DragSession {
+    /// What's being dragged.
+    data: TransferData,
+
+    /// The outline shown while dragging, as it was when the drag began.
+    outline: Rect,
+
+    /// Where the pointer was when the drag began.
+    from: Point,
+
+    /// Where the pointer is now.
+    at: Point,
+}
+
+impl DragSession {
+    /// Starts dragging `data`, with the pointer `at` the given point.
+    ///
+    /// The `outline` is usually the area of whatever stands for the data on the desktop,
+    /// such as the text selected in a text field;
+    /// it keeps its place relative to the pointer as the pointer moves.
+    pub fn new(data: TransferData, outline: Rect, at: Point) -> Self {
+        Self { data, outline, from: at, at }
+    }
+
+    /// Answers what's being dragged.
+    pub fn data(&self) -> &TransferData {
+        &self.data
+    }
+
+    /// Answers what's being dragged, ending the session.
+    pub fn into_data(self) -> TransferData {
+        self.data
+    }
+
+    /// Answers where the pointer is.
+    pub fn at(&self) -> Point {
+        self.at
+    }
+
+    /// Moves the pointer, and the outline with it, `to` the given point.
+    pub fn move_to(&mut self, to: Point) {
+        self.at = to;
+    }
+
+    /// Answers where the outline is now.
+    pub fn outline(&self) -> Rect {
+        let ((left, top), (right, bottom)) = self.outline;
+        let (dx, dy) = (self.at.0 - self.from.0, self.at.1 - self.from.1);
+        ((left + dx, top + dy), (right + dx, bottom + dy))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DragSession, TransferData};
+
+    #[test]
+    fn outline_follows_the_pointer() {
+        let text = TransferData::Text("hello".to_string());
+        let mut session = DragSession::new(text, ((10, 10), (40, 20)), (15, 12));
+        assert_eq!(session.outline(), ((10, 10), (40, 20)));
+
+        session.move_to((25, 7));
+        assert_eq!(session.at(), (25, 7));
+        assert_eq!(session.outline(), ((20, 5), (50, 15)));
+        assert_eq!(session.data().as_text(), Some("hello"));
+        assert_eq!(session.into_data().as_bitmap(), None);
+    }
+}
@@ -1,9 +1,10 @@
 //! Useful tools that are frequently used, but which don't really fit anywhere else.
 
-use crate::types::Rect;
+use crate::types::{Point, Rect, Unit};
 use crate::stencil::{Draw, Pattern};
+use crate::theme::Theme;
 
-/// The default desktop background pattern (a 50% grey stipple).
+/// The classic desktop background pattern (a 50% grey stipple), as [[CLASSIC_THEME]] uses.
 pub static DESKTOP_PATTERN: Pattern = [0xAA, 0x55, 0xAA, 0x55, 0xAA, 0x55, 0xAA, 0x55];
 
 /// A pattern consisting of all black pixels.
@@ -18,12 +19,80 @@ pub static LINE_BLACK: u8 = 0x00;
 /// A line pattern guaranteed to consist of white pixels.
 pub static LINE_WHITE: u8 = 0xFF;
 
+/// An 8x8 pixel picture, such as an arrow or a tick mark, one byte per row.
+/// Unlike a [[Pattern]], set bits are *black*.
+pub type Glyph = [u8; 8];
+
 
 /// Draws the background for a desktop environment.
 ///
-/// Currently, this function fills the entire stencil with the DESKTOP_PATTERN.
-pub fn draw_desktop(st: &mut dyn Draw) {
-    st.filled_rectangle((0, 0), st.get_dimensions(), &DESKTOP_PATTERN);
+/// Currently, this function fills the entire stencil with the `theme`'s desktop pattern.
+pub fn draw_desktop(st: &mut dyn Draw, theme: &Theme) {
+    st.filled_rectangle((0, 0), st.get_dimensions(), &theme.desktop);
+}
+
+/// Draws a `glyph` onto the provided stencil, with its top left corner `at` the given point.
+///
+/// Only the glyph's black pixels are drawn; the rest show through.
+pub fn draw_glyph(st: &mut dyn Draw, at: Point, glyph: &Glyph) {
+    let (left, top) = at;
+    for (y, &bits) in (top..).zip(glyph.iter()) {
+        let mut run_start = None;
+        for column in 0..=8 {
+            let inked = (column < 8) && ((bits & (0x80 >> column)) != 0);
+            match (inked, run_start) {
+                (true, None) => run_start = Some(column),
+                (false, Some(from)) => {
+                    st.horizontal_line((left + from, y), left + column, LINE_BLACK);
+                    run_start = None;
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+/// Draws a 16x16 pixel picture onto the provided stencil, with its top left corner `at` the given point.
+///
+/// Each row is a `u16`, with the leftmost pixel in the most significant bit.
+/// Pixels set in the `mask` are drawn:
+/// black where they're also set in the `image`, white where they're not.
+/// The rest show through.
+pub fn draw_masked_image(st: &mut dyn Draw, at: Point, image: &[u16; 16], mask: &[u16; 16]) {
+    let (left, top) = at;
+    for (y, (&image, &mask)) in (top..).zip(image.iter().zip(mask.iter())) {
+        // Draw each row as runs of black and white,
+        // ending a run wherever the pixels change colour or become transparent.
+        let mut run: Option<(Unit, u8)> = None;
+        for column in 0..=16 {
+            let bit = 0x8000u16.checked_shr(column as u32).unwrap_or(0);
+            let colour = if (mask & bit) == 0 {
+                None
+            } else if (image & bit) != 0 {
+                Some(LINE_BLACK)
+            } else {
+                Some(LINE_WHITE)
+            };
+            match run {
+                Some((from, pattern)) if colour != Some(pattern) => {
+                    st.horizontal_line((left + from, y), left + column, pattern);
+                    run = colour.map(|pattern| (column, pattern));
+                }
+                None => run = colour.map(|pattern| (column, pattern)),
+                _ => (),
+            }
+        }
+    }
+}
+
+/// Inverts the outline of `r`, leaving its inside alone.
+/// Inverting it again rubs it out.
+pub fn invert_outline(d: &mut dyn Draw, r: Rect) {
+    let ((left, top), (right, bottom)) = r;
+    d.invert_horizontal_line((left, top), right);
+    d.invert_horizontal_line((left, bottom - 1), right);
+    d.invert_rectangle((left, top + 1), (left + 1, bottom - 1));
+    d.invert_rectangle((right - 1, top + 1), (right, bottom - 1));
 }
 
 /// Draws a simple dialog box onto the provided stencil.
@@ -31,21 +100,24 @@ pub fn draw_desktop(st: &mut dyn Draw) {
 /// The `paper` parameter specifies the rectangle of the dialog's "paper" surface.  Any borders to
 /// the dialog box will be drawn *around* this rectangle, clipped as appropriate.  This allows a
 /// dialog to cover the full stencil surface if required.
+///
+/// The border and its shadow are as thick as the `theme` says.
 pub fn draw_dialog_box(
     st: &mut dyn Draw,
     paper: Rect,
+    theme: &Theme,
 ) {
     let ((paper_left, paper_top), (paper_right, paper_bottom)) = paper;
 
-    let border_left = paper_left - 1;
-    let border_top = paper_top - 1;
-    let border_right = paper_right + 1;
-    let border_bottom = paper_bottom + 1;
+    let border_left = paper_left - theme.border;
+    let border_top = paper_top - theme.border;
+    let border_right = paper_right + theme.border;
+    let border_bottom = paper_bottom + theme.border;
 
-    let shadow_left = border_left + 1;
-    let shadow_top = border_top + 1;
-    let shadow_right = border_right + 1;
-    let shadow_bottom = border_bottom + 1;
+    let shadow_left = border_left + theme.shadow;
+    let shadow_top = border_top + theme.shadow;
+    let shadow_right = border_right + theme.shadow;
+    let shadow_bottom = border_bottom + theme.shadow;
 
     st.filled_rectangle(
         (shadow_left, shadow_top),
@@ -60,7 +132,163 @@ pub fn draw_dialog_box(
     st.filled_rectangle(
         (paper_left, paper_top),
         (paper_right, paper_bottom),
-        &WHITE_PATTERN,
+        &theme.paper,
     );
 }
 
+
+/// Fixtures shared by the unit tests of several modules.
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::WHITE_PATTERN;
+    use crate::focus::FocusId;
+    use crate::mediator::Mediator;
+    use crate::pointer::{PointerShape, ARROW};
+    use crate::stencil::{Draw, Stencil};
+    use crate::theme::{Theme, CLASSIC_THEME};
+    use crate::timers::{TimerId, TimerQueue};
+    use crate::transfer::{DragSession, TransferData};
+    use crate::types::{Dimension, Point};
+
+    /// Creates a blank, all-white stencil for tests to draw or print on.
+    pub(crate) fn blank_stencil(width: Dimension, height: Dimension) -> Stencil {
+        let mut s = Stencil::new_with_dimensions(width, height);
+        s.filled_rectangle((0, 0), (width, height), &WHITE_PATTERN);
+        s
+    }
+
+    /// Answers the coordinates of every black pixel on the stencil, row by row.
+    pub(crate) fn inked_pixels(s: &Stencil) -> Vec<Point> {
+        let span = s.get_span();
+        let (width, height) = s.dimensions;
+        let mut inked = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                let byte = s.bits[y as usize * span + (x >> 3) as usize];
+                if (byte & (0x80 >> (x & 7))) == 0 {
+                    inked.push((x, y));
+                }
+            }
+        }
+        inked
+    }
+
+    /// A mediator for exercising gadgets without a host environment.
+    pub(crate) struct TestMediator {
+        /// The desktop, initially blank.
+        pub(crate) desktop: Stencil,
+
+        /// How many times a repaint was requested.
+        pub(crate) repaints: usize,
+
+        /// True once something asked to quit.
+        pub(crate) quit_requested: bool,
+
+        /// The focus owner.
+        pub(crate) focus: Option<FocusId>,
+
+        /// Timers started through the mediator.
+        /// Time stands still at zero unless a test moves it on.
+        pub(crate) timers: TimerQueue,
+
+        /// True if an idle event was requested.
+        pub(crate) idle_requested: bool,
+
+        /// The pointer's current shape.
+        pub(crate) pointer: &'static PointerShape,
+
+        /// What's on the clipboard, kept in memory.
+        pub(crate) clipboard: Option<TransferData>,
+
+        /// The drag in progress.
+        /// Nothing moves it but the test.
+        pub(crate) drag: Option<DragSession>,
+
+        /// The theme gadgets draw themselves in.
+        pub(crate) theme: &'static Theme,
+    }
+
+    impl TestMediator {
+        /// Creates a mediator with a blank desktop of the given dimensions.
+        pub(crate) fn new(width: Dimension, height: Dimension) -> Self {
+            Self {
+                desktop: blank_stencil(width, height),
+                repaints: 0,
+                quit_requested: false,
+                focus: None,
+                timers: TimerQueue::new(),
+                idle_requested: false,
+                pointer: &ARROW,
+                clipboard: None,
+                drag: None,
+                theme: &CLASSIC_THEME,
+            }
+        }
+    }
+
+    impl Mediator for TestMediator {
+        fn repaint_all(&mut self) {
+            self.repaints += 1;
+        }
+
+        fn quit(&mut self) {
+            self.quit_requested = true;
+        }
+
+        fn borrow_mut_desktop(&mut self) -> &mut Stencil {
+            &mut self.desktop
+        }
+
+        fn focus(&self) -> Option<FocusId> {
+            self.focus
+        }
+
+        fn set_focus(&mut self, focus: Option<FocusId>) {
+            self.focus = focus;
+        }
+
+        fn start_timer(&mut self, delay: u32, periodic: bool) -> TimerId {
+            self.timers.start(0, delay, periodic)
+        }
+
+        fn cancel_timer(&mut self, id: TimerId) {
+            self.timers.cancel(id);
+        }
+
+        fn request_idle(&mut self) {
+            self.idle_requested = true;
+        }
+
+        fn set_pointer(&mut self, shape: &'static PointerShape) {
+            self.pointer = shape;
+        }
+
+        fn clipboard(&self) -> Option<TransferData> {
+            self.clipboard.clone()
+        }
+
+        fn set_clipboard(&mut self, data: TransferData) {
+            self.clipboard = Some(data);
+        }
+
+        fn start_drag(&mut self, session: DragSession) {
+            self.drag = Some(session);
+        }
+
+        fn drag(&self) -> Option<&DragSession> {
+            self.drag.as_ref()
+        }
+
+        fn end_drag(&mut self) -> Option<DragSession> {
+            self.drag.take()
+        }
+
+        fn theme(&self) -> &'static Theme {
+            self.theme
+        }
+
+        fn set_theme(&mut self, theme: &'static Theme) {
+            self.theme = theme;
+        }
+    }
+}
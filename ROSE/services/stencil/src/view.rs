@@ -1,7 +1,7 @@
 //! Views
 
 use crate::mediator::Mediator;
-use crate::types::{Rect, Point};
+use crate::types::{Dimensions, Rect, Point};
 
 /// An interface for things which can be drawn to a stencil.
 pub trait View {
@@ -9,6 +9,20 @@ pub trait View {
     /// as returned by [[Mediator::borrow_mut_desktop]].
     // ISSUE: Should we just take a &mut Stencil directly here?
     fn draw(&mut self, med: &mut dyn Mediator);
+
+    /// Answers the size the thing would like to be,
+    /// for the benefit of layout managers.
+    ///
+    /// By default, things have no preference, and answer `None`.
+    fn preferred_size(&self) -> Option<Dimensions> {
+        None
+    }
+
+    /// Moves the thing to occupy `area` from now on.
+    /// It is not redrawn.
+    ///
+    /// By default, things stay where they are.
+    fn set_area(&mut self, _area: Rect) {}
 }
 
 /// Answers `true` if and only if the point `p` is contained
@@ -0,0 +1,987 @@
+//! Windows
+//!
+//! A [[WindowManager]] keeps a stack of overlapping windows on the desktop.
+//! Each window has a title bar,
+//! with a close gadget at its left end and a depth gadget at its right end,
+//! and a size gadget in its bottom right-hand corner.
+//! The operator drags a window around by its title bar,
+//! and resizes it by its size gadget;
+//! either way, an outline follows the pointer until the button is let go,
+//! and only then does the window move.
+//! The depth gadget sends the frontmost window to the back,
+//! or brings any other window to the front;
+//! so does clicking anywhere in a window behind the others.
+//! The close gadget only asks for the window to be closed;
+//! the application decides whether it should be.
+//!
+//! Like a [[Container]], each window holds a view,
+//! whose events are converted to the single event type `E` the application deals in.
+//! The view fills the window below its title bar,
+//! and is told whenever the window moves or changes size.
+//! Only the frontmost window's view hears from the mouse;
+//! since nothing covers it, its gadgets can draw themselves whenever they like.
+//! Views in windows further back must not draw themselves;
+//! the application asks for them to be drawn with [[WindowManager::refresh]] instead.
+//!
+//! The desktop behind the windows is filled with the desktop pattern,
+//! unless the application gives it a view of its own,
+//! such as an [[IconLayer]], with [[WindowManager::set_backdrop]].
+//! The backdrop hears from the mouse wherever no window is.
+//! It may draw itself whenever it likes;
+//! the window manager repairs any windows it draws over.
+//!
+//! The stencil has no notion of clipping,
+//! so when part of the desktop is uncovered (or otherwise spoiled),
+//! the window manager notes it as *damaged*.
+//! [[WindowManager::repair]] repaints the damage:
+//! it draws the desktop and every window touching the damage from back to front,
+//! and then puts back everything outside of the damage as it was.
+
+use crate::container::{component, Component};
+use crate::events::{ButtonEvent, Modifiers, MouseButton, MouseEventSink};
+use crate::mediator::Mediator;
+use crate::simple_printer::SimplePrinter;
+use crate::stencil::Draw;
+use crate::theme::{Theme, CLASSIC_THEME};
+use crate::types::{Dimension, Dimensions, Point, Rect};
+use crate::utils::{draw_dialog_box, draw_glyph, invert_outline, Glyph, LINE_BLACK};
+use crate::view::{View, rect_contains};
+
+/// Identifies a window within its window manager.
+pub type WindowId = usize;
+
+/// The height of a window's title bar, and the width of its close and depth gadgets.
+pub const TITLE_HEIGHT: Dimension = 12;
+
+/// The width and height of a window's size gadget.
+pub const SIZE_GADGET: Dimension = 10;
+
+/// The smallest a window may be resized to.
+pub const MIN_SIZE: Dimensions = (64, 32);
+
+/// The close gadget: a dot in a box.
+static CLOSE_GLYPH: Glyph = [
+    0b00000000,
+    0b01111110,
+    0b01000010,
+    0b01011010,
+    0b01011010,
+    0b01000010,
+    0b01111110,
+    0b00000000,
+];
+
+/// The depth gadget: one box in front of another.
+static DEPTH_GLYPH: Glyph = [
+    0b11110000,
+    0b10010000,
+    0b10011111,
+    0b11110001,
+    0b00010001,
+    0b00010001,
+    0b00011111,
+    0b00000000,
+];
+
+/// The size gadget: a corner to drag.
+static SIZE_GLYPH: Glyph = [
+    0b00000000,
+    0b00000010,
+    0b00000110,
+    0b00001010,
+    0b00010010,
+    0b00100010,
+    0b01111110,
+    0b00000000,
+];
+
+/// Answers the title bar of a window occupying `frame`.
+fn title_bar(frame: Rect) -> Rect {
+    let ((left, top), (right, _)) = frame;
+    ((left, top), (right, top + TITLE_HEIGHT))
+}
+
+/// Answers the close gadget of a window occupying `frame`.
+fn close_gadget(frame: Rect) -> Rect {
+    let ((left, top), _) = frame;
+    ((left, top), (left + TITLE_HEIGHT, top + TITLE_HEIGHT))
+}
+
+/// Answers the depth gadget of a window occupying `frame`.
+fn depth_gadget(frame: Rect) -> Rect {
+    let ((_, top), (right, _)) = frame;
+    ((right - TITLE_HEIGHT, top), (right, top + TITLE_HEIGHT))
+}
+
+/// Answers the size gadget of a window occupying `frame`.
+fn size_gadget(frame: Rect) -> Rect {
+    let (_, (right, bottom)) = frame;
+    ((right - SIZE_GADGET, bottom - SIZE_GADGET), (right, bottom))
+}
+
+/// Answers the area the view of a window occupying `frame` fills.
+pub fn content_area(frame: Rect) -> Rect {
+    let ((left, top), lower_right) = frame;
+    ((left, top + TITLE_HEIGHT + 1), lower_right)
+}
+
+/// Answers everything a window occupying `frame` draws on,
+/// including its border and shadow, as thick as the `theme` draws them.
+fn extent(frame: Rect, theme: &Theme) -> Rect {
+    let ((left, top), (right, bottom)) = frame;
+    let beyond = theme.border + theme.shadow;
+    ((left - theme.border, top - theme.border), (right + beyond, bottom + beyond))
+}
+
+/// Answers `true` if `a` and `b` have any pixels in common.
+fn overlaps(a: Rect, b: Rect) -> bool {
+    let ((a_left, a_top), (a_right, a_bottom)) = a;
+    let ((b_left, b_top), (b_right, b_bottom)) = b;
+    (a_left < b_right) && (b_left < a_right) && (a_top < b_bottom) && (b_top < a_bottom)
+}
+
+/// Answers the smallest rectangle covering both `a` and `b`.
+fn union(a: Rect, b: Rect) -> Rect {
+    let ((a_left, a_top), (a_right, a_bottom)) = a;
+    let ((b_left, b_top), (b_right, b_bottom)) = b;
+    ((a_left.min(b_left), a_top.min(b_top)), (a_right.max(b_right), a_bottom.max(b_bottom)))
+}
+
+/// A window on the desktop.
+struct Window<E> {
+    id: WindowId,
+
+    /// The rectangle the window occupies, inside its border.
+    frame: Rect,
+
+    /// What the title bar says.
+    title: String,
+
+    /// The view filling the window below its title bar.
+    content: Box<dyn Component<E>>,
+}
+
+/// What the operator pressed the mouse button on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Grab {
+    /// The close gadget.
+    Close,
+
+    /// The depth gadget.
+    Depth,
+
+    /// The title bar, this far from the window's top left corner.
+    Drag(Point),
+
+    /// The size gadget, this far from the window's bottom right corner.
+    Size(Point),
+
+    /// The window's view.
+    Content,
+}
+
+/// Events a window manager may answer with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowEvent<E> {
+    /// Nothing of interest happened.
+    None,
+
+    /// The operator clicked the window's close gadget.
+    /// The window stays open until the application closes it.
+    CloseRequested(WindowId),
+
+    /// The operator dragged the window somewhere else.
+    Moved(WindowId),
+
+    /// The operator changed the window's size.
+    Resized(WindowId),
+
+    /// The window's view reported an event.
+    Content(WindowId, E),
+
+    /// The backdrop reported an event.
+    Backdrop(E),
+}
+
+/// Keeps overlapping windows on the desktop.
+///
+/// Windows are stacked in the order they were opened;
+/// the most recently opened window is in front.
+pub struct WindowManager<E> {
+    /// The windows, from back to front.
+    windows: Vec<Window<E>>,
+
+    /// The identifier the next window opened will receive.
+    next_id: WindowId,
+
+    /// The window and part of it the mouse button was pressed on, if it's still held down.
+    captured: Option<(WindowId, Grab)>,
+
+    /// Set while the gadget the button was pressed on is shown pressed.
+    inverted: bool,
+
+    /// Where the outline of a window being dragged or resized is drawn, if it is.
+    outline: Option<Rect>,
+
+    /// The part of the desktop in need of repair, if any.
+    damage: Option<Rect>,
+
+    /// The view filling the desktop behind the windows, if any.
+    backdrop: Option<Box<dyn Component<E>>>,
+
+    /// Set while the mouse button pressed on the backdrop is held down.
+    backdrop_captured: bool,
+
+    /// Tracks the current mouse position.
+    mouse_pt: Point,
+
+    /// The theme windows were last drawn in,
+    /// which decides how far their borders and shadows reach.
+    theme: &'static Theme,
+}
+
+impl<E> Default for WindowManager<E> {
+    fn default() -> Self {
+        Self {
+            windows: Vec::new(),
+            next_id: 0,
+            captured: None,
+            inverted: false,
+            outline: None,
+            damage: None,
+            backdrop: None,
+            backdrop_captured: false,
+            mouse_pt: (0, 0),
+            theme: &CLASSIC_THEME,
+        }
+    }
+}
+
+impl<E: 'static> WindowManager<E> {
+    /// Creates a window manager with no windows.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Opens a window occupying `frame`, titled `title`, in front of every other window,
+    /// and draws it.
+    /// Its view is moved to fill the window below the title bar;
+    /// its events are converted with `map`.
+    ///
+    /// Answers the identifier of the window.
+    pub fn open<V, G>(&mut self, med: &mut dyn Mediator, frame: Rect, title: &str, view: V, map: fn(G) -> E) -> WindowId
+    where
+        V: View + MouseEventSink<G> + 'static,
+        G: 'static,
+    {
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut content = component(view, map);
+        content.set_area(content_area(frame));
+        let was = self.front();
+        self.windows.push(Window { id, frame, title: String::from(title), content });
+
+        self.damage_titles(was);
+        self.damage(extent(frame, self.theme));
+        self.repair(med);
+        id
+    }
+
+    /// Closes the window `id`, if it's open, and repairs the desktop underneath.
+    pub fn close(&mut self, med: &mut dyn Mediator, id: WindowId) {
+        if let Some(i) = self.index_of(id) {
+            let was = self.front();
+            let window = self.windows.remove(i);
+            if self.captured.is_some_and(|(captured, _)| captured == id) {
+                self.captured = None;
+            }
+            self.damage(extent(window.frame, self.theme));
+            self.damage_titles(was);
+            self.repair(med);
+        }
+    }
+
+    /// Answers the identifiers of the open windows, from back to front.
+    pub fn windows(&self) -> Vec<WindowId> {
+        self.windows.iter().map(|w| w.id).collect()
+    }
+
+    /// Answers the frontmost window, if any window is open.
+    pub fn front(&self) -> Option<WindowId> {
+        self.windows.last().map(|w| w.id)
+    }
+
+    /// Answers the frontmost window with any part of it under the point `pt`, if any.
+    pub fn window_at(&self, pt: Point) -> Option<WindowId> {
+        self.windows.iter().rev().find(|w| rect_contains(extent(w.frame, self.theme), pt)).map(|w| w.id)
+    }
+
+    /// Answers the rectangle the window `id` occupies, inside its border.
+    pub fn get_frame(&self, id: WindowId) -> Option<Rect> {
+        self.window(id).map(|w| w.frame)
+    }
+
+    /// Answers the area the view of the window `id` fills.
+    pub fn get_content_area(&self, id: WindowId) -> Option<Rect> {
+        self.window(id).map(|w| content_area(w.frame))
+    }
+
+    /// Answers the view of the window `id`, if it's open and its view is a `V`.
+    pub fn get_content<V: 'static>(&self, id: WindowId) -> Option<&V> {
+        self.window(id).and_then(|w| w.content.view().downcast_ref())
+    }
+
+    /// Answers the view of the window `id`, if it's open and its view is a `V`.
+    ///
+    /// Changes to the view can only be shown by [[WindowManager::refresh]],
+    /// unless the window is in front.
+    pub fn get_content_mut<V: 'static>(&mut self, id: WindowId) -> Option<&mut V> {
+        let i = self.index_of(id)?;
+        self.windows[i].content.view_mut().downcast_mut()
+    }
+
+    /// Retitles the window `id`, and redraws its title bar.
+    pub fn set_title(&mut self, med: &mut dyn Mediator, id: WindowId, title: &str) {
+        if let Some(i) = self.index_of(id) {
+            self.windows[i].title = String::from(title);
+            self.damage(title_bar(self.windows[i].frame));
+            self.repair(med);
+        }
+    }
+
+    /// Moves the window `id` to occupy `frame`, and repairs the desktop.
+    /// Its view is told of its new area.
+    pub fn set_frame(&mut self, med: &mut dyn Mediator, id: WindowId, frame: Rect) {
+        if let Some(i) = self.index_of(id) {
+            let window = &mut self.windows[i];
+            let old = extent(window.frame, self.theme);
+            window.frame = frame;
+            window.content.set_area(content_area(frame));
+            self.damage(old);
+            self.damage(extent(frame, self.theme));
+            self.repair(med);
+        }
+    }
+
+    /// Brings the window `id` in front of every other window.
+    pub fn bring_to_front(&mut self, med: &mut dyn Mediator, id: WindowId) {
+        if let Some(i) = self.index_of(id) {
+            let was = self.front();
+            let window = self.windows.remove(i);
+            self.damage(extent(window.frame, self.theme));
+            self.windows.push(window);
+            self.damage_titles(was);
+            self.repair(med);
+        }
+    }
+
+    /// Sends the window `id` behind every other window, and repairs the desktop.
+    pub fn send_to_back(&mut self, med: &mut dyn Mediator, id: WindowId) {
+        if let Some(i) = self.index_of(id) {
+            let was = self.front();
+            let window = self.windows.remove(i);
+            self.damage(extent(window.frame, self.theme));
+            self.windows.insert(0, window);
+            self.damage_titles(was);
+            self.repair(med);
+        }
+    }
+
+    /// Fills the desktop behind the windows with `view`, in place of the desktop pattern,
+    /// and redraws everything.
+    /// Its events are converted with `map`.
+    pub fn set_backdrop<V, G>(&mut self, med: &mut dyn Mediator, view: V, map: fn(G) -> E)
+    where
+        V: View + MouseEventSink<G> + 'static,
+        G: 'static,
+    {
+        let mut backdrop = component(view, map);
+        backdrop.set_area(((0, 0), med.borrow_mut_desktop().get_dimensions()));
+        self.backdrop = Some(backdrop);
+        self.backdrop_captured = false;
+        self.draw(med);
+    }
+
+    /// Answers the backdrop, if there is one and it's a `V`.
+    pub fn get_backdrop<V: 'static>(&self) -> Option<&V> {
+        self.backdrop.as_ref().and_then(|b| b.view().downcast_ref())
+    }
+
+    /// Answers the backdrop, if there is one and it's a `V`.
+    ///
+    /// Changes to it can be shown by damaging the area they cover,
+    /// and repairing the desktop.
+    pub fn get_backdrop_mut<V: 'static>(&mut self) -> Option<&mut V> {
+        self.backdrop.as_mut().and_then(|b| b.view_mut().downcast_mut())
+    }
+
+    /// Redraws the view of the window `id`, wherever it can be seen.
+    pub fn refresh(&mut self, med: &mut dyn Mediator, id: WindowId) {
+        if let Some(area) = self.get_content_area(id) {
+            self.damage(area);
+            self.repair(med);
+        }
+    }
+
+    /// Notes that `area` of the desktop needs repainting.
+    /// Nothing is repainted until [[WindowManager::repair]] is called.
+    pub fn damage(&mut self, area: Rect) {
+        self.damage = Some(self.damage.map_or(area, |d| union(d, area)));
+    }
+
+    /// Repaints whatever was damaged since the last repair:
+    /// the desktop, then every window touching the damage, from back to front.
+    /// Nothing outside of the damage changes.
+    pub fn repair(&mut self, med: &mut dyn Mediator) {
+        let Some(((left, top), (right, bottom))) = self.damage.take() else {
+            return;
+        };
+
+        // Only whole bytes can be saved and put back,
+        // so everything in the bytes the damage touches is repainted.
+        let (width, height) = med.borrow_mut_desktop().get_dimensions();
+        let damage = (((left.max(0) >> 3) << 3, top.max(0)), (((right + 7) >> 3) << 3, bottom.min(height)));
+        let ((left, top), (right, bottom)) = damage;
+        if (left >= right.min(width)) || (top >= bottom) {
+            return;
+        }
+
+        // Anything drawn to show a press or drag in progress comes off first, and goes back on last,
+        // so that the repaint neither hides it nor spoils it.
+        self.toggle_feedback(med);
+        self.theme = med.theme();
+        let desktop = med.borrow_mut_desktop();
+        let before = desktop.save_area((0, 0), (width, height));
+        match &mut self.backdrop {
+            Some(backdrop) => backdrop.draw(med),
+
+            // Patterns line up with the top of whatever they fill,
+            // so the desktop is filled from the nearest multiple of eight rows above the damage,
+            // as it was when it filled the whole stencil.
+            None => desktop.filled_rectangle((left, top & !7), (right, bottom), &self.theme.desktop),
+        }
+        for i in 0..self.windows.len() {
+            if overlaps(extent(self.windows[i].frame, self.theme), damage) {
+                self.draw_window(med, i);
+            }
+        }
+
+        let desktop = med.borrow_mut_desktop();
+        let repaired = desktop.save_area((left, top), (right, bottom));
+        desktop.restore_area(&before);
+        desktop.restore_area(&repaired);
+        self.toggle_feedback(med);
+        med.repaint_all();
+    }
+
+    /// Notes the title bars of the window which was in front and the one which now is as damaged,
+    /// if they differ; only the frontmost window's title bar is inverted.
+    fn damage_titles(&mut self, was: Option<WindowId>) {
+        let now = self.front();
+        if now != was {
+            let frames: Vec<Rect> = [was, now].into_iter().flatten().filter_map(|id| self.get_frame(id)).collect();
+            for frame in frames {
+                self.damage(title_bar(frame));
+            }
+        }
+    }
+
+    fn index_of(&self, id: WindowId) -> Option<usize> {
+        self.windows.iter().position(|w| w.id == id)
+    }
+
+    fn window(&self, id: WindowId) -> Option<&Window<E>> {
+        self.windows.iter().find(|w| w.id == id)
+    }
+
+    /// Draws the `i`th window from the back, and its view, over whatever is there.
+    fn draw_window(&mut self, med: &mut dyn Mediator, i: usize) {
+        let is_front = i + 1 == self.windows.len();
+        let window = &mut self.windows[i];
+        let frame = window.frame;
+        let ((left, top), (right, _)) = frame;
+        let theme = self.theme;
+        let d = med.borrow_mut_desktop();
+        draw_dialog_box(d, frame, theme);
+
+        // The title bar, with the close gadget at its left end and the depth gadget at its right.
+        let bar_bottom = top + TITLE_HEIGHT;
+        let close_right = left + TITLE_HEIGHT;
+        let depth_left = right - TITLE_HEIGHT;
+        d.filled_rectangle((left, top), (right, bar_bottom), &theme.paper);
+        d.horizontal_line((left, bar_bottom), right, LINE_BLACK);
+        d.vertical_line((close_right, top), bar_bottom, LINE_BLACK);
+        d.vertical_line((depth_left - 1, top), bar_bottom, LINE_BLACK);
+        draw_glyph(d, (left + 2, top + 2), &CLOSE_GLYPH);
+        draw_glyph(d, (depth_left + 2, top + 2), &DEPTH_GLYPH);
+
+        let title_right = depth_left - 1;
+        let mut p = SimplePrinter::new(d, ((close_right + 4, top + 2), (title_right, bar_bottom)), theme.font);
+        for ch in window.title.chars() {
+            if p.head.0 + theme.font.glyph_advance(ch) > title_right - 2 {
+                break;
+            }
+            p.print_char(ch);
+        }
+
+        // The frontmost window stands out with its title bar inverted.
+        if is_front {
+            d.invert_rectangle((close_right + 1, top), (depth_left - 1, bar_bottom));
+        }
+
+        window.content.draw(med);
+
+        let ((size_left, size_top), (size_right, size_bottom)) = size_gadget(frame);
+        let d = med.borrow_mut_desktop();
+        d.filled_rectangle((size_left, size_top), (size_right, size_bottom), &theme.paper);
+        d.horizontal_line((size_left, size_top), size_right, LINE_BLACK);
+        d.vertical_line((size_left, size_top), size_bottom, LINE_BLACK);
+        draw_glyph(d, (size_left + 1, size_top + 1), &SIZE_GLYPH);
+    }
+
+    /// Inverts the pressed gadget, if it's shown pressed, and the outline being dragged, if any.
+    fn toggle_feedback(&self, med: &mut dyn Mediator) {
+        if let Some((upper_left, lower_right)) = self.pressed_gadget().filter(|_| self.inverted) {
+            med.borrow_mut_desktop().invert_rectangle(upper_left, lower_right);
+        }
+        if let Some(outline) = self.outline {
+            invert_outline(med.borrow_mut_desktop(), outline);
+        }
+    }
+
+    /// Answers the gadget the captured window's button press is on, if any.
+    fn pressed_gadget(&self) -> Option<Rect> {
+        let (id, grab) = self.captured?;
+        let frame = self.window(id)?.frame;
+        match grab {
+            Grab::Close => Some(close_gadget(frame)),
+            Grab::Depth => Some(depth_gadget(frame)),
+            _ => None,
+        }
+    }
+
+    /// Shows the pressed gadget pressed if the pointer is over it, and not if not.
+    fn follow_press(&mut self, med: &mut dyn Mediator) {
+        if let Some((upper_left, lower_right)) = self.pressed_gadget() {
+            let hot = rect_contains((upper_left, lower_right), self.mouse_pt);
+            if hot != self.inverted {
+                self.inverted = hot;
+                med.borrow_mut_desktop().invert_rectangle(upper_left, lower_right);
+                med.repaint_all();
+            }
+        }
+    }
+
+    /// Answers where the window being dragged or resized would go, if the button were let go now.
+    fn proposed_frame(&self) -> Option<Rect> {
+        let (id, grab) = self.captured?;
+        let ((left, top), (right, bottom)) = self.window(id)?.frame;
+        let (x, y) = self.mouse_pt;
+        match grab {
+            Grab::Drag((dx, dy)) => {
+                let (width, height) = (right - left, bottom - top);
+                let (left, top) = (x - dx, y - dy);
+                Some(((left, top), (left + width, top + height)))
+            }
+            Grab::Size((dx, dy)) => {
+                let (min_width, min_height) = MIN_SIZE;
+                Some(((left, top), ((x + dx).max(left + min_width), (y + dy).max(top + min_height))))
+            }
+            _ => None,
+        }
+    }
+
+    /// Moves the outline of the window being dragged or resized to follow the pointer.
+    fn follow_outline(&mut self, med: &mut dyn Mediator) {
+        let proposed = self.proposed_frame();
+        if proposed != self.outline {
+            let d = med.borrow_mut_desktop();
+            if let Some(old) = self.outline {
+                invert_outline(d, old);
+            }
+            if let Some(new) = proposed {
+                invert_outline(d, new);
+            }
+            self.outline = proposed;
+            med.repaint_all();
+        }
+    }
+
+    /// Passes a mouse event on to the backdrop by way of `deliver`,
+    /// repairing any windows it draws over,
+    /// and answers with whatever the backdrop answers with.
+    fn deliver_to_backdrop<F>(&mut self, med: &mut dyn Mediator, deliver: F) -> WindowEvent<E>
+    where
+        F: FnOnce(&mut dyn Component<E>, &mut dyn Mediator) -> E,
+    {
+        let Some(backdrop) = &mut self.backdrop else {
+            return WindowEvent::None;
+        };
+        let before = med.borrow_mut_desktop().borrow_bits().to_vec();
+        let event = deliver(backdrop.as_mut(), med);
+        if med.borrow_mut_desktop().borrow_bits() != &before[..] {
+            let extents: Vec<Rect> = self.windows.iter().map(|w| extent(w.frame, self.theme)).collect();
+            for area in extents {
+                self.damage(area);
+            }
+            self.repair(med);
+        }
+        WindowEvent::Backdrop(event)
+    }
+
+    /// Answers the frontmost window, if the point `pt` is in its view.
+    fn front_content_at(&self, pt: Point) -> Option<usize> {
+        let front = self.windows.len().checked_sub(1)?;
+        rect_contains(content_area(self.windows[front].frame), pt).then_some(front)
+    }
+}
+
+impl<E: 'static> View for WindowManager<E> {
+    /// Draws the desktop, and every window on it from back to front.
+    fn draw(&mut self, med: &mut dyn Mediator) {
+        self.theme = med.theme();
+        match &mut self.backdrop {
+            Some(backdrop) => backdrop.draw(med),
+            None => {
+                let d = med.borrow_mut_desktop();
+                let size = d.get_dimensions();
+                d.filled_rectangle((0, 0), size, &self.theme.desktop);
+            }
+        }
+        for i in 0..self.windows.len() {
+            self.draw_window(med, i);
+        }
+        self.damage = None;
+        med.repaint_all();
+    }
+}
+
+impl<E: 'static> MouseEventSink<WindowEvent<E>> for WindowManager<E> {
+    /// Handles mouse motion events.
+    ///
+    /// While a window is dragged or resized, its outline follows the pointer;
+    /// otherwise, the motion goes to the view of the window (or the backdrop) the button was pressed in,
+    /// or failing that, the frontmost window's view,
+    /// answering with whatever the view answers with.
+    /// Otherwise, answers with [[WindowEvent::None]].
+    fn pointer_moved(&mut self, med: &mut dyn Mediator, to: Point) -> WindowEvent<E> {
+        self.mouse_pt = to;
+        if self.backdrop_captured {
+            return self.deliver_to_backdrop(med, |b, med| b.pointer_moved(med, to));
+        }
+        match self.captured {
+            Some((_, Grab::Close | Grab::Depth)) => {
+                self.follow_press(med);
+                WindowEvent::None
+            }
+            Some((_, Grab::Drag(_) | Grab::Size(_))) => {
+                self.follow_outline(med);
+                WindowEvent::None
+            }
+            _ => match self.windows.last_mut() {
+                Some(window) => WindowEvent::Content(window.id, window.content.pointer_moved(med, to)),
+                None => WindowEvent::None,
+            },
+        }
+    }
+
+    /// Handles mouse button-up events.
+    ///
+    /// Letting go of the button over the gadget it was pressed on
+    /// answers with [[WindowEvent::CloseRequested]] for the close gadget,
+    /// and rearranges the windows for the depth gadget.
+    /// Letting go after dragging the title bar or size gadget
+    /// moves or resizes the window,
+    /// answering with [[WindowEvent::Moved]] or [[WindowEvent::Resized]].
+    /// A button pressed in a window's view (or the backdrop) is let go there too,
+    /// answering with whatever the view answers with.
+    /// Otherwise, answers with [[WindowEvent::None]].
+    fn button_up(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> WindowEvent<E> {
+        self.mouse_pt = event.at;
+        if self.backdrop_captured {
+            self.backdrop_captured = false;
+            return self.deliver_to_backdrop(med, |b, med| b.button_up(med, event));
+        }
+        let Some((id, grab)) = self.captured else {
+            return WindowEvent::None;
+        };
+        if (grab != Grab::Content) && (event.button != MouseButton::Left) {
+            return WindowEvent::None;
+        }
+
+        self.follow_press(med);
+        let hot = self.inverted;
+        if let Some((upper_left, lower_right)) = self.pressed_gadget().filter(|_| hot) {
+            med.borrow_mut_desktop().invert_rectangle(upper_left, lower_right);
+            med.repaint_all();
+        }
+        let proposed = self.proposed_frame();
+        self.inverted = false;
+        self.captured = None;
+
+        match grab {
+            Grab::Close if hot => WindowEvent::CloseRequested(id),
+            Grab::Depth if hot => {
+                if self.front() == Some(id) {
+                    self.send_to_back(med, id);
+                } else {
+                    self.bring_to_front(med, id);
+                }
+                WindowEvent::None
+            }
+            Grab::Close | Grab::Depth => WindowEvent::None,
+            Grab::Drag(_) | Grab::Size(_) => {
+                if let Some(outline) = self.outline.take() {
+                    invert_outline(med.borrow_mut_desktop(), outline);
+                    med.repaint_all();
+                }
+                match proposed {
+                    Some(frame) if Some(frame) != self.get_frame(id) => {
+                        self.set_frame(med, id, frame);
+                        if let Grab::Drag(_) = grab { WindowEvent::Moved(id) } else { WindowEvent::Resized(id) }
+                    }
+                    _ => WindowEvent::None,
+                }
+            }
+            Grab::Content => match self.index_of(id) {
+                Some(i) => WindowEvent::Content(id, self.windows[i].content.button_up(med, event)),
+                None => WindowEvent::None,
+            },
+        }
+    }
+
+    /// Handles mouse button-down events.
+    ///
+    /// A press anywhere in a window behind the others brings it to the front first,
+    /// unless it's on the depth gadget.
+    /// A left button press on the window's gadgets or title bar is followed until it's let go.
+    /// A press in the window's view goes to the view,
+    /// and a press outside of every window goes to the backdrop,
+    /// answering with whatever the view answers with.
+    /// Otherwise, answers with [[WindowEvent::None]].
+    fn button_down(&mut self, med: &mut dyn Mediator, event: ButtonEvent) -> WindowEvent<E> {
+        self.mouse_pt = event.at;
+        if self.captured.is_some() || self.backdrop_captured {
+            return WindowEvent::None;
+        }
+        let Some(id) = self.window_at(event.at) else {
+            self.backdrop_captured = self.backdrop.is_some();
+            return self.deliver_to_backdrop(med, |b, med| b.button_down(med, event));
+        };
+        let frame = self.get_frame(id).expect("windows under the pointer are open");
+        let (x, y) = event.at;
+        let ((left, top), (right, bottom)) = frame;
+
+        let on_depth = rect_contains(depth_gadget(frame), event.at);
+        if !on_depth && (self.front() != Some(id)) {
+            self.bring_to_front(med, id);
+        }
+
+        let grab = if rect_contains(content_area(frame), event.at) && !rect_contains(size_gadget(frame), event.at) {
+            Grab::Content
+        } else if event.button != MouseButton::Left {
+            return WindowEvent::None;
+        } else if rect_contains(close_gadget(frame), event.at) {
+            Grab::Close
+        } else if on_depth {
+            Grab::Depth
+        } else if rect_contains(size_gadget(frame), event.at) {
+            Grab::Size((right - x, bottom - y))
+        } else if rect_contains(title_bar(frame), event.at) {
+            Grab::Drag((x - left, y - top))
+        } else {
+            return WindowEvent::None;
+        };
+        self.captured = Some((id, grab));
+
+        match grab {
+            Grab::Close | Grab::Depth => {
+                self.follow_press(med);
+                WindowEvent::None
+            }
+            Grab::Drag(_) | Grab::Size(_) => {
+                self.follow_outline(med);
+                WindowEvent::None
+            }
+            Grab::Content => {
+                let front = self.windows.len() - 1;
+                WindowEvent::Content(id, self.windows[front].content.button_down(med, event))
+            }
+        }
+    }
+
+    /// Handles mouse wheel events over the frontmost window's view,
+    /// answering with whatever the view answers with.
+    /// Otherwise, answers with [[WindowEvent::None]].
+    fn wheel(&mut self, med: &mut dyn Mediator, delta: Point, modifiers: Modifiers) -> WindowEvent<E> {
+        match self.front_content_at(self.mouse_pt) {
+            Some(i) => {
+                let window = &mut self.windows[i];
+                WindowEvent::Content(window.id, window.content.wheel(med, delta, modifiers))
+            }
+            None => WindowEvent::None,
+        }
+    }
+
+    /// Handles the pointer entering the desktop, by passing it on to the frontmost window's view.
+    fn enter(&mut self, med: &mut dyn Mediator, at: Point) -> WindowEvent<E> {
+        self.mouse_pt = at;
+        match self.windows.last_mut() {
+            Some(window) => WindowEvent::Content(window.id, window.content.enter(med, at)),
+            None => WindowEvent::None,
+        }
+    }
+
+    /// Handles the pointer leaving the desktop, by passing it on to the frontmost window's view.
+    fn leave(&mut self, med: &mut dyn Mediator) -> WindowEvent<E> {
+        match self.windows.last_mut() {
+            Some(window) => WindowEvent::Content(window.id, window.content.leave(med)),
+            None => WindowEvent::None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{WindowEvent, WindowManager, TITLE_HEIGHT};
+    use crate::events::{ButtonEvent, Modifiers, MouseEventSink};
+    use crate::icons::{IconEvent, IconLayer, DRAWER_ICON};
+    use crate::mediator::Mediator;
+    use crate::stencil::Draw;
+    use crate::types::{Point, Rect};
+    use crate::utils::{testing::TestMediator, BLACK_PATTERN};
+    use crate::view::View;
+
+    /// A view which paints itself black, and reports nothing.
+    struct Swatch {
+        area: Rect,
+    }
+
+    impl View for Swatch {
+        fn draw(&mut self, med: &mut dyn Mediator) {
+            med.borrow_mut_desktop().filled_rectangle(self.area.0, self.area.1, &BLACK_PATTERN);
+        }
+
+        fn set_area(&mut self, area: Rect) {
+            self.area = area;
+        }
+    }
+
+    impl MouseEventSink<()> for Swatch {
+        fn pointer_moved(&mut self, _: &mut dyn Mediator, _: Point) {}
+        fn button_up(&mut self, _: &mut dyn Mediator, _: ButtonEvent) {}
+        fn button_down(&mut self, _: &mut dyn Mediator, _: ButtonEvent) {}
+        fn wheel(&mut self, _: &mut dyn Mediator, _: Point, _: Modifiers) {}
+        fn enter(&mut self, _: &mut dyn Mediator, _: Point) {}
+        fn leave(&mut self, _: &mut dyn Mediator) {}
+    }
+
+    fn swatch() -> Swatch {
+        Swatch { area: ((0, 0), (0, 0)) }
+    }
+
+    const BACK: Rect = ((20, 20), (140, 100));
+    const FRONT: Rect = ((77, 63), (201, 150));
+
+    /// Answers the desktop as it looks with windows freshly drawn in `frames`, from back to front.
+    fn fresh(frames: &[Rect]) -> Vec<u8> {
+        let mut med = TestMediator::new(256, 192);
+        let mut wm = WindowManager::new();
+        for &frame in frames {
+            wm.open(&mut med, frame, "Window", swatch(), |()| ());
+        }
+        wm.draw(&mut med);
+        med.desktop.borrow_bits().to_vec()
+    }
+
+    /// Answers where the desktop first differs from how it looks
+    /// with windows freshly drawn in `frames`, from back to front; if it does.
+    fn difference(med: &TestMediator, frames: &[Rect]) -> Option<Point> {
+        let span = med.desktop.get_span();
+        let i = med.desktop.borrow_bits().iter().zip(fresh(frames)).position(|(&a, b)| a != b)?;
+        Some((((i % span) << 3) as isize, (i / span) as isize))
+    }
+
+    #[test]
+    fn closing_and_lowering_repair_the_desktop() {
+        let mut med = TestMediator::new(256, 192);
+        let mut wm = WindowManager::new();
+        wm.draw(&mut med);
+        let back = wm.open(&mut med, BACK, "Window", swatch(), |()| ());
+        let front = wm.open(&mut med, FRONT, "Window", swatch(), |()| ());
+        assert_eq!(difference(&med, &[BACK, FRONT]), None);
+
+        wm.send_to_back(&mut med, front);
+        assert_eq!(wm.windows(), vec![front, back]);
+        assert_eq!(difference(&med, &[FRONT, BACK]), None);
+
+        wm.close(&mut med, back);
+        assert_eq!(difference(&med, &[FRONT]), None);
+    }
+
+    #[test]
+    fn gadgets_drag_depth_and_close() {
+        let mut med = TestMediator::new(256, 192);
+        let mut wm = WindowManager::new();
+        wm.draw(&mut med);
+        let back = wm.open(&mut med, BACK, "Window", swatch(), |()| ());
+        let front = wm.open(&mut med, FRONT, "Window", swatch(), |()| ());
+
+        // Dragging the back window's title bar brings it to the front, then moves it.
+        let grab = ButtonEvent::left((60, 25));
+        assert_eq!(wm.button_down(&mut med, grab), WindowEvent::None);
+        assert_eq!(wm.front(), Some(back));
+        assert_eq!(wm.pointer_moved(&mut med, (70, 35)), WindowEvent::None);
+        assert_eq!(wm.button_up(&mut med, ButtonEvent::left((70, 35))), WindowEvent::Moved(back));
+        let moved = ((30, 30), (150, 110));
+        assert_eq!(wm.get_frame(back), Some(moved));
+        assert_eq!(difference(&med, &[FRONT, moved]), None);
+
+        // The depth gadget sends the frontmost window to the back.
+        let depth = ButtonEvent::left((150 - TITLE_HEIGHT / 2, 35));
+        let _ = wm.button_down(&mut med, depth);
+        assert_eq!(wm.button_up(&mut med, depth), WindowEvent::None);
+        assert_eq!(wm.front(), Some(front));
+        assert_eq!(difference(&med, &[moved, FRONT]), None);
+
+        // The close gadget only asks, and only if the button is let go over it.
+        let close = ButtonEvent::left((80, 66));
+        let _ = wm.button_down(&mut med, close);
+        assert_eq!(wm.button_up(&mut med, ButtonEvent::left((120, 66))), WindowEvent::None);
+        let _ = wm.button_down(&mut med, close);
+        assert_eq!(wm.button_up(&mut med, close), WindowEvent::CloseRequested(front));
+        assert_eq!(difference(&med, &[moved, FRONT]), None);
+
+        // Clicks in the view go to the view.
+        assert_eq!(wm.button_down(&mut med, ButtonEvent::left((100, 100))), WindowEvent::Content(front, ()));
+    }
+
+    /// Answers a backdrop of two icons, the first of them selected if `selected` is set.
+    fn icons(selected: bool) -> IconLayer {
+        let mut layer = IconLayer::new(((0, 0), (0, 0)));
+        let first = layer.add("Drawer", &DRAWER_ICON);
+        layer.add("Other", &DRAWER_ICON);
+        layer.set_selected(first, selected);
+        layer
+    }
+
+    #[test]
+    fn backdrop_hears_clicks_outside_windows() {
+        let mut med = TestMediator::new(256, 192);
+        let mut wm = WindowManager::new();
+        wm.set_backdrop(&mut med, icons(false), |e| e);
+        let window = wm.open(&mut med, BACK, "Window", swatch(), |()| IconEvent::None);
+
+        assert_eq!(wm.button_down(&mut med, ButtonEvent::left((10, 5))), WindowEvent::Backdrop(IconEvent::SelectionChanged));
+        assert_eq!(wm.button_up(&mut med, ButtonEvent::left((10, 5))), WindowEvent::Backdrop(IconEvent::None));
+        assert_eq!(wm.get_backdrop::<IconLayer>().map(IconLayer::selection), Some(vec![0]));
+        assert_eq!(wm.button_down(&mut med, ButtonEvent::left((100, 50))), WindowEvent::Content(window, IconEvent::None));
+
+        // The backdrop redrew itself, but the window it drew over was repaired.
+        let mut expected = TestMediator::new(256, 192);
+        let mut fresh = WindowManager::new();
+        fresh.set_backdrop(&mut expected, icons(true), |e| e);
+        fresh.open(&mut expected, BACK, "Window", swatch(), |()| IconEvent::None);
+        assert!(med.desktop == expected.desktop);
+    }
+}
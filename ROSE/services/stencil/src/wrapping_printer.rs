@@ -0,0 +1,420 @@
+//! Word-wrapped text output.
+//!
+//! [[SimplePrinter]] behaves like a dot-matrix printer:
+//! when the print head reaches the right-hand margin,
+//! it simply carries on at the start of the next line,
+//! even if that's in the middle of a word.
+//! That's fine for status lines and labels,
+//! but it makes running text hard to read.
+//!
+//! The [[WrappingPrinter]] instead lays out its text in a [[Forme]] first,
+//! so that lines break between words,
+//! and then prints each line aligned against the margins.
+//! Given a [[Hyphenator]], or text marked up with soft hyphens,
+//! long words are hyphenated rather than pushed to the next line whole.
+//! Lines are indented, tabbed, and spaced according to a [[ParagraphFormat]].
+
+use bitblt::BlitOp;
+
+use crate::chase::{Forme, FontProvider, LineBreak, HYPHEN, SOFT_HYPHEN, is_line_break, is_paragraph_break};
+use crate::hyphenation::Hyphenator;
+use crate::paragraph::{DECIMAL_POINT, PLAIN_PARAGRAPH, ParagraphFormat};
+use crate::simple_bitmap_font::SimpleBitmapFont;
+use crate::simple_printer::paste_glyph;
+use crate::stencil::Stencil;
+use crate::text_style::{StyledFont, TextStyle};
+use crate::types::{Dimension, Rect, Unit};
+
+/// How a line of text is placed between the left- and right-hand margins.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Alignment {
+    /// Lines start at the left-hand margin.
+    Left,
+
+    /// Lines end at the right-hand margin.
+    Right,
+
+    /// Lines are centered between the margins.
+    Centre,
+
+    /// Lines are stretched to touch both margins by widening the spaces between words.
+    /// The last line of each paragraph is aligned to the left-hand margin instead.
+    Justified,
+}
+
+/// Prints word-wrapped text onto a stencil.
+pub struct WrappingPrinter<'a, 'b> {
+    /// The stencil this printer will be printing on to.
+    pub stencil: &'a mut Stencil,
+
+    /// Determines the top, bottom, left, and right-hand margins relative
+    /// to the stencil on which this printer is printing to.
+    pub margins: Rect,
+
+    /// The font that text will be printed in.
+    pub font: &'b SimpleBitmapFont<'b>,
+
+    /// The style synthesized onto every glyph printed.
+    pub style: TextStyle,
+
+    /// How each line is placed between the margins.
+    pub alignment: Alignment,
+
+    /// The blitter operation to perform when pasting glyphs to the stencil.
+    pub blit_op: BlitOp,
+
+    /// Finds places to hyphenate words, if any.
+    pub hyphenator: Option<&'b Hyphenator>,
+
+    /// The indents, tab stops, and line spacing of every paragraph printed.
+    pub format: &'b ParagraphFormat,
+}
+
+impl<'a, 'b> WrappingPrinter<'a, 'b> {
+    /// Creates a new WrappingPrinter.
+    ///
+    /// The `stencil` is the surface on which the printer will render text.
+    ///
+    /// The `margins` rectangle delineates the "page" within which text will be laid out.
+    ///
+    /// The `font` indicates the font the printer will use for printing.
+    ///
+    /// By default, text is printed plain, left-aligned, in plain paragraphs without hyphenation,
+    /// and as black text on a white surface.
+    /// Adjust `style`, `alignment`, `format`, `hyphenator`, or `blit_op` prior to printing if something else is required.
+    pub fn new(stencil: &'a mut Stencil, margins: Rect, font: &'b SimpleBitmapFont<'b>) -> Self {
+        Self {
+            stencil,
+            margins,
+            font,
+
+            style: TextStyle::PLAIN,
+            alignment: Alignment::Left,
+            blit_op: BlitOp::DandNotS,
+            hyphenator: None,
+            format: &PLAIN_PARAGRAPH,
+        }
+    }
+
+    /// Prints as much of `text` as will fit between the margins,
+    /// starting at the top of the page.
+    ///
+    /// Printing stops before the first line that would cross the bottom margin.
+    /// Answers with the number of *bytes* of `text` that were printed,
+    /// such that `&text[n..]` is whatever remains to be printed on the next page.
+    pub fn print(&mut self, text: &str) -> usize {
+        let styled = StyledFont::new(self.font, self.style);
+        let ((left, top), (_, bottom)) = self.margins;
+        let mut forme = Forme::new((self.right_edge() - left).max(0) as usize, &styled);
+        forme.set_hyphenator(self.hyphenator);
+        forme.set_format(self.format);
+        forme.layout_from_str(text);
+
+        let chars: Vec<(usize, char)> = text.char_indices().collect();
+        let mut y = top;
+        let mut consumed = 0;
+        let mut first_line = true;
+
+        for (&(start, end), &brk) in forme.borrow_lines().iter().zip(forme.borrow_breaks()) {
+            if y + self.font.height > bottom {
+                break;
+            }
+
+            self.print_line(&styled, &chars[start..end], y, first_line, brk);
+
+            consumed = chars.get(end).map(|&(i, _)| i).unwrap_or(text.len());
+            y += self.format.line_spacing.line_height(self.font.height);
+            first_line = brk == LineBreak::Paragraph;
+        }
+
+        consumed
+    }
+
+    /// Answers the right-hand edge against which lines are wrapped and aligned.
+    ///
+    /// Italic glyphs overhang their advance,
+    /// so room is left for the last glyph on a line to lean into
+    /// without being clipped by the right-hand margin.
+    fn right_edge(&self) -> Unit {
+        let ((_, _), (right, _)) = self.margins;
+        right - self.style.overhang(self.font)
+    }
+
+    /// Prints a single laid-out `line`, whose top edge sits at `top`.
+    ///
+    /// The `first_line` of a paragraph takes the first line indent.
+    /// Lines ending a paragraph (or the text) are not justified,
+    /// and hyphenated lines end in a hyphen,
+    /// as described by `brk`.
+    fn print_line(&mut self, styled: &StyledFont, line: &[(usize, char)], top: Unit, first_line: bool, brk: LineBreak) {
+        // Soft hyphens are invisible, unless the line was hyphenated at one.
+        let glyphs: Vec<char> = line.iter()
+            .map(|&(_, ch)| ch)
+            .filter(|&ch| (ch != SOFT_HYPHEN) && !is_paragraph_break(ch))
+            .chain((brk == LineBreak::Hyphen).then_some(HYPHEN))
+            .collect();
+        let (xs, last_tab) = self.place(styled, &glyphs, first_line);
+
+        // Trailing whitespace hangs in the margin, invisibly.
+        let mut visible = glyphs.len();
+        while (visible > 0) && is_line_break(glyphs[visible - 1]) {
+            visible -= 1;
+        }
+        let end = match visible {
+            0 => self.format.indent(first_line),
+            _ => xs[visible - 1] + styled.get_glyph_width(glyphs[visible - 1]) as Dimension,
+        };
+
+        // Only whitespace past the last tab stop is stretched; the tabbed columns stay put.
+        let is_gap = |i: usize| last_tab.is_none_or(|t| i > t) && is_line_break(glyphs[i]);
+        let last_line = matches!(brk, LineBreak::Paragraph | LineBreak::End);
+        let ((left, _), _) = self.margins;
+        let slack = (self.right_edge() - self.format.right_indent.max(0) - left - end).max(0);
+        let gaps = (0..visible).filter(|&i| is_gap(i)).count() as Dimension;
+        let justify = (self.alignment == Alignment::Justified) && !last_line && (gaps > 0);
+
+        let origin = match self.alignment {
+            Alignment::Left | Alignment::Justified => left,
+            Alignment::Right => left + slack,
+            Alignment::Centre => left + (slack >> 1),
+        };
+
+        let mut stretch = 0;
+        let mut gap = 0;
+        for i in 0..visible {
+            let ch = glyphs[i];
+            if (ch != '\t') || last_tab.is_none_or(|t| i > t) {
+                let x = origin + xs[i] + stretch;
+                paste_glyph(self.stencil, self.font, ch, self.style, (x, top), self.margins, self.blit_op);
+            }
+
+            if justify && is_gap(i) {
+                // Spread the slack as evenly as we can; the first few gaps absorb the remainder.
+                stretch += slack / gaps;
+                if gap < slack % gaps {
+                    stretch += 1;
+                }
+                gap += 1;
+            }
+        }
+    }
+
+    /// Answers where each of the `glyphs` of a line starts, relative to the left-hand margin,
+    /// along with the index of the last tab to reach a tab stop, if any did.
+    ///
+    /// This follows [[Forme]]: the line starts at its indent,
+    /// and a tab advances to its stop according to the text following it,
+    /// up to the next tab.
+    fn place(&self, styled: &StyledFont, glyphs: &[char], first_line: bool) -> (Vec<Dimension>, Option<usize>) {
+        let mut xs = Vec::with_capacity(glyphs.len());
+        let mut x = self.format.indent(first_line);
+        let mut previous = None;
+        let mut last_tab = None;
+
+        for (i, &ch) in glyphs.iter().enumerate() {
+            let stop = if ch == '\t' { self.format.next_tab_stop(x) } else { None };
+            match stop {
+                Some(stop) => {
+                    let text = &glyphs[i + 1..];
+                    let text = &text[..text.iter().position(|&ch| ch == '\t').unwrap_or(text.len())];
+                    let point = text.iter().position(|&ch| ch == DECIMAL_POINT).unwrap_or(text.len());
+                    let width = measure(styled, text.iter().copied());
+                    let to_point = measure(styled, text[..point].iter().copied());
+
+                    xs.push(x);
+                    x += stop.advance(x, width, to_point);
+                    previous = None;
+                    last_tab = Some(i);
+                },
+                None => {
+                    if let Some(p) = previous {
+                        x += styled.get_kerning(p, ch);
+                    }
+                    xs.push(x);
+                    x += styled.get_glyph_width(ch) as Dimension;
+                    previous = Some(ch);
+                },
+            }
+        }
+        (xs, last_tab)
+    }
+}
+
+/// Answers the width of a line of text, including kerning.
+fn measure(styled: &StyledFont, line: impl Iterator<Item = char>) -> Dimension {
+    let mut width = 0;
+    let mut previous = None;
+    for ch in line {
+        if let Some(p) = previous {
+            width += styled.get_kerning(p, ch);
+        }
+        width += styled.get_glyph_width(ch) as Dimension;
+        previous = Some(ch);
+    }
+    width
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Alignment, WrappingPrinter};
+    use crate::simple_bitmap_font::text_width;
+    use crate::sysfont_bsw_9::SYSTEM_BITMAP_FONT;
+    use crate::paragraph::{LineSpacing, PLAIN_PARAGRAPH, ParagraphFormat, TabAlignment, TabStop};
+    use crate::text_style::TextStyle;
+    use crate::types::{Point, Rect, Unit};
+    use crate::utils::testing::{blank_stencil, inked_pixels};
+
+    /// Prints `text` in `style` onto a blank 96x24 page, answering with the bytes consumed
+    /// and the leftmost and rightmost inked columns of each text line.
+    fn print_aligned(text: &str, alignment: Alignment, style: TextStyle) -> (usize, Vec<Option<(Unit, Unit)>>) {
+        print_formatted(text, alignment, style, &PLAIN_PARAGRAPH)
+    }
+
+    /// Like `print_aligned`, but lays paragraphs out in `format`.
+    fn print_formatted(text: &str, alignment: Alignment, style: TextStyle, format: &ParagraphFormat) -> (usize, Vec<Option<(Unit, Unit)>>) {
+        let page: Rect = ((0, 0), (96, 24));
+        let mut s = blank_stencil(96, 24);
+        let consumed = {
+            let mut p = WrappingPrinter::new(&mut s, page, &SYSTEM_BITMAP_FONT);
+            p.alignment = alignment;
+            p.style = style;
+            p.format = format;
+            p.print(text)
+        };
+
+        let height = SYSTEM_BITMAP_FONT.height;
+        let mut extents: Vec<Option<(Unit, Unit)>> = vec![None; (24 / height) as usize];
+        for (x, y) in inked_pixels(&s) {
+            if let Some(extent) = extents.get_mut((y / height) as usize) {
+                *extent = Some(match *extent {
+                    None => (x, x),
+                    Some((l, r)) => (l.min(x), r.max(x)),
+                });
+            }
+        }
+        (consumed, extents)
+    }
+
+    #[test]
+    fn wraps_between_words() {
+        let (consumed, extents) = print_aligned("pasta carbonara al dente", Alignment::Left, TextStyle::PLAIN);
+        assert_eq!(consumed, 24);
+        let first = extents[0].unwrap();
+        assert_eq!(first.0, 0);
+        assert!(first.1 < text_width("pasta carbonara al", &SYSTEM_BITMAP_FONT));
+        assert!(extents[1].is_some());
+    }
+
+    #[test]
+    fn stops_at_bottom_margin_and_reports_consumption() {
+        let text = "one\ntwo\nthree\nfour";
+        let (consumed, extents) = print_aligned(text, Alignment::Left, TextStyle::PLAIN);
+        assert_eq!(&text[consumed..], "three\nfour");
+        assert!(extents.iter().all(Option::is_some));
+    }
+
+    #[test]
+    fn crlf_line_endings_are_not_double_spaced() {
+        let (consumed, extents) = print_aligned("one\r\ntwo\r\n", Alignment::Left, TextStyle::PLAIN);
+        assert_eq!(consumed, 10);
+        assert!(extents.iter().all(Option::is_some));
+    }
+
+    #[test]
+    fn right_and_centre_alignment() {
+        let (_, right) = print_aligned("Hi", Alignment::Right, TextStyle::PLAIN);
+        let (_, centre) = print_aligned("Hi", Alignment::Centre, TextStyle::PLAIN);
+        let (_, left) = print_aligned("Hi", Alignment::Left, TextStyle::PLAIN);
+        let (l, r) = left[0].unwrap();
+        let width = text_width("Hi", &SYSTEM_BITMAP_FONT);
+        let slack = 96 - width;
+        assert_eq!(right[0].unwrap(), (l + slack, r + slack));
+        assert_eq!(centre[0].unwrap(), (l + slack / 2, r + slack / 2));
+    }
+
+    #[test]
+    fn italic_overhang_stays_inside_right_margin() {
+        let (_, right) = print_aligned("Hi", Alignment::Right, TextStyle::ITALIC);
+        let overhang = TextStyle::ITALIC.overhang(&SYSTEM_BITMAP_FONT);
+        assert!(overhang > 0);
+        // The line is set in from the margin just far enough for the top of the 'i' to lean into.
+        let (_, left) = print_aligned("Hi", Alignment::Left, TextStyle::ITALIC);
+        let width = text_width("Hi", &SYSTEM_BITMAP_FONT);
+        assert_eq!(right[0].unwrap().0 - left[0].unwrap().0, 96 - overhang - width);
+    }
+
+    #[test]
+    fn hyphenated_lines_end_in_a_hyphen() {
+        let print = |text: &str| {
+            let mut s = blank_stencil(96, 24);
+            WrappingPrinter::new(&mut s, ((0, 0), (96, 24)), &SYSTEM_BITMAP_FONT).print(text);
+            inked_pixels(&s)
+        };
+        let first_line = |pixels: Vec<Point>| -> Vec<Point> {
+            pixels.into_iter().filter(|&(_, y)| y < SYSTEM_BITMAP_FONT.height).collect()
+        };
+
+        let hyphenated = print("abcdefghijkl\u{AD}mnopqrstuvwxyz");
+        assert_eq!(first_line(hyphenated.clone()), first_line(print("abcdefghijkl-")));
+        assert!(hyphenated.iter().any(|&(_, y)| y >= SYSTEM_BITMAP_FONT.height));
+
+        // Soft hyphens are invisible anywhere else.
+        assert_eq!(print("ab\u{AD}cd"), print("abcd"));
+    }
+
+    #[test]
+    fn paragraphs_are_indented() {
+        let text = "pasta carbonara al dente";
+        let format = ParagraphFormat { left_indent: 10, first_line_indent: 6, right_indent: 20, ..Default::default() };
+        let (_, plain) = print_aligned(text, Alignment::Left, TextStyle::PLAIN);
+        let (_, indented) = print_formatted(text, Alignment::Left, TextStyle::PLAIN, &format);
+        assert_eq!(indented[0].unwrap().0, plain[0].unwrap().0 + 16);
+        assert_eq!(indented[1].unwrap().0, plain[1].unwrap().0 + 10);
+        assert!(indented.iter().flatten().all(|&(_, right)| right < 96 - 20));
+
+        // Right-aligned lines end at the right-hand indent.
+        let (_, right) = print_formatted("Hi", Alignment::Right, TextStyle::PLAIN, &format);
+        let (_, plain) = print_aligned("Hi", Alignment::Right, TextStyle::PLAIN);
+        assert_eq!(right[0].unwrap().1, plain[0].unwrap().1 - 20);
+    }
+
+    #[test]
+    fn tab_stops_line_up_columns() {
+        let mut format = ParagraphFormat::default();
+        format.set_tab_stop(TabStop::new(80, TabAlignment::Right));
+        let (_, long) = print_formatted("a\t1234", Alignment::Left, TextStyle::PLAIN, &format);
+        let (_, short) = print_formatted("a\t12", Alignment::Left, TextStyle::PLAIN, &format);
+        assert_eq!(long[0].unwrap().1, short[0].unwrap().1);
+        assert!(long[0].unwrap().1 < 80);
+
+        let mut format = ParagraphFormat::default();
+        format.set_tab_stop(TabStop::new(40, TabAlignment::Left));
+        let (_, tabbed) = print_formatted("a\tb", Alignment::Left, TextStyle::PLAIN, &format);
+        let (_, b) = print_aligned("b", Alignment::Left, TextStyle::PLAIN);
+        assert_eq!(tabbed[0].unwrap().1, b[0].unwrap().1 + 40);
+    }
+
+    #[test]
+    fn line_spacing_spreads_lines_apart() {
+        let text = "one\ntwo";
+        let format = ParagraphFormat { line_spacing: LineSpacing::Double, ..Default::default() };
+        let (consumed, extents) = print_formatted(text, Alignment::Left, TextStyle::PLAIN, &format);
+        assert_eq!(&text[consumed..], "two");
+        assert!(extents[0].is_some());
+        assert!(extents[1].is_none());
+    }
+
+    #[test]
+    fn justified_lines_reach_both_margins_except_paragraph_ends() {
+        let text = "pasta carbonara al dente";
+        let (_, justified) = print_aligned(text, Alignment::Justified, TextStyle::PLAIN);
+        let (_, left) = print_aligned(text, Alignment::Left, TextStyle::PLAIN);
+
+        // The last inked column of the first line moves right to meet the margin;
+        // the final line of the paragraph is left alone.
+        let last_glyph = SYSTEM_BITMAP_FONT.glyph_width('a');
+        assert!(justified[0].unwrap().1 > 96 - last_glyph);
+        assert!(justified[0].unwrap().1 > left[0].unwrap().1);
+        assert_eq!(justified[1], left[1]);
+    }
+}